@@ -1,8 +1,12 @@
-use std::io::{self, Write};
+use std::convert::TryFrom;
+use std::num::NonZeroUsize;
 
-use serde::ser::{Serialize, SerializeStruct, Serializer};
+use indexmap::{map::Entry as IndexMapEntry, IndexMap};
+use paltoquet::keyers::omission_key;
+use paltoquet::tokenizers::FingerprintTokenizer;
+use unidecode::unidecode;
 
-use crate::collections::{hash_map::Entry, HashMap};
+use crate::collections::{HashMap, UnionFind};
 use crate::config::{Config, Delimiter};
 use crate::moonblade::Program;
 use crate::select::SelectColumns;
@@ -10,15 +14,75 @@ use crate::util;
 use crate::CliResult;
 
 static USAGE: &str = "
-TODO...
+Cluster the distinct values of a column in order to find near-duplicates,
+typically to clean messy names or entities, in the vein of OpenRefine.
+
+Two families of clustering methods are available:
+
+Key collision methods will compute a key for each distinct value and will
+group the values colliding on the same key:
+
+    fingerprint: lowercase, ascii-normalized, deduplicated & sorted words
+                 of the value, e.g. \"Université de Paris\" -> \"de paris universite\".
+    ngrams:      lowercase, ascii-normalized, deduplicated & sorted character
+                 ngrams of the value, ignoring whitespace & punctuation. Ngram size
+                 can be set with -N, --ngrams. Will catch more typos than
+                 \"fingerprint\".
+    omission:    consonants of the value, ordered so that the most often
+                 mispelled ones come last, followed by its vowels in order of
+                 first appearance.
+    soundex:     phonetic key made of the soundex code of each word of the
+                 value's fingerprint. Only really makes sense for english text.
+
+Nearest neighbors methods will compare distinct values with one another and
+will group them when they are sufficiently similar:
+
+    levenshtein: values whose Levenshtein distance is lesser than or equal
+                 to -r, --radius.
+    jaccard:     values whose character ngrams sets have a Jaccard similarity
+                 greater than or equal to -t, --threshold. Use -N, --ngrams to
+                 set ngram size.
+
+Note that nearest neighbors methods will group values transitively, i.e. if
+\"a\" is similar to \"b\" and \"b\" is similar to \"c\", all three will end up in
+the same cluster.
+
+You can also compute your own key by evaluating an expression using -k, --key
+(see `xan help cheatsheet` and `xan help functions` for the documentation of
+the expression language).
+
+Only clusters containing more than one distinct value will be emitted, as a
+CSV table having the following columns:
+
+    cluster_id: id of the cluster, biggest clusters first.
+    value:      a distinct value of the cluster.
+    count:      number of rows having this value.
+    canonical:  suggested canonical value for the cluster, i.e. its
+                most frequent value.
+
+This table can be edited and then fed back to `xan transform` to harmonize
+the original file:
+
+    $ xan cluster name data.csv > clusters.csv
+    $ xan transform name 'read_csv(\"clusters.csv\") | index_by(_, \"value\") | get(_, [name, \"canonical\"], name)' data.csv
 
 Usage:
     xan cluster <column> [options] [<input>]
     xan cluster --help
 
 cluster options:
-    -k, --key <expr>  An expression to evaluate to generate a key
-                      for each row by transforming the selected cell.
+    -m, --method <method>     Clustering method to use, among \"fingerprint\",
+                              \"ngrams\", \"omission\", \"soundex\", \"levenshtein\"
+                              & \"jaccard\". Will default to \"fingerprint\".
+    -k, --key <expr>          An expression to evaluate to generate a key
+                              for each row by transforming the selected cell.
+                              Cannot be used with -m, --method.
+    -N, --ngrams <n>          Size of the character ngrams used by the \"ngrams\"
+                              & \"jaccard\" methods. [default: 2]
+    -r, --radius <n>          Maximum Levenshtein distance between two values
+                              for the \"levenshtein\" method. [default: 1]
+    -t, --threshold <t>       Minimum Jaccard similarity between two values
+                              for the \"jaccard\" method. [default: 0.7]
 
 Common options:
     -h, --help               Display this message
@@ -29,11 +93,42 @@ Common options:
                              Must be a single character.
 ";
 
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+enum MethodName {
+    Fingerprint,
+    Ngrams,
+    Omission,
+    Soundex,
+    Levenshtein,
+    Jaccard,
+}
+
+impl TryFrom<String> for MethodName {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "fingerprint" => Self::Fingerprint,
+            "ngrams" => Self::Ngrams,
+            "omission" => Self::Omission,
+            "soundex" => Self::Soundex,
+            "levenshtein" => Self::Levenshtein,
+            "jaccard" => Self::Jaccard,
+            _ => return Err(format!("unknown clustering --method \"{}\"", &value)),
+        })
+    }
+}
+
 #[derive(Deserialize)]
 struct Args {
     arg_column: SelectColumns,
     arg_input: Option<String>,
+    flag_method: Option<MethodName>,
     flag_key: Option<String>,
+    flag_ngrams: NonZeroUsize,
+    flag_radius: usize,
+    flag_threshold: f64,
     flag_no_headers: bool,
     flag_output: Option<String>,
     flag_delimiter: Option<Delimiter>,
@@ -42,6 +137,14 @@ struct Args {
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
+    if args.flag_key.is_some() && args.flag_method.is_some() {
+        Err("-k, --key cannot be used with -m, --method!")?;
+    }
+
+    if !(0.0..=1.0).contains(&args.flag_threshold) {
+        Err("-t, --threshold must be between 0 and 1!")?;
+    }
+
     let rconf = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -52,171 +155,418 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let sel_index = rconf.single_selection(headers)?;
 
-    let key_expr = match &args.flag_key {
-        Some(expr) => format!("col({}) | {}", sel_index, expr),
-        None => format!("col({})", sel_index),
+    let ngrams = args.flag_ngrams.get();
+
+    let method = match &args.flag_key {
+        Some(expr) => {
+            let program = Program::parse(&format!("col({}) | {}", sel_index, expr), headers)?;
+            ClusteringMethod::KeyCollision(Keyer::Expr(Box::new(program)))
+        }
+        None => match args.flag_method.unwrap_or(MethodName::Fingerprint) {
+            MethodName::Fingerprint => ClusteringMethod::KeyCollision(Keyer::Fingerprint),
+            MethodName::Ngrams => ClusteringMethod::KeyCollision(Keyer::Ngrams(ngrams)),
+            MethodName::Omission => ClusteringMethod::KeyCollision(Keyer::Omission),
+            MethodName::Soundex => ClusteringMethod::KeyCollision(Keyer::Soundex),
+            MethodName::Levenshtein => ClusteringMethod::Levenshtein(args.flag_radius),
+            MethodName::Jaccard => ClusteringMethod::Jaccard(ngrams, args.flag_threshold),
+        },
     };
 
-    let program = Program::parse(&key_expr, headers)?;
-    let mut clustering: Box<dyn ClusteringAlgorithm> = Box::<KeyCollision>::default();
+    // Counting distinct values, and computing their keys when relevant
+    let fingerprint_tokenizer = FingerprintTokenizer::default();
+
+    let mut values: IndexMap<String, usize> = IndexMap::new();
+    let mut keys: Vec<String> = Vec::new();
 
     let mut record = csv::ByteRecord::new();
     let mut index: usize = 0;
 
     while rdr.read_byte_record(&mut record)? {
-        let value = String::from_utf8(record[sel_index].to_vec()).unwrap();
-        let key = program.generate_key(index, &record)?;
+        let value = String::from_utf8_lossy(&record[sel_index]).into_owned();
 
-        clustering.process(index, key, value);
+        match values.entry(value) {
+            IndexMapEntry::Occupied(mut entry) => {
+                *entry.get_mut() += 1;
+            }
+            IndexMapEntry::Vacant(entry) => {
+                if let ClusteringMethod::KeyCollision(keyer) = &method {
+                    keys.push(keyer.key(&fingerprint_tokenizer, index, &record, entry.key())?);
+                }
+
+                entry.insert(1);
+            }
+        };
 
         index += 1;
     }
 
-    let mut clusters = clustering.into_clusters();
+    let values = values.into_iter().collect::<Vec<_>>();
+
+    let groups = match method {
+        ClusteringMethod::KeyCollision(_) => cluster_by_key_collision(&keys),
+        ClusteringMethod::Levenshtein(radius) => cluster_by_levenshtein(&values, radius),
+        ClusteringMethod::Jaccard(n, threshold) => cluster_by_jaccard(&values, n, threshold),
+    };
+
+    let mut clusters = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| Cluster::from_values(group.into_iter().map(|i| &values[i])))
+        .collect::<Vec<_>>();
 
     clusters.sort_by(|a, b| {
         b.values
             .len()
             .cmp(&a.values.len())
-            .then_with(|| b.rows.len().cmp(&a.rows.len()))
+            .then_with(|| b.nb_rows.cmp(&a.nb_rows))
             .then_with(|| a.best().cmp(b.best()))
     });
 
-    let mut writer = Config::new(&args.flag_output).io_writer()?;
+    let mut wtr = Config::new(&args.flag_output).writer()?;
 
-    for cluster in clusters {
-        cluster.write_toml(&mut writer)?;
+    wtr.write_record(["cluster_id", "value", "count", "canonical"])?;
+
+    for (id, cluster) in clusters.iter().enumerate() {
+        let id = id.to_string();
+
+        for (value, count) in cluster.values.iter() {
+            wtr.write_record([
+                id.as_bytes(),
+                value.as_bytes(),
+                count.to_string().as_bytes(),
+                cluster.best().as_bytes(),
+            ])?;
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
+
+enum Keyer {
+    Expr(Box<Program>),
+    Fingerprint,
+    Ngrams(usize),
+    Omission,
+    Soundex,
+}
+
+impl Keyer {
+    fn key(
+        &self,
+        tokenizer: &FingerprintTokenizer,
+        index: usize,
+        record: &csv::ByteRecord,
+        value: &str,
+    ) -> CliResult<String> {
+        Ok(match self {
+            Self::Expr(program) => program.generate_key(index, record)?,
+            Self::Fingerprint => tokenizer.key(value),
+            Self::Ngrams(n) => ngrams_fingerprint(value, *n).join(""),
+            Self::Omission => omission_key(value),
+            Self::Soundex => tokenizer
+                .tokenize(value)
+                .iter()
+                .map(|token| soundex(token))
+                .filter(|code| !code.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
     }
+}
 
-    Ok(())
+enum ClusteringMethod {
+    KeyCollision(Keyer),
+    Levenshtein(usize),
+    Jaccard(usize, f64),
 }
 
 #[derive(Debug)]
 struct Cluster {
-    id: usize,
-    key: String,
-    rows: Vec<usize>,
+    nb_rows: usize,
     values: Vec<(String, usize)>,
 }
 
 impl Cluster {
-    fn write_toml<W: Write>(&self, mut writer: W) -> io::Result<()> {
-        writeln!(&mut writer, "[[cluster]]")?;
-        writeln!(&mut writer, "id = {}", self.id)?;
-        writeln!(&mut writer, "key = \"{}\"", self.key)?;
-        writeln!(&mut writer, "nb_values = {}", self.values.len())?;
-        writeln!(&mut writer, "nb_rows = {}", self.rows.len())?;
-        writeln!(
-            &mut writer,
-            "rows = \"{}\"",
-            self.rows
-                .iter()
-                .map(|i| i.to_string())
-                .collect::<Vec<_>>()
-                .join(",")
-        )?;
-        writeln!(&mut writer, "replace_with = {:?}", self.best())?;
-        writeln!(&mut writer, "values = [")?;
-
-        for (value, count) in self.values.iter() {
-            writeln!(
-                &mut writer,
-                "  {{ value = {:?}, count = {} }},",
-                value, count
-            )?;
+    fn from_values<'a>(entries: impl Iterator<Item = &'a (String, usize)>) -> Self {
+        let mut values = entries.cloned().collect::<Vec<_>>();
+        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Cluster {
+            nb_rows: values.iter().map(|(_, count)| count).sum(),
+            values,
         }
+    }
 
-        writeln!(&mut writer, "]")?;
-        writeln!(&mut writer, "harmonize = false")?;
-        writeln!(&mut writer)?;
+    fn best(&self) -> &String {
+        &self.values[0].0
+    }
+}
+
+fn cluster_by_key_collision(keys: &[String]) -> Vec<Vec<usize>> {
+    let mut collisions: IndexMap<&str, Vec<usize>> = IndexMap::new();
 
-        Ok(())
+    for (i, key) in keys.iter().enumerate() {
+        collisions.entry(key.as_str()).or_default().push(i);
     }
+
+    collisions.into_values().collect()
 }
 
-impl Serialize for Cluster {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut state = serializer.serialize_struct("Cluster", 8)?;
-        state.serialize_field("id", &self.id)?;
-        state.serialize_field("key", &self.key)?;
-        state.serialize_field("nb_values", &self.values.len())?;
-        state.serialize_field("nb_rows", &self.rows.len())?;
-        state.serialize_field(
-            "rows",
-            &self
-                .rows
-                .iter()
-                .map(|i| i.to_string())
-                .collect::<Vec<_>>()
-                .join(","),
-        )?;
-        state.serialize_field("replace_with", self.best())?;
-        state.serialize_field("values", &self.values)?;
-        state.serialize_field("harmonize", &false)?;
-        state.end()
+fn groups_from_union_find(sets: &UnionFind, n: usize) -> Vec<Vec<usize>> {
+    let mut groups: IndexMap<usize, Vec<usize>> = IndexMap::new();
+
+    for i in 0..n {
+        groups.entry(sets.find(i)).or_default().push(i);
     }
+
+    groups.into_values().collect()
 }
 
-impl Cluster {
-    fn from_entries(id: usize, key: String, entries: Vec<(usize, String)>) -> Self {
-        let mut rows = Vec::new();
-        let mut values = HashMap::new();
-
-        for (row_index, row_value) in entries {
-            rows.push(row_index);
-            values
-                .entry(row_value)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
+fn cluster_by_levenshtein(values: &[(String, usize)], radius: usize) -> Vec<Vec<usize>> {
+    let chars = values
+        .iter()
+        .map(|(value, _)| value.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut sets = UnionFind::new();
+
+    for _ in 0..values.len() {
+        sets.make_set();
+    }
+
+    // NOTE: values whose lengths differ by more than the radius cannot
+    // be within reach of each other, so we only need to compare values
+    // that are close enough when sorted by length.
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| chars[*i].len());
+
+    for (k, i) in order.iter().copied().enumerate() {
+        for j in order[k + 1..].iter().copied() {
+            if chars[j].len() - chars[i].len() > radius {
+                break;
+            }
+
+            if levenshtein_distance_within(&chars[i], &chars[j], radius) {
+                sets.union(i, j);
+            }
         }
+    }
 
-        let mut values = values.into_iter().collect::<Vec<_>>();
-        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+    groups_from_union_find(&sets, values.len())
+}
 
-        Cluster {
-            id,
-            key,
-            rows,
-            values,
+fn cluster_by_jaccard(values: &[(String, usize)], n: usize, threshold: f64) -> Vec<Vec<usize>> {
+    let grams = values
+        .iter()
+        .map(|(value, _)| ngrams_fingerprint(value, n))
+        .collect::<Vec<_>>();
+
+    let mut sets = UnionFind::new();
+
+    for _ in 0..values.len() {
+        sets.make_set();
+    }
+
+    // NOTE: two values can only have a non-zero Jaccard similarity if they
+    // share at least one ngram, hence the inverted index to avoid comparing
+    // every pair of values.
+    let mut inverted_index: HashMap<&str, Vec<usize>> = HashMap::new();
+
+    for (i, value_grams) in grams.iter().enumerate() {
+        for gram in value_grams {
+            inverted_index.entry(gram.as_str()).or_default().push(i);
         }
     }
 
-    fn best(&self) -> &String {
-        &self.values[0].0
+    let mut intersections: HashMap<usize, usize> = HashMap::new();
+
+    for (i, value_grams) in grams.iter().enumerate() {
+        intersections.clear();
+
+        for gram in value_grams {
+            for j in inverted_index[gram.as_str()].iter().copied() {
+                if j > i {
+                    *intersections.entry(j).or_insert(0) += 1;
+                }
+            }
+        }
+
+        for (j, intersection) in intersections.iter() {
+            let union = value_grams.len() + grams[*j].len() - intersection;
+
+            if *intersection as f64 / union as f64 >= threshold {
+                sets.union(i, *j);
+            }
+        }
     }
-}
 
-trait ClusteringAlgorithm {
-    fn process(&mut self, index: usize, key: String, value: String);
-    fn into_clusters(self: Box<Self>) -> Vec<Cluster>;
+    groups_from_union_find(&sets, values.len())
 }
 
-#[derive(Default)]
-struct KeyCollision {
-    collisions: HashMap<String, Vec<(usize, String)>>,
+// Sorted & deduplicated character ngrams of the lowercased, ascii-normalized
+// value, ignoring whitespace & punctuation, as done by OpenRefine.
+fn ngrams_fingerprint(value: &str, n: usize) -> Vec<String> {
+    let normalized = unidecode(value)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+
+    if normalized.is_empty() {
+        return vec![];
+    }
+
+    if normalized.len() <= n {
+        return vec![normalized.into_iter().collect()];
+    }
+
+    let mut grams = normalized
+        .windows(n)
+        .map(|gram| gram.iter().collect::<String>())
+        .collect::<Vec<_>>();
+
+    grams.sort();
+    grams.dedup();
+
+    grams
 }
 
-impl ClusteringAlgorithm for KeyCollision {
-    fn process(&mut self, index: usize, key: String, value: String) {
-        match self.collisions.entry(key) {
-            Entry::Occupied(mut entry) => {
-                entry.get_mut().push((index, value));
+// American soundex code of an ascii token.
+fn soundex(token: &str) -> String {
+    fn code(c: char) -> Option<char> {
+        Some(match c {
+            'b' | 'f' | 'p' | 'v' => '1',
+            'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => '2',
+            'd' | 't' => '3',
+            'l' => '4',
+            'm' | 'n' => '5',
+            'r' => '6',
+            _ => return None,
+        })
+    }
+
+    let mut letters = token
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase());
+
+    let first = match letters.next() {
+        None => return String::new(),
+        Some(c) => c,
+    };
+
+    let mut result = String::with_capacity(4);
+    result.push(first.to_ascii_uppercase());
+
+    let mut last_code = code(first);
+
+    for c in letters {
+        if result.len() == 4 {
+            break;
+        }
+
+        match code(c) {
+            Some(d) => {
+                if last_code != Some(d) {
+                    result.push(d);
+                }
+
+                last_code = Some(d);
             }
-            Entry::Vacant(entry) => {
-                entry.insert(vec![(index, value)]);
+            None => {
+                // NOTE: 'h' and 'w' do not separate letters having the same code
+                if c != 'h' && c != 'w' {
+                    last_code = None;
+                }
             }
-        };
+        }
     }
 
-    fn into_clusters(self: Box<Self>) -> Vec<Cluster> {
-        self.collisions
-            .into_iter()
-            .enumerate()
-            .map(|(id, (key, entries))| Cluster::from_entries(id, key, entries))
-            .filter(|cluster| cluster.values.len() > 1)
-            .collect()
+    while result.len() < 4 {
+        result.push('0');
+    }
+
+    result
+}
+
+// Whether the Levenshtein distance between both sequences is lesser than
+// or equal to the given maximum, bailing out as soon as possible.
+fn levenshtein_distance_within(a: &[char], b: &[char], max: usize) -> bool {
+    let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+
+    if b.len() - a.len() > max {
+        return false;
+    }
+
+    let mut previous = (0..=a.len()).collect::<Vec<_>>();
+    let mut current = vec![0; a.len() + 1];
+
+    for (i, cb) in b.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+
+        for (j, ca) in a.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+
+            row_min = row_min.min(current[j + 1]);
+        }
+
+        if row_min > max {
+            return false;
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[a.len()] <= max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("robert"), "R163");
+        assert_eq!(soundex("rupert"), "R163");
+        assert_eq!(soundex("ashcraft"), "A261");
+        assert_eq!(soundex("tymczak"), "T522");
+        assert_eq!(soundex("pfister"), "P236");
+        assert_eq!(soundex("lee"), "L000");
+        assert_eq!(soundex(""), "");
+    }
+
+    #[test]
+    fn test_ngrams_fingerprint() {
+        assert_eq!(ngrams_fingerprint("Paris", 2), vec!["ar", "is", "pa", "ri"]);
+        assert_eq!(
+            ngrams_fingerprint("P. a, r: is", 2),
+            vec!["ar", "is", "pa", "ri"]
+        );
+        assert_eq!(ngrams_fingerprint("ab", 3), vec!["ab"]);
+        assert!(ngrams_fingerprint("...", 2).is_empty());
+    }
+
+    #[test]
+    fn test_levenshtein_distance_within() {
+        fn within(a: &str, b: &str, max: usize) -> bool {
+            levenshtein_distance_within(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+                max,
+            )
+        }
+
+        assert!(within("kitten", "sitting", 3));
+        assert!(!within("kitten", "sitting", 2));
+        assert!(within("", "", 0));
+        assert!(within("abc", "abc", 0));
+        assert!(within("abc", "abd", 1));
+        assert!(!within("abc", "", 2));
+        assert!(within("flaw", "lawn", 2));
     }
 }
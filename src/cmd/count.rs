@@ -17,6 +17,9 @@ of records of the file in parallel to go faster. But this cannot work on streams
 or gzipped files, unless a `.gzi` index (as created by `bgzip -i`) can be found
 beside it.
 
+Finally, if the file was indexed using `xan index`, the count will be read from
+the index in constant time.
+
 Usage:
     xan count [options] [<input>]
 
@@ -57,7 +60,14 @@ struct Args {
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    if args.flag_parallel || args.flag_threads.is_some() {
+    let conf = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    // NOTE: if the file was indexed, counting is instantaneous
+    let index = conf.record_index()?;
+
+    if index.is_none() && (args.flag_parallel || args.flag_threads.is_some()) {
         if args.flag_approx {
             Err("-p/--parallel or -t/--threads cannot be used with -a/--approx!")?;
         }
@@ -73,13 +83,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         return parallel_args.run();
     }
 
-    let conf = Config::new(&args.arg_input)
-        .delimiter(args.flag_delimiter)
-        .no_headers(args.flag_no_headers);
-
     let wconf = Config::new(&args.flag_output);
 
-    let count = if args.flag_approx {
+    let count = if let Some(index) = index {
+        index.count()
+    } else if args.flag_approx {
         let mut rdr = conf.seekable_reader()?;
 
         match sample_initial_records(&mut rdr, args.flag_sample_size.get())? {
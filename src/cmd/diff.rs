@@ -0,0 +1,429 @@
+use std::cmp::Ordering;
+
+use ahash::RandomState;
+use indexmap::IndexMap;
+
+use crate::collections::HashSet;
+use crate::config::{Config, Delimiter};
+use crate::select::{SelectColumns, Selection};
+use crate::util;
+use crate::CliResult;
+
+static USAGE: &str = "
+Compare two versions of a CSV file and report the rows that were added,
+removed or modified in the new one, as identified by the given key selection,
+as well as the columns that were added, removed or moved.
+
+The diff is written as CSV, with one line per change, using the following
+columns:
+
+    diff:         kind of change, see below
+    <key>...:     one column per key column, identifying the changed row
+    column:       name of the concerned column, if relevant
+    old:          old value, if relevant
+    new:          new value, if relevant
+
+Here are the kinds of changes that can be reported:
+
+    added_column:   a column was added, \"new\" being its zero-based position
+    removed_column: a column was removed, \"old\" being its zero-based position
+    moved_column:   a column was moved from position \"old\" to position \"new\"
+    added:          a row was added, with one line per value of the row
+    removed:        a row was removed
+    modified:       a cell of a row was modified, with one line per modified cell
+
+Columns are matched by name, and rows are matched by key, which must be unique
+in both files.
+
+For instance:
+
+    $ xan diff id old.csv new.csv > diff.csv
+
+The diff can then be applied to the old file to reproduce the new one using
+`xan patch`:
+
+    $ xan patch diff.csv old.csv > new.csv
+
+By default, the old file is entirely indexed in memory. If both files are already
+sorted on the key (e.g. using `xan sort -s <key>`), use the -S/--sorted flag to
+compare them in a streaming fashion, using constant memory. The diff will then
+be reported in key order.
+
+Usage:
+    xan diff [options] <key> <old> <new>
+    xan diff --help
+
+diff options:
+    -S, --sorted           Use if both files are already sorted on the key, to
+                           compare them in constant memory.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_key: SelectColumns,
+    arg_old: String,
+    arg_new: String,
+    flag_sorted: bool,
+    flag_output: Option<String>,
+    flag_delimiter: Option<Delimiter>,
+}
+
+pub type Key = Vec<Vec<u8>>;
+
+pub const ADDED_COLUMN: &[u8] = b"added_column";
+pub const REMOVED_COLUMN: &[u8] = b"removed_column";
+pub const MOVED_COLUMN: &[u8] = b"moved_column";
+pub const ADDED: &[u8] = b"added";
+pub const REMOVED: &[u8] = b"removed";
+pub const MODIFIED: &[u8] = b"modified";
+
+pub fn diff_headers(key_names: &[&[u8]]) -> csv::ByteRecord {
+    let mut headers = csv::ByteRecord::new();
+    headers.push_field(b"diff");
+
+    for name in key_names {
+        headers.push_field(name);
+    }
+
+    headers.push_field(b"column");
+    headers.push_field(b"old");
+    headers.push_field(b"new");
+
+    headers
+}
+
+struct DiffWriter<W: std::io::Write> {
+    writer: csv::Writer<W>,
+    key_len: usize,
+    record: csv::ByteRecord,
+}
+
+impl<W: std::io::Write> DiffWriter<W> {
+    fn write(
+        &mut self,
+        op: &[u8],
+        key: Option<&Key>,
+        column: &[u8],
+        old: &[u8],
+        new: &[u8],
+    ) -> CliResult<()> {
+        self.record.clear();
+        self.record.push_field(op);
+
+        match key {
+            Some(key) => {
+                for cell in key {
+                    self.record.push_field(cell);
+                }
+            }
+            None => {
+                for _ in 0..self.key_len {
+                    self.record.push_field(b"");
+                }
+            }
+        }
+
+        self.record.push_field(column);
+        self.record.push_field(old);
+        self.record.push_field(new);
+
+        Ok(self.writer.write_byte_record(&self.record)?)
+    }
+}
+
+// NOTE: this struct knows how to compare rows of the old and new file, whose
+// columns might differ.
+struct RowComparator {
+    new_headers: csv::ByteRecord,
+    // For each non-key column of the new file: its index & its index in the old file, if any
+    columns: Vec<(usize, Option<usize>)>,
+}
+
+impl RowComparator {
+    fn new(
+        old_headers: &csv::ByteRecord,
+        new_headers: &csv::ByteRecord,
+        new_key_sel: &Selection,
+    ) -> Self {
+        let columns = new_headers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !new_key_sel.contains(*i))
+            .map(|(i, name)| (i, old_headers.iter().position(|h| h == name)))
+            .collect();
+
+        Self {
+            new_headers: new_headers.clone(),
+            columns,
+        }
+    }
+
+    fn write_added<W: std::io::Write>(
+        &self,
+        wtr: &mut DiffWriter<W>,
+        key: &Key,
+        new_record: &csv::ByteRecord,
+    ) -> CliResult<()> {
+        // NOTE: we still need to report something if the key spans all columns
+        if self.columns.is_empty() {
+            return wtr.write(ADDED, Some(key), b"", b"", b"");
+        }
+
+        for (i, _) in self.columns.iter() {
+            wtr.write(
+                ADDED,
+                Some(key),
+                &self.new_headers[*i],
+                b"",
+                &new_record[*i],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_modified<W: std::io::Write>(
+        &self,
+        wtr: &mut DiffWriter<W>,
+        key: &Key,
+        old_record: &csv::ByteRecord,
+        new_record: &csv::ByteRecord,
+    ) -> CliResult<()> {
+        for (i, old_i) in self.columns.iter() {
+            let old_cell = old_i.map(|j| &old_record[j]).unwrap_or(b"");
+            let new_cell = &new_record[*i];
+
+            if old_cell != new_cell {
+                wtr.write(
+                    MODIFIED,
+                    Some(key),
+                    &self.new_headers[*i],
+                    old_cell,
+                    new_cell,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_header_changes<W: std::io::Write>(
+    wtr: &mut DiffWriter<W>,
+    old_headers: &csv::ByteRecord,
+    new_headers: &csv::ByteRecord,
+) -> CliResult<()> {
+    for (i, name) in old_headers.iter().enumerate() {
+        if !new_headers.iter().any(|h| h == name) {
+            wtr.write(REMOVED_COLUMN, None, name, i.to_string().as_bytes(), b"")?;
+        }
+    }
+
+    for (i, name) in new_headers.iter().enumerate() {
+        match old_headers.iter().position(|h| h == name) {
+            None => wtr.write(ADDED_COLUMN, None, name, b"", i.to_string().as_bytes())?,
+            Some(j) if j != i => wtr.write(
+                MOVED_COLUMN,
+                None,
+                name,
+                j.to_string().as_bytes(),
+                i.to_string().as_bytes(),
+            )?,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn format_key(key: &Key) -> String {
+    key.iter()
+        .map(|cell| format!("\"{}\"", String::from_utf8_lossy(cell)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn duplicate_key_error(path: &str, key: &Key) -> String {
+    format!("key {} is not unique in {}!", format_key(key), path)
+}
+
+// NOTE: a stream of records from a file sorted on some key, checking that
+// the file is sorted & that keys are unique along the way.
+pub struct SortedRecords<R: std::io::Read> {
+    path: String,
+    reader: csv::Reader<R>,
+    sel: Selection,
+    current: Option<(Key, csv::ByteRecord)>,
+}
+
+impl<R: std::io::Read> SortedRecords<R> {
+    pub fn new(path: &str, reader: csv::Reader<R>, sel: Selection) -> CliResult<Self> {
+        let mut records = Self {
+            path: path.to_string(),
+            reader,
+            sel,
+            current: None,
+        };
+
+        records.advance()?;
+
+        Ok(records)
+    }
+
+    pub fn peek_key(&self) -> Option<&Key> {
+        self.current.as_ref().map(|(key, _)| key)
+    }
+
+    pub fn advance(&mut self) -> CliResult<Option<(Key, csv::ByteRecord)>> {
+        let mut record = csv::ByteRecord::new();
+
+        let next = if self.reader.read_byte_record(&mut record)? {
+            let key = self.sel.collect(&record);
+
+            if let Some((last_key, _)) = &self.current {
+                match last_key.cmp(&key) {
+                    Ordering::Greater => Err(format!(
+                        "{} is not sorted on the key! Sort it using `xan sort -s <key>` first.",
+                        self.path
+                    ))?,
+                    Ordering::Equal => Err(duplicate_key_error(&self.path, &key))?,
+                    Ordering::Less => (),
+                };
+            }
+
+            Some((key, record))
+        } else {
+            None
+        };
+
+        Ok(std::mem::replace(&mut self.current, next))
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let old_conf = Config::new(&Some(args.arg_old.clone()))
+        .delimiter(args.flag_delimiter)
+        .select(args.arg_key.clone());
+    let new_conf = Config::new(&Some(args.arg_new.clone()))
+        .delimiter(args.flag_delimiter)
+        .select(args.arg_key.clone());
+
+    let mut old_rdr = old_conf.reader()?;
+    let mut new_rdr = new_conf.reader()?;
+
+    let old_headers = old_rdr.byte_headers()?.clone();
+    let new_headers = new_rdr.byte_headers()?.clone();
+
+    let old_sel = old_conf.selection(&old_headers)?;
+    let new_sel = new_conf.selection(&new_headers)?;
+
+    let old_key_names = old_sel.select(&old_headers).collect::<Vec<_>>();
+    let new_key_names = new_sel.select(&new_headers).collect::<Vec<_>>();
+
+    if old_key_names != new_key_names {
+        Err("key columns should be the same in both files!")?;
+    }
+
+    let mut wtr = DiffWriter {
+        writer: Config::new(&args.flag_output).writer()?,
+        key_len: new_sel.len(),
+        record: csv::ByteRecord::new(),
+    };
+
+    wtr.writer
+        .write_byte_record(&diff_headers(&new_key_names))?;
+
+    write_header_changes(&mut wtr, &old_headers, &new_headers)?;
+
+    let comparator = RowComparator::new(&old_headers, &new_headers, &new_sel);
+
+    if args.flag_sorted {
+        let mut old_records = SortedRecords::new(&args.arg_old, old_rdr, old_sel)?;
+        let mut new_records = SortedRecords::new(&args.arg_new, new_rdr, new_sel)?;
+
+        loop {
+            let ordering = match (old_records.peek_key(), new_records.peek_key()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(old_key), Some(new_key)) => old_key.cmp(new_key),
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    let (key, _) = old_records.advance()?.unwrap();
+                    wtr.write(REMOVED, Some(&key), b"", b"", b"")?;
+                }
+                Ordering::Greater => {
+                    let (key, new_record) = new_records.advance()?.unwrap();
+                    comparator.write_added(&mut wtr, &key, &new_record)?;
+                }
+                Ordering::Equal => {
+                    let (key, old_record) = old_records.advance()?.unwrap();
+                    let (_, new_record) = new_records.advance()?.unwrap();
+                    comparator.write_modified(&mut wtr, &key, &old_record, &new_record)?;
+                }
+            }
+        }
+
+        return Ok(wtr.writer.flush()?);
+    }
+
+    // NOTE: the boolean tracks whether the row was found in the new file
+    let mut index: IndexMap<Key, (csv::ByteRecord, bool), RandomState> = IndexMap::default();
+
+    for result in old_rdr.into_byte_records() {
+        let record = result?;
+        let key = old_sel.collect(&record);
+
+        if index.contains_key(&key) {
+            Err(duplicate_key_error(&args.arg_old, &key))?;
+        }
+
+        index.insert(key, (record, false));
+    }
+
+    // NOTE: keys of added rows must also be tracked to check their uniqueness
+    let mut added_keys: HashSet<Key> = HashSet::new();
+
+    for result in new_rdr.into_byte_records() {
+        let new_record = result?;
+        let key = new_sel.collect(&new_record);
+
+        match index.get_mut(&key) {
+            None => {
+                if added_keys.contains(&key) {
+                    Err(duplicate_key_error(&args.arg_new, &key))?;
+                }
+
+                comparator.write_added(&mut wtr, &key, &new_record)?;
+                added_keys.insert(key);
+            }
+            Some((old_record, seen)) => {
+                if *seen {
+                    Err(duplicate_key_error(&args.arg_new, &key))?;
+                }
+
+                *seen = true;
+
+                comparator.write_modified(&mut wtr, &key, old_record, &new_record)?;
+            }
+        }
+    }
+
+    for (key, (_, seen)) in index.iter() {
+        if !seen {
+            wtr.write(REMOVED, Some(key), b"", b"", b"")?;
+        }
+    }
+
+    Ok(wtr.writer.flush()?)
+}
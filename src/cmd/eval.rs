@@ -2,7 +2,7 @@ use std::io::{self, stdout, Write};
 
 use colored::Colorize;
 
-use crate::moonblade::Program;
+use crate::moonblade::{load_prelude, Program};
 use crate::util;
 use crate::CliResult;
 
@@ -18,6 +18,7 @@ eval options:
     -E, --explain          Print concrete expression plan.
     -H, --headers <names>  Pretend headers, separated by commas, to consider.
     -R, --row <values>     Pretend row with comma-separated cells.
+    --prelude <path>       Load user-defined functions from this file.
 
 Common options:
     -h, --help  Display this message
@@ -30,11 +31,16 @@ struct Args {
     flag_explain: bool,
     flag_headers: Option<String>,
     flag_row: Option<String>,
+    flag_prelude: Option<String>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
+    if let Some(path) = &args.flag_prelude {
+        load_prelude(path)?;
+    }
+
     let mut out = stdout();
 
     let mut dummy_headers = csv::ByteRecord::new();
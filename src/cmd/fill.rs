@@ -1,8 +1,40 @@
+use std::convert::TryFrom;
+
+use jiff::{tz::TimeZone, Unit, Zoned};
+
+use crate::collections::HashMap;
 use crate::config::{Config, Delimiter};
+use crate::dates::{format_zoned, next_zoned, parse_temporal_value, TemporalUnit};
+use crate::moonblade::DynamicValue;
 use crate::select::SelectColumns;
 use crate::util;
 use crate::CliResult;
 
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "String")]
+enum FillMethod {
+    Forward,
+    Backward,
+    Nearest,
+    Linear,
+    Spline,
+}
+
+impl TryFrom<String> for FillMethod {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "forward" => Self::Forward,
+            "backward" => Self::Backward,
+            "nearest" => Self::Nearest,
+            "linear" => Self::Linear,
+            "spline" => Self::Spline,
+            _ => return Err(format!("unsupported --method \"{}\"", &value)),
+        })
+    }
+}
+
 static USAGE: &str = "
 Fill empty cells of a CSV file by filling them with any non-empty value seen
 before (this is usually called forward filling), or with any constant value
@@ -12,14 +44,57 @@ For instance, replacing empty values with 0 everywhere in the file:
 
     $ xan fill -v 0 data.csv > filled.csv
 
+Other filling methods can be selected using the -m, --method flag:
+
+    - \"forward\": use last non-empty value (default).
+    - \"backward\": use next non-empty value.
+    - \"nearest\": use closest non-empty value.
+    - \"linear\": linearly interpolate numbers from surrounding non-empty values.
+    - \"spline\": interpolate numbers using a natural cubic spline going through
+      all the non-empty values.
+
+Values can be filled independently per group of rows using -g, --groupby, and
+interpolation can use a column of dates or datetimes given to -t, --time as x axis,
+instead of the position of rows.
+
+Finally, the -S, --spine flag can be used to generate the missing rows of a
+time series before filling them. For instance, given a file of daily
+measurements sorted by date, and where some days are missing:
+
+    $ xan fill -t date --spine -m linear -s temperature data.csv
+
+Times are understood in the time zone of the first one found in the file:
+times lacking a time zone are parsed in it and others are converted to it.
+
+Note that, except when simply forward filling or filling with a constant value,
+the whole file will be buffered into memory.
+
 Usage:
     xan fill [options] [<input>]
     xan fill --help
 
 fill options:
-    -s, --select <cols>  Selection of columns to fill.
-    -v, --value <value>  Fill empty cells using provided value instead of using
-                         last non-empty value.
+    -s, --select <cols>    Selection of columns to fill.
+    -v, --value <value>    Fill empty cells using provided value instead of using
+                           last non-empty value. When used with -m, --method, will
+                           fill the cells that the method could not fill instead,
+                           e.g. before the first non-empty value.
+    -m, --method <method>  Method used to fill empty cells. Must be one of \"forward\",
+                           \"backward\", \"nearest\", \"linear\" or \"spline\".
+                           Will default to \"forward\".
+    -g, --groupby <cols>   Fill empty cells independently for each group of rows
+                           identified by given selection.
+    -t, --time <column>    Column containing dates or datetimes, to be used as
+                           x axis by the \"nearest\", \"linear\" & \"spline\" methods.
+    -S, --spine            Generate rows for dates or datetimes missing from the
+                           sequence found in the column given to -t, --time,
+                           before filling them. Rows must be sorted by time
+                           (within each group when using -g, --groupby).
+    -u, --unit <unit>      Time step used to generate rows with -S, --spine.
+                           Must be one of \"years\", \"months\", \"days\",
+                           \"hours\", \"minutes\" or \"seconds\". Defaults to the
+                           precision of partial dates such as \"2024\", \"2024-01\"
+                           or \"2024-01-01\".
 
 Common options:
     -h, --help             Display this message
@@ -35,35 +110,434 @@ struct Args {
     arg_input: Option<String>,
     flag_select: SelectColumns,
     flag_value: Option<String>,
+    flag_method: Option<FillMethod>,
+    flag_groupby: Option<SelectColumns>,
+    flag_time: Option<SelectColumns>,
+    flag_spine: bool,
+    flag_unit: Option<TemporalUnit>,
     flag_no_headers: bool,
     flag_delimiter: Option<Delimiter>,
     flag_output: Option<String>,
 }
 
+// NOTE: every time is converted to the time zone of the first one, so that
+// the spine remains aligned when the file mixes time zones.
+fn parse_time(cell: &[u8], timezone: &mut Option<TimeZone>) -> CliResult<(Zoned, Option<Unit>)> {
+    let (time, precision) = std::str::from_utf8(cell)
+        .ok()
+        .and_then(|string| parse_temporal_value(string, timezone.as_ref()))
+        .ok_or_else(|| {
+            format!(
+                "could not parse \"{}\" as a date!",
+                String::from_utf8_lossy(cell)
+            )
+        })?;
+
+    if timezone.is_none() {
+        *timezone = Some(time.time_zone().clone());
+    }
+
+    Ok((time, precision))
+}
+
+fn parse_number(cell: &[u8]) -> CliResult<f64> {
+    fast_float::parse::<f64, &[u8]>(cell).map_err(|_| {
+        format!(
+            "could not parse \"{}\" as a number to interpolate!",
+            String::from_utf8_lossy(cell)
+        )
+        .into()
+    })
+}
+
+fn format_number(number: f64) -> Vec<u8> {
+    DynamicValue::from(number).serialize_as_bytes().into_owned()
+}
+
+// NOTE: natural cubic spline, solved using the Thomas algorithm.
+// Returns the second derivatives at each point.
+fn spline_second_derivatives(points: &[(f64, f64)]) -> Vec<f64> {
+    let n = points.len();
+    let mut m = vec![0.0; n];
+
+    if n < 3 {
+        return m;
+    }
+
+    let mut c_prime = vec![0.0; n];
+    let mut d_prime = vec![0.0; n];
+
+    for i in 1..n - 1 {
+        let h0 = points[i].0 - points[i - 1].0;
+        let h1 = points[i + 1].0 - points[i].0;
+
+        let a = h0 / 6.0;
+        let b = (h0 + h1) / 3.0;
+        let c = h1 / 6.0;
+        let d = (points[i + 1].1 - points[i].1) / h1 - (points[i].1 - points[i - 1].1) / h0;
+
+        let denominator = b - a * c_prime[i - 1];
+
+        c_prime[i] = c / denominator;
+        d_prime[i] = (d - a * d_prime[i - 1]) / denominator;
+    }
+
+    for i in (1..n - 1).rev() {
+        m[i] = d_prime[i] - c_prime[i] * m[i + 1];
+    }
+
+    m
+}
+
+fn evaluate_spline(points: &[(f64, f64)], m: &[f64], k: usize, x: f64) -> f64 {
+    let (x0, y0) = points[k];
+    let (x1, y1) = points[k + 1];
+    let h = x1 - x0;
+
+    let a = (x1 - x) / h;
+    let b = (x - x0) / h;
+
+    a * y0 + b * y1 + ((a.powi(3) - a) * m[k] + (b.powi(3) - b) * m[k + 1]) * h * h / 6.0
+}
+
+// NOTE: fills given column of a sequence of rows, whose x coordinates are given,
+// and returns the cells that could be filled.
+fn fill_column(
+    method: FillMethod,
+    records: &[csv::ByteRecord],
+    rows: &[usize],
+    xs: &[f64],
+    column: usize,
+) -> CliResult<Vec<(usize, Vec<u8>)>> {
+    let known = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| !records[**row][column].is_empty())
+        .map(|(k, _)| k)
+        .collect::<Vec<_>>();
+
+    let mut filled = Vec::new();
+
+    if known.is_empty() {
+        return Ok(filled);
+    }
+
+    let numbers = match method {
+        FillMethod::Linear | FillMethod::Spline => known
+            .iter()
+            .map(|k| parse_number(&records[rows[*k]][column]))
+            .collect::<CliResult<Vec<_>>>()?,
+        _ => vec![],
+    };
+
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    let mut point_indices: Vec<usize> = Vec::new();
+
+    if method == FillMethod::Spline {
+        for (i, k) in known.iter().enumerate() {
+            if points.last().is_some_and(|(x, _)| *x >= xs[*k]) {
+                continue;
+            }
+
+            points.push((xs[*k], numbers[i]));
+            point_indices.push(*k);
+        }
+    }
+
+    let second_derivatives = spline_second_derivatives(&points);
+
+    // Index, in `known`, of the first known row coming after current one
+    let mut next = 0;
+
+    for (k, row) in rows.iter().enumerate() {
+        if next < known.len() && known[next] == k {
+            next += 1;
+            continue;
+        }
+
+        let before = next.checked_sub(1);
+        let after = (next < known.len()).then_some(next);
+
+        let cell = match method {
+            FillMethod::Forward => before.map(|i| records[rows[known[i]]][column].to_vec()),
+            FillMethod::Backward => after.map(|i| records[rows[known[i]]][column].to_vec()),
+            FillMethod::Nearest => {
+                let i = match (before, after) {
+                    (Some(b), Some(a)) => {
+                        if xs[k] - xs[known[b]] <= xs[known[a]] - xs[k] {
+                            b
+                        } else {
+                            a
+                        }
+                    }
+                    (Some(b), None) => b,
+                    (None, Some(a)) => a,
+                    (None, None) => unreachable!(),
+                };
+
+                Some(records[rows[known[i]]][column].to_vec())
+            }
+            FillMethod::Linear => match (before, after) {
+                (Some(b), Some(a)) => {
+                    let (x0, y0) = (xs[known[b]], numbers[b]);
+                    let (x1, y1) = (xs[known[a]], numbers[a]);
+
+                    let y = if x1 == x0 {
+                        y0
+                    } else {
+                        y0 + (y1 - y0) * (xs[k] - x0) / (x1 - x0)
+                    };
+
+                    Some(format_number(y))
+                }
+                _ => None,
+            },
+            FillMethod::Spline => match (before, after) {
+                (Some(_), Some(_)) if points.len() > 1 => {
+                    let x = xs[k];
+                    let segment = point_indices
+                        .partition_point(|i| xs[*i] <= x)
+                        .clamp(1, points.len() - 1)
+                        - 1;
+
+                    Some(format_number(evaluate_spline(
+                        &points,
+                        &second_derivatives,
+                        segment,
+                        x,
+                    )))
+                }
+                _ => None,
+            },
+        };
+
+        if let Some(cell) = cell {
+            filled.push((*row, cell));
+        }
+    }
+
+    Ok(filled)
+}
+
+fn run_buffered(
+    args: &Args,
+    mut rdr: csv::Reader<Box<dyn std::io::Read + Send>>,
+    mut wtr: csv::Writer<Box<dyn std::io::Write + Send>>,
+    headers: &csv::ByteRecord,
+    mask: &[bool],
+) -> CliResult<()> {
+    let groupby_sel_opt = args
+        .flag_groupby
+        .clone()
+        .map(|s| s.selection(headers, !args.flag_no_headers))
+        .transpose()?;
+
+    let time_column_opt = args
+        .flag_time
+        .clone()
+        .map(|s| s.single_selection(headers, !args.flag_no_headers))
+        .transpose()?;
+
+    // NOTE: time & group columns are never filled
+    let columns = mask
+        .iter()
+        .enumerate()
+        .filter_map(|(i, should_fill)| {
+            let is_excluded = Some(i) == time_column_opt
+                || groupby_sel_opt.as_ref().is_some_and(|sel| sel.contains(i));
+
+            (*should_fill && !is_excluded).then_some(i)
+        })
+        .collect::<Vec<_>>();
+
+    // NOTE: records are sorted at the end using a key made of the index of the
+    // original row they precede, so that generated rows end up at the right place.
+    let mut records: Vec<csv::ByteRecord> = Vec::new();
+    let mut sort_keys: Vec<(usize, usize)> = Vec::new();
+    let mut xs: Vec<f64> = Vec::new();
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_ids: HashMap<Vec<Vec<u8>>, usize> = HashMap::new();
+    let mut last_times: Vec<Option<Zoned>> = Vec::new();
+    let mut timezone: Option<TimeZone> = None;
+
+    let mut record = csv::ByteRecord::new();
+    let mut index: usize = 0;
+
+    while rdr.read_byte_record(&mut record)? {
+        let group_id = match &groupby_sel_opt {
+            None => 0,
+            Some(sel) => {
+                let next_id = group_ids.len();
+
+                *group_ids.entry(sel.collect(&record)).or_insert(next_id)
+            }
+        };
+
+        if group_id == groups.len() {
+            groups.push(Vec::new());
+            last_times.push(None);
+        }
+
+        let time_opt = time_column_opt
+            .map(|column| parse_time(&record[column], &mut timezone))
+            .transpose()?;
+
+        if let Some((time, precision)) = &time_opt {
+            if args.flag_spine {
+                if let Some(last_time) = &last_times[group_id] {
+                    if time < last_time {
+                        Err(format!(
+                            "rows must be sorted by time when using --spine, but found \"{}\" after a later value!",
+                            String::from_utf8_lossy(&record[time_column_opt.unwrap()])
+                        ))?;
+                    }
+
+                    let unit = match args.flag_unit {
+                        Some(unit) => unit.into_inner(),
+                        None => precision
+                            .ok_or("-u, --unit must be given when times are not partial dates!")?,
+                    };
+
+                    let mut current = next_zoned(last_time, unit)?;
+                    let mut rank: usize = 0;
+
+                    while &current < time {
+                        let mut generated_record = csv::ByteRecord::new();
+
+                        for i in 0..record.len() {
+                            let is_group_column =
+                                groupby_sel_opt.as_ref().is_some_and(|sel| sel.contains(i));
+
+                            if Some(i) == time_column_opt {
+                                generated_record
+                                    .push_field(format_zoned(&current, unit).as_bytes());
+                            } else if is_group_column {
+                                generated_record.push_field(&record[i]);
+                            } else {
+                                generated_record.push_field(b"");
+                            }
+                        }
+
+                        groups[group_id].push(records.len());
+                        records.push(generated_record);
+                        sort_keys.push((index, rank));
+                        xs.push(current.timestamp().as_millisecond() as f64);
+
+                        current = next_zoned(&current, unit)?;
+                        rank += 1;
+                    }
+                }
+
+                last_times[group_id] = Some(time.clone());
+            }
+        }
+
+        groups[group_id].push(records.len());
+        records.push(record.clone());
+        sort_keys.push((index, usize::MAX));
+        xs.push(match &time_opt {
+            Some((time, _)) => time.timestamp().as_millisecond() as f64,
+            None => 0.0,
+        });
+
+        index += 1;
+    }
+
+    let method = args.flag_method.unwrap_or(FillMethod::Forward);
+    let mut group_xs: Vec<f64> = Vec::new();
+
+    for rows in groups.iter() {
+        group_xs.clear();
+
+        if time_column_opt.is_some() {
+            group_xs.extend(rows.iter().map(|row| xs[*row]));
+        } else {
+            group_xs.extend((0..rows.len()).map(|k| k as f64));
+        }
+
+        for column in columns.iter().copied() {
+            let filled = if args.flag_method.is_some() || args.flag_value.is_none() {
+                fill_column(method, &records, rows, &group_xs, column)?
+            } else {
+                vec![]
+            };
+
+            for (row, cell) in filled {
+                let mut new_record = csv::ByteRecord::new();
+
+                for (i, current_cell) in records[row].iter().enumerate() {
+                    new_record.push_field(if i == column { &cell } else { current_cell });
+                }
+
+                records[row] = new_record;
+            }
+        }
+    }
+
+    let mut order = (0..records.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| sort_keys[*i]);
+
+    for i in order {
+        let record = &records[i];
+
+        match &args.flag_value {
+            Some(value) => {
+                wtr.write_record(record.iter().enumerate().map(|(j, cell)| {
+                    if cell.is_empty() && columns.contains(&j) {
+                        value.as_bytes()
+                    } else {
+                        cell
+                    }
+                }))?;
+            }
+            None => {
+                wtr.write_byte_record(record)?;
+            }
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let rconf = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select);
+        .select(args.flag_select.clone());
+
+    if args.flag_spine && args.flag_time.is_none() {
+        Err("--spine requires -t, --time!")?;
+    }
 
     let mut rdr = rconf.reader()?;
     let mut wtr = Config::new(&args.flag_output).writer()?;
 
-    let headers = rdr.byte_headers()?;
+    let headers = rdr.byte_headers()?.clone();
 
-    let sel = rconf.selection(headers)?;
+    let sel = rconf.selection(&headers)?;
     let mask = sel.mask(headers.len());
 
     rconf.write_headers(&mut rdr, &mut wtr)?;
 
+    let requires_buffering = args.flag_groupby.is_some()
+        || args.flag_spine
+        || args
+            .flag_method
+            .is_some_and(|method| method != FillMethod::Forward);
+
+    if requires_buffering {
+        return run_buffered(&args, rdr, wtr, &headers, &mask);
+    }
+
     let mut previous: Option<csv::ByteRecord> = None;
 
     for result in rdr.byte_records() {
         let record = result?;
 
         // Default value
-        if let Some(value) = &args.flag_value {
+        if let (Some(value), None) = (&args.flag_value, args.flag_method) {
             wtr.write_record(mask.iter().copied().enumerate().map(|(i, should_fill)| {
                 let current_cell = &record[i];
 
@@ -77,8 +551,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         else {
             match previous.as_mut() {
                 None => {
-                    wtr.write_byte_record(&record)?;
-                    previous = Some(record);
+                    let filled_record = match &args.flag_value {
+                        None => record,
+                        Some(value) => mask
+                            .iter()
+                            .enumerate()
+                            .map(|(i, should_fill)| {
+                                let current_cell = &record[i];
+
+                                match (should_fill, current_cell.is_empty()) {
+                                    (true, true) => value.as_bytes(),
+                                    _ => current_cell,
+                                }
+                            })
+                            .collect::<csv::ByteRecord>(),
+                    };
+
+                    wtr.write_byte_record(&filled_record)?;
+                    previous = Some(filled_record);
                 }
                 Some(previous_record) => {
                     let filled_record = mask
@@ -3,7 +3,7 @@ use std::fs;
 use pariter::IteratorExt;
 
 use crate::config::{Config, Delimiter};
-use crate::moonblade::Program;
+use crate::moonblade::{load_prelude, Program};
 use crate::util;
 use crate::CliResult;
 
@@ -43,6 +43,9 @@ Usage:
 
 filter options:
     -f, --evaluate-file        Read evaluation expression from a file instead.
+    --prelude <path>           Load user-defined functions from this file. Defaults to
+                               the file given by the XAN_PRELUDE environment variable,
+                               if any.
     -v, --invert-match         If set, will invert the evaluated value.
     -l, --limit <n>            Maximum number of rows to return. Useful to avoid downstream
                                buffering some times (e.g. when searching for very few
@@ -76,6 +79,7 @@ struct Args {
     flag_threads: Option<usize>,
     flag_invert_match: bool,
     flag_evaluate_file: bool,
+    flag_prelude: Option<String>,
 }
 
 impl Args {
@@ -84,6 +88,10 @@ impl Args {
             self.arg_expression = fs::read_to_string(&self.arg_expression)?;
         }
 
+        if let Some(path) = &self.flag_prelude {
+            load_prelude(path)?;
+        }
+
         Ok(())
     }
 }
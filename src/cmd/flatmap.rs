@@ -3,7 +3,7 @@ use std::fs;
 use pariter::IteratorExt;
 
 use crate::config::{Config, Delimiter};
-use crate::moonblade::Program;
+use crate::moonblade::{load_prelude, Program};
 use crate::select::SelectColumns;
 use crate::util::{self, ImmutableRecordHelpers};
 use crate::CliResult;
@@ -83,6 +83,9 @@ Usage:
 
 flatmap options:
     -f, --evaluate-file        Read evaluation expression from a file instead.
+    --prelude <path>           Load user-defined functions from this file. Defaults to
+                               the file given by the XAN_PRELUDE environment variable,
+                               if any.
     -r, --replace <column>     Name of the column that will be replaced by the mapped values.
     -p, --parallel             Whether to use parallelization to speed up computations.
                                Will automatically select a suitable number of threads to use
@@ -112,6 +115,7 @@ struct Args {
     flag_threads: Option<usize>,
     flag_replace: Option<SelectColumns>,
     flag_evaluate_file: bool,
+    flag_prelude: Option<String>,
 }
 
 impl Args {
@@ -120,6 +124,10 @@ impl Args {
             self.arg_expression = fs::read_to_string(&self.arg_expression)?;
         }
 
+        if let Some(path) = &self.flag_prelude {
+            load_prelude(path)?;
+        }
+
         Ok(())
     }
 }
@@ -1,13 +1,21 @@
 use std::convert::TryFrom;
 use std::num::NonZeroUsize;
 use std::{
+    fmt::Write as FmtWrite,
     fs,
-    io::{self, BufRead, BufReader, Cursor, Read},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek},
     path::Path,
 };
 
-use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader as ArrowFileReader, StreamReader as ArrowStreamReader};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use bytes::Bytes;
+use calamine::{open_workbook_auto_from_rs, Data, Dimensions, Range, Reader, Sheets};
 use flate2::read::MultiGzDecoder;
+use quick_xml::events::{BytesStart, Event};
+use scraper::{ElementRef, Html, Selector};
 use serde_json::{Map, Value};
 
 use crate::config::Config;
@@ -26,6 +34,10 @@ enum SupportedFormat {
     Npy,
     Tar,
     Md,
+    Parquet,
+    Arrow,
+    Html,
+    Xml,
 }
 
 impl SupportedFormat {
@@ -38,6 +50,10 @@ impl SupportedFormat {
             "npy" => Self::Npy,
             "tar" | "tar.gz" => Self::Tar,
             "md" | "markdown" => Self::Md,
+            "parquet" => Self::Parquet,
+            "arrow" | "ipc" | "feather" => Self::Arrow,
+            "html" | "htm" => Self::Html,
+            "xml" => Self::Xml,
             _ => return None,
         })
     }
@@ -78,9 +94,15 @@ Supported formats:
     - npy: numpy array
     - tar: tarball archive
     - md, markdown: Markdown table
+    - parquet: Apache Parquet file
+    - arrow, ipc, feather: Apache Arrow IPC file or stream
+    - html, htm: HTML table
+    - xml: XML document
 
 Some formats can be streamed, some others require the full file to be loaded into
-memory. The streamable formats are `ndjson`, `jsonl`, `tar`, `txt` and `npy`.
+memory. The streamable formats are `ndjson`, `jsonl`, `tar`, `txt`, `npy`, `parquet`
+and `arrow`. Note that `parquet` and Arrow IPC files (not streams) need to be
+loaded into memory when read from stdin.
 
 Some formats will handle gzip decompression on the fly if the filename ends
 in `.gz`: `json`, `ndjson`, `jsonl` and `txt`.
@@ -88,6 +110,19 @@ in `.gz`: `json`, `ndjson`, `jsonl` and `txt`.
 Tarball extraction was designed for utf8-encoded text files. Expect weird or
 broken results with other encodings or binary files.
 
+When converting HTML tables, cells spanning multiple rows or columns (through
+their `rowspan` & `colspan` attributes) are repeated in every cell they cover.
+Leading rows containing only `<th>` cells are used as headers, and are joined
+together if there are several of them. Else, headers are generated.
+
+When converting XML, the --row-path flag must indicate which elements should
+be converted as rows. Columns are then named after the path of descendant
+elements relative to the row, e.g. \"author/name\", or \"@id\" for attributes.
+Repeated elements are joined using \"|\". As for JSON, columns are inferred by
+sampling the first rows using --sample-size.
+
+    $ xan from -f xml --row-path channel/item feed.rss
+
 from options:
     -f, --format <format>  Format to convert from. Will be inferred from file
                            extension if not given. Must be specified when reading
@@ -95,12 +130,28 @@ from options:
                            work with.
 
 Excel/OpenOffice-related options:
-    --sheet-index <i>    0-based index of the sheet to convert. Defaults to converting
-                         the first sheet. Use -s/--sheet alternatively to select a
-                         sheet by name.
-                         [default: 0]
-    --sheet-name <name>  Name of the sheet to convert.
-    --list-sheets        Print sheet names instead of converting file.
+    --sheet-index <i>      0-based index of the sheet to convert. Defaults to converting
+                           the first sheet. Use --sheet-name alternatively to select a
+                           sheet by name.
+                           [default: 0]
+    --sheet-name <name>    Name of the sheet to convert.
+    --list-sheets          Print sheet names instead of converting file.
+    --all-sheets           Convert all the sheets of the workbook at once. Sheets will
+                           be concatenated, with an additional column containing the
+                           name of their sheet, and must therefore have the same
+                           headers. Use --output-dir to write one CSV file per
+                           sheet instead.
+    --sheet-column <name>  Name of the column containing the sheet names, when
+                           concatenating all sheets.
+                           [default: sheet]
+    --output-dir <dir>     Write every sheet of the workbook as a CSV file, named
+                           after the sheet, in the given directory.
+    --skip-rows <n>        Number of rows to skip at the top of the sheets, before
+                           the header row.
+                           [default: 0]
+    --fill-merged          Repeat the value of merged cells across all the cells
+                           they span, instead of leaving them empty. Only works
+                           with xlsx & xls files.
 
 JSON options:
     --sample-size <n>      Number of records to sample before emitting headers.
@@ -114,10 +165,20 @@ Text lines options:
     -c, --column <name>    Name of the column to create.
                            [default: value]
 
-Markdown options:
-    -n, --nth-table <n>    Select nth table in document, starting at 0.
-                           Negative index can be used to select from the end.
-                           [default: 0]
+Markdown & HTML options:
+    -n, --nth-table <n>     Select nth table in document, starting at 0.
+                            Negative index can be used to select from the end.
+                            [default: 0]
+    --table-selector <css>  CSS selector used to find tables in an HTML document.
+                            Use --nth-table to select one of them if the selector
+                            matches multiple tables.
+                            [default: table]
+
+XML options:
+    --row-path <path>  Slash-separated path of the XML elements to convert as
+                       rows, e.g. \"channel/item\". The path is matched against
+                       the end of the ancestry of elements, unless it starts
+                       with a slash, in which case it must match from the root.
 
 Common options:
     -h, --help             Display this message
@@ -130,6 +191,11 @@ struct Args {
     flag_sheet_index: usize,
     flag_sheet_name: Option<String>,
     flag_list_sheets: bool,
+    flag_all_sheets: bool,
+    flag_sheet_column: String,
+    flag_output_dir: Option<String>,
+    flag_skip_rows: usize,
+    flag_fill_merged: bool,
     flag_format: Option<SupportedFormat>,
     flag_output: Option<String>,
     flag_sample_size: NonZeroUsize,
@@ -137,6 +203,8 @@ struct Args {
     flag_value_column: String,
     flag_column: String,
     flag_nth_table: isize,
+    flag_table_selector: String,
+    flag_row_path: Option<String>,
 }
 
 impl Args {
@@ -170,19 +238,20 @@ impl Args {
             return Ok(());
         }
 
-        let mut wtr = self.writer()?;
-        let mut record = csv::StringRecord::new();
+        if self.flag_all_sheets || self.flag_output_dir.is_some() {
+            return self.convert_all_sheets(&mut workbook);
+        }
 
         let range = match &self.flag_sheet_name {
             Some(name) => Some(workbook.worksheet_range(name)),
             None => workbook.worksheet_range_at(self.flag_sheet_index),
         };
 
-        match range {
+        let mut range = match range {
             None => {
                 let sheets = workbook.sheet_names().len();
 
-                Err(format!(
+                return Err(format!(
                     "--sheet-index {} is out-of-bounds (number of sheets: {})!",
                     self.flag_sheet_index, sheets
                 ))?;
@@ -190,38 +259,127 @@ impl Args {
             Some(Err(_)) => {
                 let sheets = workbook.sheet_names().join(", ");
 
-                Err(format!(
+                return Err(format!(
                     "could not find the \"{}\" sheet\nshould be one of: {}",
                     self.flag_sheet_name.as_ref().unwrap(),
                     sheets
                 ))?;
             }
-            Some(Ok(range)) => {
-                for row in range.rows() {
-                    record.clear();
+            Some(Ok(range)) => range,
+        };
 
-                    for cell in row {
-                        match cell {
-                            Data::String(value) => record.push_field(value),
-                            Data::DateTimeIso(value) => record.push_field(value),
-                            Data::DurationIso(value) => record.push_field(value),
-                            Data::Bool(value) => {
-                                record.push_field(if *value { "true" } else { "false" })
+        if self.flag_fill_merged {
+            let name = match &self.flag_sheet_name {
+                Some(name) => name.clone(),
+                None => workbook.sheet_names()[self.flag_sheet_index].clone(),
+            };
+
+            fill_merged_cells(&mut range, &worksheet_merged_cells(&mut workbook, &name)?);
+        }
+
+        let mut wtr = self.writer()?;
+        let mut record = csv::StringRecord::new();
+
+        for row in self.sheet_rows(&range) {
+            fill_record_with_row(&mut record, row);
+            wtr.write_record(&record)?;
+        }
+
+        Ok(wtr.flush()?)
+    }
+
+    fn sheet_rows<'a>(&self, range: &'a Range<Data>) -> impl Iterator<Item = &'a [Data]> {
+        // NOTE: ranges start at their first non-empty cell, while rows to skip
+        // are counted from the top of the sheet.
+        let start_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+
+        range
+            .rows()
+            .skip(self.flag_skip_rows.saturating_sub(start_row))
+    }
+
+    fn convert_all_sheets<RS: Read + Seek>(&self, workbook: &mut Sheets<RS>) -> CliResult<()> {
+        let sheet_names = workbook.sheet_names();
+
+        if let Some(dir) = &self.flag_output_dir {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut concatenated_wtr = match &self.flag_output_dir {
+            Some(_) => None,
+            None => Some(self.writer()?),
+        };
+
+        let mut first_headers: Option<csv::StringRecord> = None;
+        let mut record = csv::StringRecord::new();
+
+        for name in sheet_names {
+            let mut range = workbook.worksheet_range(&name)?;
+
+            if self.flag_fill_merged {
+                fill_merged_cells(&mut range, &worksheet_merged_cells(workbook, &name)?);
+            }
+
+            let mut rows = self.sheet_rows(&range);
+
+            let headers = match rows.next() {
+                None => continue,
+                Some(row) => {
+                    let mut headers = csv::StringRecord::new();
+                    fill_record_with_row(&mut headers, row);
+                    headers
+                }
+            };
+
+            match &mut concatenated_wtr {
+                None => {
+                    let path = Path::new(self.flag_output_dir.as_ref().unwrap())
+                        .join(format!("{}.csv", name.replace(['/', '\\'], "_")));
+
+                    let mut wtr =
+                        Config::new(&Some(path.to_string_lossy().into_owned())).writer()?;
+
+                    wtr.write_record(&headers)?;
+
+                    for row in rows {
+                        fill_record_with_row(&mut record, row);
+                        wtr.write_record(&record)?;
+                    }
+
+                    wtr.flush()?;
+                }
+                Some(wtr) => {
+                    match &first_headers {
+                        None => {
+                            wtr.write_record(
+                                std::iter::once(self.flag_sheet_column.as_str())
+                                    .chain(headers.iter()),
+                            )?;
+                            first_headers = Some(headers);
+                        }
+                        Some(first_headers) => {
+                            if first_headers != &headers {
+                                Err(format!(
+                                    "sheet \"{}\" does not have the same headers as the first one!\nUse --output-dir to write sheets as separate files instead.",
+                                    name
+                                ))?;
                             }
-                            Data::Int(value) => record.push_field(&value.to_string()),
-                            Data::Float(value) => record.push_field(&value.to_string()),
-                            Data::DateTime(value) => record.push_field(&value.to_string()),
-                            Data::Error(err) => record.push_field(&err.to_string()),
-                            Data::Empty => record.push_field(""),
                         }
-                    }
+                    };
 
-                    wtr.write_record(&record)?;
+                    for row in rows {
+                        fill_record_with_row(&mut record, row);
+                        wtr.write_record(std::iter::once(name.as_str()).chain(record.iter()))?;
+                    }
                 }
             }
         }
 
-        Ok(wtr.flush()?)
+        if let Some(mut wtr) = concatenated_wtr {
+            wtr.flush()?;
+        }
+
+        Ok(())
     }
 
     fn convert_ndjson(&self) -> CliResult<()> {
@@ -422,6 +580,28 @@ impl Args {
         Ok(wtr.flush()?)
     }
 
+    fn nth_table_index(&self, count: usize, document_kind: &str) -> CliResult<usize> {
+        usize::try_from(self.flag_nth_table)
+            .ok()
+            // select from end if negative.
+            .or_else(|| count.checked_add_signed(self.flag_nth_table))
+            .filter(|i| *i < count)
+            .ok_or_else(|| {
+                let bounds = if self.flag_nth_table >= 0 {
+                    [0, count].map(|n| n.to_string())
+                } else {
+                    // Saturating to avoid underflow.
+                    // isize::MIN is smallest supported number anyway due to type of `flag_select`.
+                    let low = 0isize.saturating_sub_unsigned(count);
+                    [-1, low].map(|n| n.to_string())
+                };
+                From::from(format!(
+                    "table index {} is out of bounds in target {} (must be between {} and {})",
+                    self.flag_nth_table, document_kind, bounds[0], bounds[1]
+                ))
+            })
+    }
+
     fn convert_markdown(&self) -> CliResult<()> {
         use comrak::nodes::NodeValue;
         use comrak::{parse_document, Arena, Options};
@@ -442,27 +622,7 @@ impl Args {
         if tables.is_empty() {
             Err("target Markdown does not contain a table")?;
         }
-        let table = usize::try_from(self.flag_nth_table)
-            .ok()
-            // select from end if negative.
-            .or_else(|| tables.len().checked_add_signed(self.flag_nth_table))
-            .and_then(|i| tables.get(i))
-            .ok_or_else(|| {
-                let bounds = if self.flag_nth_table >= 0 {
-                    [0, tables.len()].map(|n| n.to_string())
-                } else {
-                    // Saturating to avoid underflow.
-                    // isize::MIN is smallest supported number anyway due to type of `flag_select`.
-                    let low = 0isize.saturating_sub_unsigned(tables.len());
-                    [-1, low].map(|n| n.to_string())
-                };
-                format!(
-                    "table index {} is out of bounds in target Markdown (must be between {} and {})",
-                    self.flag_nth_table,
-                    bounds[0],
-                    bounds[1]
-                )
-            })?;
+        let table = tables[self.nth_table_index(tables.len(), "Markdown")?];
 
         let mut wtr = self.writer()?;
         let mut record = csv::ByteRecord::new();
@@ -504,8 +664,562 @@ impl Args {
 
         Ok(wtr.flush()?)
     }
+
+    fn convert_html(&self) -> CliResult<()> {
+        let mut rdr = Config::new(&self.arg_input).io_reader()?;
+        let mut buf = String::new();
+        rdr.read_to_string(&mut buf)?;
+
+        let selector = Selector::parse(&self.flag_table_selector)
+            .map_err(|_| format!("invalid CSS selector: {}", self.flag_table_selector))?;
+
+        let document = Html::parse_document(&buf);
+        let tables = document.select(&selector).collect::<Vec<_>>();
+
+        if tables.is_empty() {
+            Err("target HTML does not contain a table")?;
+        }
+
+        let table = tables[self.nth_table_index(tables.len(), "HTML")?];
+
+        if table.value().name() != "table" {
+            Err(format!(
+                "--table-selector matched a <{}> element instead of a <table>!",
+                table.value().name()
+            ))?;
+        }
+
+        let mut rows = html_table_rows(table);
+
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+        for row in rows.iter_mut() {
+            row.resize_with(width, || (String::new(), false));
+        }
+
+        let header_rows = rows
+            .iter()
+            .take_while(|row| row.iter().all(|(_, is_header)| *is_header))
+            .count()
+            .min(rows.len().saturating_sub(1).max(1));
+
+        let mut wtr = self.writer()?;
+
+        let headers = if header_rows == 0 || rows.is_empty() {
+            (0..width).map(|i| format!("col_{}", i)).collect::<Vec<_>>()
+        } else {
+            (0..width)
+                .map(|i| {
+                    let mut parts: Vec<&str> = Vec::new();
+
+                    for row in rows[..header_rows].iter() {
+                        let part = row[i].0.as_str();
+
+                        if !part.is_empty() && parts.last() != Some(&part) {
+                            parts.push(part);
+                        }
+                    }
+
+                    parts.join(" ")
+                })
+                .collect()
+        };
+
+        wtr.write_record(&headers)?;
+
+        for row in rows.iter().skip(header_rows) {
+            wtr.write_record(row.iter().map(|(cell, _)| cell))?;
+        }
+
+        Ok(wtr.flush()?)
+    }
+
+    fn convert_xml(&self) -> CliResult<()> {
+        let row_path = match &self.flag_row_path {
+            None => Err("--row-path is required when converting from XML!")?,
+            Some(path) => XMLRowPath::parse(path)?,
+        };
+
+        let rdr = BufReader::new(Config::new(&self.arg_input).io_reader()?);
+        let mut reader = quick_xml::Reader::from_reader(rdr);
+
+        let mut wtr = self.writer()?;
+
+        for_each_json_value_as_csv_record(
+            XMLRowIterator::new(&mut reader, row_path),
+            self.flag_sample_size,
+            |record| -> CliResult<()> {
+                wtr.write_record(record)?;
+                Ok(())
+            },
+        )?;
+
+        Ok(wtr.flush()?)
+    }
+
+    fn write_record_batches<I>(
+        &self,
+        schema: &arrow::datatypes::Schema,
+        batches: I,
+    ) -> CliResult<()>
+    where
+        I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+    {
+        let mut wtr = self.writer()?;
+        let mut record = csv::StringRecord::new();
+
+        for field in schema.fields() {
+            record.push_field(field.name());
+        }
+
+        wtr.write_record(&record)?;
+
+        let options = FormatOptions::default().with_display_error(true);
+        let mut cell = String::new();
+
+        for result in batches {
+            let batch = result?;
+
+            let formatters = batch
+                .columns()
+                .iter()
+                .map(|array| ArrayFormatter::try_new(array.as_ref(), &options))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for i in 0..batch.num_rows() {
+                record.clear();
+
+                for formatter in formatters.iter() {
+                    cell.clear();
+                    write!(&mut cell, "{}", formatter.value(i)).unwrap();
+                    record.push_field(&cell);
+                }
+
+                wtr.write_record(&record)?;
+            }
+        }
+
+        Ok(wtr.flush()?)
+    }
+
+    fn convert_parquet(&self) -> CliResult<()> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let reader = match self.arg_input.as_ref() {
+            None => {
+                let mut contents = Vec::<u8>::new();
+                io::stdin().read_to_end(&mut contents)?;
+                ParquetRecordBatchReaderBuilder::try_new(Bytes::from(contents))?.build()?
+            }
+            Some(path) => {
+                ParquetRecordBatchReaderBuilder::try_new(fs::File::open(path)?)?.build()?
+            }
+        };
+
+        self.write_record_batches(&reader.schema(), reader)
+    }
+
+    fn convert_arrow(&self) -> CliResult<()> {
+        // NOTE: Arrow IPC files start with a magic number, while IPC streams don't
+        const ARROW_MAGIC: &[u8; 6] = b"ARROW1";
+
+        let mut rdr = Config::new(&self.arg_input).io_reader()?;
+        let mut contents = Vec::<u8>::new();
+
+        (&mut rdr).take(6).read_to_end(&mut contents)?;
+
+        if contents.as_slice() == ARROW_MAGIC {
+            let reader = match self.arg_input.as_ref() {
+                None => {
+                    rdr.read_to_end(&mut contents)?;
+                    ArrowFileReader::try_new(
+                        Box::new(Cursor::new(contents)) as Box<dyn ReadSeek>,
+                        None,
+                    )?
+                }
+                Some(path) => ArrowFileReader::try_new(
+                    Box::new(fs::File::open(path)?) as Box<dyn ReadSeek>,
+                    None,
+                )?,
+            };
+
+            self.write_record_batches(&reader.schema(), reader)
+        } else {
+            let reader = ArrowStreamReader::try_new(Cursor::new(contents).chain(rdr), None)?;
+
+            self.write_record_batches(&reader.schema(), reader)
+        }
+    }
+}
+
+fn worksheet_merged_cells<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    name: &str,
+) -> CliResult<Vec<Dimensions>> {
+    Ok(match workbook {
+        Sheets::Xlsx(xlsx) => xlsx
+            .worksheet_merge_cells(name)
+            .transpose()
+            .map_err(calamine::Error::from)?
+            .unwrap_or_default(),
+        Sheets::Xls(xls) => xls.worksheet_merge_cells(name).unwrap_or_default(),
+        _ => Err("--fill-merged only works with xlsx & xls files!")?,
+    })
+}
+
+fn fill_merged_cells(range: &mut Range<Data>, merged_cells: &[Dimensions]) {
+    for dimensions in merged_cells {
+        let value = match range.get_value(dimensions.start) {
+            None | Some(Data::Empty) => continue,
+            Some(value) => value.clone(),
+        };
+
+        for row in dimensions.start.0..=dimensions.end.0 {
+            for col in dimensions.start.1..=dimensions.end.1 {
+                range.set_value((row, col), value.clone());
+            }
+        }
+    }
+}
+
+fn fill_record_with_row(record: &mut csv::StringRecord, row: &[Data]) {
+    record.clear();
+
+    for cell in row {
+        match cell {
+            Data::String(value) => record.push_field(value),
+            Data::DateTimeIso(value) => record.push_field(value),
+            Data::DurationIso(value) => record.push_field(value),
+            Data::Bool(value) => record.push_field(if *value { "true" } else { "false" }),
+            Data::Int(value) => record.push_field(&value.to_string()),
+            Data::Float(value) => record.push_field(&value.to_string()),
+            Data::DateTime(value) => match value.as_datetime().filter(|_| value.is_datetime()) {
+                Some(datetime) => {
+                    if datetime.and_utc().timestamp() % 86400 == 0 {
+                        record.push_field(&datetime.format("%Y-%m-%d").to_string());
+                    } else {
+                        record.push_field(&datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string());
+                    }
+                }
+                None => record.push_field(&value.to_string()),
+            },
+            Data::Error(err) => record.push_field(&err.to_string()),
+            Data::Empty => record.push_field(""),
+        }
+    }
+}
+
+fn html_cell_text(cell: &ElementRef) -> String {
+    cell.text()
+        .flat_map(|text| text.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn html_span(cell: &ElementRef, attribute: &str) -> usize {
+    cell.value()
+        .attr(attribute)
+        .and_then(|span| span.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 1000)
+}
+
+type HTMLCell = (String, bool);
+
+fn fill_pending_html_cells(pending: &mut [(usize, HTMLCell)], row: &mut Vec<HTMLCell>) {
+    while let Some((remaining, cell)) = pending.get_mut(row.len()) {
+        if *remaining == 0 {
+            break;
+        }
+
+        *remaining -= 1;
+        row.push(cell.clone());
+    }
+}
+
+// NOTE: rows are returned as lists of (text, is_header) cells, with cells
+// spanning multiple rows or columns being repeated.
+fn html_table_rows(table: ElementRef) -> Vec<Vec<HTMLCell>> {
+    let tr_elements = table
+        .children()
+        .filter_map(ElementRef::wrap)
+        .flat_map(|child| match child.value().name() {
+            "tr" => vec![child],
+            "thead" | "tbody" | "tfoot" => child
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|e| e.value().name() == "tr")
+                .collect(),
+            _ => vec![],
+        });
+
+    // Cells spanning over next rows, indexed by column: (remaining rows, cell)
+    let mut pending: Vec<(usize, HTMLCell)> = Vec::new();
+    let mut rows = Vec::new();
+
+    for tr in tr_elements {
+        let mut row: Vec<HTMLCell> = Vec::new();
+
+        for cell in tr
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| matches!(e.value().name(), "td" | "th"))
+        {
+            fill_pending_html_cells(&mut pending, &mut row);
+
+            let value = (html_cell_text(&cell), cell.value().name() == "th");
+            let rowspan = html_span(&cell, "rowspan");
+
+            for _ in 0..html_span(&cell, "colspan") {
+                let col = row.len();
+
+                if pending.len() <= col {
+                    pending.resize_with(col + 1, || (0, (String::new(), false)));
+                }
+
+                pending[col] = (rowspan - 1, value.clone());
+                row.push(value.clone());
+            }
+        }
+
+        while row.len() < pending.len() {
+            let before = row.len();
+
+            fill_pending_html_cells(&mut pending, &mut row);
+
+            if row.len() == before {
+                row.push((String::new(), false));
+            }
+        }
+
+        rows.push(row);
+    }
+
+    rows
+}
+
+struct XMLRowPath {
+    segments: Vec<Vec<u8>>,
+    anchored: bool,
+}
+
+impl XMLRowPath {
+    fn parse(path: &str) -> CliResult<Self> {
+        let anchored = path.starts_with('/');
+
+        let segments = path
+            .trim_matches('/')
+            .split('/')
+            .map(|segment| segment.as_bytes().to_vec())
+            .collect::<Vec<_>>();
+
+        if segments.iter().any(|segment| segment.is_empty()) {
+            Err(format!("invalid --row-path \"{}\"", path))?;
+        }
+
+        Ok(Self { segments, anchored })
+    }
+
+    fn matches(&self, stack: &[Vec<u8>]) -> bool {
+        if self.anchored {
+            stack == self.segments.as_slice()
+        } else {
+            stack.ends_with(&self.segments)
+        }
+    }
+}
+
+struct XMLRowIterator<'r, R: BufRead> {
+    reader: &'r mut quick_xml::Reader<R>,
+    row_path: XMLRowPath,
+    buf: Vec<u8>,
+    stack: Vec<Vec<u8>>,
+    // Depth of the current row element, if any
+    row_depth: Option<usize>,
+    row: Map<String, Value>,
+    // Text & whether it has child elements or attributes, for each element
+    // within the row
+    texts: Vec<(String, bool)>,
+    done: bool,
+}
+
+impl<'r, R: BufRead> XMLRowIterator<'r, R> {
+    fn new(reader: &'r mut quick_xml::Reader<R>, row_path: XMLRowPath) -> Self {
+        Self {
+            reader,
+            row_path,
+            buf: Vec::new(),
+            stack: Vec::new(),
+            row_depth: None,
+            row: Map::new(),
+            texts: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn relative_path(&self) -> String {
+        let depth = self.row_depth.unwrap();
+
+        self.stack[depth..]
+            .iter()
+            .map(|name| String::from_utf8_lossy(name))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn insert(&mut self, key: String, value: String) {
+        match self.row.get_mut(&key) {
+            Some(Value::String(current)) => {
+                if !current.is_empty() {
+                    current.push('|');
+                }
+
+                current.push_str(&value);
+            }
+            _ => {
+                self.row.insert(key, Value::String(value));
+            }
+        }
+    }
+
+    fn open(&mut self, element: &BytesStart) -> CliResult<()> {
+        self.stack.push(element.name().as_ref().to_vec());
+
+        if self.row_depth.is_none() {
+            if !self.row_path.matches(&self.stack) {
+                return Ok(());
+            }
+
+            self.row_depth = Some(self.stack.len());
+            self.row = Map::new();
+            self.texts.clear();
+        } else if let Some((_, has_children_or_attributes)) = self.texts.last_mut() {
+            *has_children_or_attributes = true;
+        }
+
+        let relative_path = self.relative_path();
+        let mut has_attributes = false;
+
+        for result in element.attributes() {
+            has_attributes = true;
+
+            let attribute = result?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref());
+            let value = attribute
+                .decode_and_unescape_value(self.reader.decoder())?
+                .into_owned();
+
+            if relative_path.is_empty() {
+                self.insert(format!("@{}", key), value);
+            } else {
+                self.insert(format!("{}/@{}", relative_path, key), value);
+            }
+        }
+
+        // NOTE: elements only holding attributes don't get a column
+        self.texts.push((String::new(), has_attributes));
+
+        Ok(())
+    }
+
+    // NOTE: returns the row when its element is closed
+    fn close(&mut self) -> Option<Map<String, Value>> {
+        let depth = match self.row_depth {
+            Some(depth) if self.stack.len() >= depth => depth,
+            _ => {
+                self.stack.pop();
+                return None;
+            }
+        };
+
+        let (text, has_children_or_attributes) = self.texts.pop().unwrap();
+        let text = text.trim();
+
+        if !text.is_empty() || !has_children_or_attributes {
+            let key = if self.stack.len() == depth {
+                String::from_utf8_lossy(&self.stack[depth - 1]).into_owned()
+            } else {
+                self.relative_path()
+            };
+
+            if !text.is_empty() || !self.row.contains_key(&key) {
+                self.insert(key, text.to_string());
+            }
+        }
+
+        self.stack.pop();
+
+        if self.stack.len() < depth {
+            self.row_depth = None;
+            return Some(std::mem::take(&mut self.row));
+        }
+
+        None
+    }
+
+    fn next_row(&mut self) -> CliResult<Option<Map<String, Value>>> {
+        loop {
+            self.buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(element) => {
+                    let element = element.into_owned();
+                    self.open(&element)?;
+                }
+                Event::Empty(element) => {
+                    let element = element.into_owned();
+                    self.open(&element)?;
+
+                    if let Some(row) = self.close() {
+                        return Ok(Some(row));
+                    }
+                }
+                Event::End(_) => {
+                    if let Some(row) = self.close() {
+                        return Ok(Some(row));
+                    }
+                }
+                Event::Text(text) if self.row_depth.is_some() => {
+                    let text = text.unescape()?.into_owned();
+                    self.texts.last_mut().unwrap().0.push_str(&text);
+                }
+                Event::CData(text) if self.row_depth.is_some() => {
+                    let text = String::from_utf8_lossy(&text).into_owned();
+                    self.texts.last_mut().unwrap().0.push_str(&text);
+                }
+                Event::Eof => return Ok(None),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for XMLRowIterator<'_, R> {
+    type Item = CliResult<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_row() {
+            Ok(Some(row)) => Some(Ok(Value::Object(row))),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
 }
 
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -536,5 +1250,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         SupportedFormat::Npy => args.convert_npy(),
         SupportedFormat::Tar => args.convert_tar(),
         SupportedFormat::Md => args.convert_markdown(),
+        SupportedFormat::Parquet => args.convert_parquet(),
+        SupportedFormat::Arrow => args.convert_arrow(),
+        SupportedFormat::Html => args.convert_html(),
+        SupportedFormat::Xml => args.convert_xml(),
     }
 }
@@ -3,11 +3,13 @@ use std::io::{stdout, Write};
 use std::num::NonZeroUsize;
 
 use colored::{ColoredString, Colorize};
+use colorgrad::BasisGradient;
 use numfmt::{Formatter, Precision};
 use unicode_width::UnicodeWidthStr;
 
 use crate::config::{Config, Delimiter};
 use crate::scales::{Extent, ExtentBuilder, GradientName, LinearScale};
+use crate::svg::{self, Anchor, ImageOutput, SvgDocument, TextStyle};
 use crate::util;
 use crate::CliResult;
 
@@ -123,6 +125,64 @@ impl Matrix {
     }
 }
 
+// NOTE: this struct knows how to find the color of any cell of the matrix,
+// wrt the chosen normalization.
+struct ColorScales {
+    forced_extent: (Option<f64>, Option<f64>),
+    full_scale: Option<LinearScale>,
+    row_normalization: bool,
+    col_scales: Option<Vec<Option<LinearScale>>>,
+    gradient: BasisGradient,
+}
+
+impl ColorScales {
+    fn new(
+        matrix: &Matrix,
+        normalization: &Normalization,
+        forced_extent: (Option<f64>, Option<f64>),
+        gradient: BasisGradient,
+    ) -> Self {
+        Self {
+            forced_extent,
+            full_scale: matrix.extent.map(LinearScale::from_extent),
+            row_normalization: normalization.is_row(),
+            col_scales: normalization.is_column().then(|| {
+                matrix
+                    .extent_per_column(forced_extent)
+                    .into_iter()
+                    .map(|extent_opt| extent_opt.map(LinearScale::from_extent))
+                    .collect::<Vec<_>>()
+            }),
+            gradient,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        !self.row_normalization && self.col_scales.is_none()
+    }
+
+    fn row_scale(&self, row: &[Option<f64>]) -> Option<Option<LinearScale>> {
+        self.row_normalization
+            .then(|| compute_row_extent(row, self.forced_extent).map(LinearScale::from_extent))
+    }
+
+    fn color(
+        &self,
+        row_scale: &Option<Option<LinearScale>>,
+        col_i: usize,
+        value: f64,
+    ) -> Option<[u8; 4]> {
+        let scale_opt = row_scale.clone().unwrap_or_else(|| {
+            self.col_scales
+                .as_ref()
+                .and_then(|scales| scales[col_i].clone())
+                .or_else(|| self.full_scale.clone())
+        });
+
+        scale_opt.map(|scale| scale.map_color(&self.gradient, value).to_rgba8())
+    }
+}
+
 fn compute_row_extent(
     row: &[Option<f64>],
     forced_extent: (Option<f64>, Option<f64>),
@@ -142,6 +202,9 @@ Draw a heatmap from CSV data.
 Use the --show-gradients flag to display a showcase of available
 color gradients.
 
+Use the --svg and/or --png flags to write the heatmap to an image file instead
+of printing it in the terminal, e.g. to paste it in a report.
+
 Usage:
     xan heatmap [options] [<input>]
     xan heatmap --show-gradients
@@ -171,6 +234,14 @@ heatmap options:
                            handle them.
     --show-gradients       Display a showcase of available gradients.
 
+heatmap image options:
+    --svg <path>           Write the heatmap as SVG to the given file instead of
+                           printing it in the terminal.
+    --png <path>           Write the heatmap as PNG to the given file instead of
+                           printing it in the terminal.
+    --image-width <px>     Width of the image in pixels.
+                           [default: 800]
+
 Common options:
     -h, --help             Display this message
     -n, --no-headers       When set, the file will be considered as having no
@@ -195,6 +266,9 @@ struct Args {
     flag_delimiter: Option<Delimiter>,
     flag_show_gradients: bool,
     flag_green_hills: bool,
+    flag_svg: Option<String>,
+    flag_png: Option<String>,
+    flag_image_width: NonZeroUsize,
 }
 
 impl Args {
@@ -246,8 +320,6 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let forced_extent = (args.flag_min, args.flag_max);
 
-    let gradient = args.flag_gradient.build();
-
     let mut rdr = conf.reader()?;
     let mut record = csv::ByteRecord::new();
 
@@ -293,13 +365,29 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     matrix.finalize();
 
+    let color_scales = ColorScales::new(
+        &matrix,
+        &args.flag_normalize,
+        forced_extent,
+        args.flag_gradient.build(),
+    );
+
+    if let Some(image_output) = ImageOutput::new(&args.flag_svg, &args.flag_png) {
+        let document = draw_svg_heatmap(
+            &matrix,
+            &color_scales,
+            args.flag_image_width.get() as f64,
+            formatter.as_mut(),
+        );
+
+        return image_output.write(&document);
+    }
+
     let cols = util::acquire_term_cols(&None);
     let label_cols =
         ((cols as f64 * 0.3).floor() as usize).min(matrix.max_row_label_width().unwrap() + 1);
     let left_padding = " ".repeat(label_cols);
 
-    let full_scale = matrix.extent.map(LinearScale::from_extent);
-
     let size = args.flag_size.get();
 
     // Printing column info
@@ -340,19 +428,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // Printing rows
     let midpoint = size / 2;
 
-    let col_scales = args.flag_normalize.is_column().then(|| {
-        matrix
-            .extent_per_column(forced_extent)
-            .into_iter()
-            .map(|extent_opt| extent_opt.map(LinearScale::from_extent))
-            .collect::<Vec<_>>()
-    });
-
     for (row_label, row) in matrix.rows() {
-        let row_scale = args
-            .flag_normalize
-            .is_row()
-            .then(|| compute_row_extent(row, forced_extent).map(LinearScale::from_extent));
+        let row_scale = color_scales.row_scale(row);
 
         for i in 0..size {
             if i == 0 {
@@ -373,15 +450,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 match cell {
                     None => write!(&mut out, "{}", "  ".repeat(size))?,
                     Some(f) => {
-                        let scale_opt = row_scale.clone().unwrap_or_else(|| {
-                            col_scales
-                                .as_ref()
-                                .and_then(|scales| scales[col_i].clone())
-                                .or_else(|| full_scale.clone())
-                        });
-
-                        let color_opt =
-                            scale_opt.map(|scale| scale.map_color(&gradient, *f).to_rgba8());
+                        let color_opt = color_scales.color(&row_scale, col_i, *f);
 
                         let body = match formatter.as_mut() {
                             Some(fmt) if i == midpoint => {
@@ -428,6 +497,150 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+const SVG_MARGIN: f64 = 16.0;
+const SVG_MAX_CELL_SIZE: f64 = 64.0;
+const SVG_LEGEND_STEPS: usize = 50;
+
+fn draw_svg_heatmap(
+    matrix: &Matrix,
+    color_scales: &ColorScales,
+    width: f64,
+    mut formatter: Option<&mut Formatter>,
+) -> SvgDocument {
+    let row_labels_width = matrix
+        .row_labels
+        .iter()
+        .map(|label| svg::text_width(label, svg::FONT_SIZE))
+        .fold(0.0, f64::max)
+        .min(width * 0.3);
+
+    // NOTE: column labels are rotated by 45 degrees
+    let column_labels_height = matrix
+        .column_labels
+        .iter()
+        .map(|label| svg::text_width(label, svg::FONT_SIZE))
+        .fold(0.0, f64::max)
+        * std::f64::consts::FRAC_1_SQRT_2;
+
+    let left = SVG_MARGIN + row_labels_width + 8.0;
+    let top = SVG_MARGIN + column_labels_height + 8.0;
+
+    let cell_size = ((width - left - SVG_MARGIN - column_labels_height)
+        / matrix.column_labels.len() as f64)
+        .clamp(4.0, SVG_MAX_CELL_SIZE);
+
+    let matrix_height = cell_size * matrix.row_labels.len() as f64;
+    let legend_height = if color_scales.is_full() {
+        svg::FONT_SIZE * 3.0 + 16.0
+    } else {
+        0.0
+    };
+
+    // NOTE: the image may be narrower than requested if cells are capped
+    let width = width.min(
+        left + cell_size * matrix.column_labels.len() as f64 + column_labels_height + SVG_MARGIN,
+    );
+
+    let mut document = SvgDocument::new(width, top + matrix_height + legend_height + SVG_MARGIN);
+
+    for (i, label) in matrix.column_labels.iter().enumerate() {
+        let x = left + (i as f64 + 0.5) * cell_size;
+
+        document.text((x, top - 6.0), label, &TextStyle::default().rotate(-45.0));
+    }
+
+    let font_size = (cell_size / 3.0).min(svg::FONT_SIZE);
+
+    for (row_i, (row_label, row)) in matrix.rows().enumerate() {
+        let y = top + row_i as f64 * cell_size;
+
+        document.text(
+            (left - 8.0, y + cell_size / 2.0 + svg::FONT_SIZE / 3.0),
+            &util::unicode_aware_ellipsis(
+                row_label,
+                (row_labels_width / (svg::FONT_SIZE * 0.6)) as usize,
+            ),
+            &TextStyle::default().anchor(Anchor::End),
+        );
+
+        let row_scale = color_scales.row_scale(row);
+
+        for (col_i, cell) in row.iter().enumerate() {
+            let f = match cell {
+                None => continue,
+                Some(f) => *f,
+            };
+
+            let color_opt = color_scales.color(&row_scale, col_i, f);
+            let x = left + col_i as f64 * cell_size;
+
+            if let Some(color) = color_opt {
+                document.rect(x, y, cell_size, cell_size, &svg::rgba_to_hex(&color));
+            }
+
+            if let Some(fmt) = formatter.as_mut() {
+                let text_color = match color_opt {
+                    Some(color) if !text_should_be_black(&color) => "#ffffff",
+                    _ => "#000000",
+                };
+
+                document.text(
+                    (x + cell_size / 2.0, y + cell_size / 2.0 + font_size / 3.0),
+                    &util::format_number_with_formatter(fmt, f),
+                    &TextStyle {
+                        size: font_size,
+                        ..TextStyle::default()
+                    }
+                    .anchor(Anchor::Middle)
+                    .fill(text_color),
+                );
+            }
+        }
+    }
+
+    // Drawing the gradient legend
+    if let (true, Some(scale), Some(extent)) = (
+        color_scales.is_full(),
+        &color_scales.full_scale,
+        &matrix.extent,
+    ) {
+        let legend_top = top + matrix_height + 16.0;
+        let legend_width = (width - left - SVG_MARGIN).min(240.0);
+        let step_width = legend_width / SVG_LEGEND_STEPS as f64;
+        let (min, max) = (extent.min(), extent.max());
+
+        for i in 0..SVG_LEGEND_STEPS {
+            let t = i as f64 / (SVG_LEGEND_STEPS - 1) as f64;
+            let color = scale
+                .map_color(&color_scales.gradient, min + (max - min) * t)
+                .to_rgba8();
+
+            document.rect(
+                left + i as f64 * step_width,
+                legend_top,
+                step_width + 0.5,
+                svg::FONT_SIZE,
+                &svg::rgba_to_hex(&color),
+            );
+        }
+
+        let labels_y = legend_top + svg::FONT_SIZE * 2.0 + 4.0;
+
+        document.text(
+            (left, labels_y),
+            &util::format_number(min),
+            &TextStyle::default(),
+        );
+        document.text(
+            (left + legend_width, labels_y),
+            &util::format_number(max),
+            &TextStyle::default().anchor(Anchor::End),
+        );
+    }
+
+    document
+}
+
 static GREEN_HILLS: &[u8] = b"
 gggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggg
 rrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrr
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::io::{stdout, Write};
+use std::num::NonZeroUsize;
 
 use ahash::RandomState;
 use colored::Colorize;
@@ -11,6 +12,7 @@ use crate::config::{Config, Delimiter};
 use crate::dates;
 use crate::scales::LinearScale;
 use crate::select::SelectColumns;
+use crate::svg::{self, Anchor, ImageOutput, SvgDocument, TextStyle};
 use crate::util;
 use crate::CliResult;
 
@@ -25,6 +27,9 @@ representing a bar in the resulting graph.
 This command is very useful when used in conjunction with the `frequency` or `bins`
 command.
 
+Use the --svg and/or --png flags to write the histograms to an image file instead
+of printing them in the terminal, e.g. to paste them in a report.
+
 Usage:
     xan hist [options] [<input>]
     xan hist --help
@@ -65,6 +70,14 @@ hist options:
     -G, --compress-gaps <n>  If given, will compress gaps of minimum <n> consecutive
                              entries set to 0 and replace it with an ellipsis.
 
+hist image options:
+    --svg <path>             Write the histograms as SVG to the given file instead
+                             of printing them in the terminal.
+    --png <path>             Write the histograms as PNG to the given file instead
+                             of printing them in the terminal.
+    --image-width <px>       Width of the image in pixels.
+                             [default: 800]
+
 Common options:
     -h, --help             Display this message
     -n, --no-headers       When set, the file will be considered as having no
@@ -92,6 +105,9 @@ struct Args {
     flag_bar_size: String,
     flag_dates: bool,
     flag_compress_gaps: Option<usize>,
+    flag_svg: Option<String>,
+    flag_png: Option<String>,
+    flag_image_width: NonZeroUsize,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -180,6 +196,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    let image_output = ImageOutput::new(&args.flag_svg, &args.flag_png);
+    let mut document_opt = image_output
+        .as_ref()
+        .map(|_| SvgDocument::new(args.flag_image_width.get() as f64, 0.0));
+    let mut image_top = 0.0;
+
     let cols = util::acquire_term_cols_ratio(&args.flag_cols)?;
 
     let unit = args.flag_unit.as_deref().unwrap_or("");
@@ -212,6 +234,27 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             Err("-m/--domain-max should be >= 0!")?;
         }
 
+        if let Some(document) = document_opt.as_mut() {
+            let category_legend = category_column_index.map(|category_col| {
+                (
+                    std::str::from_utf8(&headers[category_col]).expect("could not decode header"),
+                    &category_colors,
+                    categories_overflow.as_slice(),
+                )
+            });
+
+            image_top = draw_svg_histogram(
+                document,
+                image_top,
+                &args,
+                histogram,
+                (sum, domain_max),
+                category_legend,
+            );
+
+            continue;
+        }
+
         writeln!(
             &mut out,
             "\nHistogram for {} (bars: {}, sum: {}{}, max: {}{}):\n",
@@ -356,11 +399,201 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    if let (Some(image_output), Some(mut document)) = (image_output, document_opt) {
+        document.set_height(image_top + SVG_MARGIN);
+
+        return image_output.write(&document);
+    }
+
     writeln!(&mut out)?;
 
     Ok(())
 }
 
+const SVG_MARGIN: f64 = 16.0;
+const SVG_BAR_HEIGHT: f64 = 16.0;
+const SVG_LINE_HEIGHT: f64 = 22.0;
+
+type CategoryLegend<'a> = (
+    &'a str,
+    &'a IndexMap<String, usize, RandomState>,
+    &'a [String],
+);
+
+// NOTE: returns the y position where the next histogram should be drawn
+fn draw_svg_histogram(
+    document: &mut SvgDocument,
+    mut top: f64,
+    args: &Args,
+    histogram: &Histogram,
+    (sum, domain_max): (f64, f64),
+    category_legend: Option<CategoryLegend>,
+) -> f64 {
+    let unit = args.flag_unit.as_deref().unwrap_or("");
+    let width = document.width();
+    let text_offset = SVG_BAR_HEIGHT / 2.0 + svg::FONT_SIZE / 3.0;
+
+    top += SVG_MARGIN;
+
+    document.text(
+        (SVG_MARGIN, top + svg::FONT_SIZE),
+        &format!(
+            "Histogram for {} (bars: {}, sum: {}{}, max: {}{})",
+            histogram.field,
+            util::format_number(histogram.len()),
+            util::format_number(sum),
+            unit,
+            util::format_number(histogram.max().unwrap()),
+            unit,
+        ),
+        &TextStyle::default().bold(),
+    );
+
+    top += SVG_LINE_HEIGHT + 8.0;
+
+    let label_width = f64::min(
+        (width * 0.3).floor(),
+        svg::text_width(
+            &"w".repeat(histogram.label_max_width().unwrap()),
+            svg::FONT_SIZE,
+        ),
+    );
+    let value_width = svg::text_width(
+        &"0".repeat(histogram.value_max_width().unwrap() + unit.width()),
+        svg::FONT_SIZE,
+    );
+    let pct_width = if args.flag_hide_percent {
+        0.0
+    } else {
+        svg::text_width("100.00%", svg::FONT_SIZE) + 8.0
+    };
+
+    let value_x = SVG_MARGIN + label_width + 8.0 + value_width;
+    let bar_x = value_x + pct_width + 12.0;
+    let bar_width = (width - SVG_MARGIN - bar_x).max(1.0);
+
+    let scale = LinearScale::new((0.0, domain_max), (0.0, bar_width));
+
+    let mut odd = false;
+
+    for (i, bar_opt) in histogram
+        .compressed_bars(args.flag_compress_gaps)
+        .into_iter()
+        .enumerate()
+    {
+        let bar = match bar_opt {
+            None => {
+                document.text(
+                    (SVG_MARGIN, top + text_offset),
+                    "...",
+                    &TextStyle::default().fill(svg::DIM_COLOR),
+                );
+                top += SVG_LINE_HEIGHT;
+                continue;
+            }
+            Some(bar) => bar,
+        };
+
+        let color = if category_legend.is_some() {
+            bar.category
+                .map(svg::rainbow_color)
+                .unwrap_or(svg::DIM_COLOR)
+        } else if args.flag_rainbow {
+            svg::rainbow_color(i)
+        } else {
+            odd = !odd;
+
+            if odd {
+                svg::AXIS_COLOR
+            } else {
+                svg::DIM_COLOR
+            }
+        };
+
+        let label_style = match bar.label.as_str() {
+            "<rest>" | "<null>" | "<NaN>" | "<empty>" => TextStyle::default().fill(svg::DIM_COLOR),
+            _ => TextStyle::default(),
+        };
+
+        document.text(
+            (SVG_MARGIN, top + text_offset),
+            &util::unicode_aware_ellipsis(
+                &bar.label,
+                (label_width / (svg::FONT_SIZE * 0.6)) as usize,
+            ),
+            &label_style,
+        );
+        document.text(
+            (value_x, top + text_offset),
+            &format!("{}{}", util::format_number(bar.value), unit),
+            &TextStyle::default()
+                .anchor(Anchor::End)
+                .fill(svg::series_color(0)),
+        );
+
+        if !args.flag_hide_percent {
+            document.text(
+                (value_x + pct_width, top + text_offset),
+                &format!("{:.2}%", bar.value / sum * 100.0),
+                &TextStyle::default()
+                    .anchor(Anchor::End)
+                    .fill(svg::series_color(5)),
+            );
+        }
+
+        document.rect(
+            bar_x,
+            top,
+            scale.map(bar.value.min(domain_max)),
+            SVG_BAR_HEIGHT,
+            color,
+        );
+
+        top += SVG_LINE_HEIGHT;
+    }
+
+    // Drawing the categorical legend
+    if let Some((category_column_name, category_colors, categories_overflow)) = category_legend {
+        top += 8.0;
+
+        document.text(
+            (SVG_MARGIN, top + text_offset),
+            &format!("Colors by {}:", category_column_name),
+            &TextStyle::default().bold(),
+        );
+
+        top += SVG_LINE_HEIGHT;
+
+        let mut legend = category_colors
+            .iter()
+            .map(|(category, color_index)| (category.to_string(), svg::rainbow_color(*color_index)))
+            .collect::<Vec<_>>();
+
+        if !categories_overflow.is_empty() {
+            legend.push((categories_overflow.join(", "), svg::DIM_COLOR));
+        }
+
+        for (category, color) in legend {
+            document.rect(
+                SVG_MARGIN,
+                top + (SVG_BAR_HEIGHT - svg::FONT_SIZE) / 2.0,
+                svg::FONT_SIZE,
+                svg::FONT_SIZE,
+                color,
+            );
+            document.text(
+                (SVG_MARGIN + svg::FONT_SIZE + 6.0, top + text_offset),
+                &category,
+                &TextStyle::default(),
+            );
+
+            top += SVG_LINE_HEIGHT;
+        }
+    }
+
+    top
+}
+
 fn create_bar(chars: &[&str], width: f64) -> String {
     let f = width.fract();
 
@@ -0,0 +1,82 @@
+use std::fs;
+
+use crate::config::{Config, Delimiter};
+use crate::index::{index_path_for, RecordIndexWriter};
+use crate::util;
+use crate::CliResult;
+
+static USAGE: &str = "
+Index the rows of a CSV file by writing the byte offset of each of them in a
+sidecar \"<input>.xidx\" file, so that some commands are able to access any row
+of the file without needing to read it from the start.
+
+The following commands are able to take advantage of such an index:
+
+    - `xan count` will count rows in constant time.
+    - `xan slice` will directly seek to the requested rows, when selecting
+      them using their indices.
+    - `xan parallel`, and any command able to run in parallel, as well as
+      `xan split --chunks` will segment the file exactly, instead of relying
+      on heuristics.
+
+Note that an index is stale as soon as the indexed file is modified, in which
+case it will be ignored with a warning until the file is indexed again.
+
+Also note that gzipped files & streams cannot be indexed.
+
+Usage:
+    xan index [options] <input>
+    xan index --help
+
+index options:
+    -q, --quiet  Don't print the number of indexed rows.
+
+Common options:
+    -h, --help             Display this message
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input: String,
+    flag_quiet: bool,
+    flag_delimiter: Option<Delimiter>,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.arg_input.ends_with(".gz") {
+        Err("cannot index gzipped files!")?;
+    }
+
+    // NOTE: header row is indexed like any other row so that the index remains
+    // valid whether -n/--no-headers is given or not.
+    let rconf = Config::new(&Some(args.arg_input.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(true);
+
+    let mut io_reader = rconf.io_reader_for_random_access()?;
+    let offset_before_csv_parsing = io_reader.stream_position()?;
+
+    let mut rdr = rconf.csv_reader_from_reader(io_reader);
+
+    let path = rconf.path.as_ref().unwrap();
+    let index_file = fs::File::create(index_path_for(path))?;
+    let mut index_writer = RecordIndexWriter::new(index_file, path)?;
+
+    let mut record = csv::ByteRecord::new();
+
+    while rdr.read_byte_record(&mut record)? {
+        index_writer.add_row(offset_before_csv_parsing + record.position().unwrap().byte())?;
+    }
+
+    let rows = index_writer.finish(offset_before_csv_parsing + rdr.position().byte())?;
+
+    if !args.flag_quiet {
+        eprintln!("Indexed {} rows.", util::format_number(rows));
+    }
+
+    Ok(())
+}
@@ -1,11 +1,16 @@
+use std::fs;
 use std::io;
 use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
+use ahash::RandomState;
 use bstr::ByteSlice;
+use bytesize::MB;
 use csv::ByteRecord;
 
 use crate::collections::{hash_map::Entry, HashMap, HashSet};
 use crate::config::{Config, Delimiter};
+use crate::dates::{parse_duration, parse_zoned};
 use crate::select::{SelectColumns, Selection};
 use crate::util;
 use crate::CliResult;
@@ -79,7 +84,7 @@ impl IndexNode {
 // save up more memory, but the output is less understandable
 // for the user and not aligned with usual affordances.
 #[derive(Debug)]
-struct Index {
+pub struct Index {
     case_insensitive: bool,
     nulls: bool,
     map: HashMap<IndexKey, (usize, usize)>,
@@ -96,7 +101,7 @@ impl Index {
         }
     }
 
-    fn from_csv_reader<R: io::Read>(
+    pub fn from_csv_reader<R: io::Read>(
         reader: &mut csv::Reader<R>,
         sel: &Selection,
         case_insensitive: bool,
@@ -166,7 +171,7 @@ impl Index {
         Ok(())
     }
 
-    fn for_each_record<F, E>(
+    pub fn for_each_record<F, E>(
         &mut self,
         sel: &Selection,
         record: &ByteRecord,
@@ -189,6 +194,372 @@ impl Index {
     }
 }
 
+type BoxedReader = csv::Reader<Box<dyn io::Read + Send>>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum JoinMode {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Semi,
+    Anti,
+}
+
+impl JoinMode {
+    // NOTE: returns whether the left file is the one indexed in memory
+    fn indexes_left(&self) -> bool {
+        matches!(self, Self::Inner | Self::Right | Self::Full)
+    }
+}
+
+// NOTE: those are the constants driving the grace hash join used when
+// --external is given. Both files are split into PARTITIONS headless CSV
+// files, according to a hash of the join keys, so that matching rows end up
+// in the same pair of partitions. Partitions that remain too large to fit in
+// memory are split again, up to MAX_PARTITIONING_DEPTH times (this can happen
+// when keys are very skewed, in which case splitting again would be useless).
+const PARTITIONS: u64 = 32;
+const MAX_PARTITIONING_DEPTH: u64 = 4;
+
+type PartitionReader = csv::Reader<Box<dyn io::Read>>;
+
+struct Partition {
+    left: PathBuf,
+    right: PathBuf,
+}
+
+impl Partition {
+    fn paths(dir: &Path, depth: u64, id: &str) -> Vec<Self> {
+        (0..PARTITIONS)
+            .map(|i| Self {
+                left: dir.join(format!("{}{}-{}-left.csv", id, depth, i)),
+                right: dir.join(format!("{}{}-{}-right.csv", id, depth, i)),
+            })
+            .collect()
+    }
+
+    // NOTE: partition files are only created when some row falls into them,
+    // so a missing file is equivalent to an empty one.
+    fn is_empty(&self) -> bool {
+        !self.left.exists() && !self.right.exists()
+    }
+
+    fn len(path: &Path) -> u64 {
+        fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn readers(&self) -> CliResult<(PartitionReader, PartitionReader)> {
+        let open = |path: &Path| -> CliResult<PartitionReader> {
+            let reader: Box<dyn io::Read> = if path.exists() {
+                Box::new(fs::File::open(path)?)
+            } else {
+                Box::new(io::empty())
+            };
+
+            Ok(csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(reader))
+        };
+
+        Ok((open(&self.left)?, open(&self.right)?))
+    }
+
+    fn remove(&self) -> io::Result<()> {
+        for path in [&self.left, &self.right] {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Partitioner {
+    hasher: RandomState,
+    case_insensitive: bool,
+}
+
+impl Partitioner {
+    fn new(depth: u64, case_insensitive: bool) -> Self {
+        Self {
+            hasher: RandomState::with_seeds(depth, 0x9e3779b97f4a7c15, 0xbf58476d1ce4e5b9, 42),
+            case_insensitive,
+        }
+    }
+
+    fn split<R: io::Read>(
+        &self,
+        reader: &mut csv::Reader<R>,
+        sel: &Selection,
+        paths: impl Iterator<Item = PathBuf>,
+    ) -> CliResult<()> {
+        let paths = paths.collect::<Vec<_>>();
+        let mut writers: Vec<Option<csv::Writer<fs::File>>> = paths.iter().map(|_| None).collect();
+
+        let mut record = ByteRecord::new();
+
+        while reader.read_byte_record(&mut record)? {
+            let key = get_row_key(sel, &record, self.case_insensitive);
+            let i = (self.hasher.hash_one(&key) % paths.len() as u64) as usize;
+
+            let writer = match &mut writers[i] {
+                Some(writer) => writer,
+                slot => slot.insert(
+                    csv::WriterBuilder::new()
+                        .flexible(true)
+                        .from_path(&paths[i])?,
+                ),
+            };
+
+            writer.write_byte_record(&record)?;
+        }
+
+        for writer in writers.iter_mut().flatten() {
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn split_pair<L: io::Read, R: io::Read>(
+        &self,
+        left: (&mut csv::Reader<L>, &Selection),
+        right: (&mut csv::Reader<R>, &Selection),
+        partitions: &[Partition],
+    ) -> CliResult<()> {
+        self.split(left.0, left.1, partitions.iter().map(|p| p.left.clone()))?;
+        self.split(right.0, right.1, partitions.iter().map(|p| p.right.clone()))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PointKind {
+    Number,
+    Date,
+}
+
+impl PointKind {
+    fn infer(cell: &[u8]) -> Self {
+        if fast_float::parse::<f64, &[u8]>(cell).is_ok() {
+            Self::Number
+        } else {
+            Self::Date
+        }
+    }
+
+    // NOTE: dates are represented as fractional seconds since the Unix epoch
+    fn parse(&self, cell: &[u8]) -> CliResult<Option<f64>> {
+        if cell.is_empty() {
+            return Ok(None);
+        }
+
+        let parsed = match self {
+            Self::Number => fast_float::parse::<f64, &[u8]>(cell).ok(),
+            Self::Date => std::str::from_utf8(cell)
+                .ok()
+                .and_then(|string| parse_zoned(string, None, None).ok())
+                .map(|zoned| zoned.timestamp().as_nanosecond() as f64 / 1e9),
+        };
+
+        match parsed {
+            Some(point) => Ok(Some(point)),
+            None => Err(format!(
+                "could not parse \"{}\" as {}!",
+                String::from_utf8_lossy(cell),
+                match self {
+                    Self::Number => "a number",
+                    Self::Date => "a date",
+                }
+            ))?,
+        }
+    }
+
+    fn parse_tolerance(&self, string: &str) -> CliResult<f64> {
+        let tolerance = match self {
+            Self::Number => string.parse::<f64>().ok(),
+            Self::Date => parse_duration(string).map(|duration| duration.as_secs_f64()),
+        };
+
+        match tolerance {
+            Some(t) if t >= 0.0 => Ok(t),
+            _ => Err(format!("invalid --tolerance \"{}\"!", string))?,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "String")]
+enum AsofDirection {
+    Backward,
+    Forward,
+    Nearest,
+}
+
+impl TryFrom<String> for AsofDirection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "backward" => Self::Backward,
+            "forward" => Self::Forward,
+            "nearest" => Self::Nearest,
+            _ => return Err(format!("unsupported --direction \"{}\"", &value)),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct RangeEntry {
+    start: f64,
+    end: f64,
+    max_end: f64,
+    id: usize,
+}
+
+// NOTE: index used by as-of & interval joins. Entries of each group are sorted
+// by start, and we keep track of the max end seen so far, so that we can stop
+// looking for intervals containing some value as soon as possible.
+#[derive(Debug)]
+struct RangeIndex {
+    kind: PointKind,
+    groups: HashMap<IndexKey, Vec<RangeEntry>>,
+    records: Vec<ByteRecord>,
+}
+
+impl RangeIndex {
+    fn from_csv_reader<R: io::Read>(
+        reader: &mut csv::Reader<R>,
+        sel: &Selection,
+        by: Option<&Selection>,
+        case_insensitive: bool,
+    ) -> CliResult<Self> {
+        let mut kind: Option<PointKind> = None;
+        let mut groups: HashMap<IndexKey, Vec<RangeEntry>> = HashMap::new();
+        let mut records = Vec::new();
+
+        for result in reader.byte_records() {
+            let record = result?;
+            let start_cell = &record[sel[0]];
+            let end_cell = sel.get(1).map(|i| &record[*i]);
+
+            if start_cell.is_empty() {
+                continue;
+            }
+
+            let kind = *kind.get_or_insert_with(|| PointKind::infer(start_cell));
+
+            let start = kind.parse(start_cell)?.unwrap();
+
+            let end = match end_cell {
+                None => start,
+                Some(cell) => match kind.parse(cell)? {
+                    None => continue,
+                    Some(end) => end,
+                },
+            };
+
+            let key = by
+                .map(|by_sel| get_row_key(by_sel, &record, case_insensitive))
+                .unwrap_or_default();
+
+            groups.entry(key).or_default().push(RangeEntry {
+                start,
+                end,
+                max_end: end,
+                id: records.len(),
+            });
+
+            records.push(record);
+        }
+
+        for entries in groups.values_mut() {
+            entries.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+            let mut max_end = f64::NEG_INFINITY;
+
+            for entry in entries.iter_mut() {
+                max_end = max_end.max(entry.end);
+                entry.max_end = max_end;
+            }
+        }
+
+        Ok(Self {
+            kind: kind.unwrap_or(PointKind::Number),
+            groups,
+            records,
+        })
+    }
+
+    fn closest(
+        &self,
+        key: &IndexKey,
+        point: f64,
+        direction: AsofDirection,
+        tolerance: Option<f64>,
+    ) -> Option<&ByteRecord> {
+        let entries = self.groups.get(key)?;
+
+        let backward = || {
+            let i = entries.partition_point(|entry| entry.start <= point);
+            i.checked_sub(1).map(|i| &entries[i])
+        };
+
+        let forward = || {
+            let i = entries.partition_point(|entry| entry.start < point);
+            entries.get(i)
+        };
+
+        let entry = match direction {
+            AsofDirection::Backward => backward(),
+            AsofDirection::Forward => forward(),
+            AsofDirection::Nearest => match (backward(), forward()) {
+                (Some(b), Some(f)) => {
+                    if f.start - point < point - b.start {
+                        Some(f)
+                    } else {
+                        Some(b)
+                    }
+                }
+                (b, f) => b.or(f),
+            },
+        }?;
+
+        if let Some(t) = tolerance {
+            if (entry.start - point).abs() > t {
+                return None;
+            }
+        }
+
+        Some(&self.records[entry.id])
+    }
+
+    fn containing(&self, key: &IndexKey, point: f64) -> Vec<&ByteRecord> {
+        let mut ids = Vec::new();
+
+        if let Some(entries) = self.groups.get(key) {
+            let i = entries.partition_point(|entry| entry.start <= point);
+
+            for entry in entries[..i].iter().rev() {
+                if entry.max_end <= point {
+                    break;
+                }
+
+                if entry.end > point {
+                    ids.push(entry.id);
+                }
+            }
+        }
+
+        ids.sort();
+        ids.into_iter().map(|id| &self.records[id]).collect()
+    }
+}
+
 static USAGE: &str = "
 Join two sets of CSV data on the specified columns.
 
@@ -232,6 +603,35 @@ gzipped files out of the box.
                     file is streamed. Prefer placing the smaller file
                     on the left.
 
+If the indexed file cannot fit in memory, you can use the -e/--external flag
+to perform a \"grace hash join\" instead. Both files will first be partitioned
+on disk according to a hash of their join keys, and matching partitions will
+then be joined one at a time, so that the size of indexed data never exceeds
+the limit given to -m/--memory-limit. Partitions will be written in --tmp-dir
+and deleted once the join is done. Note that, in this case, the order of output
+rows is not guaranteed anymore, and that it does not work with --cross.
+
+# As-of & interval joins
+
+Using the --asof flag, the command will match each row of the first file with
+the row of the second file having the closest preceding value in the selected
+columns, e.g. to find the last known price of some stock at the time of a trade.
+The --direction flag can be used to match the closest following value, or the
+nearest one instead, and the --tolerance flag can be used to restrict the
+maximum distance between matched values.
+
+Using the --interval flag, the command will match each row of the first file
+with every row of the second file whose [start, end) range, as given by two
+selected columns, contains the value of the column selected in the first file.
+
+In both cases, values will be compared as numbers or, if they cannot be parsed
+as such, as dates. When comparing dates, the tolerance must be given as a
+duration, e.g. \"90s\", \"2h 30m\" or \"3 days\". The -B/--by flag can be used
+to only match rows sharing the same values in some columns, and the --left flag
+can be used to keep rows of the first file that were not matched. Note that
+the second file will be indexed in memory, but none of the files need to be
+sorted beforehand.
+
 Usage:
     xan join [options] <columns1> <input1> <columns2> <input2>
     xan join [options] --cross <input1> <input2>
@@ -266,6 +666,25 @@ join options:
                                  first dataset.
     -R, --prefix-right <prefix>  Add a prefix to the names of the columns in the
                                  second dataset.
+    --asof                       Do an \"as-of\" join. See the section about as-of
+                                 & interval joins for more details.
+    --interval                   Do an \"interval\" join. The second selection must
+                                 contain two columns, for the start & end of ranges.
+    --direction <dir>            Direction in which to find the closest value when
+                                 doing an as-of join. Must be one of \"backward\",
+                                 \"forward\" or \"nearest\". [default: backward]
+    --tolerance <value>          Maximum distance between values matched by an as-of
+                                 join. Must be a duration when comparing dates.
+    -B, --by <columns>           Only match rows having the same values in the given
+                                 columns, when doing an as-of or interval join.
+    -e, --external               Whether to partition both files on disk to perform
+                                 a grace hash join, if the indexed file cannot fit
+                                 in memory.
+    --tmp-dir <arg>              Directory where partitions will be written when
+                                 using external joins. Will default to the first
+                                 file's directory or \"./\" if reading an incoming stream.
+    -m, --memory-limit <arg>     Maximum size of indexed partitions when using
+                                 external joins, in megabytes. [default: 512].
 
 Common options:
     -h, --help                  Display this message
@@ -296,10 +715,16 @@ struct Args {
     flag_delimiter: Option<Delimiter>,
     flag_prefix_left: Option<String>,
     flag_prefix_right: Option<String>,
+    flag_external: bool,
+    flag_tmp_dir: Option<String>,
+    flag_memory_limit: u64,
+    flag_asof: bool,
+    flag_interval: bool,
+    flag_direction: AsofDirection,
+    flag_tolerance: Option<String>,
+    flag_by: Option<SelectColumns>,
 }
 
-type BoxedReader = csv::Reader<Box<dyn io::Read + Send>>;
-
 impl Args {
     fn readers_and_selections(
         &self,
@@ -320,7 +745,17 @@ impl Args {
         let left_sel = left.selection(left_reader.byte_headers()?)?;
         let right_sel = right.selection(right_reader.byte_headers()?)?;
 
-        if !self.flag_cross && left_sel.len() != right_sel.len() {
+        if self.flag_asof || self.flag_interval {
+            let expected = if self.flag_interval { 2 } else { 1 };
+
+            if left_sel.len() != 1 || right_sel.len() != expected {
+                Err(if self.flag_interval {
+                    "--interval expects a single column on left & two columns on right!"
+                } else {
+                    "--asof expects a single column on left & right!"
+                })?;
+            }
+        } else if !self.flag_cross && left_sel.len() != right_sel.len() {
             Err("not the same number of columns selected on left & right!")?;
         }
 
@@ -331,7 +766,7 @@ impl Args {
         Config::new(&self.flag_output)
     }
 
-    fn index(&self, reader: &mut BoxedReader, sel: &Selection) -> CliResult<Index> {
+    fn index<R: io::Read>(&self, reader: &mut csv::Reader<R>, sel: &Selection) -> CliResult<Index> {
         Index::from_csv_reader(reader, sel, self.flag_ignore_case, self.flag_nulls)
     }
 
@@ -353,53 +788,185 @@ impl Args {
         Ok(())
     }
 
-    fn inner_join(self) -> CliResult<()> {
+    fn tmp_dir(&self) -> PathBuf {
+        match &self.flag_tmp_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.arg_input1)
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from("./")),
+        }
+    }
+
+    fn join(self, mode: JoinMode) -> CliResult<()> {
         let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
             self.readers_and_selections()?;
 
         let mut writer = self.wconf().writer()?;
 
-        self.write_headers(
-            &mut writer,
-            left_reader.byte_headers()?,
-            right_reader.byte_headers()?,
-        )?;
-
-        let mut index = self.index(&mut left_reader, &left_sel)?;
+        let left_headers = left_reader.byte_headers()?.clone();
+        let right_headers = right_reader.byte_headers()?.clone();
 
-        let mut right_record = csv::ByteRecord::new();
+        if matches!(mode, JoinMode::Semi | JoinMode::Anti) {
+            if !self.flag_no_headers {
+                writer.write_byte_record(&left_headers)?;
+            }
+        } else {
+            self.write_headers(&mut writer, &left_headers, &right_headers)?;
+        }
 
-        while right_reader.read_byte_record(&mut right_record)? {
-            index.for_each_record(&right_sel, &right_record, |left_record| {
-                writer.write_record(left_record.iter().chain(right_record.iter()))
-            })?;
+        let paddings = (get_padding(&left_headers), get_padding(&right_headers));
+
+        if self.flag_external {
+            let dir = tempfile::Builder::new()
+                .prefix(".xan-join-")
+                .tempdir_in(self.tmp_dir())?;
+
+            let partitions = Partition::paths(dir.path(), 0, "");
+
+            Partitioner::new(0, self.flag_ignore_case).split_pair(
+                (&mut left_reader, &left_sel),
+                (&mut right_reader, &right_sel),
+                &partitions,
+            )?;
+
+            for (i, partition) in partitions.iter().enumerate() {
+                self.join_partition(
+                    mode,
+                    partition,
+                    (&left_sel, &right_sel),
+                    &paddings,
+                    &mut writer,
+                    (1, &format!("{}-", i)),
+                )?;
+            }
+        } else {
+            self.join_readers(
+                mode,
+                (&mut left_reader, &left_sel),
+                (&mut right_reader, &right_sel),
+                &paddings,
+                &mut writer,
+            )?;
         }
 
         Ok(writer.flush()?)
     }
 
-    fn full_outer_join(self) -> CliResult<()> {
-        let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
-            self.readers_and_selections()?;
+    fn join_partition<W: io::Write>(
+        &self,
+        mode: JoinMode,
+        partition: &Partition,
+        (left_sel, right_sel): (&Selection, &Selection),
+        paddings: &(ByteRecord, ByteRecord),
+        writer: &mut csv::Writer<W>,
+        (depth, id): (u64, &str),
+    ) -> CliResult<()> {
+        if partition.is_empty() {
+            return Ok(());
+        }
 
-        let mut writer = self.wconf().writer()?;
+        let indexed_path = if mode.indexes_left() {
+            &partition.left
+        } else {
+            &partition.right
+        };
 
-        let left_headers = left_reader.byte_headers()?.clone();
-        let right_headers = right_reader.byte_headers()?.clone();
+        let (mut left_reader, mut right_reader) = partition.readers()?;
+
+        if depth <= MAX_PARTITIONING_DEPTH
+            && Partition::len(indexed_path) > self.flag_memory_limit * MB
+        {
+            let dir = partition.left.parent().unwrap();
+            let sub_partitions = Partition::paths(dir, depth, id);
+
+            Partitioner::new(depth, self.flag_ignore_case).split_pair(
+                (&mut left_reader, left_sel),
+                (&mut right_reader, right_sel),
+                &sub_partitions,
+            )?;
+
+            partition.remove()?;
+
+            for (i, sub_partition) in sub_partitions.iter().enumerate() {
+                self.join_partition(
+                    mode,
+                    sub_partition,
+                    (left_sel, right_sel),
+                    paddings,
+                    writer,
+                    (depth + 1, &format!("{}{}-", id, i)),
+                )?;
+            }
 
-        let left_padding = get_padding(&left_headers);
-        let right_padding = get_padding(&right_headers);
+            return Ok(());
+        }
 
-        self.write_headers(&mut writer, &left_headers, &right_headers)?;
+        self.join_readers(
+            mode,
+            (&mut left_reader, left_sel),
+            (&mut right_reader, right_sel),
+            paddings,
+            writer,
+        )?;
 
-        let mut index = self.index(&mut left_reader, &left_sel)?;
+        partition.remove()?;
+
+        Ok(())
+    }
+
+    fn join_readers<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        mode: JoinMode,
+        left: (&mut csv::Reader<L>, &Selection),
+        right: (&mut csv::Reader<R>, &Selection),
+        paddings: &(ByteRecord, ByteRecord),
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        match mode {
+            JoinMode::Inner => self.inner_join(left, right, writer),
+            JoinMode::Left => self.left_join(left, right, &paddings.1, writer),
+            JoinMode::Right => self.right_join(left, right, &paddings.0, writer),
+            JoinMode::Full => self.full_outer_join(left, right, paddings, writer),
+            JoinMode::Semi => self.semi_join(left, right, false, writer),
+            JoinMode::Anti => self.semi_join(left, right, true, writer),
+        }
+    }
+
+    fn inner_join<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        (left_reader, left_sel): (&mut csv::Reader<L>, &Selection),
+        (right_reader, right_sel): (&mut csv::Reader<R>, &Selection),
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        let mut index = self.index(left_reader, left_sel)?;
+
+        let mut right_record = csv::ByteRecord::new();
+
+        while right_reader.read_byte_record(&mut right_record)? {
+            index.for_each_record(right_sel, &right_record, |left_record| {
+                writer.write_record(left_record.iter().chain(right_record.iter()))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn full_outer_join<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        (left_reader, left_sel): (&mut csv::Reader<L>, &Selection),
+        (right_reader, right_sel): (&mut csv::Reader<R>, &Selection),
+        (left_padding, right_padding): &(ByteRecord, ByteRecord),
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        let mut index = self.index(left_reader, left_sel)?;
 
         let mut right_record = csv::ByteRecord::new();
 
         while right_reader.read_byte_record(&mut right_record)? {
             let mut something_was_written: bool = false;
 
-            index.for_each_node_mut(&right_sel, &right_record, |left_node| {
+            index.for_each_node_mut(right_sel, &right_record, |left_node| {
                 something_was_written = true;
                 left_node.written = true;
                 writer.write_record(left_node.record.iter().chain(right_record.iter()))
@@ -414,30 +981,24 @@ impl Args {
             writer.write_record(left_record.iter().chain(right_padding.iter()))?;
         }
 
-        Ok(writer.flush()?)
+        Ok(())
     }
 
-    fn left_join(self) -> CliResult<()> {
-        let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
-            self.readers_and_selections()?;
-
-        let mut writer = self.wconf().writer()?;
-
-        let left_headers = left_reader.byte_headers()?.clone();
-        let right_headers = right_reader.byte_headers()?.clone();
-
-        let right_padding = get_padding(&right_headers);
-
-        self.write_headers(&mut writer, &left_headers, &right_headers)?;
-
-        let mut index = self.index(&mut right_reader, &right_sel)?;
+    fn left_join<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        (left_reader, left_sel): (&mut csv::Reader<L>, &Selection),
+        (right_reader, right_sel): (&mut csv::Reader<R>, &Selection),
+        right_padding: &ByteRecord,
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        let mut index = self.index(right_reader, right_sel)?;
 
         let mut left_record = csv::ByteRecord::new();
 
         while left_reader.read_byte_record(&mut left_record)? {
             let mut something_was_written: bool = false;
 
-            index.for_each_record(&left_sel, &left_record, |right_record| {
+            index.for_each_record(left_sel, &left_record, |right_record| {
                 something_was_written = true;
                 writer.write_record(left_record.iter().chain(right_record.iter()))
             })?;
@@ -447,30 +1008,24 @@ impl Args {
             }
         }
 
-        Ok(writer.flush()?)
+        Ok(())
     }
 
-    fn right_join(self) -> CliResult<()> {
-        let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
-            self.readers_and_selections()?;
-
-        let mut writer = self.wconf().writer()?;
-
-        let left_headers = left_reader.byte_headers()?.clone();
-        let right_headers = right_reader.byte_headers()?.clone();
-
-        let left_padding = get_padding(&left_headers);
-
-        self.write_headers(&mut writer, &left_headers, &right_headers)?;
-
-        let mut index = self.index(&mut left_reader, &left_sel)?;
+    fn right_join<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        (left_reader, left_sel): (&mut csv::Reader<L>, &Selection),
+        (right_reader, right_sel): (&mut csv::Reader<R>, &Selection),
+        left_padding: &ByteRecord,
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        let mut index = self.index(left_reader, left_sel)?;
 
         let mut right_record = csv::ByteRecord::new();
 
         while right_reader.read_byte_record(&mut right_record)? {
             let mut something_was_written: bool = false;
 
-            index.for_each_record(&right_sel, &right_record, |left_record| {
+            index.for_each_record(right_sel, &right_record, |left_record| {
                 something_was_written = true;
                 writer.write_record(left_record.iter().chain(right_record.iter()))
             })?;
@@ -480,25 +1035,22 @@ impl Args {
             }
         }
 
-        Ok(writer.flush()?)
+        Ok(())
     }
 
-    fn semi_join(self, anti: bool) -> CliResult<()> {
-        let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
-            self.readers_and_selections()?;
-
-        let mut writer = self.wconf().writer()?;
-
-        if !self.flag_no_headers {
-            writer.write_byte_record(left_reader.byte_headers()?)?;
-        }
-
+    fn semi_join<L: io::Read, R: io::Read, W: io::Write>(
+        &self,
+        (left_reader, left_sel): (&mut csv::Reader<L>, &Selection),
+        (right_reader, right_sel): (&mut csv::Reader<R>, &Selection),
+        anti: bool,
+        writer: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
         let mut index: HashSet<IndexKey> = HashSet::new();
 
         let mut right_record = csv::ByteRecord::new();
 
         while right_reader.read_byte_record(&mut right_record)? {
-            let key = get_row_key(&right_sel, &right_record, self.flag_ignore_case);
+            let key = get_row_key(right_sel, &right_record, self.flag_ignore_case);
 
             if !self.flag_nulls && key.iter().all(|c| c.is_empty()) {
                 continue;
@@ -510,7 +1062,7 @@ impl Args {
         let mut left_record = csv::ByteRecord::new();
 
         while left_reader.read_byte_record(&mut left_record)? {
-            let key = get_row_key(&left_sel, &left_record, self.flag_ignore_case);
+            let key = get_row_key(left_sel, &left_record, self.flag_ignore_case);
             let mut is_match = index.contains(&key);
 
             if anti {
@@ -522,6 +1074,79 @@ impl Args {
             }
         }
 
+        Ok(())
+    }
+
+    fn range_join(self) -> CliResult<()> {
+        let ((mut left_reader, left_sel), (mut right_reader, right_sel)) =
+            self.readers_and_selections()?;
+
+        let mut writer = self.wconf().writer()?;
+
+        let left_headers = left_reader.byte_headers()?.clone();
+        let right_headers = right_reader.byte_headers()?.clone();
+
+        let right_padding = get_padding(&right_headers);
+
+        self.write_headers(&mut writer, &left_headers, &right_headers)?;
+
+        let by_sels = self
+            .flag_by
+            .as_ref()
+            .map(|by| -> CliResult<(Selection, Selection)> {
+                let left_by_sel = by.selection(&left_headers, !self.flag_no_headers)?;
+                let right_by_sel = by.selection(&right_headers, !self.flag_no_headers)?;
+
+                Ok((left_by_sel, right_by_sel))
+            })
+            .transpose()?;
+
+        let index = RangeIndex::from_csv_reader(
+            &mut right_reader,
+            &right_sel,
+            by_sels.as_ref().map(|(_, right_by_sel)| right_by_sel),
+            self.flag_ignore_case,
+        )?;
+
+        let tolerance = self
+            .flag_tolerance
+            .as_ref()
+            .map(|string| index.kind.parse_tolerance(string))
+            .transpose()?;
+
+        let mut left_record = csv::ByteRecord::new();
+
+        while left_reader.read_byte_record(&mut left_record)? {
+            let key = by_sels
+                .as_ref()
+                .map(|(left_by_sel, _)| {
+                    get_row_key(left_by_sel, &left_record, self.flag_ignore_case)
+                })
+                .unwrap_or_default();
+
+            let matches = match index.kind.parse(&left_record[left_sel[0]])? {
+                None => vec![],
+                Some(point) => {
+                    if self.flag_asof {
+                        index
+                            .closest(&key, point, self.flag_direction, tolerance)
+                            .into_iter()
+                            .collect()
+                    } else {
+                        index.containing(&key, point)
+                    }
+                }
+            };
+
+            if matches.is_empty() && self.flag_left {
+                writer.write_record(left_record.iter().chain(right_padding.iter()))?;
+            }
+
+            for right_record in matches {
+                writer.write_record(left_record.iter().chain(right_record.iter()))?;
+            }
+        }
+
         Ok(writer.flush()?)
     }
 
@@ -571,21 +1196,47 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Err("Please pick exactly one join operation.")?;
     }
 
-    if args.flag_left {
-        args.left_join()
+    if args.flag_asof || args.flag_interval {
+        if args.flag_asof && args.flag_interval {
+            Err("--asof cannot be used with --interval!")?;
+        }
+
+        if args.flag_right
+            || args.flag_full
+            || args.flag_semi
+            || args.flag_anti
+            || args.flag_cross
+            || args.flag_external
+        {
+            Err("--asof & --interval only work with --left!")?;
+        }
+
+        return args.range_join();
+    }
+
+    if args.flag_cross {
+        if args.flag_external {
+            Err("-e/--external does not work with --cross!")?;
+        }
+
+        return args.cross_join();
+    }
+
+    let mode = if args.flag_left {
+        JoinMode::Left
     } else if args.flag_right {
-        args.right_join()
+        JoinMode::Right
     } else if args.flag_full {
-        args.full_outer_join()
-    } else if args.flag_cross {
-        args.cross_join()
+        JoinMode::Full
     } else if args.flag_semi {
-        args.semi_join(false)
+        JoinMode::Semi
     } else if args.flag_anti {
-        args.semi_join(true)
+        JoinMode::Anti
     } else {
-        args.inner_join()
-    }
+        JoinMode::Inner
+    };
+
+    args.join(mode)
 }
 
 #[cfg(test)]
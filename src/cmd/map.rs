@@ -3,7 +3,7 @@ use std::fs;
 use pariter::IteratorExt;
 
 use crate::config::{Config, Delimiter};
-use crate::moonblade::SelectionProgram;
+use crate::moonblade::{load_prelude, SelectionProgram};
 use crate::util;
 use crate::CliResult;
 
@@ -73,6 +73,9 @@ Usage:
 
 map options:
     -f, --evaluate-file        Read evaluation expression from a file instead.
+    --prelude <path>           Load user-defined functions from this file. Defaults to
+                               the file given by the XAN_PRELUDE environment variable,
+                               if any.
     -O, --overwrite            If set, expressions named with a column already existing
                                in the file will be overwritten with the result of the
                                expression instead of adding a new column at the end.
@@ -104,6 +107,7 @@ struct Args {
     flag_parallel: bool,
     flag_threads: Option<usize>,
     flag_evaluate_file: bool,
+    flag_prelude: Option<String>,
     flag_overwrite: bool,
 }
 
@@ -113,6 +117,10 @@ impl Args {
             self.arg_expression = fs::read_to_string(&self.arg_expression)?;
         }
 
+        if let Some(path) = &self.flag_prelude {
+            load_prelude(path)?;
+        }
+
         Ok(())
     }
 }
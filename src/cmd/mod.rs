@@ -8,6 +8,7 @@ pub mod compgen;
 pub mod completions;
 pub mod count;
 pub mod dedup;
+pub mod diff;
 pub mod drop;
 pub mod enumerate;
 pub mod eval;
@@ -28,6 +29,7 @@ pub mod heatmap;
 pub mod help;
 pub mod hist;
 pub mod implode;
+pub mod index;
 pub mod input;
 pub mod join;
 pub mod map;
@@ -36,13 +38,16 @@ pub mod merge;
 pub mod network;
 pub mod parallel;
 pub mod partition;
+pub mod patch;
 pub mod pivot;
 pub mod plot;
 pub mod progress;
 pub mod range;
 pub mod rename;
+pub mod resample;
 pub mod reverse;
 pub mod sample;
+pub mod schema;
 pub mod scrape;
 pub mod search;
 pub mod select;
@@ -50,6 +55,7 @@ pub mod shuffle;
 pub mod slice;
 pub mod sort;
 pub mod split;
+pub mod sql;
 pub mod stats;
 pub mod tail;
 pub mod to;
@@ -1,10 +1,14 @@
 use std::io::{stderr, Write};
 
+use ahash::RandomState;
 use colored::Colorize;
+use indexmap::IndexMap;
 
 use crate::collections::IncrementalId;
 use crate::config::{Config, Delimiter};
-use crate::graph::GraphBuilder;
+use crate::graph::{
+    BipartiteProjection, CommunityAlgorithm, GraphBuilder, NodeColumn, ProjectionWeight,
+};
 use crate::json::{Attributes, JSONEmptyMode, JSONTypeInferrenceBuffer};
 use crate::select::{SelectColumns, Selection};
 use crate::util;
@@ -18,53 +22,133 @@ Supported input types:
                sources and another column targets.
     bipartite: converts a CSV with two columns representing the
                edges between both parts of a bipartite graph.
+    cooccurrence: converts a CSV with a column containing nodes that
+               co-occur when found in the same group of rows (see -g/--groupby)
+               or in the same cell (see --sep), into an undirected graph whose
+               edges are weighted by their number of co-occurrences.
+    adjacency: converts a CSV with a column representing nodes and
+               another one containing their neighbors (see --sep). Other
+               columns are used as node attributes.
 
 Supported output formats:
     json - Graphology JSON serialization format
            ref: https://graphology.github.io/serialization.html
     gexf - Graph eXchange XML Format
            ref: https://gexf.net/
+    graphml - GraphML XML format
+           ref: http://graphml.graphdrawing.org/
+    dot - Graphviz DOT language
+           ref: https://graphviz.org/doc/info/lang.html
+    pajek - Pajek NET format
     nodelist - CSV nodelist
+    edgelist - CSV edgelist, once parallel edges have been aggregated
+           and nodes filtered (see -L/--largest-component)
 
 Usage:
     xan network edgelist [options] <source> <target> [<input>]
     xan network bipartite [options] <part1> <part2> [<input>]
+    xan network cooccurrence [options] <column> [<input>]
+    xan network adjacency [options] <node> <neighbors> [<input>]
     xan network --help
 
 xan network options:
-    -f, --format <format>     One of \"json\", \"gexf\" or \"nodelist\".
+    -f, --format <format>     One of \"json\", \"gexf\", \"graphml\", \"dot\",
+                              \"pajek\", \"nodelist\" or \"edgelist\".
                               [default: json]
     --gexf-version <version>  GEXF version to output. Can be one of \"1.2\"
                               or \"1.3\".
                               [default: 1.2]
     -L, --largest-component   Only keep the largest connected component
                               in the resulting graph.
+    --min-weight <weight>     Only keep edges whose weight, once parallel edges
+                              have been aggregated, is at least <weight>.
+    -k, --k-core <k>          Only keep the k-core of the graph, i.e. the largest
+                              subgraph whose nodes all have a degree of at
+                              least <k>. Edge direction is ignored.
+    --ego <nodes>             Only keep the nodes found at most --radius hops away
+                              from given seed nodes, separated by \"|\". Edge
+                              direction is ignored.
+    --radius <n>              Radius of the ego network built with --ego.
+                              [default: 1]
     --stats                   Print useful statistics about the generated graph
                               in stderr.
 
-network edgelist options:
+Filters are applied in this order: --min-weight, -k/--k-core, --ego and
+finally -L/--largest-component.
+
+network edgelist & adjacency options:
     -U, --undirected       Whether the graph is undirected.
+
+network edgelist options:
+    -w, --weight <column>  Column containing numerical edge weights, that will
+                           be added as a \"weight\" edge attribute. If not
+                           given, each edge row has a weight of 1. Weights
+                           of parallel edges, i.e. rows with same source &
+                           target, are always summed.
     --nodes <path>         Path to a CSV file containing node metadata
                            (use \"-\" to feed the file from stdin).
     --node-column <name>   Name of the column containing node keys.
                            [default: node]
 
 network bipartite options:
-    -D, --disjoint-keys  Pass this if you know both partitions of the graph
-                         use disjoint sets of keys (i.e. if you know they share
-                         no common keys at all). Incorrect graphs will be produced
-                         if some keys are used by both partitions!
+    -D, --disjoint-keys           Pass this if you know both partitions of the graph
+                                  use disjoint sets of keys (i.e. if you know they share
+                                  no common keys at all). Incorrect graphs will be produced
+                                  if some keys are used by both partitions!
+    -P, --project <part>          Project the bipartite graph onto one of its parts,
+                                  given as either <part1> or <part2>. Nodes of the
+                                  resulting undirected graph are linked when they
+                                  share neighbors in the other part.
+    --projection-weight <method>  How to weight edges of the projected graph. Can
+                                  be one of:
+                                      - \"count\": number of shared neighbors
+                                      - \"jaccard\": Jaccard similarity of both
+                                        nodes neighborhoods
+                                      - \"newman\": shared neighbors weighted by
+                                        the inverse of their degree minus one
+                                  [default: count]
+
+network cooccurrence options:
+    -g, --groupby <cols>  Columns identifying the groups of rows within which
+                          nodes co-occur. If not given, each row is its own group.
+
+network cooccurrence & adjacency options:
+    --sep <sep>  Separator used to split cells into multiple nodes. Required
+                 by \"cooccurrence\" if -g/--groupby is not given. Defaults
+                 to \"|\" for \"adjacency\".
 
 network -f \"nodelist\" options:
-    --degrees  Whether to compute node degrees and add relevant columns to the
-               CSV output.
+    --degrees              Whether to compute node degrees and add relevant columns to the
+                           CSV output.
+    --weighted-degrees     Whether to compute the sum of the weights of node edges
+                           and add relevant columns to the CSV output.
+    --pagerank             Whether to compute the weighted PageRank of nodes and add
+                           a \"pagerank\" column to the CSV output.
+    --alpha <alpha>        Damping factor used by PageRank.
+                           [default: 0.85]
+    --betweenness          Whether to compute the betweenness centrality of nodes,
+                           using unweighted shortest paths, and add a \"betweenness\"
+                           column to the CSV output.
+    --samples <k>          Approximate betweenness centrality by only using k
+                           random source nodes. Recommended for large graphs.
+    --seed <seed>          Seed for the RNG used by --samples.
+    --components           Whether to add a \"component\" column containing the id
+                           of the connected component of each node. Components
+                           are weakly connected if the graph is directed.
+    --communities <algo>   Whether to detect communities using the given algorithm
+                           and add a \"community\" column to the CSV output. Can
+                           be either \"louvain\" or \"leiden\". Directed graphs
+                           are considered as undirected and edge weights are used.
+    --resolution <r>       Resolution used by community detection. Higher values
+                           yield more & smaller communities.
+                           [default: 1]
 
 Common options:
     -h, --help             Display this message
     -o, --output <file>    Write output to <file> instead of stdout.
     -n, --no-headers       When set, the file will be considered as having no
                            headers.
-    -d, --delimiter <arg>  The field delimiter foDirectedr reading CSV data.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character.
 ";
 
@@ -72,20 +156,43 @@ Common options:
 struct Args {
     cmd_edgelist: bool,
     cmd_bipartite: bool,
+    cmd_cooccurrence: bool,
+    cmd_adjacency: bool,
     arg_input: Option<String>,
     arg_source: Option<SelectColumns>,
     arg_target: Option<SelectColumns>,
     arg_part1: Option<SelectColumns>,
     arg_part2: Option<SelectColumns>,
+    arg_column: Option<SelectColumns>,
+    arg_node: Option<SelectColumns>,
+    arg_neighbors: Option<SelectColumns>,
     flag_format: String,
     flag_gexf_version: String,
     flag_largest_component: bool,
     flag_stats: bool,
     flag_undirected: bool,
+    flag_weight: Option<SelectColumns>,
     flag_nodes: Option<String>,
     flag_node_column: SelectColumns,
     flag_disjoint_keys: bool,
+    flag_project: Option<SelectColumns>,
+    flag_projection_weight: ProjectionWeight,
+    flag_min_weight: Option<f64>,
+    flag_k_core: Option<usize>,
+    flag_ego: Option<String>,
+    flag_radius: usize,
+    flag_groupby: Option<SelectColumns>,
+    flag_sep: Option<String>,
     flag_degrees: bool,
+    flag_weighted_degrees: bool,
+    flag_pagerank: bool,
+    flag_alpha: f64,
+    flag_betweenness: bool,
+    flag_samples: Option<usize>,
+    flag_seed: Option<usize>,
+    flag_components: bool,
+    flag_communities: Option<CommunityAlgorithm>,
+    flag_resolution: f64,
     flag_no_headers: bool,
     flag_delimiter: Option<Delimiter>,
     flag_output: Option<String>,
@@ -97,7 +204,7 @@ impl Args {
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers);
 
-        let mut graph_builder = GraphBuilder::new(self.flag_largest_component);
+        let mut graph_builder = GraphBuilder::new();
 
         let mut record = csv::StringRecord::new();
 
@@ -163,10 +270,20 @@ impl Args {
             .unwrap()
             .single_selection(&edge_headers, !self.flag_no_headers)?;
 
-        let edge_attr_sel = Selection::without_indices(
-            edge_headers.len(),
-            &[source_column_index, target_column_index],
-        );
+        let weight_column_index = self
+            .flag_weight
+            .as_ref()
+            .map(|sel| sel.single_selection(&edge_headers, !self.flag_no_headers))
+            .transpose()?;
+
+        let mut excluded_indices = vec![source_column_index, target_column_index];
+
+        if let Some(i) = weight_column_index {
+            excluded_indices.push(i);
+            graph_builder.mark_as_weighted();
+        }
+
+        let edge_attr_sel = Selection::without_indices(edge_headers.len(), &excluded_indices);
 
         let mut edge_attr_inferrence =
             JSONTypeInferrenceBuffer::new(edge_attr_sel.clone(), 512, JSONEmptyMode::Omit);
@@ -184,10 +301,18 @@ impl Args {
             edge_attr_inferrence.types(),
         );
 
-        let mut process_edge_record = |record: &csv::StringRecord| {
+        let mut process_edge_record = |record: &csv::StringRecord| -> CliResult<()> {
             let source = record[source_column_index].to_string();
             let target = record[target_column_index].to_string();
 
+            let weight = match weight_column_index {
+                None => 1.0,
+                Some(i) => record[i]
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("could not parse edge weight \"{}\"", &record[i]))?,
+            };
+
             let source_id = graph_builder.add_node(source, Attributes::default());
             let target_id = graph_builder.add_node(target, Attributes::default());
 
@@ -197,15 +322,17 @@ impl Args {
                 attributes.insert(k, v);
             }
 
-            graph_builder.add_edge(source_id, target_id, attributes);
+            graph_builder.add_edge(source_id, target_id, weight, attributes);
+
+            Ok(())
         };
 
         for buffered_record in edge_attr_inferrence.records() {
-            process_edge_record(buffered_record);
+            process_edge_record(buffered_record)?;
         }
 
         while edge_reader.read_record(&mut record)? {
-            process_edge_record(&record);
+            process_edge_record(&record)?;
         }
 
         Ok(graph_builder)
@@ -216,7 +343,7 @@ impl Args {
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers);
 
-        let mut graph_builder = GraphBuilder::new(self.flag_largest_component);
+        let mut graph_builder = GraphBuilder::new();
 
         graph_builder.mark_as_undirected();
 
@@ -237,6 +364,26 @@ impl Args {
             .unwrap()
             .single_selection(&headers, !self.flag_no_headers)?;
 
+        if let Some(part) = &self.flag_project {
+            let part_index = part.single_selection(&headers, !self.flag_no_headers)?;
+
+            let (projected_index, other_index) = if part_index == first_part_index {
+                (first_part_index, second_part_index)
+            } else if part_index == second_part_index {
+                (second_part_index, first_part_index)
+            } else {
+                Err("-P/--project must be either <part1> or <part2>!")?
+            };
+
+            let mut projection = BipartiteProjection::new();
+
+            while reader.read_record(&mut record)? {
+                projection.add_edge(&record[projected_index], &record[other_index]);
+            }
+
+            return Ok(projection.into_graph_builder(self.flag_projection_weight));
+        }
+
         let mut incremental_id =
             (!self.flag_disjoint_keys).then(IncrementalId::<(usize, String)>::new);
 
@@ -256,12 +403,166 @@ impl Args {
             graph_builder.add_edge(
                 first_part_node_id,
                 second_part_node_id,
+                1.0,
                 Attributes::default(),
             );
         }
 
         Ok(graph_builder)
     }
+
+    fn cooccurrence(&self) -> CliResult<GraphBuilder> {
+        let rconf = Config::new(&self.arg_input)
+            .delimiter(self.flag_delimiter)
+            .no_headers(self.flag_no_headers);
+
+        if self.flag_groupby.is_none() && self.flag_sep.is_none() {
+            Err("cooccurrence requires -g/--groupby or --sep!")?;
+        }
+
+        let mut graph_builder = GraphBuilder::new();
+
+        graph_builder.mark_as_undirected();
+        graph_builder.mark_as_weighted();
+
+        let mut reader = rconf.reader()?;
+        let mut record = csv::StringRecord::new();
+
+        let headers = reader.byte_headers()?.clone();
+
+        let column_index = self
+            .arg_column
+            .as_ref()
+            .unwrap()
+            .single_selection(&headers, !self.flag_no_headers)?;
+
+        let groupby_sel = self
+            .flag_groupby
+            .as_ref()
+            .map(|sel| sel.selection(&headers, !self.flag_no_headers))
+            .transpose()?;
+
+        let mut groups: IndexMap<Vec<Vec<u8>>, Vec<usize>, RandomState> = IndexMap::default();
+        let mut group: Vec<usize> = Vec::new();
+
+        fn add_cooccurrences(graph_builder: &mut GraphBuilder, group: &mut Vec<usize>) {
+            group.sort_unstable();
+            group.dedup();
+
+            for (i, source) in group.iter().enumerate() {
+                for target in group[i + 1..].iter() {
+                    graph_builder.add_edge(*source, *target, 1.0, Attributes::default());
+                }
+            }
+        }
+
+        while reader.read_record(&mut record)? {
+            let cell = &record[column_index];
+
+            let nodes: Box<dyn Iterator<Item = &str>> = match &self.flag_sep {
+                Some(sep) => Box::new(cell.split(sep.as_str())),
+                None => Box::new(std::iter::once(cell)),
+            };
+
+            let group = match &groupby_sel {
+                Some(sel) => groups
+                    .entry(sel.collect(record.as_byte_record()))
+                    .or_default(),
+                None => {
+                    group.clear();
+                    &mut group
+                }
+            };
+
+            for node in nodes.filter(|node| !node.is_empty()) {
+                group.push(graph_builder.add_node(node.to_string(), Attributes::default()));
+            }
+
+            if groupby_sel.is_none() {
+                add_cooccurrences(&mut graph_builder, group);
+            }
+        }
+
+        for group in groups.values_mut() {
+            add_cooccurrences(&mut graph_builder, group);
+        }
+
+        Ok(graph_builder)
+    }
+
+    fn adjacency(&self) -> CliResult<GraphBuilder> {
+        let rconf = Config::new(&self.arg_input)
+            .delimiter(self.flag_delimiter)
+            .no_headers(self.flag_no_headers);
+
+        let mut graph_builder = GraphBuilder::new();
+
+        if self.flag_undirected {
+            graph_builder.mark_as_undirected();
+        }
+
+        let sep = self.flag_sep.as_deref().unwrap_or("|");
+
+        let mut reader = rconf.reader()?;
+        let mut record = csv::StringRecord::new();
+
+        let byte_headers = reader.byte_headers()?.clone();
+
+        let node_column_index = self
+            .arg_node
+            .as_ref()
+            .unwrap()
+            .single_selection(&byte_headers, !self.flag_no_headers)?;
+        let neighbors_column_index = self
+            .arg_neighbors
+            .as_ref()
+            .unwrap()
+            .single_selection(&byte_headers, !self.flag_no_headers)?;
+
+        let node_attr_sel = Selection::without_indices(
+            byte_headers.len(),
+            &[node_column_index, neighbors_column_index],
+        );
+
+        let mut node_attr_inferrence =
+            JSONTypeInferrenceBuffer::new(node_attr_sel.clone(), 512, JSONEmptyMode::Omit);
+
+        node_attr_inferrence.read(&mut reader)?;
+
+        let headers = reader.headers()?.clone();
+
+        graph_builder.set_node_model(node_attr_sel.select(&headers), node_attr_inferrence.types());
+
+        let mut process_record = |record: &csv::StringRecord| {
+            let mut attributes = Attributes::with_capacity(node_attr_sel.len());
+
+            for (k, v) in node_attr_inferrence.cast(&headers, record).flatten() {
+                attributes.insert(k, v);
+            }
+
+            let node_id = graph_builder.add_node(record[node_column_index].to_string(), attributes);
+
+            for neighbor in record[neighbors_column_index]
+                .split(sep)
+                .filter(|neighbor| !neighbor.is_empty())
+            {
+                let neighbor_id =
+                    graph_builder.add_node(neighbor.to_string(), Attributes::default());
+
+                graph_builder.add_edge(node_id, neighbor_id, 1.0, Attributes::default());
+            }
+        };
+
+        for buffered_record in node_attr_inferrence.records() {
+            process_record(buffered_record);
+        }
+
+        while reader.read_record(&mut record)? {
+            process_record(&record);
+        }
+
+        Ok(graph_builder)
+    }
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -275,17 +576,43 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ))?;
     }
 
+    if !(0.0..=1.0).contains(&args.flag_alpha) {
+        Err("--alpha must be between 0 and 1!")?;
+    }
+
+    if matches!(args.flag_samples, Some(0)) {
+        Err("--samples must be > 0!")?;
+    }
+
     let builder = (if args.cmd_edgelist {
         args.edgelist()
     } else if args.cmd_bipartite {
         args.bipartite()
+    } else if args.cmd_cooccurrence {
+        args.cooccurrence()
+    } else if args.cmd_adjacency {
+        args.adjacency()
     } else {
         unreachable!()
     })?;
 
-    let degree_map = args.flag_degrees.then(|| builder.compute_degrees());
+    let mut graph = builder.build();
+
+    if let Some(min_weight) = args.flag_min_weight {
+        graph.filter_min_weight(min_weight);
+    }
+
+    if let Some(k) = args.flag_k_core {
+        graph.filter_k_core(k);
+    }
 
-    let graph = builder.build();
+    if let Some(seeds) = &args.flag_ego {
+        graph.filter_ego(&seeds.split('|').collect::<Vec<_>>(), args.flag_radius)?;
+    }
+
+    if args.flag_largest_component {
+        graph.filter_largest_component();
+    }
 
     if args.flag_stats {
         colored::control::set_override(true);
@@ -331,10 +658,51 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         writeln!(&mut out, "{} {}", "density    ".cyan(), stats.density)?;
     }
 
+    let mut columns: Vec<NodeColumn> = Vec::new();
+
+    if args.flag_format == "nodelist" {
+        if args.flag_degrees {
+            columns.extend(graph.compute_degrees().into_columns(""));
+        }
+
+        if args.flag_weighted_degrees {
+            columns.extend(graph.compute_weighted_degrees().into_columns("weighted_"));
+        }
+
+        if args.flag_pagerank {
+            columns.push(NodeColumn::new(
+                "pagerank",
+                graph.compute_pagerank(args.flag_alpha),
+            ));
+        }
+
+        if args.flag_betweenness {
+            columns.push(NodeColumn::new(
+                "betweenness",
+                graph.compute_betweenness(args.flag_samples, args.flag_seed),
+            ));
+        }
+
+        if args.flag_components {
+            columns.push(NodeColumn::new("component", graph.compute_components()));
+        }
+
+        if let Some(algorithm) = args.flag_communities {
+            columns.push(NodeColumn::new(
+                "community",
+                graph.compute_communities(algorithm, args.flag_resolution),
+            ));
+        }
+    }
+
     match args.flag_format.as_str() {
         "gexf" => graph.write_gexf(&mut writer, &args.flag_gexf_version),
         "json" => graph.write_json(&mut writer),
-        "nodelist" => graph.write_csv_nodelist(&mut writer, degree_map),
+        "graphml" => graph.write_graphml(&mut writer),
+        "dot" => graph.write_dot(&mut writer),
+        "pajek" => graph.write_pajek(&mut writer),
+        "edgelist" => graph.write_csv_edgelist(&mut writer),
+        "nodelist" => graph.write_csv_nodelist(&mut writer, &columns),
         _ => Err(format!("unsupported format: {}!", &args.flag_format))?,
     }
 }
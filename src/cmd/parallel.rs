@@ -728,15 +728,24 @@ impl Args {
                 .delimiter(self.flag_delimiter)
                 .no_headers(self.flag_no_headers);
 
-            let mut reader = config.io_reader_for_random_access()?;
+            // NOTE: if the file was indexed, segmentation can be exact
+            let (segments, headers) = if let Some(mut index) = config.record_index()? {
+                let headers = config.reader()?.byte_headers()?.clone();
 
-            // NOTE: we could fallback to not chunking the file
-            let (segments, sample) = segment_csv_file(
-                &mut reader,
-                || config.csv_reader_builder(),
-                SegmentationOptions::chunks(t),
-            )?
-            .ok_or_else(|| format!("could not segment {}", p))?;
+                (index.segments(t)?, headers)
+            } else {
+                let mut reader = config.io_reader_for_random_access()?;
+
+                // NOTE: we could fallback to not chunking the file
+                let (segments, sample) = segment_csv_file(
+                    &mut reader,
+                    || config.csv_reader_builder(),
+                    SegmentationOptions::chunks(t),
+                )?
+                .ok_or_else(|| format!("could not segment {}", p))?;
+
+                (segments, sample.headers)
+            };
 
             actual_threads += segments.len();
 
@@ -753,7 +762,7 @@ impl Args {
                     from,
                     to,
                     position: i,
-                    headers: sample.headers.clone(),
+                    headers: headers.clone(),
                 }));
             }
         }
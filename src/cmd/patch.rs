@@ -0,0 +1,553 @@
+use std::cmp::Ordering;
+
+use ahash::RandomState;
+use indexmap::IndexMap;
+
+use crate::cmd::diff::{
+    format_key, Key, SortedRecords, ADDED, ADDED_COLUMN, MODIFIED, MOVED_COLUMN, REMOVED,
+    REMOVED_COLUMN,
+};
+use crate::collections::HashMap;
+use crate::config::{Config, Delimiter};
+use crate::select::Selection;
+use crate::util;
+use crate::CliResult;
+
+static USAGE: &str = "
+Apply a diff produced by `xan diff` to the old version of a CSV file, in order
+to reproduce its new version.
+
+The key columns are read from the diff's header, and the patch is checked
+while being applied: the command will fail if a row to modify or remove cannot
+be found, if a row to add already exists, or if some modified cell does not
+hold the expected old value.
+
+For instance:
+
+    $ xan diff id old.csv new.csv > diff.csv
+    $ xan patch diff.csv old.csv > new.csv
+
+By default, the diff is entirely loaded in memory, rows of the old file are
+emitted in their original order and added rows are appended at the end of the
+file. If the old file is sorted on the key and the diff was produced using
+`xan diff --sorted`, use the -S/--sorted flag to apply the patch in a streaming
+fashion, which will exactly reproduce the new file if it was also sorted.
+
+Usage:
+    xan patch [options] <diff> [<input>]
+    xan patch --help
+
+patch options:
+    -S, --sorted           Use if both the old file and the diff are sorted on the
+                           key, to apply the patch in constant memory.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_diff: String,
+    arg_input: Option<String>,
+    flag_sorted: bool,
+    flag_output: Option<String>,
+    flag_delimiter: Option<Delimiter>,
+}
+
+enum RowPatch {
+    // column, new
+    Added(Vec<(Vec<u8>, Vec<u8>)>),
+    Removed,
+    // column, old, new
+    Modified(Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>),
+}
+
+struct ColumnChange {
+    op: Vec<u8>,
+    name: Vec<u8>,
+    old: Option<usize>,
+    new: Option<usize>,
+}
+
+fn invalid_diff(msg: &str) -> String {
+    format!("invalid diff: {}", msg)
+}
+
+fn parse_position(cell: &[u8]) -> CliResult<usize> {
+    Ok(std::str::from_utf8(cell)
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or_else(|| invalid_diff("column positions should be integers"))?)
+}
+
+struct DiffReader<R: std::io::Read> {
+    reader: csv::Reader<R>,
+    key_len: usize,
+    peeked: Option<csv::ByteRecord>,
+}
+
+impl<R: std::io::Read> DiffReader<R> {
+    fn new(mut reader: csv::Reader<R>) -> CliResult<(Self, Vec<Vec<u8>>)> {
+        let headers = reader.byte_headers()?.clone();
+        let n = headers.len();
+
+        if n < 5
+            || &headers[0] != b"diff"
+            || &headers[n - 3] != b"column"
+            || &headers[n - 2] != b"old"
+            || &headers[n - 1] != b"new"
+        {
+            Err(invalid_diff(
+                "unexpected headers, was it produced by `xan diff`?",
+            ))?;
+        }
+
+        let key_names = headers
+            .iter()
+            .skip(1)
+            .take(n - 4)
+            .map(|h| h.to_vec())
+            .collect();
+
+        Ok((
+            Self {
+                reader,
+                key_len: n - 4,
+                peeked: None,
+            },
+            key_names,
+        ))
+    }
+
+    fn next_line(&mut self) -> CliResult<Option<csv::ByteRecord>> {
+        if let Some(record) = self.peeked.take() {
+            return Ok(Some(record));
+        }
+
+        let mut record = csv::ByteRecord::new();
+
+        Ok(if self.reader.read_byte_record(&mut record)? {
+            Some(record)
+        } else {
+            None
+        })
+    }
+
+    fn key(&self, record: &csv::ByteRecord) -> Key {
+        record
+            .iter()
+            .skip(1)
+            .take(self.key_len)
+            .map(|cell| cell.to_vec())
+            .collect()
+    }
+
+    fn column_changes(&mut self) -> CliResult<Vec<ColumnChange>> {
+        let mut changes = Vec::new();
+
+        while let Some(record) = self.next_line()? {
+            let op = &record[0];
+
+            if op != ADDED_COLUMN && op != REMOVED_COLUMN && op != MOVED_COLUMN {
+                self.peeked = Some(record);
+                break;
+            }
+
+            let n = record.len();
+            let old = &record[n - 2];
+            let new = &record[n - 1];
+
+            changes.push(ColumnChange {
+                op: op.to_vec(),
+                name: record[n - 3].to_vec(),
+                old: if old.is_empty() {
+                    None
+                } else {
+                    Some(parse_position(old)?)
+                },
+                new: if new.is_empty() {
+                    None
+                } else {
+                    Some(parse_position(new)?)
+                },
+            });
+        }
+
+        Ok(changes)
+    }
+
+    fn next_row_patch(&mut self) -> CliResult<Option<(Key, RowPatch)>> {
+        let record = match self.next_line()? {
+            None => return Ok(None),
+            Some(record) => record,
+        };
+
+        let op = record[0].to_vec();
+        let key = self.key(&record);
+
+        let n = record.len();
+        let cells = |record: &csv::ByteRecord| {
+            (
+                record[n - 3].to_vec(),
+                record[n - 2].to_vec(),
+                record[n - 1].to_vec(),
+            )
+        };
+
+        if op == REMOVED {
+            return Ok(Some((key, RowPatch::Removed)));
+        }
+
+        if op != ADDED && op != MODIFIED {
+            if op == ADDED_COLUMN || op == REMOVED_COLUMN || op == MOVED_COLUMN {
+                Err(invalid_diff("column changes should come first"))?;
+            }
+
+            Err(invalid_diff(&format!(
+                "unknown change \"{}\"",
+                String::from_utf8_lossy(&op)
+            )))?;
+        }
+
+        let mut changes = vec![cells(&record)];
+
+        while let Some(next_record) = self.next_line()? {
+            if next_record[0] != op || self.key(&next_record) != key {
+                self.peeked = Some(next_record);
+                break;
+            }
+
+            changes.push(cells(&next_record));
+        }
+
+        let patch = if op == ADDED {
+            RowPatch::Added(
+                changes
+                    .into_iter()
+                    .map(|(column, _, new)| (column, new))
+                    .collect(),
+            )
+        } else {
+            RowPatch::Modified(changes)
+        };
+
+        Ok(Some((key, patch)))
+    }
+}
+
+struct Patcher {
+    new_headers: csv::ByteRecord,
+    // For each column of the new file: its index in the old file, if any
+    sources: Vec<Option<usize>>,
+    positions: HashMap<Vec<u8>, usize>,
+    old_key_sel: Selection,
+    new_key_indices: Vec<usize>,
+}
+
+impl Patcher {
+    fn new(
+        old_headers: &csv::ByteRecord,
+        key_names: &[Vec<u8>],
+        changes: Vec<ColumnChange>,
+    ) -> CliResult<Self> {
+        let inconsistent = || "patch does not apply: inconsistent column changes";
+
+        let mut removed: Vec<usize> = Vec::new();
+        let mut moved: Vec<usize> = Vec::new();
+        let mut added_count: usize = 0;
+
+        for change in changes.iter() {
+            if let Some(old) = change.old {
+                if old_headers.get(old) != Some(&change.name) {
+                    Err(inconsistent())?;
+                }
+            }
+
+            match change.op.as_slice() {
+                REMOVED_COLUMN => removed.push(change.old.ok_or_else(inconsistent)?),
+                MOVED_COLUMN => moved.push(change.old.ok_or_else(inconsistent)?),
+                _ => added_count += 1,
+            }
+        }
+
+        let len = (old_headers.len() + added_count)
+            .checked_sub(removed.len())
+            .ok_or_else(inconsistent)?;
+
+        let mut slots: Vec<Option<(Vec<u8>, Option<usize>)>> = vec![None; len];
+
+        let mut fill =
+            |position: usize, name: &[u8], source: Option<usize>| match slots.get_mut(position) {
+                Some(slot @ None) => {
+                    *slot = Some((name.to_vec(), source));
+                    Ok(())
+                }
+                _ => Err(inconsistent()),
+            };
+
+        for change in changes.iter() {
+            if change.op.as_slice() != REMOVED_COLUMN {
+                fill(
+                    change.new.ok_or_else(inconsistent)?,
+                    &change.name,
+                    change.old,
+                )?;
+            }
+        }
+
+        for (i, name) in old_headers.iter().enumerate() {
+            if !removed.contains(&i) && !moved.contains(&i) {
+                fill(i, name, Some(i))?;
+            }
+        }
+
+        let mut new_headers = csv::ByteRecord::new();
+        let mut sources = Vec::with_capacity(len);
+
+        for slot in slots {
+            let (name, source) = slot.ok_or_else(inconsistent)?;
+            new_headers.push_field(&name);
+            sources.push(source);
+        }
+
+        let positions: HashMap<Vec<u8>, usize> = new_headers
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.to_vec(), i))
+            .collect();
+
+        let mut old_key_sel = Selection::empty();
+        let mut new_key_indices = Vec::with_capacity(key_names.len());
+
+        for (i, name) in key_names.iter().enumerate() {
+            let not_found = || {
+                format!(
+                    "patch does not apply: key column \"{}\" not found",
+                    String::from_utf8_lossy(name)
+                )
+            };
+
+            old_key_sel.insert(
+                i,
+                old_headers
+                    .iter()
+                    .position(|h| h == name)
+                    .ok_or_else(not_found)?,
+            );
+            new_key_indices.push(*positions.get(name).ok_or_else(not_found)?);
+        }
+
+        Ok(Self {
+            new_headers,
+            sources,
+            positions,
+            old_key_sel,
+            new_key_indices,
+        })
+    }
+
+    fn position(&self, column: &[u8]) -> CliResult<usize> {
+        Ok(*self.positions.get(column).ok_or_else(|| {
+            format!(
+                "patch does not apply: unknown column \"{}\"",
+                String::from_utf8_lossy(column)
+            )
+        })?)
+    }
+
+    fn remap(&self, old_record: &csv::ByteRecord) -> Vec<Vec<u8>> {
+        self.sources
+            .iter()
+            .map(|source| match source {
+                Some(j) => old_record[*j].to_vec(),
+                None => vec![],
+            })
+            .collect()
+    }
+
+    fn modify(
+        &self,
+        key: &Key,
+        old_record: &csv::ByteRecord,
+        changes: &[(Vec<u8>, Vec<u8>, Vec<u8>)],
+    ) -> CliResult<Vec<Vec<u8>>> {
+        let mut record = self.remap(old_record);
+
+        for (column, old, new) in changes {
+            let i = self.position(column)?;
+
+            if &record[i] != old {
+                Err(format!(
+                    "patch does not apply: expected \"{}\" but found \"{}\" in column \"{}\" for key {}",
+                    String::from_utf8_lossy(old),
+                    String::from_utf8_lossy(&record[i]),
+                    String::from_utf8_lossy(column),
+                    format_key(key)
+                ))?;
+            }
+
+            record[i] = new.clone();
+        }
+
+        Ok(record)
+    }
+
+    fn add(&self, key: &Key, changes: &[(Vec<u8>, Vec<u8>)]) -> CliResult<Vec<Vec<u8>>> {
+        let mut record = vec![vec![]; self.new_headers.len()];
+
+        for (i, cell) in self.new_key_indices.iter().zip(key) {
+            record[*i] = cell.clone();
+        }
+
+        for (column, new) in changes {
+            // NOTE: empty cells are already empty, and this skips the line
+            // emitted for rows having no other column than the key
+            if new.is_empty() {
+                continue;
+            }
+
+            record[self.position(column)?] = new.clone();
+        }
+
+        Ok(record)
+    }
+}
+
+fn already_exists_error(key: &Key) -> String {
+    format!(
+        "patch does not apply: row with key {} already exists",
+        format_key(key)
+    )
+}
+
+fn not_found_error(key: &Key) -> String {
+    format!(
+        "patch does not apply: could not find row with key {}",
+        format_key(key)
+    )
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let diff_rdr = Config::new(&Some(args.arg_diff.clone()))
+        .delimiter(args.flag_delimiter)
+        .reader()?;
+
+    let conf = Config::new(&args.arg_input).delimiter(args.flag_delimiter);
+    let mut rdr = conf.reader()?;
+    let old_headers = rdr.byte_headers()?.clone();
+
+    let (mut diff, key_names) = DiffReader::new(diff_rdr)?;
+    let patcher = Patcher::new(&old_headers, &key_names, diff.column_changes()?)?;
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    wtr.write_byte_record(&patcher.new_headers)?;
+
+    if args.flag_sorted {
+        let mut records = SortedRecords::new(
+            args.arg_input.as_deref().unwrap_or("<stdin>"),
+            rdr,
+            patcher.old_key_sel.clone(),
+        )?;
+
+        let mut current_patch = diff.next_row_patch()?;
+
+        loop {
+            let ordering = match (records.peek_key(), &current_patch) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(old_key), Some((key, _))) => old_key.cmp(key),
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    let (_, old_record) = records.advance()?.unwrap();
+                    wtr.write_record(patcher.remap(&old_record))?;
+                    continue;
+                }
+                Ordering::Greater => {
+                    let (key, patch) = current_patch.as_ref().unwrap();
+
+                    match patch {
+                        RowPatch::Added(changes) => wtr.write_record(patcher.add(key, changes)?)?,
+                        _ => Err(not_found_error(key))?,
+                    }
+                }
+                Ordering::Equal => {
+                    let (key, old_record) = records.advance()?.unwrap();
+
+                    match &current_patch.as_ref().unwrap().1 {
+                        RowPatch::Added(_) => Err(already_exists_error(&key))?,
+                        RowPatch::Removed => (),
+                        RowPatch::Modified(changes) => {
+                            wtr.write_record(patcher.modify(&key, &old_record, changes)?)?
+                        }
+                    }
+                }
+            }
+
+            let next_patch = diff.next_row_patch()?;
+
+            if let (Some((last_key, _)), Some((key, _))) = (&current_patch, &next_patch) {
+                if last_key >= key {
+                    Err(invalid_diff(
+                        "diff is not sorted on the key, was it produced using `xan diff --sorted`?",
+                    ))?;
+                }
+            }
+
+            current_patch = next_patch;
+        }
+
+        return Ok(wtr.flush()?);
+    }
+
+    // NOTE: the boolean tracks whether the patch was applied
+    let mut index: IndexMap<Key, (RowPatch, bool), RandomState> = IndexMap::default();
+
+    while let Some((key, patch)) = diff.next_row_patch()? {
+        if index.contains_key(&key) {
+            Err(invalid_diff(&format!(
+                "key {} appears in multiple changes",
+                format_key(&key)
+            )))?;
+        }
+
+        index.insert(key, (patch, false));
+    }
+
+    for result in rdr.into_byte_records() {
+        let old_record = result?;
+        let key = patcher.old_key_sel.collect(&old_record);
+
+        match index.get_mut(&key) {
+            None => wtr.write_record(patcher.remap(&old_record))?,
+            Some((patch, applied)) => {
+                match patch {
+                    RowPatch::Added(_) => Err(already_exists_error(&key))?,
+                    RowPatch::Removed => (),
+                    RowPatch::Modified(changes) => {
+                        wtr.write_record(patcher.modify(&key, &old_record, changes)?)?
+                    }
+                }
+
+                *applied = true;
+            }
+        }
+    }
+
+    for (key, (patch, applied)) in index.iter() {
+        match patch {
+            RowPatch::Added(changes) => wtr.write_record(patcher.add(key, changes)?)?,
+            _ if !applied => Err(not_found_error(key))?,
+            _ => (),
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
@@ -1,15 +1,34 @@
+use std::convert::TryFrom;
 use std::io::{stdout, Write};
 
+use crate::collections::HashSet;
 use crate::config::{Config, Delimiter};
-use crate::moonblade::PivotAggregationProgram;
+use crate::moonblade::{PivotAggregationProgram, PivotColumnOrder, PivotOutputOptions};
 use crate::select::{SelectColumns, Selection};
 use crate::util;
 use crate::CliResult;
 
-// TODO: optimize when names are known beforehand
 // TODO: -S/--sorted
 // TODO: multiselections
 
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(try_from = "String")]
+struct ColumnOrder(PivotColumnOrder);
+
+impl TryFrom<String> for ColumnOrder {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(Self(match value.as_str() {
+            "first-seen" => PivotColumnOrder::FirstSeen,
+            "name" => PivotColumnOrder::Name,
+            "numeric" => PivotColumnOrder::Numeric,
+            "total" => PivotColumnOrder::Total,
+            _ => return Err(format!("unsupported --sort-columns \"{}\"", &value)),
+        }))
+    }
+}
+
 static USAGE: &str = r#"
 Pivot a CSV file by allowing distinct values from a column to be separated into
 their own column.
@@ -57,6 +76,22 @@ you can easily call `xan pivot` downstream of `xan unpivot`:
 
     $ xan unpivot january: monthly.csv | <processing> | xan pivot
 
+Multiple aggregations can be given at once, in which case the output will
+contain one column per aggregation & pivoted value, named "<aggregation>_<value>":
+
+    $ xan pivot year 'sum(population) as pop, count() as n' -g country file.csv
+
+Will produce:
+
+country,pop_2000,pop_2010,pop_2020,n_2000,n_2010,n_2020
+NL,1005,1065,1158,1,1,1
+US,8579,8783,9510,2,2,2
+
+Totals can also be added as a last row & column per aggregation using the flag
+named --margins. Note that they are computed by aggregating the relevant rows,
+and not by summing the pivoted cells, so that aggregations such as means or
+medians remain correct.
+
 Usage:
     xan pivot [-P...] [options] <column> <expr> [<input>]
     xan pivot [-P...] [options] [<input>]
@@ -66,6 +101,20 @@ pivot options:
     -g, --groupby <columns>  Group results by given selection of columns instead
                              of grouping by columns not used to pivot nor in
                              aggregation.
+    --fill <value>           Value used to fill cells of missing combinations of
+                             groups & pivoted values. Defaults to an empty string.
+    --margins                Add totals as a last row & last column per aggregation.
+    --margins-name <name>    Name of the totals row & columns when using --margins.
+                             [default: total]
+    --sort-columns <by>      How to order pivoted columns. Must be one of "first-seen",
+                             "name", "numeric" or "total" (decreasing order of
+                             the first aggregation over the whole column).
+                             Defaults to "first-seen", or to "name" when
+                             using --two-pass.
+    --two-pass               Read the file a first time to discover the distinct
+                             pivoted values, so that output columns are the same
+                             regardless of the order of rows in the file. Does
+                             not work with stdin.
 
 pivotal options:
     -P  Use at least three times to get help from your friends!
@@ -85,6 +134,11 @@ struct Args {
     arg_column: Option<SelectColumns>,
     arg_expr: Option<String>,
     flag_groupby: Option<SelectColumns>,
+    flag_fill: Option<String>,
+    flag_margins: bool,
+    flag_margins_name: String,
+    flag_sort_columns: Option<ColumnOrder>,
+    flag_two_pass: bool,
     #[serde(rename = "flag_P")]
     flag_p: usize,
     flag_output: Option<String>,
@@ -144,10 +198,42 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Selection::without_indices(headers.len(), &disappearing_columns)
     };
 
+    let column_order =
+        args.flag_sort_columns
+            .map(|order| order.0)
+            .unwrap_or(if args.flag_two_pass {
+                PivotColumnOrder::Name
+            } else {
+                PivotColumnOrder::FirstSeen
+            });
+
+    if args.flag_margins || column_order == PivotColumnOrder::Total {
+        program.compute_margins();
+    }
+
+    let mut record = csv::ByteRecord::new();
+
+    if args.flag_two_pass {
+        if args.arg_input.is_none() {
+            Err("--two-pass does not work with stdin!")?;
+        }
+
+        let mut first_pass_rdr = rconf.reader()?;
+        let mut seen = HashSet::<Vec<u8>>::new();
+
+        while first_pass_rdr.read_byte_record(&mut record)? {
+            let pivot = &record[pivot_col_index];
+
+            if !seen.contains(pivot) {
+                program.add_pivoted_column_name(pivot);
+                seen.insert(pivot.to_vec());
+            }
+        }
+    }
+
     let mut wtr = Config::new(&args.flag_output).writer()?;
 
     let mut index: usize = 0;
-    let mut record = csv::ByteRecord::new();
 
     while rdr.read_byte_record(&mut record)? {
         let group = groupby_sel.collect(&record);
@@ -158,21 +244,26 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         index += 1;
     }
 
-    if !rconf.no_headers {
-        let mut output_headers = groupby_sel.select(&headers).collect::<csv::ByteRecord>();
-
-        for name in program.pivoted_column_names() {
-            output_headers.push_field(name);
-        }
+    let group_headers =
+        (!rconf.no_headers).then(|| groupby_sel.select(&headers).collect::<csv::ByteRecord>());
 
-        wtr.write_byte_record(&output_headers)?;
-    }
+    let options = PivotOutputOptions {
+        fill: args.flag_fill.unwrap_or_default().into_bytes(),
+        column_order,
+        margins_name: args
+            .flag_margins
+            .then(|| args.flag_margins_name.into_bytes()),
+    };
 
-    program.flush(|output_record| -> CliResult<()> {
-        wtr.write_byte_record(output_record)?;
+    program.flush(
+        group_headers.as_ref(),
+        &options,
+        |output_record| -> CliResult<()> {
+            wtr.write_byte_record(output_record)?;
 
-        Ok(())
-    })?;
+            Ok(())
+        },
+    )?;
 
     Ok(wtr.flush()?)
 }
@@ -22,10 +22,15 @@ use ratatui::widgets::{Axis, Chart, Dataset, GraphType};
 
 use crate::collections::HashMap;
 use crate::config::{Config, Delimiter};
-use crate::dates::{infer_temporal_granularity, parse_partial_date, parse_zoned};
+use crate::dates::{
+    infer_temporal_granularity, parse_partial_date, parse_temporal_unit, parse_zoned,
+};
+use crate::moonblade::agg::aggregators::Numbers;
+use crate::moonblade::DynamicNumber;
 use crate::ratatui::print_ratatui_frame_to_stdout;
 use crate::scales::{Scale, ScaleType};
 use crate::select::SelectColumns;
+use crate::svg::{self, Anchor, ImageOutput, SvgDocument, TextStyle};
 use crate::util;
 use crate::{CliError, CliResult};
 
@@ -70,23 +75,26 @@ impl TryFrom<String> for Granularity {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        Ok(Self(match value.as_str() {
-            "year" | "years" => Unit::Year,
-            "month" | "months" => Unit::Month,
-            "day" | "days" => Unit::Day,
-            "hour" | "hours" => Unit::Hour,
-            "minute" | "minutes" => Unit::Minute,
-            "second" | "seconds" => Unit::Second,
-            _ => return Err(format!("invalid granularity \"{}\"!", value)),
-        }))
+        parse_temporal_unit(&value)
+            .map(Self)
+            .ok_or_else(|| format!("invalid granularity \"{}\"!", value))
     }
 }
 
 static USAGE: &str = "
 Draw a scatter plot or a line plot based on 2-dimensional data.
 
+Use the --svg and/or --png flags to write the chart to an image file instead
+of printing it in the terminal, e.g. to paste it in a report. The image will use
+the same scales, ticks, colors & small multiples as the terminal version.
+
+Use the --box, --violin or --density flags to draw box plots, violin plots or
+density plots respectively, e.g. to compare the distribution of the <x> column's
+values across the categories given by the -c/--category flag.
+
 Usage:
     xan plot --count [options] <x> [<input>]
+    xan plot (--box | --violin | --density) [options] <x> [<input>]
     xan plot [options] <x> <y> [<input>]
     xan plot --help
 
@@ -100,6 +108,17 @@ plot options:
                                y values will be summed wrt the newly discretized x axis.
     --count                    Omit the y column and count rows instead. Only relevant when
                                used with -T, --time that will discretize the x axis.
+    --box                      Draw horizontal box plots of the distribution of <x> values.
+                               Boxes span from the first to the third quartile and
+                               whiskers extend to the furthest values within 1.5 times
+                               the interquartile range. Values beyond are drawn as outliers.
+    --violin                   Draw horizontal violin plots, i.e. kernel density estimations,
+                               of the distribution of <x> values.
+    --density                  Draw the kernel density estimation of the distribution of
+                               <x> values as a line plot.
+    --bandwidth <h>            Bandwidth of the gaussian kernel used when drawing
+                               violins or densities. Defaults to Silverman's rule
+                               of thumb.
     -c, --category <col>       Name of the categorical column that will be used to
                                draw distinct series per category.
                                Incompatible with -Y, --add-series.
@@ -149,6 +168,17 @@ plot options:
                                handle them.
     -i, --ignore               Ignore values that cannot be correctly parsed.
 
+plot image options:
+    --svg <path>               Write the chart as SVG to the given file instead of
+                               printing it in the terminal.
+    --png <path>               Write the chart as PNG to the given file instead of
+                               printing it in the terminal.
+    --image-width <px>         Width of the image in pixels.
+                               [default: 800]
+    --image-height <px>        Height of the image in pixels. Defaults to 500, or
+                               300 per row of plots when drawing small multiples
+                               with -S, --small-multiples.
+
 Common options:
     -h, --help             Display this message
     -n, --no-headers       When set, the file will be considered as having no
@@ -168,6 +198,10 @@ struct Args {
     flag_bars: bool,
     flag_time: bool,
     flag_count: bool,
+    flag_box: bool,
+    flag_violin: bool,
+    flag_density: bool,
+    flag_bandwidth: Option<f64>,
     flag_cols: Option<String>,
     flag_rows: Option<String>,
     flag_small_multiples: Option<NonZeroUsize>,
@@ -189,9 +223,30 @@ struct Args {
     flag_y_scale: ScaleType,
     flag_force_colors: bool,
     flag_ignore: bool,
+    flag_svg: Option<String>,
+    flag_png: Option<String>,
+    flag_image_width: NonZeroUsize,
+    flag_image_height: Option<NonZeroUsize>,
 }
 
 impl Args {
+    fn resolve(&mut self) {
+        // NOTE: density plots are line plots of the estimated density
+        if self.flag_density {
+            self.flag_line = true;
+        }
+    }
+
+    fn distribution_kind(&self) -> Option<DistributionKind> {
+        if self.flag_box {
+            Some(DistributionKind::Box)
+        } else if self.flag_violin {
+            Some(DistributionKind::Violin)
+        } else {
+            None
+        }
+    }
+
     fn parse_x_bounds(&self) -> CliResult<(Option<f64>, Option<f64>)> {
         if self.flag_time {
             Ok((
@@ -244,7 +299,25 @@ impl Args {
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
-    let args: Args = util::get_args(USAGE, argv)?;
+    let mut args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_box || args.flag_violin || args.flag_density {
+        if args.flag_line
+            || args.flag_bars
+            || args.flag_time
+            || args.flag_regression_line
+            || !args.flag_add_series.is_empty()
+        {
+            Err("--box, --violin & --density cannot work with -L/--line, -B/--bars, -T/--time, -R/--regression-line nor -Y/--add-series!")?;
+        }
+
+        if matches!(args.flag_bandwidth, Some(h) if h <= 0.0) {
+            Err("--bandwidth must be > 0!")?;
+        }
+    }
+
+    args.resolve();
+
     let rconf = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers);
@@ -341,6 +414,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     .to_string()
             }
         }
+        None if args.flag_density => "density".to_string(),
+        None if args.distribution_kind().is_some() => "".to_string(),
         None => "count()".to_string(),
     };
 
@@ -479,6 +554,23 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let mut finalized_series = series_builder.into_finalized_series();
 
+    if args.flag_density {
+        for (_, series) in finalized_series.iter_mut() {
+            *series = Distribution::new(series, args.flag_bandwidth)
+                .density_series(args.flag_x_scale.is_logarithmic());
+        }
+    }
+
+    let distributions = args.distribution_kind().map(|kind| {
+        (
+            finalized_series
+                .iter()
+                .map(|(_, series)| Distribution::new(series, args.flag_bandwidth))
+                .collect::<Vec<_>>(),
+            kind,
+        )
+    });
+
     for (_, series) in finalized_series.iter_mut() {
         if args.flag_time {
             series.mark_as_temporal(args.flag_granularity.map(|g| g.into_inner()));
@@ -511,6 +603,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    // Exporting to an image file
+    if let Some(image_output) = ImageOutput::new(&args.flag_svg, &args.flag_png) {
+        let chart = SvgChart {
+            args: &args,
+            series: &finalized_series,
+            x_title: &x_column_name,
+            y_title: &y_column_name,
+            has_added_series,
+            showing_multiple_series,
+            share_scales: (share_x_scale, share_y_scale),
+            distributions: distributions
+                .as_ref()
+                .map(|(distributions, kind)| (distributions.as_slice(), *kind)),
+        };
+
+        return image_output.write(&chart.draw());
+    }
+
     // Solving cols & rows
     let cols = util::acquire_term_cols_ratio(&args.flag_cols)?;
 
@@ -537,6 +647,17 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let y_ticks = args.infer_y_ticks(rows);
 
+    if let Some((distributions, kind)) = distributions {
+        return print_distributions(
+            &args,
+            &finalized_series,
+            (&distributions, kind),
+            &x_column_name,
+            share_x_scale,
+            (cols, rows),
+        );
+    }
+
     // Drawing
     match args.flag_small_multiples {
         None => {
@@ -783,6 +904,799 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+fn print_distributions(
+    args: &Args,
+    series: &[(Option<String>, Series)],
+    (distributions, kind): (&[Distribution], DistributionKind),
+    x_column_name: &str,
+    share_x_scale: bool,
+    (cols, rows): (usize, usize),
+) -> CliResult<()> {
+    let scale_types = (args.flag_x_scale, args.flag_y_scale);
+    let (harmonized_x_axis_info, _) = AxisInfo::from_multiple_series(scale_types, series.iter());
+
+    // NOTE: without small multiples, everything is drawn in a single panel
+    let grid_cols = args
+        .flag_small_multiples
+        .map(|n| n.get())
+        .unwrap_or(series.len());
+
+    let indices = (0..series.len()).collect::<Vec<_>>();
+
+    for (row_i, row_indices) in indices.chunks(grid_cols).enumerate() {
+        if row_i > 0 {
+            writeln!(&mut stdout())?;
+        }
+
+        let panels = if args.flag_small_multiples.is_some() {
+            row_indices.iter().map(|i| vec![*i]).collect::<Vec<_>>()
+        } else {
+            vec![row_indices.to_vec()]
+        };
+
+        print_ratatui_frame_to_stdout(cols, rows, |frame| {
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .spacing(2)
+                .constraints(vec![
+                    Constraint::Ratio(
+                        1,
+                        if args.flag_small_multiples.is_some() {
+                            grid_cols as u32
+                        } else {
+                            1
+                        }
+                    );
+                    panels.len()
+                ])
+                .split(frame.area());
+
+            for (panel_i, panel) in panels.iter().enumerate() {
+                let x_axis_info = if args.flag_small_multiples.is_some() && !share_x_scale {
+                    AxisInfo::from_single_series(scale_types, &series[panel[0]]).0
+                } else {
+                    harmonized_x_axis_info.clone()
+                };
+
+                let shapes = panel
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(j, i)| {
+                        distributions[*i]
+                            .shapes(
+                                kind,
+                                &x_axis_info.scale,
+                                distribution_center(j, panel.len()),
+                                kind.half_height(panel.len()),
+                            )
+                            .into_iter()
+                            .map(move |shape| (*i, shape))
+                    })
+                    .collect::<Vec<_>>();
+
+                let datasets = shapes
+                    .iter()
+                    .map(|(i, shape)| {
+                        let (graph_type, points) = match shape {
+                            Shape::Line(points) | Shape::Area(points) => (GraphType::Line, points),
+                            Shape::Points(points) => (GraphType::Scatter, points),
+                        };
+
+                        Dataset::default()
+                            .marker(args.flag_marker.into_inner())
+                            .graph_type(graph_type)
+                            .style(get_series_color(*i))
+                            .data(points)
+                    })
+                    .collect::<Vec<_>>();
+
+                // NOTE: y labels are evenly distributed from bottom to top, so
+                // we pad them to align them with the distributions' centers
+                let mut y_labels = vec![String::new()];
+
+                y_labels.extend(panel.iter().rev().map(|i| {
+                    series[*i]
+                        .0
+                        .clone()
+                        .unwrap_or_else(|| x_column_name.to_string())
+                }));
+
+                y_labels.push(String::new());
+
+                let longest_y_label = y_labels
+                    .iter()
+                    .max_by_key(|label| label.width())
+                    .cloned()
+                    .unwrap();
+
+                let x_ticks = infer_x_ticks(
+                    args.flag_x_ticks,
+                    &x_axis_info,
+                    &[longest_y_label],
+                    layout[panel_i].width as usize,
+                );
+                let x_ticks_labels = x_axis_info.ticks(x_ticks);
+
+                let y_axis = Axis::default()
+                    .labels_alignment(Alignment::Right)
+                    .style(Style::default().white())
+                    .bounds([0.0, 1.0])
+                    .labels(y_labels);
+
+                let x_axis = Axis::default()
+                    .title(x_column_name.dim())
+                    .style(Style::default().white())
+                    .bounds([0.0, 1.0])
+                    .labels(x_ticks_labels.clone());
+
+                let chart = Chart::new(datasets)
+                    .x_axis(x_axis)
+                    .y_axis(y_axis)
+                    .legend_position(None);
+
+                frame.render_widget(chart, layout[panel_i]);
+                patch_buffer(
+                    frame.buffer_mut(),
+                    Some(&layout[panel_i]),
+                    &x_ticks_labels,
+                    args.flag_grid,
+                );
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+const SVG_MARGIN: f64 = 16.0;
+const SVG_GUTTER: f64 = 32.0;
+const SVG_DEFAULT_HEIGHT: f64 = 500.0;
+const SVG_DEFAULT_SMALL_MULTIPLE_HEIGHT: f64 = 300.0;
+
+struct SvgChart<'a> {
+    args: &'a Args,
+    series: &'a [(Option<String>, Series)],
+    x_title: &'a str,
+    y_title: &'a str,
+    has_added_series: bool,
+    showing_multiple_series: bool,
+    share_scales: (bool, bool),
+    distributions: Option<(&'a [Distribution], DistributionKind)>,
+}
+
+impl SvgChart<'_> {
+    fn scale_types(&self) -> (ScaleType, ScaleType) {
+        (self.args.flag_x_scale, self.args.flag_y_scale)
+    }
+
+    fn draw(&self) -> SvgDocument {
+        let width = self.args.flag_image_width.get() as f64;
+        let height_opt = self.args.flag_image_height.map(|h| h.get() as f64);
+
+        match self.args.flag_small_multiples {
+            None => {
+                let height = height_opt.unwrap_or(SVG_DEFAULT_HEIGHT);
+                let mut document = SvgDocument::new(width, height);
+                let mut top = SVG_MARGIN;
+
+                // NOTE: distributions are labelled on the y axis instead
+                if self.showing_multiple_series && self.distributions.is_none() {
+                    top += self.draw_legend(&mut document, top);
+                }
+
+                let axes = AxisInfo::from_multiple_series(self.scale_types(), self.series.iter());
+
+                self.draw_panel(
+                    &mut document,
+                    (
+                        SVG_MARGIN,
+                        top,
+                        width - SVG_MARGIN * 2.0,
+                        height - top - SVG_MARGIN,
+                    ),
+                    (0..self.series.len()).collect(),
+                    axes,
+                    None,
+                    if self.has_added_series {
+                        ""
+                    } else {
+                        self.y_title
+                    },
+                );
+
+                document
+            }
+            Some(grid_cols) => {
+                let grid_cols = grid_cols.get();
+                let grid_rows = self.series.len().div_ceil(grid_cols);
+
+                let panel_height = match height_opt {
+                    Some(height) => height / grid_rows as f64,
+                    None if grid_rows > 1 => SVG_DEFAULT_SMALL_MULTIPLE_HEIGHT,
+                    None => SVG_DEFAULT_HEIGHT,
+                };
+                let panel_width = (width - SVG_MARGIN * 2.0 - SVG_GUTTER * (grid_cols - 1) as f64)
+                    / grid_cols as f64;
+
+                let mut document = SvgDocument::new(width, panel_height * grid_rows as f64);
+
+                let (harmonized_x_axis_info, harmonized_y_axis_info) =
+                    AxisInfo::from_multiple_series(self.scale_types(), self.series.iter());
+
+                for (i, single_series) in self.series.iter().enumerate() {
+                    let (mut x_axis_info, mut y_axis_info) =
+                        AxisInfo::from_single_series(self.scale_types(), single_series);
+
+                    if self.share_scales.0 {
+                        x_axis_info = harmonized_x_axis_info.clone();
+                    }
+
+                    if self.share_scales.1 {
+                        y_axis_info = harmonized_y_axis_info.clone();
+                    }
+
+                    let grid_x = (i % grid_cols) as f64;
+                    let grid_y = (i / grid_cols) as f64;
+
+                    self.draw_panel(
+                        &mut document,
+                        (
+                            SVG_MARGIN + grid_x * (panel_width + SVG_GUTTER),
+                            SVG_MARGIN + grid_y * panel_height,
+                            panel_width,
+                            panel_height - SVG_MARGIN * 2.0,
+                        ),
+                        vec![i],
+                        (x_axis_info, y_axis_info),
+                        single_series.0.as_deref(),
+                        if self.has_added_series {
+                            ""
+                        } else {
+                            self.y_title
+                        },
+                    );
+                }
+
+                document
+            }
+        }
+    }
+
+    // NOTE: returns the height taken by the legend
+    fn draw_legend(&self, document: &mut SvgDocument, top: f64) -> f64 {
+        let line_height = svg::FONT_SIZE + 8.0;
+        let max_x = document.width() - SVG_MARGIN;
+
+        let mut x = SVG_MARGIN;
+        let mut y = top;
+
+        for (i, (name_opt, _)) in self.series.iter().enumerate() {
+            let name = name_opt.as_deref().unwrap_or("");
+            let item_width = svg::FONT_SIZE + 6.0 + svg::text_width(name, svg::FONT_SIZE) + 16.0;
+
+            if x > SVG_MARGIN && x + item_width > max_x {
+                x = SVG_MARGIN;
+                y += line_height;
+            }
+
+            document.rect(
+                x,
+                y,
+                svg::FONT_SIZE - 2.0,
+                svg::FONT_SIZE - 2.0,
+                svg::series_color(i),
+            );
+            document.text(
+                (x + svg::FONT_SIZE + 4.0, y + svg::FONT_SIZE - 2.0),
+                name,
+                &TextStyle::default(),
+            );
+
+            x += item_width;
+        }
+
+        y + line_height - top
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_panel(
+        &self,
+        document: &mut SvgDocument,
+        area: (f64, f64, f64, f64),
+        indices: Vec<usize>,
+        axes: (AxisInfo, AxisInfo),
+        title: Option<&str>,
+        y_title: &str,
+    ) {
+        let (x_axis_info, y_axis_info) = axes;
+        let (mut left, mut top, width, height) = area;
+        let bottom = top + height;
+        let right = left + width;
+
+        let line_height = svg::FONT_SIZE + 6.0;
+
+        if let Some(title) = title {
+            document.text(
+                (left, top + svg::FONT_SIZE),
+                title,
+                &TextStyle::default().bold(),
+            );
+            top += line_height + 4.0;
+        }
+
+        // Making some room for the axes
+        if !y_title.is_empty() {
+            document.text(
+                (left + svg::FONT_SIZE, top + (bottom - top) / 2.0),
+                y_title,
+                &TextStyle::default()
+                    .anchor(Anchor::Middle)
+                    .fill(svg::DIM_COLOR)
+                    .rotate(-90.0),
+            );
+            left += line_height;
+        }
+
+        // NOTE: leaving some room for the top tick label
+        top += svg::FONT_SIZE / 2.0;
+
+        let plot_bottom = bottom - line_height * 2.0;
+
+        let y_ticks = match self.distributions {
+            Some(_) => indices
+                .iter()
+                .enumerate()
+                .map(|(j, i)| {
+                    (
+                        distribution_center(j, indices.len()),
+                        self.series[*i]
+                            .0
+                            .clone()
+                            .unwrap_or_else(|| self.x_title.to_string()),
+                    )
+                })
+                .collect(),
+            None => y_axis_info.scale.positioned_ticks(
+                self.args
+                    .flag_y_ticks
+                    .map(|n| n.get())
+                    .unwrap_or(((plot_bottom - top) / 50.0) as usize)
+                    .max(2),
+            ),
+        };
+
+        let y_labels_width = y_ticks
+            .iter()
+            .map(|(_, label)| svg::text_width(label, svg::FONT_SIZE))
+            .fold(0.0, f64::max);
+
+        let plot_left = left + y_labels_width + 10.0;
+
+        let x_ticks_count = self.args.flag_x_ticks.map(|n| n.get()).unwrap_or_else(|| {
+            let max_label_width = x_axis_info
+                .scale
+                .formatted_ticks(15)
+                .iter()
+                .map(|label| svg::text_width(label, svg::FONT_SIZE))
+                .fold(0.0, f64::max);
+
+            ((right - plot_left) / (max_label_width + 32.0)) as usize
+        });
+
+        let mut x_ticks = x_axis_info.scale.positioned_ticks(x_ticks_count.max(2));
+
+        // NOTE: temporal ticks can be redundant wrt the granularity
+        x_ticks.dedup_by(|a, b| a.1 == b.1);
+
+        // NOTE: leaving some room for the last tick label
+        let plot_right = right
+            - x_ticks
+                .last()
+                .map(|(_, label)| svg::text_width(label, svg::FONT_SIZE) / 2.0)
+                .unwrap_or(0.0);
+
+        let plot_height = plot_bottom - top;
+
+        // NOTE: bars must not overflow the axes
+        let bar_width = indices
+            .iter()
+            .map(|i| self.series[*i].1.len())
+            .max()
+            .map(|n| ((plot_right - plot_left) / n as f64 * 0.8).clamp(1.0, 40.0))
+            .unwrap_or(1.0);
+        let x_padding = if self.args.flag_bars {
+            bar_width / 2.0 + 2.0
+        } else {
+            0.0
+        };
+
+        let plot_width = plot_right - plot_left - x_padding * 2.0;
+
+        let project = |(x, y): (f64, f64)| {
+            (
+                plot_left + x_padding + x * plot_width,
+                plot_bottom - y * plot_height,
+            )
+        };
+
+        // Ticks & grid
+        for (t, label) in y_ticks.iter() {
+            let (_, y) = project((0.0, *t));
+
+            if self.args.flag_grid {
+                document.line((plot_left, y), (plot_right, y), svg::GRID_COLOR, 1.0);
+            }
+
+            document.line((plot_left - 4.0, y), (plot_left, y), svg::AXIS_COLOR, 1.0);
+            document.text(
+                (plot_left - 6.0, y + svg::FONT_SIZE / 3.0),
+                label,
+                &TextStyle::default().anchor(Anchor::End),
+            );
+        }
+
+        for (t, label) in x_ticks.iter() {
+            let (x, _) = project((*t, 0.0));
+
+            if self.args.flag_grid {
+                document.line((x, top), (x, plot_bottom), svg::GRID_COLOR, 1.0);
+            }
+
+            document.line(
+                (x, plot_bottom),
+                (x, plot_bottom + 4.0),
+                svg::AXIS_COLOR,
+                1.0,
+            );
+            document.text(
+                (x, plot_bottom + 4.0 + svg::FONT_SIZE),
+                label,
+                &TextStyle::default().anchor(Anchor::Middle),
+            );
+        }
+
+        // Axes
+        document.line(
+            (plot_left, top),
+            (plot_left, plot_bottom),
+            svg::AXIS_COLOR,
+            1.0,
+        );
+        document.line(
+            (plot_left, plot_bottom),
+            (plot_right, plot_bottom),
+            svg::AXIS_COLOR,
+            1.0,
+        );
+
+        document.text(
+            (plot_left + (plot_right - plot_left) / 2.0, bottom - 2.0),
+            self.x_title,
+            &TextStyle::default()
+                .anchor(Anchor::Middle)
+                .fill(svg::DIM_COLOR),
+        );
+
+        // Data
+        if let Some((distributions, kind)) = self.distributions {
+            for (j, i) in indices.iter().enumerate() {
+                let color = svg::series_color(*i);
+
+                for shape in distributions[*i].shapes(
+                    kind,
+                    &x_axis_info.scale,
+                    distribution_center(j, indices.len()),
+                    kind.half_height(indices.len()),
+                ) {
+                    match shape {
+                        Shape::Line(points) => document.polyline(
+                            &points.into_iter().map(project).collect::<Vec<_>>(),
+                            color,
+                            1.5,
+                        ),
+                        Shape::Area(points) => document.polygon(
+                            &points.into_iter().map(project).collect::<Vec<_>>(),
+                            color,
+                            0.3,
+                        ),
+                        Shape::Points(points) => {
+                            for point in points {
+                                document.circle(project(point), 2.5, color);
+                            }
+                        }
+                    }
+                }
+            }
+
+            return;
+        }
+
+        let scales = (&x_axis_info.scale, &y_axis_info.scale);
+
+        for i in indices {
+            let (_, single_series) = &self.series[i];
+            let color = svg::series_color(i);
+            let points = single_series.to_scaled_floats(scales);
+
+            if self.args.flag_line {
+                document.polyline(
+                    &points.into_iter().map(project).collect::<Vec<_>>(),
+                    color,
+                    1.5,
+                );
+            } else if self.args.flag_bars {
+                let (_, baseline) = project((0.0, scales.1.percent(0.0).clamp(0.0, 1.0)));
+
+                for point in points {
+                    let (x, y) = project(point);
+
+                    document.rect(
+                        x - bar_width / 2.0,
+                        y.min(baseline),
+                        bar_width,
+                        (baseline - y).abs(),
+                        color,
+                    );
+                }
+            } else {
+                for point in points {
+                    document.circle(project(point), 2.5, color);
+                }
+            }
+
+            if self.args.flag_regression_line {
+                if let Some([a, b]) = single_series.regression_line_endpoints(scales) {
+                    document.line(project(a), project(b), svg::series_color(1), 1.5);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DistributionKind {
+    Box,
+    Violin,
+}
+
+impl DistributionKind {
+    // NOTE: as a percentage of the y axis
+    fn half_height(&self, count: usize) -> f64 {
+        let ratio = match self {
+            Self::Box => 0.3,
+            Self::Violin => 0.45,
+        };
+
+        ratio / (count + 1) as f64
+    }
+}
+
+// NOTE: distributions are drawn from top to bottom, as a percentage of the y axis
+fn distribution_center(i: usize, count: usize) -> f64 {
+    1.0 - (i + 1) as f64 / (count + 1) as f64
+}
+
+#[derive(Debug)]
+enum Shape {
+    Line(Vec<(f64, f64)>),
+    Area(Vec<(f64, f64)>),
+    Points(Vec<(f64, f64)>),
+}
+
+const KDE_STEPS: usize = 100;
+const KDE_CUTOFF: f64 = 5.0;
+
+#[derive(Debug)]
+struct Distribution {
+    values: Vec<f64>,
+    quartiles: [f64; 3],
+    whiskers: (f64, f64),
+    outliers: Vec<f64>,
+    bandwidth: f64,
+}
+
+impl Distribution {
+    fn new(series: &Series, bandwidth: Option<f64>) -> Self {
+        let mut numbers = Numbers::new();
+        let mut values = Vec::with_capacity(series.len());
+
+        for (x, _) in series.points.iter() {
+            numbers.add(DynamicNumber::Float(*x));
+            values.push(*x);
+        }
+
+        numbers.finalize(false);
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // NOTE: this is the same method as `xan stats -q`
+        let quartiles = match numbers.quartiles() {
+            Some(quartiles) => [
+                quartiles[0].as_float(),
+                quartiles[1].as_float(),
+                quartiles[2].as_float(),
+            ],
+            None => [values[0]; 3],
+        };
+
+        let iqr = quartiles[2] - quartiles[0];
+        let fences = (quartiles[0] - 1.5 * iqr, quartiles[2] + 1.5 * iqr);
+
+        let mut whiskers = (quartiles[0], quartiles[2]);
+        let mut outliers = Vec::new();
+
+        for value in values.iter().copied() {
+            if value < fences.0 || value > fences.1 {
+                outliers.push(value);
+            } else {
+                whiskers.0 = whiskers.0.min(value);
+                whiskers.1 = whiskers.1.max(value);
+            }
+        }
+
+        let bandwidth = bandwidth.unwrap_or_else(|| silverman_bandwidth(&values, iqr));
+
+        Self {
+            values,
+            quartiles,
+            whiskers,
+            outliers,
+            bandwidth,
+        }
+    }
+
+    fn min(&self) -> f64 {
+        self.values[0]
+    }
+
+    fn max(&self) -> f64 {
+        self.values[self.values.len() - 1]
+    }
+
+    // NOTE: gaussian kernel density estimation, evaluated on evenly spaced
+    // points of the given domain.
+    fn density(&self, domain: (f64, f64)) -> Vec<(f64, f64)> {
+        let h = self.bandwidth;
+        let n = self.values.len() as f64;
+        let normalization = n * h * (2.0 * std::f64::consts::PI).sqrt();
+
+        let steps = if domain.0 == domain.1 { 1 } else { KDE_STEPS };
+
+        (0..=steps)
+            .map(|i| {
+                let x = lerp(domain.0, domain.1, i as f64 / steps as f64);
+
+                // NOTE: values are sorted, so we can skip negligible contributions
+                let start = self.values.partition_point(|v| *v < x - KDE_CUTOFF * h);
+                let end = self.values.partition_point(|v| *v <= x + KDE_CUTOFF * h);
+
+                let sum: f64 = self.values[start..end]
+                    .iter()
+                    .map(|v| {
+                        let u = (x - v) / h;
+                        (-0.5 * u * u).exp()
+                    })
+                    .sum();
+
+                (x, sum / normalization)
+            })
+            .collect()
+    }
+
+    fn density_series(&self, is_logarithmic: bool) -> Series {
+        // NOTE: the tails of the density cannot be drawn on a log scale
+        let domain = if is_logarithmic {
+            (self.min(), self.max())
+        } else {
+            (
+                self.min() - 3.0 * self.bandwidth,
+                self.max() + 3.0 * self.bandwidth,
+            )
+        };
+
+        let mut series = Series::new();
+
+        for (x, y) in self.density(domain) {
+            series.add(x, y);
+        }
+
+        series
+    }
+
+    fn shapes(
+        &self,
+        kind: DistributionKind,
+        scale: &Scale,
+        center: f64,
+        half_height: f64,
+    ) -> Vec<Shape> {
+        let p = |value: f64| scale.percent(value);
+        let [q1, median, q3] = self.quartiles.map(p);
+
+        match kind {
+            DistributionKind::Box => {
+                let (low, high) = (p(self.whiskers.0), p(self.whiskers.1));
+                let (top, bottom) = (center + half_height, center - half_height);
+                let (cap_top, cap_bottom) =
+                    (center + half_height / 2.0, center - half_height / 2.0);
+
+                let mut shapes = vec![
+                    Shape::Area(vec![
+                        (q1, bottom),
+                        (q3, bottom),
+                        (q3, top),
+                        (q1, top),
+                        (q1, bottom),
+                    ]),
+                    Shape::Line(vec![(median, bottom), (median, top)]),
+                    Shape::Line(vec![(low, center), (q1, center)]),
+                    Shape::Line(vec![(q3, center), (high, center)]),
+                    Shape::Line(vec![(low, cap_bottom), (low, cap_top)]),
+                    Shape::Line(vec![(high, cap_bottom), (high, cap_top)]),
+                ];
+
+                if !self.outliers.is_empty() {
+                    shapes.push(Shape::Points(
+                        self.outliers.iter().map(|v| (p(*v), center)).collect(),
+                    ));
+                }
+
+                shapes
+            }
+            DistributionKind::Violin => {
+                let density = self.density((self.min(), self.max()));
+                let max_density = density.iter().map(|(_, d)| *d).fold(0.0, f64::max);
+
+                let offset = |d: f64| {
+                    if max_density > 0.0 {
+                        d / max_density * half_height
+                    } else {
+                        0.0
+                    }
+                };
+
+                let mut outline = density
+                    .iter()
+                    .map(|(x, d)| (p(*x), center + offset(*d)))
+                    .collect::<Vec<_>>();
+
+                outline.extend(
+                    density
+                        .iter()
+                        .rev()
+                        .map(|(x, d)| (p(*x), center - offset(*d))),
+                );
+
+                outline.push(outline[0]);
+
+                vec![
+                    Shape::Area(outline),
+                    Shape::Line(vec![(q1, center), (q3, center)]),
+                    Shape::Points(vec![(median, center)]),
+                ]
+            }
+        }
+    }
+}
+
+// NOTE: Silverman's rule of thumb
+fn silverman_bandwidth(values: &[f64], iqr: f64) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let stddev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+    let spread = match iqr / 1.34 {
+        s if s > 0.0 && s < stddev => s,
+        _ => stddev,
+    };
+
+    if spread > 0.0 {
+        0.9 * spread * n.powf(-0.2)
+    } else {
+        // NOTE: all values are identical
+        (values[0].abs() * 0.1).max(1.0)
+    }
+}
+
 fn is_int(float: f64) -> bool {
     float.fract() <= f64::EPSILON
 }
@@ -0,0 +1,299 @@
+use jiff::{tz::TimeZone, Unit, Zoned};
+
+use crate::collections::HashMap;
+use crate::config::{Config, Delimiter};
+use crate::dates::{
+    floor_zoned, format_zoned, infer_temporal_granularity, next_zoned, parse_temporal_value,
+    TemporalUnit,
+};
+use crate::moonblade::{DynamicValue, GroupAggregationProgram};
+use crate::select::SelectColumns;
+use crate::util;
+use crate::CliResult;
+
+// NOTE: number of buckets we aim for, at least, when inferring the unit
+const TYPICAL_BUCKETS: usize = 10;
+
+static USAGE: &str = "
+Resample a CSV file by bucketing its rows into regular time intervals, according
+to the datetimes found in the given column, then aggregate data per bucket
+using a custom aggregation expression, exactly like `xan groupby` would.
+
+The output will contain one row per bucket, sorted chronologically, and
+buckets containing no rows will still be emitted, so that the resulting time
+series has no gaps. By default those gaps will contain empty values but they can
+also be filled with a constant value using --fill, or linearly interpolated from
+surrounding buckets using --interpolate.
+
+For instance, counting events & summing amounts per day:
+
+    $ xan resample -u day datetime 'count() as events, sum(amount) as amount' file.csv
+
+Interpolating the mean temperature of days having no measurements:
+
+    $ xan resample -u day datetime 'mean(temperature) as temp' --interpolate file.csv
+
+If the unit is not given with -u, --unit, it will be inferred from the time
+span covered by the file, which means the whole file will be buffered into
+memory first. Note that the command will error if too many buckets would be
+emitted, e.g. when using a small unit over a long time span, unless you raise
+the limit using --max-buckets.
+
+Rows whose datetime is empty will be ignored. Datetimes are bucketed in the
+time zone of the first one found in the file: datetimes lacking a time zone are
+understood in it and others are converted to it.
+
+For a list of available aggregation functions, use `xan help aggs`.
+
+Usage:
+    xan resample [options] <column> <expression> [<input>]
+    xan resample --help
+
+resample options:
+    -u, --unit <unit>  Size of the time intervals. Must be one of \"years\", \"months\",
+                       \"days\", \"hours\", \"minutes\" or \"seconds\". Will be inferred
+                       if omitted.
+    --fill <value>     Value used to fill the columns of empty buckets.
+    --interpolate      Linearly interpolate numerical values of empty buckets using
+                       the closest non-empty buckets. Non-numerical values will
+                       be filled according to --fill.
+    --max-buckets <n>  Maximum number of buckets to emit.
+                       [default: 1000000]
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be evaled
+                           as headers.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_column: SelectColumns,
+    arg_expression: String,
+    arg_input: Option<String>,
+    flag_unit: Option<TemporalUnit>,
+    flag_fill: Option<String>,
+    flag_interpolate: bool,
+    flag_max_buckets: usize,
+    flag_no_headers: bool,
+    flag_output: Option<String>,
+    flag_delimiter: Option<Delimiter>,
+}
+
+// NOTE: every value is converted to the time zone of the first one, else
+// buckets would not align when the file mixes time zones.
+fn parse_cell(cell: &[u8], timezone: &mut Option<TimeZone>) -> CliResult<Option<Zoned>> {
+    if cell.is_empty() {
+        return Ok(None);
+    }
+
+    let string = std::str::from_utf8(cell).map_err(|_| "could not decode datetime as utf-8")?;
+
+    match parse_temporal_value(string, timezone.as_ref()) {
+        Some((zoned, _)) => {
+            if timezone.is_none() {
+                *timezone = Some(zoned.time_zone().clone());
+            }
+
+            Ok(Some(zoned))
+        }
+        None => Err(format!("could not parse \"{}\" as a datetime!", string))?,
+    }
+}
+
+fn parse_float(cell: &[u8]) -> Option<f64> {
+    std::str::from_utf8(cell).ok()?.parse::<f64>().ok()
+}
+
+fn interpolate(buckets: &mut [(Zoned, Option<csv::ByteRecord>)], width: usize, fill: &[u8]) {
+    let mut filled: Vec<Vec<Vec<u8>>> = vec![vec![fill.to_vec(); width]; buckets.len()];
+
+    for j in 0..width {
+        let mut last: Option<(usize, f64)> = None;
+
+        for (i, (_, record_opt)) in buckets.iter().enumerate() {
+            let value = match record_opt {
+                None => continue,
+                Some(record) => parse_float(&record[j]),
+            };
+
+            if let (Some((k, a)), Some(b)) = (last, value) {
+                for (l, row) in filled.iter_mut().enumerate().take(i).skip(k + 1) {
+                    let t = (l - k) as f64 / (i - k) as f64;
+
+                    row[j] = DynamicValue::from(a + (b - a) * t)
+                        .serialize_as_bytes()
+                        .into_owned();
+                }
+            }
+
+            last = value.map(|v| (i, v));
+        }
+    }
+
+    for ((_, record_opt), row) in buckets.iter_mut().zip(filled) {
+        if record_opt.is_none() {
+            *record_opt = Some(row.into_iter().collect());
+        }
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let rconf = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.arg_column);
+
+    let mut reader = rconf.reader()?;
+    let mut writer = Config::new(&args.flag_output).writer()?;
+
+    let headers = reader.byte_headers()?.clone();
+    let column_index = rconf.single_selection(&headers)?;
+
+    let mut program = GroupAggregationProgram::parse(&args.arg_expression, &headers)?;
+
+    let mut extent: Option<(Zoned, Zoned)> = None;
+    let mut timezone: Option<TimeZone> = None;
+
+    let mut update_extent = |zoned: &Zoned| match extent.as_mut() {
+        None => extent = Some((zoned.clone(), zoned.clone())),
+        Some((earliest, latest)) => {
+            if *zoned < *earliest {
+                *earliest = zoned.clone();
+            }
+            if *zoned > *latest {
+                *latest = zoned.clone();
+            }
+        }
+    };
+
+    let mut record = csv::ByteRecord::new();
+    let mut index: usize = 0;
+
+    let mut run_with_bucket =
+        |zoned: &Zoned, unit: Unit, index: usize, record: &csv::ByteRecord| -> CliResult<Zoned> {
+            let bucket = floor_zoned(zoned, unit);
+            let key = bucket.timestamp().as_second().to_string().into_bytes();
+
+            program.run_with_record(vec![key], index, record)?;
+
+            Ok(bucket)
+        };
+
+    let unit = match args.flag_unit {
+        Some(unit) => {
+            let unit = unit.into_inner();
+
+            while reader.read_byte_record(&mut record)? {
+                if let Some(zoned) = parse_cell(&record[column_index], &mut timezone)? {
+                    update_extent(&run_with_bucket(&zoned, unit, index, &record)?);
+                }
+
+                index += 1;
+            }
+
+            unit
+        }
+        None => {
+            let mut buffer: Vec<(usize, Zoned, csv::ByteRecord)> = Vec::new();
+
+            while reader.read_byte_record(&mut record)? {
+                if let Some(zoned) = parse_cell(&record[column_index], &mut timezone)? {
+                    update_extent(&zoned);
+                    buffer.push((index, zoned, record.clone()));
+                }
+
+                index += 1;
+            }
+
+            let unit = match extent.as_ref() {
+                Some((earliest, latest)) => {
+                    infer_temporal_granularity(earliest, latest, TYPICAL_BUCKETS)
+                }
+                None => Unit::Day,
+            };
+
+            extent = extent.map(|(earliest, latest)| {
+                (floor_zoned(&earliest, unit), floor_zoned(&latest, unit))
+            });
+
+            for (i, zoned, record) in buffer {
+                run_with_bucket(&zoned, unit, i, &record)?;
+            }
+
+            unit
+        }
+    };
+
+    if !args.flag_no_headers {
+        let mut output_headers = csv::ByteRecord::new();
+        output_headers.push_field(&headers[column_index]);
+        output_headers.extend(program.headers());
+
+        writer.write_byte_record(&output_headers)?;
+    }
+
+    let Some((earliest, latest)) = extent else {
+        return Ok(writer.flush()?);
+    };
+
+    let width = program.headers().count();
+    let mut results: HashMap<i64, csv::ByteRecord> = HashMap::new();
+
+    for result in program.into_byte_records(false) {
+        let (key, group_record) = result?;
+        let seconds = std::str::from_utf8(&key[0])
+            .unwrap()
+            .parse::<i64>()
+            .unwrap();
+
+        results.insert(seconds, group_record);
+    }
+
+    let mut buckets: Vec<(Zoned, Option<csv::ByteRecord>)> = Vec::new();
+    let mut current = earliest;
+
+    while current <= latest {
+        if buckets.len() >= args.flag_max_buckets {
+            Err(format!(
+                "more than {} buckets would be emitted!\nUse a larger -u, --unit or raise --max-buckets.",
+                args.flag_max_buckets
+            ))?;
+        }
+
+        let record_opt = results.remove(&current.timestamp().as_second());
+        let next = floor_zoned(&next_zoned(&current, unit)?, unit);
+
+        buckets.push((current, record_opt));
+        current = next;
+    }
+
+    let fill = args.flag_fill.unwrap_or_default().into_bytes();
+
+    if args.flag_interpolate {
+        interpolate(&mut buckets, width, &fill);
+    }
+
+    for (bucket, record_opt) in buckets {
+        let mut output_record = csv::ByteRecord::new();
+        output_record.push_field(format_zoned(&bucket, unit).as_bytes());
+
+        match record_opt {
+            Some(group_record) => output_record.extend(&group_record),
+            None => {
+                for _ in 0..width {
+                    output_record.push_field(&fill);
+                }
+            }
+        }
+
+        writer.write_byte_record(&output_record)?;
+    }
+
+    Ok(writer.flush()?)
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::io::Write;
+
+use crate::config::{Config, Delimiter};
+use crate::schema::{
+    InferenceOptions, Schema, SchemaFormat, SchemaInferrer, SchemaValidator, Violation,
+};
+use crate::select::SelectColumns;
+use crate::util;
+use crate::CliResult;
+
+static USAGE: &str = r#"
+Infer a schema from CSV data, or check that CSV data conforms to a schema.
+
+`xan schema infer` reads the whole file and outputs a JSON schema describing
+each of its columns using the same type detection as `xan stats`, i.e. "int",
+"float", "date", "url" or "string". The schema also records whether the column
+can be empty (nullable), the numerical range of numbers and, for strings,
+either the set of allowed values, if the column is categorical, or their
+minimum & maximum lengths as well as a pattern, if all values share the same
+shape (e.g. "AB-123" will yield "[A-Z]{2}\-[0-9]{3}"):
+
+    $ xan schema infer partners.csv > schema.json
+
+Here is what the schema looks like:
+
+    {
+      "fields": [
+        {"name": "id", "type": "int", "nullable": false, "min": 1, "max": 4567},
+        {"name": "country", "type": "string", "nullable": true, "enum": ["FR", "US"]},
+        {"name": "code", "type": "string", "nullable": false, "min_length": 6, "max_length": 6, "pattern": "[A-Z]{2}\\-[0-9]{3}"}
+      ]
+    }
+
+Fields can also be marked as "unique", and "missing_values" can be given at the
+root of the schema to list strings to be considered as empty (defaults to [""]).
+Patterns must match whole values. Available types are "int", "float", "date",
+"url", "boolean", "string" & "any".
+
+`xan schema check` validates a CSV file against a schema and outputs a CSV
+report of violations, with the following columns:
+
+    row_index: zero-based index of the offending row, empty for header issues
+    column:    name of the offending column
+    value:     offending value
+    rule:      violated rule, e.g. "type", "nullable", "enum", "min", "max",
+               "min_length", "max_length", "pattern", "unique", as well as
+               "missing_column" or "extra_column" for header issues
+    expected:  what the rule expected
+
+The command exits with a non-zero status if any violation is found:
+
+    $ xan schema check schema.json partners.csv
+
+Columns are matched by name, regardless of their order. Besides xan's own
+format, `xan schema check` can also read Frictionless Table Schemas
+(https://specs.frictionlessdata.io/table-schema/) and JSON Schemas
+(https://json-schema.org/) describing a row as an object, and
+`xan schema infer` can output both formats using -f/--format.
+
+Usage:
+    xan schema infer [options] [<input>]
+    xan schema check [options] <schema> [<input>]
+    xan schema --help
+
+schema infer options:
+    -s, --select <cols>         Only infer a schema for the selected columns.
+    -f, --format <format>       Format of the output schema, one of "xan",
+                                "frictionless" or "json-schema". [default: xan]
+    -l, --limit <n>             Only read the first <n> rows of the file.
+    -e, --enum-threshold <n>    Maximum number of distinct values for a string column
+                                to be considered as categorical. Use 0 to disable.
+                                [default: 10]
+    --no-patterns               Don't infer patterns for string columns.
+
+schema check options:
+    --ignore-extra              Don't report columns absent from the schema.
+    -m, --max-violations <n>    Stop after reporting this many violations.
+    -q, --quiet                 Don't output the report, only exit with a non-zero
+                                status if the file does not conform to the schema.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. Columns are then named by their
+                           zero-based index.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+"#;
+
+#[derive(Deserialize)]
+struct Args {
+    cmd_infer: bool,
+    cmd_check: bool,
+    arg_input: Option<String>,
+    arg_schema: Option<String>,
+    flag_select: SelectColumns,
+    flag_format: String,
+    flag_limit: Option<usize>,
+    flag_enum_threshold: usize,
+    flag_no_patterns: bool,
+    flag_ignore_extra: bool,
+    flag_max_violations: Option<usize>,
+    flag_quiet: bool,
+    flag_output: Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter: Option<Delimiter>,
+}
+
+impl Args {
+    fn config(&self) -> Config {
+        Config::new(&self.arg_input)
+            .delimiter(self.flag_delimiter)
+            .no_headers(self.flag_no_headers)
+    }
+
+    fn headers(&self, byte_headers: &csv::ByteRecord) -> csv::ByteRecord {
+        if self.flag_no_headers {
+            (0..byte_headers.len()).map(|i| i.to_string()).collect()
+        } else {
+            byte_headers.clone()
+        }
+    }
+
+    fn infer(&self) -> CliResult<()> {
+        let format = SchemaFormat::parse(&self.flag_format)?;
+
+        let conf = self.config().select(self.flag_select.clone());
+
+        let mut rdr = conf.reader()?;
+        let byte_headers = rdr.byte_headers()?.clone();
+        let sel = conf.selection(&byte_headers)?;
+
+        let names = sel
+            .select(&self.headers(&byte_headers))
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect();
+
+        let mut inferrer = SchemaInferrer::new(
+            names,
+            InferenceOptions {
+                enum_threshold: self.flag_enum_threshold,
+                patterns: !self.flag_no_patterns,
+            },
+        );
+
+        let mut record = csv::ByteRecord::new();
+        let mut i: usize = 0;
+
+        while rdr.read_byte_record(&mut record)? {
+            if let Some(limit) = self.flag_limit {
+                if i >= limit {
+                    break;
+                }
+            }
+
+            inferrer.process(&sel.select(&record).collect());
+            i += 1;
+        }
+
+        let schema = inferrer.finalize();
+
+        let mut writer = Config::new(&self.flag_output).io_writer()?;
+        serde_json::to_writer_pretty(&mut writer, &schema.to_json(format))
+            .map_err(|err| err.to_string())?;
+        writeln!(&mut writer)?;
+
+        Ok(writer.flush()?)
+    }
+
+    fn check(&self) -> CliResult<()> {
+        let schema_path = self.arg_schema.as_ref().unwrap();
+
+        let schema = Schema::from_json_str(&fs::read_to_string(schema_path)?)
+            .map_err(|err| format!("{}: {}", schema_path, err))?;
+
+        let conf = self.config();
+        let mut rdr = conf.reader()?;
+        let headers = self.headers(rdr.byte_headers()?);
+
+        let mut wtr = Config::new(&self.flag_output).writer()?;
+
+        if !self.flag_quiet {
+            wtr.write_record(["row_index", "column", "value", "rule", "expected"])?;
+        }
+
+        let mut count: usize = 0;
+        let mut violations: Vec<Violation> = Vec::new();
+
+        let mut validator =
+            SchemaValidator::new(schema, &headers, self.flag_ignore_extra, |violation| {
+                violations.push(violation)
+            })?;
+
+        let mut record = csv::ByteRecord::new();
+        let mut i: usize = 0;
+
+        loop {
+            for violation in violations.drain(..) {
+                if let Some(max) = self.flag_max_violations {
+                    if count >= max {
+                        break;
+                    }
+                }
+
+                count += 1;
+
+                if self.flag_quiet {
+                    continue;
+                }
+
+                wtr.write_record([
+                    violation
+                        .row_index
+                        .map(|i| i.to_string())
+                        .unwrap_or_default()
+                        .as_str(),
+                    &violation.column,
+                    &violation.value,
+                    &violation.rule.to_string(),
+                    &violation.expected,
+                ])?;
+            }
+
+            if let Some(max) = self.flag_max_violations {
+                if count >= max {
+                    break;
+                }
+            }
+
+            if !rdr.read_byte_record(&mut record)? {
+                break;
+            }
+
+            validator.validate(i, &record, |violation| violations.push(violation));
+            i += 1;
+        }
+
+        wtr.flush()?;
+
+        if count > 0 {
+            let stopped_early = self.flag_max_violations.is_some_and(|max| count >= max);
+
+            Err(format!(
+                "{} does not conform to the schema ({}{} violation{} found)",
+                self.arg_input.as_deref().unwrap_or("<stdin>"),
+                if stopped_early { "at least " } else { "" },
+                count,
+                if count > 1 { "s" } else { "" }
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.cmd_infer {
+        args.infer()
+    } else if args.cmd_check {
+        args.check()
+    } else {
+        unreachable!()
+    }
+}
@@ -1,7 +1,8 @@
 use std::collections::VecDeque;
 use std::io::{copy, Read, SeekFrom};
 
-use crate::config::{Config, Delimiter};
+use crate::config::{Config, Delimiter, SeekRead};
+use crate::index::RecordIndex;
 use crate::moonblade::Program;
 use crate::read::read_byte_record_up_to;
 use crate::util;
@@ -80,6 +81,10 @@ found and won't need to read to whole file or stream if unnecessary.
 Of course, flags related to byte offsets will only work with seekable inputs, e.g. files
 on disk but no stdin nor gzipped files.
 
+Also note that if the file was indexed using `xan index`, the command will be
+able to directly seek to the requested rows instead of reading the file from the
+start, as long as no condition or byte offset is given.
+
 Note that it is perfectly fine to mix & match flags related to row indices,
 byte offsets and conditions. In which case, here is description of the order
 of operations:
@@ -207,6 +212,27 @@ impl Args {
             return self.run_last();
         }
 
+        // NOTE: if the file was indexed, we can seek directly to relevant rows
+        if self.flag_byte_offset.is_none() && self.flag_start_condition.is_none() {
+            let rconf = self.rconfig();
+
+            if let Some(mut index) = rconf.record_index()? {
+                return if self.flag_indices.is_some() {
+                    if self.flag_end_condition.is_some() {
+                        Err("-I/--indices does not work with -S/--start-condition nor -E/--end-condition!")?;
+                    }
+
+                    self.run_plural_indexed(&mut index)
+                } else {
+                    let (start, end) = self.range()?;
+                    let offset = index.record_offset(start as u64)?;
+
+                    let rdr = self.reader_at_offset(offset)?;
+                    self.run_default(rdr, (0, end.saturating_sub(start)))
+                };
+            }
+        }
+
         if self.flag_indices.is_some() {
             if self.flag_start_condition.is_some() || self.flag_end_condition.is_some() {
                 Err(
@@ -245,21 +271,24 @@ impl Args {
 
             rdr.seek_raw(SeekFrom::Start(offset), pos)?;
 
-            self.run_default(rdr)
+            self.run_default(rdr, self.range()?)
         } else {
             let rdr = rconf.reader()?;
-            self.run_default(rdr)
+            self.run_default(rdr, self.range()?)
         }
     }
 
-    fn run_default<R: Read>(&self, mut rdr: csv::Reader<R>) -> CliResult<()> {
+    fn run_default<R: Read>(
+        &self,
+        mut rdr: csv::Reader<R>,
+        (start, end): (usize, usize),
+    ) -> CliResult<()> {
         let mut wtr = self.wconfig().writer()?;
         self.rconfig().write_headers(&mut rdr, &mut wtr)?;
 
         let mut record = csv::ByteRecord::new();
         let mut conditions = self.conditions(rdr.byte_headers()?)?;
 
-        let (start, end) = self.range()?;
         let mut record_index: usize = 0;
         let mut i: usize = 0;
 
@@ -294,6 +323,22 @@ impl Args {
 
         let n = self.flag_last.unwrap();
 
+        // NOTE: if the file was indexed, we can seek directly to the last rows
+        if let Some(mut index) = rconf.record_index()? {
+            let offset = index.record_offset(index.count().saturating_sub(n as u64))?;
+            let mut rdr = self.reader_at_offset(offset)?;
+
+            rconf.write_headers(&mut rdr, &mut wtr)?;
+
+            let mut record = csv::ByteRecord::new();
+
+            while rdr.read_byte_record(&mut record)? {
+                wtr.write_byte_record(&record)?;
+            }
+
+            return Ok(wtr.flush()?);
+        }
+
         match rconf.reverse_reader() {
             Ok((headers, mut reverse_reader)) => {
                 if !self.flag_no_headers {
@@ -368,6 +413,50 @@ impl Args {
         Ok(wtr.flush()?)
     }
 
+    fn run_plural_indexed(&self, index: &mut RecordIndex) -> CliResult<()> {
+        let mut wtr = self.wconfig().writer()?;
+        let mut rdr = self.reader_at_offset(index.record_offset(0)?)?;
+
+        self.rconfig().write_headers(&mut rdr, &mut wtr)?;
+
+        let mut indices = self.plural_indices()?;
+        indices.dedup();
+
+        let mut record = csv::ByteRecord::new();
+
+        for i in indices {
+            if i as u64 >= index.count() {
+                break;
+            }
+
+            let offset = index.record_offset(i as u64)?;
+
+            let mut pos = csv::Position::new();
+            pos.set_byte(offset);
+            rdr.seek_raw(SeekFrom::Start(offset), pos)?;
+
+            if rdr.read_byte_record(&mut record)? {
+                wtr.write_byte_record(&record)?;
+            }
+        }
+
+        Ok(wtr.flush()?)
+    }
+
+    fn reader_at_offset(
+        &self,
+        offset: u64,
+    ) -> CliResult<csv::Reader<Box<dyn SeekRead + Send + 'static>>> {
+        let mut rdr = self.rconfig().seekable_reader()?;
+
+        let mut pos = csv::Position::new();
+        pos.set_byte(offset);
+
+        rdr.seek_raw(SeekFrom::Start(offset), pos)?;
+
+        Ok(rdr)
+    }
+
     fn range(&self) -> Result<(usize, usize), String> {
         util::range(
             self.flag_start,
@@ -105,14 +105,7 @@ impl Args {
         }
 
         let rconfig = self.rconfig();
-        let mut reader = rconfig.io_reader_for_random_access()?;
-
-        let (segments, _) = segment_csv_file(
-            &mut reader,
-            || rconfig.csv_reader_builder(),
-            SegmentationOptions::chunks(self.flag_chunks.unwrap().get()),
-        )?
-        .ok_or("could not segment the file properly!")?;
+        let segments = self.find_segments(&rconfig)?;
 
         let mut reader = rconfig.reader()?;
         let headers = reader.byte_headers()?.clone();
@@ -136,14 +129,7 @@ impl Args {
 
     fn segments(&self) -> CliResult<()> {
         let rconfig = self.rconfig();
-        let mut reader = rconfig.io_reader_for_random_access()?;
-
-        let (segments, _) = segment_csv_file(
-            &mut reader,
-            || rconfig.csv_reader_builder(),
-            SegmentationOptions::chunks(self.flag_chunks.unwrap().get()),
-        )?
-        .ok_or("could not segment the file properly!")?;
+        let segments = self.find_segments(&rconfig)?;
 
         let mut wtr = Config::new(&None).writer()?;
         let mut record = csv::ByteRecord::new();
@@ -166,6 +152,26 @@ impl Args {
         Ok(())
     }
 
+    fn find_segments(&self, rconfig: &Config) -> CliResult<Vec<(u64, u64)>> {
+        let chunks = self.flag_chunks.unwrap().get();
+
+        // NOTE: if the file was indexed, segmentation can be exact
+        if let Some(mut index) = rconfig.record_index()? {
+            return Ok(index.segments(chunks)?);
+        }
+
+        let mut reader = rconfig.io_reader_for_random_access()?;
+
+        let (segments, _) = segment_csv_file(
+            &mut reader,
+            || rconfig.csv_reader_builder(),
+            SegmentationOptions::chunks(chunks),
+        )?
+        .ok_or("could not segment the file properly!")?;
+
+        Ok(segments)
+    }
+
     fn new_writer(
         &self,
         headers: &csv::ByteRecord,
@@ -0,0 +1,1063 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+
+use csv::ByteRecord;
+
+use crate::cmd::join::Index;
+use crate::cmd::sort::{compare_num, parse_num};
+use crate::collections::HashSet;
+use crate::config::{Config, Delimiter};
+use crate::moonblade::{
+    is_aggregation_function, AggregationProgram, GroupAggregationProgram, Program, SelectionProgram,
+};
+use crate::select::Selection;
+use crate::sql::{parse_query, BinaryOperator, Expr, JoinKind, SelectItem};
+use crate::util;
+use crate::{CliError, CliResult};
+
+static USAGE: &str = r#"
+Run a SQL query over CSV files and output the result as CSV.
+
+Tables are referenced directly by their path after FROM & JOIN, and can be
+given an alias. When no alias is given, the file name without its extension
+is used instead:
+
+    $ xan sql "SELECT name, age FROM people.csv WHERE age >= 18 ORDER BY age DESC"
+
+    $ xan sql "SELECT p.name, c.label FROM data/people.csv AS p JOIN data/cities.csv c ON p.city = c.id"
+
+Paths containing spaces or special characters can be quoted using single
+quotes, double quotes or backticks. Column names can be quoted using double
+quotes or backticks.
+
+The query is not executed by a SQL engine but compiled to xan's own internals
+(the same expression language, aggregators, hash joins and sorting used by
+commands such as `xan map`, `xan groupby`, `xan join` or `xan sort`), which
+means only a practical subset of SQL is supported:
+
+    SELECT [DISTINCT] <expr> [AS <alias>], ...
+    FROM <path> [[AS] <alias>]
+    [[INNER | LEFT [OUTER]] JOIN <path> [[AS] <alias>] ON <equalities>]...
+    [WHERE <expr>]
+    [GROUP BY <expr>, ...]
+    [HAVING <expr>]
+    [ORDER BY <expr> [ASC | DESC], ...]
+    [LIMIT <n>] [OFFSET <n>]
+
+Supported expressions:
+
+    - column names, optionally qualified: name, p.name, "full name"
+    - literals: 42, 3.14, 'string', TRUE, FALSE, NULL
+    - arithmetic: +, -, *, /, %
+    - string concatenation: ||
+    - comparisons: =, <>, !=, <, <=, >, >=
+    - boolean logic: AND, OR, NOT
+    - x [NOT] IN (...), x [NOT] BETWEEN a AND b, x IS [NOT] NULL
+    - x [NOT] LIKE 'pattern', x [NOT] ILIKE 'pattern'
+    - CASE [x] WHEN ... THEN ... [ELSE ...] END
+    - any function of the expression language, e.g. lower(name), len(text)
+    - any aggregation function, e.g. count(*), count(DISTINCT x), sum(x),
+      avg(x), median(x), cardinality(x), values(x) etc.
+
+Note that NULL is the same as an empty cell, and that comparing a value
+with a number is done numerically (yielding NULL when the value is not a
+number), while comparing it with a string is done lexicographically.
+
+Like in SQL, min(x) & max(x) also work on strings, which are then compared
+lexicographically (numbers take precedence when x contains both), and sum(x)
+is NULL when there is no value to sum.
+
+JOIN conditions must be equalities between a column of the already joined
+tables and a column of the newly joined one, optionally chained with AND.
+Joined files are indexed in memory.
+
+ORDER BY can reference output column aliases or positions (starting at 1).
+Values are compared numerically when both of them are numbers, and
+lexicographically otherwise.
+
+For a list of available functions, use `xan help functions`.
+For a list of available aggregation functions, use `xan help aggs`.
+
+Usage:
+    xan sql [options] <query>
+    xan sql --help
+
+sql options:
+    -f, --evaluate-file    Read query from a file instead.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+"#;
+
+#[derive(Deserialize)]
+struct Args {
+    arg_query: String,
+    flag_evaluate_file: bool,
+    flag_output: Option<String>,
+    flag_delimiter: Option<Delimiter>,
+}
+
+#[derive(Default)]
+struct Scope {
+    tables: Vec<String>,
+    columns: Vec<(String, String)>,
+}
+
+impl Scope {
+    fn add_table(&mut self, alias: &str, headers: &ByteRecord) -> CliResult<()> {
+        if self.tables.iter().any(|t| t == alias) {
+            Err(format!(
+                "table name \"{}\" is used more than once, use an alias!",
+                alias
+            ))?;
+        }
+
+        self.tables.push(alias.to_string());
+
+        for name in headers.iter() {
+            self.columns.push((
+                alias.to_string(),
+                String::from_utf8_lossy(name).into_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn resolve(&self, table: Option<&str>, name: &str) -> Result<usize, String> {
+        if let Some(table) = table {
+            if !self.tables.iter().any(|t| t == table) {
+                return Err(format!("unknown table \"{}\"", table));
+            }
+        }
+
+        let mut matches = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, (t, n))| n == name && table.map(|table| table == t).unwrap_or(true));
+
+        match (matches.next(), matches.next()) {
+            (Some((i, _)), None) => Ok(i),
+            (None, _) => Err(format!("unknown column \"{}\"", name)),
+            (Some(_), Some(_)) => Err(format!(
+                "column \"{}\" is ambiguous, qualify it with a table name",
+                name
+            )),
+        }
+    }
+
+    fn headers(&self) -> ByteRecord {
+        (0..self.columns.len())
+            .map(|i| format!("_c{}", i))
+            .collect()
+    }
+}
+
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Not(e) | Expr::Neg(e) => vec![e],
+        Expr::Binary(l, _, r) => vec![l, r],
+        Expr::Function { args, .. } => args.iter().collect(),
+        Expr::InList { expr, list, .. } => std::iter::once(&**expr).chain(list).collect(),
+        Expr::Between {
+            expr, low, high, ..
+        } => vec![expr, low, high],
+        Expr::Like { expr, pattern, .. } => vec![expr, pattern],
+        Expr::IsNull { expr, .. } => vec![expr],
+        Expr::Case {
+            operand,
+            branches,
+            otherwise,
+        } => operand
+            .iter()
+            .map(|e| &**e)
+            .chain(branches.iter().flat_map(|(c, r)| [c, r]))
+            .chain(otherwise.iter().map(|e| &**e))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn is_aggregate_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function { name, args, .. } => {
+            is_aggregation_function(name)
+                && !(matches!(name.as_str(), "min" | "max") && args.len() > 1)
+        }
+        _ => false,
+    }
+}
+
+fn contains_aggregate(expr: &Expr) -> bool {
+    is_aggregate_call(expr) || children(expr).into_iter().any(contains_aggregate)
+}
+
+fn contains_column(expr: &Expr) -> bool {
+    matches!(expr, Expr::Column(_, _)) || children(expr).into_iter().any(contains_column)
+}
+
+fn is_numeric(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(_) | Expr::Neg(_) => true,
+        Expr::Binary(_, op, _) => op.is_arithmetic(),
+        Expr::Function { name, .. } => matches!(
+            name.as_str(),
+            "count" | "sum" | "avg" | "mean" | "cardinality" | "len" | "length" | "abs" | "round"
+        ),
+        _ => false,
+    }
+}
+
+fn is_string(expr: &Expr) -> bool {
+    match expr {
+        Expr::String(_) => true,
+        Expr::Binary(_, op, _) => *op == BinaryOperator::Concat,
+        Expr::Function { name, .. } => {
+            matches!(name.as_str(), "lower" | "upper" | "concat" | "trim")
+        }
+        _ => false,
+    }
+}
+
+fn quote(string: &str) -> String {
+    format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn like_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("(?s)^");
+
+    for c in pattern.chars() {
+        match c {
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+
+    // NOTE: escaping for the expression language regex literals
+    regex.replace("\\\\", "\\\\\\\\").replace('/', "\\/")
+}
+
+// NOTE: comparing with a number is done numerically, comparing with a string
+// lexicographically. Numerical comparisons are wrapped in `try` so that they
+// yield null (as SQL would) instead of failing when a value is not a number.
+fn compile_comparison(op: BinaryOperator, left: &Expr, right: &Expr, l: &str, r: &str) -> String {
+    let numeric = match op {
+        BinaryOperator::Eq | BinaryOperator::Ne => is_numeric(left) || is_numeric(right),
+        _ => !(is_string(left) || is_string(right)),
+    };
+
+    if numeric {
+        let symbol = match op {
+            BinaryOperator::Eq => "==",
+            BinaryOperator::Ne => "!=",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::Le => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::Ge => ">=",
+            _ => unreachable!(),
+        };
+
+        format!("try(({}) {} ({}))", l, symbol, r)
+    } else {
+        let symbol = match op {
+            BinaryOperator::Eq => "eq",
+            BinaryOperator::Ne => "ne",
+            BinaryOperator::Lt => "lt",
+            BinaryOperator::Le => "le",
+            BinaryOperator::Gt => "gt",
+            BinaryOperator::Ge => "ge",
+            _ => unreachable!(),
+        };
+
+        format!("(({}) {} ({}))", l, symbol, r)
+    }
+}
+
+#[derive(Default)]
+struct Aggregation {
+    keys: Vec<String>,
+    aggregates: Vec<String>,
+}
+
+impl Aggregation {
+    fn headers(&self) -> ByteRecord {
+        (0..self.keys.len())
+            .map(|i| format!("_k{}", i))
+            .chain((0..self.aggregates.len()).map(|i| format!("_a{}", i)))
+            .collect()
+    }
+
+    fn code(&self) -> String {
+        self.aggregates
+            .iter()
+            .enumerate()
+            .map(|(i, code)| format!("{} as _a{}", code, i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn keys_code(&self) -> String {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(i, code)| format!("{} as _k{}", code, i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// Compiles SQL expressions into the expression language, either against the
+// joined rows (columns are named `_c0`, `_c1`...), or against the result of
+// an aggregation (group keys are named `_k0`, `_k1`... and aggregated
+// values `_a0`, `_a1`...).
+struct Compiler<'a> {
+    scope: &'a Scope,
+    aggregation: Option<&'a mut Aggregation>,
+}
+
+impl<'a> Compiler<'a> {
+    fn rows(scope: &'a Scope) -> Self {
+        Self {
+            scope,
+            aggregation: None,
+        }
+    }
+
+    fn groups(scope: &'a Scope, aggregation: &'a mut Aggregation) -> Self {
+        Self {
+            scope,
+            aggregation: Some(aggregation),
+        }
+    }
+
+    fn column_index(&mut self, index: usize) -> Result<String, String> {
+        let code = format!("_c{}", index);
+
+        match self.aggregation.as_deref() {
+            None => Ok(code),
+            Some(aggregation) => match aggregation.keys.iter().position(|k| *k == code) {
+                Some(i) => Ok(format!("_k{}", i)),
+                None => Err(format!(
+                    "column \"{}\" must appear in the GROUP BY clause or be used in an aggregate function",
+                    self.scope.columns[index].1
+                )),
+            },
+        }
+    }
+
+    fn compile(&mut self, expr: &Expr) -> Result<String, String> {
+        if self.aggregation.is_some() {
+            if is_aggregate_call(expr) {
+                return self.compile_aggregate(expr);
+            }
+
+            if !contains_aggregate(expr) {
+                let code = Compiler::rows(self.scope).compile(expr)?;
+                let aggregation = self.aggregation.as_deref().unwrap();
+
+                if let Some(i) = aggregation.keys.iter().position(|k| *k == code) {
+                    return Ok(format!("_k{}", i));
+                }
+
+                if !contains_column(expr) {
+                    return Ok(code);
+                }
+            }
+        }
+
+        self.compile_structure(expr)
+    }
+
+    fn register_aggregate(&mut self, code: String) -> String {
+        let aggregation = self.aggregation.as_deref_mut().unwrap();
+
+        let i = match aggregation.aggregates.iter().position(|a| *a == code) {
+            Some(i) => i,
+            None => {
+                aggregation.aggregates.push(code);
+                aggregation.aggregates.len() - 1
+            }
+        };
+
+        format!("_a{}", i)
+    }
+
+    fn compile_aggregate(&mut self, expr: &Expr) -> Result<String, String> {
+        let (name, args, distinct) = match expr {
+            Expr::Function {
+                name,
+                args,
+                distinct,
+            } => (name, args, *distinct),
+            _ => unreachable!(),
+        };
+
+        if args.iter().any(contains_aggregate) {
+            return Err(format!(
+                "aggregate function calls cannot be nested: {}",
+                expr
+            ));
+        }
+
+        let mut compiler = Compiler::rows(self.scope);
+
+        if name == "count" {
+            if distinct {
+                if args.len() != 1 || args[0] == Expr::Wildcard {
+                    return Err("count(DISTINCT ...) expects a single expression".to_string());
+                }
+
+                let code = format!("cardinality({})", compiler.compile(&args[0])?);
+                return Ok(self.register_aggregate(code));
+            }
+
+            if args.is_empty() || args[0] == Expr::Wildcard {
+                return Ok(self.register_aggregate("count()".to_string()));
+            }
+        } else if distinct {
+            return Err(format!(
+                "DISTINCT is only supported with count, not {}",
+                name
+            ));
+        }
+
+        let args_code = compiler.compile_list(args)?;
+
+        match name.as_str() {
+            // NOTE: like in SQL, min & max also work on strings. Numbers take
+            // precedence and strings are compared lexicographically when the
+            // aggregated values contain no number at all.
+            "min" | "max" if args.len() == 1 && !is_numeric(&args[0]) => {
+                let (numeric, lexicographic) = if name == "min" {
+                    ("min", "lex_first")
+                } else {
+                    ("max", "lex_last")
+                };
+
+                let lexicographic_code = format!("{}({})", lexicographic, args_code);
+
+                if is_string(&args[0]) {
+                    return Ok(self.register_aggregate(lexicographic_code));
+                }
+
+                let numeric_code =
+                    self.register_aggregate(format!("{}(try(({}) + 0))", numeric, args_code));
+                let lexicographic_code = self.register_aggregate(lexicographic_code);
+
+                Ok(format!(
+                    "if(eq({}, \"\"), {}, {})",
+                    numeric_code, lexicographic_code, numeric_code
+                ))
+            }
+            // NOTE: sum of an empty set is NULL in SQL
+            "sum" if args.len() == 1 => {
+                let sum_code = self.register_aggregate(format!("sum({})", args_code));
+                let min_code = self.register_aggregate(format!("min(try(({}) + 0))", args_code));
+
+                Ok(format!("if(eq({}, \"\"), \"\", {})", min_code, sum_code))
+            }
+            _ => Ok(self.register_aggregate(format!("{}({})", name, args_code))),
+        }
+    }
+
+    fn compile_list(&mut self, exprs: &[Expr]) -> Result<String, String> {
+        Ok(exprs
+            .iter()
+            .map(|expr| self.compile(expr))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(", "))
+    }
+
+    fn compile_structure(&mut self, expr: &Expr) -> Result<String, String> {
+        Ok(match expr {
+            Expr::Column(table, name) => {
+                let index = self.scope.resolve(table.as_deref(), name)?;
+                self.column_index(index)?
+            }
+            Expr::Wildcard => return Err("unexpected *".to_string()),
+            Expr::Number(n) => n.clone(),
+            Expr::String(s) => quote(s),
+            Expr::Bool(b) => b.to_string(),
+            Expr::Null => "null".to_string(),
+            Expr::Not(e) => format!("!({})", self.compile(e)?),
+            Expr::Neg(e) => format!("-({})", self.compile(e)?),
+            Expr::Binary(left, op, right) => {
+                let l = self.compile(left)?;
+                let r = self.compile(right)?;
+
+                if op.is_comparison() {
+                    compile_comparison(*op, left, right, &l, &r)
+                } else {
+                    let symbol = match op {
+                        BinaryOperator::Or => "||",
+                        BinaryOperator::And => "&&",
+                        BinaryOperator::Add => "+",
+                        BinaryOperator::Sub => "-",
+                        BinaryOperator::Mul => "*",
+                        BinaryOperator::Div => "/",
+                        BinaryOperator::Mod => "%",
+                        BinaryOperator::Concat => "++",
+                        _ => unreachable!(),
+                    };
+
+                    format!("(({}) {} ({}))", l, symbol, r)
+                }
+            }
+            Expr::Function {
+                name,
+                args,
+                distinct,
+            } => {
+                if is_aggregate_call(expr) {
+                    return Err(format!(
+                        "aggregate functions are not allowed here: {}",
+                        expr
+                    ));
+                }
+
+                if *distinct {
+                    return Err(format!(
+                        "DISTINCT is only supported with count, not {}",
+                        name
+                    ));
+                }
+
+                match name.as_str() {
+                    "coalesce" if args.len() == 1 => self.compile(&args[0])?,
+                    "coalesce" => format!("or({})", self.compile_list(args)?),
+                    "length" => format!("len({})", self.compile_list(args)?),
+                    _ => format!("{}({})", name, self.compile_list(args)?),
+                }
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => format!(
+                "(({}) {} [{}])",
+                self.compile(expr)?,
+                if *negated { "not in" } else { "in" },
+                self.compile_list(list)?
+            ),
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let e = self.compile(expr)?;
+                let condition = format!(
+                    "({} && {})",
+                    compile_comparison(BinaryOperator::Ge, expr, low, &e, &self.compile(low)?),
+                    compile_comparison(BinaryOperator::Le, expr, high, &e, &self.compile(high)?)
+                );
+
+                if *negated {
+                    format!("!{}", condition)
+                } else {
+                    condition
+                }
+            }
+            Expr::Like {
+                expr,
+                pattern,
+                negated,
+                case_insensitive,
+            } => {
+                let pattern = match &**pattern {
+                    Expr::String(pattern) => pattern,
+                    _ => return Err("LIKE patterns must be string literals".to_string()),
+                };
+
+                format!(
+                    "{}contains({}, /{}/{})",
+                    if *negated { "!" } else { "" },
+                    self.compile(expr)?,
+                    like_to_regex(pattern),
+                    if *case_insensitive { "i" } else { "" }
+                )
+            }
+            Expr::IsNull { expr, negated } => format!(
+                "(({}) {} \"\")",
+                self.compile(expr)?,
+                if *negated { "ne" } else { "eq" }
+            ),
+            Expr::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                let mut code = match otherwise {
+                    Some(otherwise) => self.compile(otherwise)?,
+                    None => "null".to_string(),
+                };
+
+                let operand_code = match operand {
+                    Some(operand) => Some(self.compile(operand)?),
+                    None => None,
+                };
+
+                for (condition, result) in branches.iter().rev() {
+                    let mut condition_code = self.compile(condition)?;
+
+                    if let (Some(operand), Some(operand_code)) = (operand, &operand_code) {
+                        condition_code = compile_comparison(
+                            BinaryOperator::Eq,
+                            operand,
+                            condition,
+                            operand_code,
+                            &condition_code,
+                        );
+                    }
+
+                    code = format!(
+                        "if({}, {}, {})",
+                        condition_code,
+                        self.compile(result)?,
+                        code
+                    );
+                }
+
+                code
+            }
+        })
+    }
+}
+
+// NOTE: JOIN conditions must be a chain of equalities between columns, so
+// that they can be resolved using a hash join.
+fn collect_join_keys(
+    expr: &Expr,
+    left: &Scope,
+    right: &Scope,
+    keys: &mut (Selection, Selection),
+) -> Result<(), String> {
+    match expr {
+        Expr::Binary(l, BinaryOperator::And, r) => {
+            collect_join_keys(l, left, right, keys)?;
+            collect_join_keys(r, left, right, keys)
+        }
+        Expr::Binary(a, BinaryOperator::Eq, b) => match (&**a, &**b) {
+            (Expr::Column(ta, na), Expr::Column(tb, nb)) => {
+                let resolve = |(tl, nl): (&Option<String>, &str),
+                               (tr, nr): (&Option<String>, &str)| {
+                    Some((
+                        left.resolve(tl.as_deref(), nl).ok()?,
+                        right.resolve(tr.as_deref(), nr).ok()?,
+                    ))
+                };
+
+                let (l, r) = resolve((ta, na), (tb, nb))
+                    .or_else(|| resolve((tb, nb), (ta, na)))
+                    .ok_or_else(|| format!("could not resolve JOIN condition: {}", expr))?;
+
+                keys.0.insert(keys.0.len(), l);
+                keys.1.insert(keys.1.len(), r);
+
+                Ok(())
+            }
+            _ => Err(format!(
+                "JOIN conditions must be equalities between columns: {}",
+                expr
+            )),
+        },
+        _ => Err(format!(
+            "JOIN conditions must be equalities between columns, optionally chained with AND: {}",
+            expr
+        )),
+    }
+}
+
+struct JoinPlan {
+    kind: JoinKind,
+    left_sel: Selection,
+    index: Index,
+    padding: ByteRecord,
+}
+
+impl JoinPlan {
+    fn expand(&mut self, rows: Vec<ByteRecord>) -> CliResult<Vec<ByteRecord>> {
+        let mut expanded = Vec::new();
+
+        for mut row in rows {
+            let mut matched = false;
+
+            self.index.for_each_record(&self.left_sel, &row, |right| {
+                matched = true;
+
+                let mut joined = row.clone();
+                joined.extend(right);
+                expanded.push(joined);
+
+                Ok::<_, CliError>(())
+            })?;
+
+            if !matched && self.kind == JoinKind::Left {
+                row.extend(&self.padding);
+                expanded.push(row);
+            }
+        }
+
+        Ok(expanded)
+    }
+}
+
+fn compare_keys(a: &ByteRecord, b: &ByteRecord, descending: &[bool]) -> Ordering {
+    for ((x, y), desc) in a.iter().zip(b.iter()).zip(descending) {
+        let ordering = match (parse_num(x), parse_num(y)) {
+            (Some(nx), Some(ny)) => compare_num(nx, ny),
+            _ => x.cmp(y),
+        };
+
+        let ordering = if *desc { ordering.reverse() } else { ordering };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+// Receives rows before projection and takes care of projection, sorting,
+// deduplication, offset & limit before writing the output.
+struct Sink<W: io::Write> {
+    writer: csv::Writer<W>,
+    projection: SelectionProgram,
+    sorting: Option<(SelectionProgram, Vec<bool>)>,
+    sorted: Vec<(ByteRecord, ByteRecord)>,
+    seen: Option<HashSet<Vec<Vec<u8>>>>,
+    offset: usize,
+    limit: Option<usize>,
+    emitted: usize,
+}
+
+impl<W: io::Write> Sink<W> {
+    // NOTE: returns whether more rows are needed
+    fn process(&mut self, index: usize, record: &ByteRecord) -> CliResult<bool> {
+        let mut output = ByteRecord::new();
+        self.projection.extend_into(index, record, &mut output)?;
+
+        if let Some((program, _)) = &self.sorting {
+            let mut combined = record.clone();
+            combined.extend(&output);
+
+            let mut keys = ByteRecord::new();
+            program.extend_into(index, &combined, &mut keys)?;
+
+            self.sorted.push((keys, output));
+
+            return Ok(true);
+        }
+
+        self.emit(output)
+    }
+
+    fn emit(&mut self, output: ByteRecord) -> CliResult<bool> {
+        if let Some(seen) = self.seen.as_mut() {
+            if !seen.insert(output.iter().map(|cell| cell.to_vec()).collect()) {
+                return Ok(true);
+            }
+        }
+
+        self.emitted += 1;
+
+        let end = self.limit.map(|limit| self.offset + limit);
+
+        if self.emitted > self.offset && end.map(|end| self.emitted <= end).unwrap_or(true) {
+            self.writer.write_byte_record(&output)?;
+        }
+
+        Ok(end.map(|end| self.emitted < end).unwrap_or(true))
+    }
+
+    fn finish(mut self) -> CliResult<()> {
+        if let Some((_, descending)) = self.sorting.take() {
+            let mut sorted = std::mem::take(&mut self.sorted);
+            sorted.sort_by(|(a, _), (b, _)| compare_keys(a, b, &descending));
+
+            for (_, output) in sorted {
+                if !self.emit(output)? {
+                    break;
+                }
+            }
+        }
+
+        Ok(self.writer.flush()?)
+    }
+}
+
+fn selection_code(codes: &[String], prefix: &str) -> String {
+    codes
+        .iter()
+        .enumerate()
+        .map(|(i, code)| format!("{} as {}{}", code, prefix, i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let mut args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_evaluate_file {
+        args.arg_query = fs::read_to_string(&args.arg_query)?;
+    }
+
+    let query = parse_query(&args.arg_query).map_err(|err| format!("invalid query: {}", err))?;
+
+    let table_config =
+        |path: &str| Config::new(&Some(path.to_string())).delimiter(args.flag_delimiter);
+
+    // Reading tables & indexing joined ones
+    let mut rdr = table_config(&query.from.path).reader()?;
+    let mut scope = Scope::default();
+    scope.add_table(&query.from.alias, rdr.byte_headers()?)?;
+
+    let mut joins = Vec::new();
+
+    for join in query.joins.iter() {
+        let mut join_rdr = table_config(&join.table.path).reader()?;
+        let join_headers = join_rdr.byte_headers()?.clone();
+
+        let mut join_scope = Scope::default();
+        join_scope.add_table(&join.table.alias, &join_headers)?;
+
+        let mut keys = (Selection::empty(), Selection::empty());
+        collect_join_keys(&join.on, &scope, &join_scope, &mut keys)?;
+
+        let (left_sel, right_sel) = keys;
+
+        joins.push(JoinPlan {
+            kind: join.kind,
+            left_sel,
+            index: Index::from_csv_reader(&mut join_rdr, &right_sel, false, false)?,
+            padding: join_headers.iter().map(|_| b"").collect(),
+        });
+
+        scope.add_table(&join.table.alias, &join_headers)?;
+    }
+
+    let headers = scope.headers();
+
+    // Compiling the query
+    let where_program = match &query.selection {
+        Some(expr) => Some(Program::parse(
+            &Compiler::rows(&scope).compile(expr)?,
+            &headers,
+        )?),
+        None => None,
+    };
+
+    let aggregated = !query.group_by.is_empty()
+        || query.having.is_some()
+        || query.items.iter().any(|item| match item {
+            SelectItem::Expr(expr, _) => contains_aggregate(expr),
+            _ => false,
+        });
+
+    let mut aggregation = Aggregation::default();
+
+    for expr in query.group_by.iter() {
+        if contains_aggregate(expr) {
+            Err(format!(
+                "aggregate functions are not allowed in GROUP BY: {}",
+                expr
+            ))?;
+        }
+
+        aggregation.keys.push(Compiler::rows(&scope).compile(expr)?);
+    }
+
+    let mut output_names: Vec<String> = Vec::new();
+    let mut output_codes: Vec<String> = Vec::new();
+    let having_code;
+    let mut sort_codes: Vec<String> = Vec::new();
+
+    {
+        let mut compiler = if aggregated {
+            Compiler::groups(&scope, &mut aggregation)
+        } else {
+            Compiler::rows(&scope)
+        };
+
+        for item in query.items.iter() {
+            match item {
+                SelectItem::Wildcard(table) => {
+                    if let Some(table) = table {
+                        if !scope.tables.contains(table) {
+                            Err(format!("unknown table \"{}\"", table))?;
+                        }
+                    }
+
+                    for (i, (t, name)) in scope.columns.iter().enumerate() {
+                        if table.as_ref().map(|table| table == t).unwrap_or(true) {
+                            output_names.push(name.clone());
+                            output_codes.push(compiler.column_index(i)?);
+                        }
+                    }
+                }
+                SelectItem::Expr(expr, alias) => {
+                    output_names.push(match (alias, expr) {
+                        (Some(alias), _) => alias.clone(),
+                        (None, Expr::Column(_, name)) => name.clone(),
+                        (None, _) => expr.to_string(),
+                    });
+                    output_codes.push(compiler.compile(expr)?);
+                }
+            }
+        }
+
+        having_code = match &query.having {
+            Some(expr) => Some(compiler.compile(expr)?),
+            None => None,
+        };
+
+        for order_by in query.order_by.iter() {
+            let code = match &order_by.expr {
+                Expr::Column(None, name) if output_names.contains(name) => {
+                    format!("_o{}", output_names.iter().position(|n| n == name).unwrap())
+                }
+                Expr::Number(n) => match n.parse::<usize>() {
+                    Ok(p) if p >= 1 && p <= output_names.len() => format!("_o{}", p - 1),
+                    _ => Err(format!("ORDER BY position {} is out of range", n))?,
+                },
+                expr => compiler.compile(expr)?,
+            };
+
+            sort_codes.push(code);
+        }
+    }
+
+    let pre_headers = if aggregated {
+        if aggregation.aggregates.is_empty() {
+            aggregation.aggregates.push("count()".to_string());
+        }
+
+        aggregation.headers()
+    } else {
+        headers.clone()
+    };
+
+    let having_program = match having_code {
+        Some(code) => Some(Program::parse(&code, &pre_headers)?),
+        None => None,
+    };
+
+    let mut sort_headers = pre_headers.clone();
+    sort_headers.extend((0..output_codes.len()).map(|i| format!("_o{}", i)));
+
+    let mut sink = Sink {
+        writer: Config::new(&args.flag_output).writer()?,
+        projection: SelectionProgram::parse(&selection_code(&output_codes, "_o"), &pre_headers)?,
+        sorting: if sort_codes.is_empty() {
+            None
+        } else {
+            Some((
+                SelectionProgram::parse(&selection_code(&sort_codes, "_s"), &sort_headers)?,
+                query.order_by.iter().map(|o| o.descending).collect(),
+            ))
+        },
+        sorted: Vec::new(),
+        seen: query.distinct.then(HashSet::new),
+        offset: query.offset.unwrap_or(0),
+        limit: query.limit,
+        emitted: 0,
+    };
+
+    sink.writer.write_record(&output_names)?;
+
+    let mut keys_program = None;
+    let mut group_program = None;
+    let mut program = None;
+
+    if aggregated {
+        if aggregation.keys.is_empty() {
+            program = Some(AggregationProgram::parse(&aggregation.code(), &headers)?);
+        } else {
+            keys_program = Some(SelectionProgram::parse(&aggregation.keys_code(), &headers)?);
+            group_program = Some(GroupAggregationProgram::parse(
+                &aggregation.code(),
+                &headers,
+            )?);
+        }
+    }
+
+    // Streaming rows
+    let mut record = ByteRecord::new();
+    let mut index: usize = 0;
+    let mut group = ByteRecord::new();
+
+    'rows: while rdr.read_byte_record(&mut record)? {
+        let mut rows = vec![record.clone()];
+
+        for join in joins.iter_mut() {
+            rows = join.expand(rows)?;
+        }
+
+        for row in rows {
+            index += 1;
+
+            if let Some(where_program) = &where_program {
+                if !where_program.run_with_record(index - 1, &row)?.is_truthy() {
+                    continue;
+                }
+            }
+
+            if let Some(program) = program.as_mut() {
+                program.run_with_record(index - 1, &row)?;
+            } else if let Some(group_program) = group_program.as_mut() {
+                group.clear();
+                keys_program
+                    .as_ref()
+                    .unwrap()
+                    .extend_into(index - 1, &row, &mut group)?;
+
+                group_program.run_with_record(
+                    group.iter().map(|cell| cell.to_vec()).collect(),
+                    index - 1,
+                    &row,
+                )?;
+            } else if !sink.process(index - 1, &row)? {
+                break 'rows;
+            }
+        }
+    }
+
+    // Flushing aggregated rows
+    let mut aggregated_rows = Vec::new();
+
+    if let Some(mut program) = program {
+        aggregated_rows.push(program.finalize(false)?);
+    } else if let Some(group_program) = group_program {
+        for result in group_program.into_byte_records(false) {
+            let (group, values) = result?;
+
+            let mut row: ByteRecord = group.iter().collect();
+            row.extend(&values);
+
+            aggregated_rows.push(row);
+        }
+    }
+
+    for (i, row) in aggregated_rows.into_iter().enumerate() {
+        if let Some(having_program) = &having_program {
+            if !having_program.run_with_record(i, &row)?.is_truthy() {
+                continue;
+            }
+        }
+
+        if !sink.process(i, &row)? {
+            break;
+        }
+    }
+
+    sink.finish()
+}
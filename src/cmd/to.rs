@@ -2,14 +2,27 @@ use std::fs;
 use std::io::{self, IsTerminal, Read, Write};
 use std::iter;
 use std::num::NonZeroUsize;
-
+use std::path::Path;
+
+use arrow::array::{
+    ArrayBuilder, ArrayRef, BooleanBuilder, Float64Builder, Int64Builder, RecordBatch,
+    StringBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::writer::FileWriter as ArrowFileWriter;
+use jiff::civil;
 use npyz::WriterBuilder;
 use pad::PadStr;
-use rust_xlsxwriter::Workbook;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
 use unicode_width::UnicodeWidthStr;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
 use crate::config::{Config, Delimiter};
-use crate::json::{JSONEmptyMode, JSONTypeInferrenceBuffer, OmittableAttributes};
+use crate::json::{JSONEmptyMode, JSONType, JSONTypeInferrenceBuffer, OmittableAttributes};
 use crate::select::SelectColumns;
 use crate::util;
 use crate::xml::XMLWriter;
@@ -19,23 +32,26 @@ static USAGE: &str = "
 Convert a CSV file to a variety of data formats.
 
 Usage:
-    xan to <format> [options] [<input>]
+    xan to <format> [options] [<input>...]
     xan to --help
 
 Supported formats:
+    arrow   - Apache Arrow IPC file
     html    - HTML table
     json    - JSON array or object
     jsonl   - JSON lines (same as `ndjson`)
     md      - Markdown table
     ndjson  - Newline-delimited JSON (same as `jsonl`)
     npy     - Numpy array
+    ods     - OpenOffice spreadsheet
+    parquet - Apache Parquet file
     txt     - Text lines
-    xlsx    - Excel spreasheet
+    xlsx    - Excel spreadsheet
 
 Some formats can be streamed, some others require the full CSV file to be loaded into
 memory.
 
-Streamable formats are `html`, `jsonl`, `ndjson` and `txt`.
+Streamable formats are `arrow`, `html`, `jsonl`, `ndjson`, `parquet` and `txt`.
 
 JSON options:
     -B, --buffer-size <size>  Number of CSV rows to sample to infer column types.
@@ -43,6 +59,40 @@ JSON options:
     --nulls                   Convert empty string to a null value.
     --omit                    Ignore the empty values.
 
+Parquet & Arrow options:
+    Column types are inferred by sampling the first rows of the file, as for JSON,
+    using -B, --buffer-size. Empty cells will be written as nulls for numerical and
+    boolean columns, and as empty strings for string columns, unless --nulls is given.
+
+    --types <spec>             Override the inferred type of some columns, using a
+                               comma-separated list of \"selection:type\" pairs,
+                               e.g. \"id:string,count:int\" or \"2:5:float\", where
+                               selections follow the \"xan select\" syntax. Supported
+                               types are \"string\", \"int\", \"float\" & \"bool\".
+    --compression <codec>      Compression codec to use for parquet, among \"uncompressed\",
+                               \"snappy\", \"gzip\", \"lz4\" & \"zstd\".
+                               [default: snappy]
+
+Excel & OpenOffice options:
+    Multiple CSV files can be given when converting to xlsx or ods, in which case
+    each one of them will be written as a distinct sheet of the same workbook.
+
+    Column types are inferred by sampling the first rows of each file, as for JSON,
+    using -B, --buffer-size, so that cells can be written as numbers, booleans or
+    dates (formatted as \"YYYY-MM-DD\", with optional time). Zero-padded integers,
+    such as zip codes, and integers too large to be represented exactly are kept
+    as strings.
+
+    --sheet-names <names>  Comma-separated names of the sheets to write. Defaults
+                           to the file names without their extension, or to
+                           \"Sheet1\" when reading from stdin.
+    --freeze               Freeze the header row.
+    --autofilter           Add an autofilter to the header row.
+    --widths <spec>        Width of the columns, in characters. Can be \"auto\" to
+                           fit columns to their content, a single width to use for
+                           every column, or a comma-separated list of widths.
+    --strings              Write every cell as a string, without inferring types.
+
 NPY options:
     --dtype <type>  Number type to use for the npy conversion. Must be one of \"f32\"
                     or \"f64\". [default: f64]
@@ -64,7 +114,7 @@ Common options:
 #[derive(Deserialize)]
 struct Args {
     arg_format: String,
-    arg_input: Option<String>,
+    arg_input: Vec<String>,
     flag_output: Option<String>,
     flag_no_headers: bool,
     flag_select: SelectColumns,
@@ -73,6 +123,13 @@ struct Args {
     flag_nulls: bool,
     flag_omit: bool,
     flag_dtype: String,
+    flag_types: Option<String>,
+    flag_compression: String,
+    flag_sheet_names: Option<String>,
+    flag_freeze: bool,
+    flag_autofilter: bool,
+    flag_widths: Option<String>,
+    flag_strings: bool,
 }
 
 impl Args {
@@ -157,27 +214,230 @@ impl Args {
         Ok(())
     }
 
-    fn convert_to_xlsx<R: Read>(
-        &self,
-        mut rdr: csv::Reader<R>,
-        mut writer: Box<dyn Write>,
-    ) -> CliResult<()> {
+    fn read_sheets(&self) -> CliResult<Vec<Sheet>> {
+        let inputs = if self.arg_input.is_empty() {
+            vec![None]
+        } else {
+            self.arg_input.iter().cloned().map(Some).collect()
+        };
+
+        let names = match &self.flag_sheet_names {
+            Some(names) => {
+                let names = names.split(',').map(String::from).collect::<Vec<_>>();
+
+                if names.len() != inputs.len() {
+                    Err(format!(
+                        "--sheet-names contains {} names, but {} files were given!",
+                        names.len(),
+                        inputs.len()
+                    ))?;
+                }
+
+                names
+            }
+            None => inputs
+                .iter()
+                .map(|input| match input {
+                    None => "Sheet1".to_string(),
+                    Some(path) => Path::new(path.strip_suffix(".gz").unwrap_or(path))
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.to_string()),
+                })
+                .collect(),
+        };
+
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                Err(format!(
+                    "duplicate sheet name \"{}\"!\nUse --sheet-names to name sheets explicitly.",
+                    name
+                ))?;
+            }
+        }
+
+        let widths = self
+            .flag_widths
+            .as_deref()
+            .map(ColumnWidths::parse)
+            .transpose()?;
+
+        let mut sheets = Vec::with_capacity(inputs.len());
+
+        for (input, name) in inputs.into_iter().zip(names) {
+            let mut rdr = Config::new(&input)
+                .no_headers(self.flag_no_headers)
+                .delimiter(self.flag_delimiter)
+                .reader()?;
+
+            let headers = (!self.flag_no_headers)
+                .then(|| rdr.headers().cloned())
+                .transpose()?;
+
+            let records = rdr.into_records().collect::<Result<Vec<_>, _>>()?;
+            let columns = headers
+                .as_ref()
+                .or(records.first())
+                .map(|record| record.len())
+                .unwrap_or(0);
+
+            let types = if self.flag_strings {
+                vec![SheetColumnType::String; columns]
+            } else {
+                let sample = &records[..records.len().min(self.flag_buffer_size.get())];
+                let mut inferrence_buffer = JSONTypeInferrenceBuffer::with_columns(
+                    columns,
+                    sample.len(),
+                    JSONEmptyMode::Null,
+                );
+
+                for record in sample {
+                    inferrence_buffer.process(record.clone());
+                }
+
+                inferrence_buffer
+                    .types()
+                    .enumerate()
+                    .map(|(i, json_type)| {
+                        SheetColumnType::infer(json_type, sample.iter().map(|record| &record[i]))
+                    })
+                    .collect()
+            };
+
+            let column_widths = match &widths {
+                None => None,
+                Some(ColumnWidths::Auto) => Some(
+                    (0..columns)
+                        .map(|i| {
+                            headers
+                                .iter()
+                                .chain(records.iter())
+                                .map(|record| record.get(i).map(|cell| cell.width()).unwrap_or(0))
+                                .max()
+                                .unwrap_or(0)
+                                .clamp(4, 60) as f64
+                                + 2.0
+                        })
+                        .collect(),
+                ),
+                Some(ColumnWidths::Fixed(widths)) => Some(
+                    (0..columns)
+                        .map(|i| {
+                            if widths.len() == 1 {
+                                widths[0]
+                            } else {
+                                widths.get(i).copied().unwrap_or(DEFAULT_COLUMN_WIDTH)
+                            }
+                        })
+                        .collect(),
+                ),
+            };
+
+            sheets.push(Sheet {
+                name,
+                headers,
+                records,
+                types,
+                column_widths,
+            });
+        }
+
+        Ok(sheets)
+    }
+
+    fn convert_to_xlsx(&self, mut writer: Box<dyn Write>) -> CliResult<()> {
         if !self.is_writing_to_file() {
             Err("cannot export in xlsx without a path.\nUse -o, --output or pipe the result!")?;
         }
 
+        let sheets = self.read_sheets()?;
+
+        let date_format = Format::new().set_num_format("yyyy-mm-dd");
+        let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+
         let mut workbook = Workbook::new();
-        let headers = rdr.headers()?.clone();
-        let worksheet = workbook.add_worksheet();
 
-        for (col, header) in headers.iter().enumerate() {
-            worksheet.write_string(0, col as u16, header)?;
-        }
+        for sheet in sheets.iter() {
+            let worksheet = workbook.add_worksheet();
+            worksheet.set_name(&sheet.name)?;
+
+            let mut row: u32 = 0;
+
+            if let Some(headers) = &sheet.headers {
+                for (col, header) in headers.iter().enumerate() {
+                    worksheet.write_string(0, col as u16, header)?;
+                }
+
+                row += 1;
+            }
+
+            for record in sheet.records.iter() {
+                for (col, (cell, column_type)) in record.iter().zip(sheet.types.iter()).enumerate()
+                {
+                    let col = col as u16;
+
+                    match SheetCell::cast(cell, *column_type) {
+                        SheetCell::Empty => (),
+                        SheetCell::String(string) => {
+                            worksheet.write_string(row, col, string)?;
+                        }
+                        SheetCell::Number(number) => {
+                            worksheet.write_number(row, col, number)?;
+                        }
+                        SheetCell::Bool(boolean) => {
+                            worksheet.write_boolean(row, col, boolean)?;
+                        }
+                        SheetCell::Date(date) => match excel_date(date) {
+                            Some(datetime) => {
+                                worksheet.write_datetime_with_format(
+                                    row,
+                                    col,
+                                    &datetime,
+                                    &date_format,
+                                )?;
+                            }
+                            None => {
+                                worksheet.write_string(row, col, cell)?;
+                            }
+                        },
+                        SheetCell::DateTime(datetime) => match excel_datetime(datetime) {
+                            Some(datetime) => {
+                                worksheet.write_datetime_with_format(
+                                    row,
+                                    col,
+                                    &datetime,
+                                    &datetime_format,
+                                )?;
+                            }
+                            None => {
+                                worksheet.write_string(row, col, cell)?;
+                            }
+                        },
+                    }
+                }
+
+                row += 1;
+            }
+
+            if sheet.headers.is_some() {
+                if self.flag_freeze {
+                    worksheet.set_freeze_panes(1, 0)?;
+                }
+
+                if self.flag_autofilter && !sheet.types.is_empty() {
+                    worksheet.autofilter(
+                        0,
+                        0,
+                        row.saturating_sub(1),
+                        (sheet.types.len() - 1) as u16,
+                    )?;
+                }
+            }
 
-        for (row, value) in rdr.records().enumerate() {
-            let record = value?;
-            for (col, field) in record.iter().enumerate() {
-                worksheet.write_string((row + 1) as u32, col as u16, field)?;
+            if let Some(widths) = &sheet.column_widths {
+                for (col, width) in widths.iter().enumerate() {
+                    worksheet.set_column_width(col as u16, *width)?;
+                }
             }
         }
 
@@ -190,6 +450,40 @@ impl Args {
         Ok(())
     }
 
+    fn convert_to_ods(&self, mut writer: Box<dyn Write>) -> CliResult<()> {
+        if !self.is_writing_to_file() {
+            Err("cannot export in ods without a path.\nUse -o, --output or pipe the result!")?;
+        }
+
+        let sheets = self.read_sheets()?;
+
+        let mut zip = ZipWriter::new(io::Cursor::new(Vec::new()));
+
+        // NOTE: the mimetype file must come first and remain uncompressed
+        zip.start_file(
+            "mimetype",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(b"application/vnd.oasis.opendocument.spreadsheet")?;
+
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("META-INF/manifest.xml", options)?;
+        write_ods_manifest(&mut zip)?;
+
+        zip.start_file("content.xml", options)?;
+        write_ods_content(&mut zip, &sheets, self.flag_autofilter)?;
+
+        zip.start_file("settings.xml", options)?;
+        write_ods_settings(&mut zip, &sheets, self.flag_freeze)?;
+
+        let buf = zip.finish()?.into_inner();
+        writer.write_all(&buf)?;
+
+        writer.flush()?;
+        Ok(())
+    }
+
     fn convert_to_html<R: Read>(
         &self,
         mut rdr: csv::Reader<R>,
@@ -339,6 +633,110 @@ impl Args {
         Ok(())
     }
 
+    fn convert_to_columnar<R, W, F>(
+        &self,
+        mut rdr: csv::Reader<R>,
+        create_writer: F,
+    ) -> CliResult<()>
+    where
+        R: Read,
+        W: BatchWriter,
+        F: FnOnce(SchemaRef) -> CliResult<W>,
+    {
+        let headers = rdr.headers()?.clone();
+
+        if headers.is_empty() {
+            Err("cannot convert a file without columns to a columnar format!")?;
+        }
+
+        let mut inferrence_buffer = JSONTypeInferrenceBuffer::with_columns(
+            headers.len(),
+            self.flag_buffer_size.get(),
+            self.json_empty_mode(),
+        );
+
+        inferrence_buffer.read(&mut rdr)?;
+
+        let mut column_types = inferrence_buffer
+            .types()
+            .map(ColumnType::from_json_type)
+            .collect::<Vec<_>>();
+
+        if let Some(spec) = &self.flag_types {
+            for (i, column_type) in parse_column_types(spec, &headers, !self.flag_no_headers)? {
+                column_types[i] = column_type;
+            }
+        }
+
+        let mut batcher = RecordBatcher::new(&headers, &column_types, self.flag_nulls);
+        let mut writer = create_writer(batcher.schema())?;
+
+        let mut index: usize = 0;
+
+        for record in inferrence_buffer.records() {
+            if let Some(batch) = batcher.push(index, record)? {
+                writer.write_batch(&batch)?;
+            }
+
+            index += 1;
+        }
+
+        let mut record = csv::StringRecord::new();
+
+        while rdr.read_record(&mut record)? {
+            if let Some(batch) = batcher.push(index, &record)? {
+                writer.write_batch(&batch)?;
+            }
+
+            index += 1;
+        }
+
+        if let Some(batch) = batcher.flush()? {
+            writer.write_batch(&batch)?;
+        }
+
+        writer.finish()
+    }
+
+    fn convert_to_parquet<R: Read>(
+        &self,
+        rdr: csv::Reader<R>,
+        writer: Box<dyn Write + Send>,
+    ) -> CliResult<()> {
+        if !self.is_writing_to_file() {
+            Err("cannot export in parquet without a path.\nUse -o, --output or pipe the result!")?;
+        }
+
+        let compression = match self.flag_compression.as_str() {
+            "uncompressed" | "none" => Compression::UNCOMPRESSED,
+            "snappy" => Compression::SNAPPY,
+            "gzip" => Compression::GZIP(Default::default()),
+            "lz4" => Compression::LZ4_RAW,
+            "zstd" => Compression::ZSTD(Default::default()),
+            _ => Err(format!("unknown --compression {}", self.flag_compression))?,
+        };
+
+        let properties = WriterProperties::builder()
+            .set_compression(compression)
+            .build();
+
+        self.convert_to_columnar(rdr, |schema| {
+            Ok(ArrowWriter::try_new(writer, schema, Some(properties))?)
+        })
+    }
+
+    fn convert_to_arrow<R: Read>(
+        &self,
+        rdr: csv::Reader<R>,
+        writer: Box<dyn Write + Send>,
+    ) -> CliResult<()> {
+        if !self.is_writing_to_file() {
+            Err("cannot export in arrow without a path.\nUse -o, --output or pipe the result!")?;
+        }
+
+        self.convert_to_columnar(rdr, |schema| Ok(ArrowFileWriter::try_new(writer, &schema)?))
+    }
+
     fn convert_to_txt<R: Read>(
         &self,
         mut rdr: csv::Reader<R>,
@@ -365,24 +763,835 @@ impl Args {
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
-    let conf = Config::new(&args.arg_input)
-        .no_headers(args.flag_no_headers)
-        .delimiter(args.flag_delimiter);
-    let rdr = conf.reader()?;
 
-    let writer: Box<dyn Write> = match &args.flag_output {
+    let is_spreadsheet = matches!(args.arg_format.as_str(), "xlsx" | "ods");
+
+    if args.arg_input.len() > 1 && !is_spreadsheet {
+        Err("only the xlsx & ods formats can convert multiple files at once!")?;
+    }
+
+    let writer: Box<dyn Write + Send> = match &args.flag_output {
         Some(output_path) => Box::new(fs::File::create(output_path)?),
         None => Box::new(io::stdout()),
     };
 
     match args.arg_format.as_str() {
+        "xlsx" => return args.convert_to_xlsx(writer),
+        "ods" => return args.convert_to_ods(writer),
+        _ => (),
+    };
+
+    let conf = Config::new(&args.arg_input.first().cloned())
+        .no_headers(args.flag_no_headers)
+        .delimiter(args.flag_delimiter);
+    let rdr = conf.reader()?;
+
+    match args.arg_format.as_str() {
+        "arrow" | "ipc" | "feather" => args.convert_to_arrow(rdr, writer),
         "html" => args.convert_to_html(rdr, writer),
         "json" => args.convert_to_json(rdr, writer),
         "jsonl" | "ndjson" => args.convert_to_ndjson(rdr, writer),
         "md" => args.convert_to_md(rdr, writer),
         "npy" => args.convert_to_npy(rdr, writer),
+        "parquet" => args.convert_to_parquet(rdr, writer),
         "txt" | "text" => args.convert_to_txt(rdr, writer),
-        "xlsx" => args.convert_to_xlsx(rdr, writer),
         _ => Err("could not export the file to this format!")?,
     }
 }
+
+const RECORD_BATCH_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" | "str" => Self::String,
+            "int" | "integer" => Self::Int,
+            "float" => Self::Float,
+            "bool" | "boolean" => Self::Bool,
+            _ => return None,
+        })
+    }
+
+    fn from_json_type(json_type: JSONType) -> Self {
+        match json_type {
+            JSONType::Null | JSONType::String => Self::String,
+            JSONType::Integer => Self::Int,
+            JSONType::Float => Self::Float,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::String => DataType::Utf8,
+            Self::Int => DataType::Int64,
+            Self::Float => DataType::Float64,
+            Self::Bool => DataType::Boolean,
+        }
+    }
+}
+
+fn parse_column_types(
+    spec: &str,
+    headers: &csv::StringRecord,
+    use_names: bool,
+) -> CliResult<Vec<(usize, ColumnType)>> {
+    let mut column_types = Vec::new();
+
+    for pair in spec.split(',') {
+        let (column, type_name) = pair
+            .rsplit_once(':')
+            .ok_or_else(|| format!("invalid --types \"{}\", expecting \"column:type\"", pair))?;
+
+        let selection = SelectColumns::parse(column)
+            .map_err(|err| format!("invalid selection \"{}\" in --types: {}", column, err))?
+            .selection(headers.as_byte_record(), use_names)?;
+
+        let column_type = ColumnType::parse(type_name)
+            .ok_or_else(|| format!("unknown type \"{}\" in --types", type_name))?;
+
+        for index in selection.iter().copied() {
+            column_types.push((index, column_type));
+        }
+    }
+
+    Ok(column_types)
+}
+
+enum ColumnBuilder {
+    String(StringBuilder),
+    Int(Int64Builder),
+    Float(Float64Builder),
+    Bool(BooleanBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::String => Self::String(StringBuilder::new()),
+            ColumnType::Int => Self::Int(Int64Builder::new()),
+            ColumnType::Float => Self::Float(Float64Builder::new()),
+            ColumnType::Bool => Self::Bool(BooleanBuilder::new()),
+        }
+    }
+
+    // NOTE: returns false if the cell could not be parsed as the column's type
+    fn push(&mut self, cell: &str, nulls: bool) -> bool {
+        match self {
+            Self::String(builder) => {
+                if nulls && cell.is_empty() {
+                    builder.append_null();
+                } else {
+                    builder.append_value(cell);
+                }
+            }
+            Self::Int(builder) => {
+                if cell.is_empty() {
+                    builder.append_null();
+                } else {
+                    match cell.parse::<i64>() {
+                        Ok(integer) => builder.append_value(integer),
+                        Err(_) => return false,
+                    }
+                }
+            }
+            Self::Float(builder) => {
+                if cell.is_empty() {
+                    builder.append_null();
+                } else {
+                    match cell.parse::<f64>() {
+                        Ok(float) => builder.append_value(float),
+                        Err(_) => return false,
+                    }
+                }
+            }
+            Self::Bool(builder) => {
+                if cell.is_empty() {
+                    builder.append_null();
+                } else if cell.eq_ignore_ascii_case("true") {
+                    builder.append_value(true);
+                } else if cell.eq_ignore_ascii_case("false") {
+                    builder.append_value(false);
+                } else {
+                    return false;
+                }
+            }
+        };
+
+        true
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::String(builder) => builder.len(),
+            Self::Int(builder) => builder.len(),
+            Self::Float(builder) => builder.len(),
+            Self::Bool(builder) => builder.len(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::String(builder) => ArrayBuilder::finish(builder),
+            Self::Int(builder) => ArrayBuilder::finish(builder),
+            Self::Float(builder) => ArrayBuilder::finish(builder),
+            Self::Bool(builder) => ArrayBuilder::finish(builder),
+        }
+    }
+}
+
+struct RecordBatcher {
+    schema: SchemaRef,
+    column_types: Vec<ColumnType>,
+    builders: Vec<ColumnBuilder>,
+    nulls: bool,
+}
+
+impl RecordBatcher {
+    fn new(headers: &csv::StringRecord, column_types: &[ColumnType], nulls: bool) -> Self {
+        let fields = headers
+            .iter()
+            .zip(column_types.iter())
+            .map(|(header, column_type)| Field::new(header, column_type.data_type(), true))
+            .collect::<Vec<_>>();
+
+        Self {
+            schema: SchemaRef::new(Schema::new(fields)),
+            column_types: column_types.to_vec(),
+            builders: column_types
+                .iter()
+                .copied()
+                .map(ColumnBuilder::new)
+                .collect(),
+            nulls,
+        }
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn push(&mut self, index: usize, record: &csv::StringRecord) -> CliResult<Option<RecordBatch>> {
+        for (i, (builder, cell)) in self.builders.iter_mut().zip(record.iter()).enumerate() {
+            if !builder.push(cell, self.nulls) {
+                Err(format!(
+                    "row {}: could not parse \"{}\" as {} in column \"{}\"!\nUse a larger -B, --buffer-size or override the column type with --types.",
+                    index,
+                    cell,
+                    self.column_types[i].name(),
+                    self.schema.field(i).name()
+                ))?;
+            }
+        }
+
+        if self.builders[0].len() >= RECORD_BATCH_SIZE {
+            return self.flush();
+        }
+
+        Ok(None)
+    }
+
+    fn flush(&mut self) -> CliResult<Option<RecordBatch>> {
+        if self.builders.is_empty() || self.builders[0].len() == 0 {
+            return Ok(None);
+        }
+
+        let columns = self
+            .builders
+            .iter_mut()
+            .map(|builder| builder.finish())
+            .collect();
+
+        Ok(Some(RecordBatch::try_new(self.schema.clone(), columns)?))
+    }
+}
+
+trait BatchWriter {
+    fn write_batch(&mut self, batch: &RecordBatch) -> CliResult<()>;
+    fn finish(self) -> CliResult<()>;
+}
+
+impl<W: Write + Send> BatchWriter for ArrowWriter<W> {
+    fn write_batch(&mut self, batch: &RecordBatch) -> CliResult<()> {
+        Ok(self.write(batch)?)
+    }
+
+    fn finish(self) -> CliResult<()> {
+        self.close()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> BatchWriter for ArrowFileWriter<W> {
+    fn write_batch(&mut self, batch: &RecordBatch) -> CliResult<()> {
+        Ok(self.write(batch)?)
+    }
+
+    fn finish(mut self) -> CliResult<()> {
+        Ok(ArrowFileWriter::finish(&mut self)?)
+    }
+}
+
+const DEFAULT_COLUMN_WIDTH: f64 = 8.43;
+
+enum ColumnWidths {
+    Auto,
+    Fixed(Vec<f64>),
+}
+
+impl ColumnWidths {
+    fn parse(spec: &str) -> CliResult<Self> {
+        if spec == "auto" {
+            return Ok(Self::Auto);
+        }
+
+        let widths = spec
+            .split(',')
+            .map(|width| match width.trim().parse::<f64>() {
+                Ok(width) if width >= 0.0 => Ok(width),
+                _ => Err(format!("invalid width \"{}\" in --widths", width)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::Fixed(widths))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SheetColumnType {
+    String,
+    Number,
+    Bool,
+    Date,
+    DateTime,
+}
+
+impl SheetColumnType {
+    // NOTE: numbers are inferred exactly as for JSON, unless some of them would
+    // not survive the conversion, and string columns are then checked for
+    // booleans & dates.
+    fn infer<'a>(json_type: JSONType, mut cells: impl Iterator<Item = &'a str>) -> Self {
+        match json_type {
+            JSONType::Integer | JSONType::Float => {
+                return if cells.any(is_lossy_number) {
+                    Self::String
+                } else {
+                    Self::Number
+                };
+            }
+            JSONType::Null => return Self::String,
+            JSONType::String => (),
+        };
+
+        let mut inferred: Option<Self> = None;
+
+        for cell in cells.filter(|cell| !cell.is_empty()) {
+            let cell_type = if parse_bool(cell).is_some() {
+                Self::Bool
+            } else if parse_date(cell).is_some() {
+                Self::Date
+            } else if parse_datetime(cell).is_some() {
+                Self::DateTime
+            } else {
+                return Self::String;
+            };
+
+            inferred = Some(match (inferred, cell_type) {
+                (None, _) => cell_type,
+                (Some(current), _) if current == cell_type => current,
+                (Some(Self::Date), Self::DateTime) | (Some(Self::DateTime), Self::Date) => {
+                    Self::DateTime
+                }
+                _ => return Self::String,
+            });
+        }
+
+        inferred.unwrap_or(Self::String)
+    }
+}
+
+// NOTE: spreadsheets store numbers as floats, so identifiers such as zip codes
+// would lose their leading zeros, and large integers their precision.
+fn is_lossy_number(cell: &str) -> bool {
+    let digits = cell.strip_prefix(['-', '+']).unwrap_or(cell).as_bytes();
+
+    if digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit() {
+        return true;
+    }
+
+    match cell.parse::<i128>() {
+        Ok(n) => n.unsigned_abs() > (1 << f64::MANTISSA_DIGITS),
+        Err(_) => false,
+    }
+}
+
+fn parse_bool(cell: &str) -> Option<bool> {
+    if cell.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if cell.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn parse_date(cell: &str) -> Option<civil::Date> {
+    if cell.len() != 10 {
+        return None;
+    }
+
+    cell.parse::<civil::Date>().ok()
+}
+
+fn parse_datetime(cell: &str) -> Option<civil::DateTime> {
+    if cell.len() <= 10 {
+        return None;
+    }
+
+    cell.parse::<civil::DateTime>().ok()
+}
+
+fn excel_date(date: civil::Date) -> Option<ExcelDateTime> {
+    ExcelDateTime::from_ymd(
+        u16::try_from(date.year()).ok()?,
+        date.month() as u8,
+        date.day() as u8,
+    )
+    .ok()
+}
+
+fn excel_datetime(datetime: civil::DateTime) -> Option<ExcelDateTime> {
+    excel_date(datetime.date())?
+        .and_hms(
+            datetime.hour() as u16,
+            datetime.minute() as u8,
+            datetime.second() as f64 + datetime.subsec_nanosecond() as f64 / 1e9,
+        )
+        .ok()
+}
+
+enum SheetCell<'a> {
+    Empty,
+    String(&'a str),
+    Number(f64),
+    Bool(bool),
+    Date(civil::Date),
+    DateTime(civil::DateTime),
+}
+
+impl<'a> SheetCell<'a> {
+    // NOTE: cells that cannot be parsed as their column's type are kept as strings
+    fn cast(cell: &'a str, column_type: SheetColumnType) -> Self {
+        if cell.is_empty() {
+            return Self::Empty;
+        }
+
+        let cast = match column_type {
+            SheetColumnType::String => None,
+            SheetColumnType::Number if is_lossy_number(cell) => None,
+            SheetColumnType::Number => cell.parse::<f64>().ok().map(Self::Number),
+            SheetColumnType::Bool => parse_bool(cell).map(Self::Bool),
+            SheetColumnType::Date => parse_date(cell).map(Self::Date),
+            SheetColumnType::DateTime => parse_datetime(cell)
+                .or_else(|| parse_date(cell).map(|date| date.to_datetime(civil::Time::midnight())))
+                .map(Self::DateTime),
+        };
+
+        cast.unwrap_or(Self::String(cell))
+    }
+}
+
+struct Sheet {
+    name: String,
+    headers: Option<csv::StringRecord>,
+    records: Vec<csv::StringRecord>,
+    types: Vec<SheetColumnType>,
+    column_widths: Option<Vec<f64>>,
+}
+
+impl Sheet {
+    fn rows(&self) -> impl Iterator<Item = &csv::StringRecord> {
+        self.headers.iter().chain(self.records.iter())
+    }
+}
+
+fn ods_column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+
+        if index < 26 {
+            break;
+        }
+
+        index = index / 26 - 1;
+    }
+
+    name.reverse();
+
+    String::from_utf8(name).unwrap()
+}
+
+fn ods_escape_sheet_name(name: &str) -> String {
+    format!("'{}'", name.replace('\'', "''"))
+}
+
+fn write_ods_manifest<W: Write>(writer: W) -> CliResult<()> {
+    let mut xml_writer = XMLWriter::without_indent(writer);
+
+    xml_writer.write_declaration()?;
+    xml_writer.open(
+        "manifest:manifest",
+        [
+            (
+                "xmlns:manifest",
+                "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0",
+            ),
+            ("manifest:version", "1.2"),
+        ],
+    )?;
+
+    for (path, media_type) in [
+        ("/", "application/vnd.oasis.opendocument.spreadsheet"),
+        ("content.xml", "text/xml"),
+        ("settings.xml", "text/xml"),
+    ] {
+        xml_writer.open_empty(
+            "manifest:file-entry",
+            [
+                ("manifest:full-path", path),
+                ("manifest:media-type", media_type),
+            ],
+        )?;
+    }
+
+    xml_writer.close("manifest:manifest")?;
+    xml_writer.finish()?;
+
+    Ok(())
+}
+
+fn write_ods_content<W: Write>(writer: W, sheets: &[Sheet], autofilter: bool) -> CliResult<()> {
+    // NOTE: some readers don't tolerate whitespace between cells
+    let mut xml_writer = XMLWriter::without_indent(writer);
+
+    xml_writer.write_declaration()?;
+    xml_writer.open(
+        "office:document-content",
+        [
+            (
+                "xmlns:office",
+                "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
+            ),
+            (
+                "xmlns:style",
+                "urn:oasis:names:tc:opendocument:xmlns:style:1.0",
+            ),
+            (
+                "xmlns:text",
+                "urn:oasis:names:tc:opendocument:xmlns:text:1.0",
+            ),
+            (
+                "xmlns:table",
+                "urn:oasis:names:tc:opendocument:xmlns:table:1.0",
+            ),
+            (
+                "xmlns:number",
+                "urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0",
+            ),
+            ("office:version", "1.2"),
+        ],
+    )?;
+
+    // Styles
+    xml_writer.open_no_attributes("office:automatic-styles")?;
+
+    for (name, with_time) in [("N1", false), ("N2", true)] {
+        xml_writer.open("number:date-style", [("style:name", name)])?;
+        xml_writer.open_empty("number:year", [("number:style", "long")])?;
+        xml_writer.open_no_attributes("number:text")?;
+        xml_writer.write_text("-")?;
+        xml_writer.close("number:text")?;
+        xml_writer.open_empty("number:month", [("number:style", "long")])?;
+        xml_writer.open_no_attributes("number:text")?;
+        xml_writer.write_text("-")?;
+        xml_writer.close("number:text")?;
+        xml_writer.open_empty("number:day", [("number:style", "long")])?;
+
+        if with_time {
+            xml_writer.open_no_attributes("number:text")?;
+            xml_writer.write_text(" ")?;
+            xml_writer.close("number:text")?;
+            xml_writer.open_empty("number:hours", [("number:style", "long")])?;
+            xml_writer.open_no_attributes("number:text")?;
+            xml_writer.write_text(":")?;
+            xml_writer.close("number:text")?;
+            xml_writer.open_empty("number:minutes", [("number:style", "long")])?;
+            xml_writer.open_no_attributes("number:text")?;
+            xml_writer.write_text(":")?;
+            xml_writer.close("number:text")?;
+            xml_writer.open_empty("number:seconds", [("number:style", "long")])?;
+        }
+
+        xml_writer.close("number:date-style")?;
+    }
+
+    for (name, data_style) in [("ce1", "N1"), ("ce2", "N2")] {
+        xml_writer.open_empty(
+            "style:style",
+            [
+                ("style:name", name),
+                ("style:family", "table-cell"),
+                ("style:data-style-name", data_style),
+            ],
+        )?;
+    }
+
+    for (i, sheet) in sheets.iter().enumerate() {
+        if let Some(widths) = &sheet.column_widths {
+            for (j, width) in widths.iter().enumerate() {
+                // NOTE: a character is roughly 0.075 inches wide
+                xml_writer.open(
+                    "style:style",
+                    [
+                        ("style:name", format!("co{}_{}", i, j).as_str()),
+                        ("style:family", "table-column"),
+                    ],
+                )?;
+                xml_writer.open_empty(
+                    "style:table-column-properties",
+                    [(
+                        "style:column-width",
+                        format!("{:.3}in", width * 0.075).as_str(),
+                    )],
+                )?;
+                xml_writer.close("style:style")?;
+            }
+        }
+    }
+
+    xml_writer.close("office:automatic-styles")?;
+
+    // Sheets
+    xml_writer.open_no_attributes("office:body")?;
+    xml_writer.open_no_attributes("office:spreadsheet")?;
+
+    for (i, sheet) in sheets.iter().enumerate() {
+        xml_writer.open("table:table", [("table:name", sheet.name.as_str())])?;
+
+        if let Some(widths) = &sheet.column_widths {
+            for j in 0..widths.len() {
+                xml_writer.open_empty(
+                    "table:table-column",
+                    [("table:style-name", format!("co{}_{}", i, j).as_str())],
+                )?;
+            }
+        }
+
+        if let Some(headers) = &sheet.headers {
+            xml_writer.open_no_attributes("table:table-row")?;
+
+            for header in headers.iter() {
+                write_ods_cell(&mut xml_writer, header, SheetCell::String(header))?;
+            }
+
+            xml_writer.close("table:table-row")?;
+        }
+
+        for record in sheet.records.iter() {
+            xml_writer.open_no_attributes("table:table-row")?;
+
+            for (cell, column_type) in record.iter().zip(sheet.types.iter()) {
+                write_ods_cell(&mut xml_writer, cell, SheetCell::cast(cell, *column_type))?;
+            }
+
+            xml_writer.close("table:table-row")?;
+        }
+
+        xml_writer.close("table:table")?;
+    }
+
+    if autofilter {
+        xml_writer.open_no_attributes("table:database-ranges")?;
+
+        for (i, sheet) in sheets.iter().enumerate() {
+            if sheet.headers.is_none() || sheet.types.is_empty() {
+                continue;
+            }
+
+            let escaped_name = ods_escape_sheet_name(&sheet.name);
+            let address = format!(
+                "{}.A1:{}.{}{}",
+                escaped_name,
+                escaped_name,
+                ods_column_name(sheet.types.len() - 1),
+                sheet.rows().count()
+            );
+
+            xml_writer.open_empty(
+                "table:database-range",
+                [
+                    (
+                        "table:name",
+                        format!("__Anonymous_Sheet_DB__{}", i).as_str(),
+                    ),
+                    ("table:target-range-address", address.as_str()),
+                    ("table:display-filter-buttons", "true"),
+                ],
+            )?;
+        }
+
+        xml_writer.close("table:database-ranges")?;
+    }
+
+    xml_writer.close("office:spreadsheet")?;
+    xml_writer.close("office:body")?;
+    xml_writer.close("office:document-content")?;
+    xml_writer.finish()?;
+
+    Ok(())
+}
+
+fn write_ods_cell<W: Write>(
+    xml_writer: &mut XMLWriter<W>,
+    raw: &str,
+    cell: SheetCell,
+) -> CliResult<()> {
+    let value;
+
+    let attributes: Vec<(&str, &str)> = match cell {
+        SheetCell::Empty => {
+            xml_writer.open_empty("table:table-cell", iter::empty::<(&str, &str)>())?;
+            return Ok(());
+        }
+        SheetCell::String(_) => vec![("office:value-type", "string")],
+        SheetCell::Number(number) => {
+            value = number.to_string();
+            vec![("office:value-type", "float"), ("office:value", &value)]
+        }
+        SheetCell::Bool(boolean) => vec![
+            ("office:value-type", "boolean"),
+            (
+                "office:boolean-value",
+                if boolean { "true" } else { "false" },
+            ),
+        ],
+        SheetCell::Date(date) => {
+            value = date.to_string();
+            vec![
+                ("office:value-type", "date"),
+                ("office:date-value", &value),
+                ("table:style-name", "ce1"),
+            ]
+        }
+        SheetCell::DateTime(datetime) => {
+            value = datetime.strftime("%Y-%m-%dT%H:%M:%S").to_string();
+            vec![
+                ("office:value-type", "date"),
+                ("office:date-value", &value),
+                ("table:style-name", "ce2"),
+            ]
+        }
+    };
+
+    xml_writer.open("table:table-cell", attributes)?;
+    xml_writer.open_no_attributes("text:p")?;
+    xml_writer.write_text(raw)?;
+    xml_writer.close("text:p")?;
+    xml_writer.close("table:table-cell")?;
+
+    Ok(())
+}
+
+fn write_ods_settings<W: Write>(writer: W, sheets: &[Sheet], freeze: bool) -> CliResult<()> {
+    let mut xml_writer = XMLWriter::without_indent(writer);
+
+    xml_writer.write_declaration()?;
+    xml_writer.open(
+        "office:document-settings",
+        [
+            (
+                "xmlns:office",
+                "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
+            ),
+            (
+                "xmlns:config",
+                "urn:oasis:names:tc:opendocument:xmlns:config:1.0",
+            ),
+            ("office:version", "1.2"),
+        ],
+    )?;
+    xml_writer.open_no_attributes("office:settings")?;
+    xml_writer.open(
+        "config:config-item-set",
+        [("config:name", "ooo:view-settings")],
+    )?;
+    xml_writer.open("config:config-item-map-indexed", [("config:name", "Views")])?;
+    xml_writer.open_no_attributes("config:config-item-map-entry")?;
+
+    xml_writer.open(
+        "config:config-item",
+        [("config:name", "ViewId"), ("config:type", "string")],
+    )?;
+    xml_writer.write_text("view1")?;
+    xml_writer.close("config:config-item")?;
+
+    xml_writer.open("config:config-item-map-named", [("config:name", "Tables")])?;
+
+    for sheet in sheets.iter() {
+        if !freeze || sheet.headers.is_none() {
+            continue;
+        }
+
+        xml_writer.open(
+            "config:config-item-map-entry",
+            [("config:name", sheet.name.as_str())],
+        )?;
+
+        // NOTE: what LibreOffice calls a vertical split is a split between rows
+        for (name, item_type, value) in [
+            ("HorizontalSplitMode", "short", "0"),
+            ("VerticalSplitMode", "short", "2"),
+            ("VerticalSplitPosition", "int", "1"),
+            ("ActiveSplitRange", "short", "2"),
+            ("PositionTop", "int", "0"),
+            ("PositionBottom", "int", "1"),
+        ] {
+            xml_writer.open(
+                "config:config-item",
+                [("config:name", name), ("config:type", item_type)],
+            )?;
+            xml_writer.write_text(value)?;
+            xml_writer.close("config:config-item")?;
+        }
+
+        xml_writer.close("config:config-item-map-entry")?;
+    }
+
+    xml_writer.close("config:config-item-map-named")?;
+    xml_writer.close("config:config-item-map-entry")?;
+    xml_writer.close("config:config-item-map-indexed")?;
+    xml_writer.close("config:config-item-set")?;
+    xml_writer.close("office:settings")?;
+    xml_writer.close("office:document-settings")?;
+    xml_writer.finish()?;
+
+    Ok(())
+}
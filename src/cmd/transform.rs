@@ -3,7 +3,7 @@ use std::fs;
 use pariter::IteratorExt;
 
 use crate::config::{Config, Delimiter};
-use crate::moonblade::{DynamicValue, Program};
+use crate::moonblade::{load_prelude, DynamicValue, Program};
 use crate::select::SelectColumns;
 use crate::util;
 use crate::CliResult;
@@ -53,6 +53,9 @@ Usage:
 
 transform options:
     -f, --evaluate-file        Read evaluation expression from a file instead.
+    --prelude <path>           Load user-defined functions from this file. Defaults to
+                               the file given by the XAN_PRELUDE environment variable,
+                               if any.
     -r, --rename <name>        New name for the transformed column.
     -p, --parallel             Whether to use parallelization to speed up computations.
                                Will automatically select a suitable number of threads to use
@@ -82,6 +85,7 @@ struct Args {
     flag_parallel: bool,
     flag_threads: Option<usize>,
     flag_evaluate_file: bool,
+    flag_prelude: Option<String>,
 }
 
 impl Args {
@@ -90,6 +94,10 @@ impl Args {
             self.arg_expression = fs::read_to_string(&self.arg_expression)?;
         }
 
+        if let Some(path) = &self.flag_prelude {
+            load_prelude(path)?;
+        }
+
         Ok(())
     }
 }
@@ -1,5 +1,5 @@
 use std::env;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Seek, Write};
 use std::num::NonZeroUsize;
 use std::str::FromStr;
 
@@ -9,6 +9,7 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::config::{Config, Delimiter};
 use crate::select::SelectColumns;
+use crate::tui::{InteractiveView, InteractiveViewOptions, LazyRows};
 use crate::util::{self, ImmutableRecordHelpers};
 use crate::CliResult;
 
@@ -190,6 +191,14 @@ If you want to display all the columns using a pager, prefer using
 the -p/--pager flag that internally rely on the ubiquitous \"less\"
 command.
 
+You can also browse the whole file using the -i/--interactive flag, that
+opens a full-screen view where you can scroll vertically & horizontally while
+the headers & the row index remain visible, hide, move & sort columns, jump
+to some row or search for some pattern. Press \"?\" once the view is open to
+display all the available keys. Rows of files on disk are lazily read when
+needed (and if the file was indexed with `xan index`, accessed directly), so
+that even very large files can be browsed instantly.
+
 If you still want to use a pager manually, don't forget to use
 the -e/--expand and -C/--force-colors flags before piping like so:
 
@@ -218,6 +227,7 @@ view options:
                             [default: table]
     -p, --pager             Automatically use the \"less\" command to page the results.
                             This flag does not work on windows!
+    -i, --interactive       Open an interactive full-screen view of the data.
     -A, --all               Remove the row limit and display everything.
     -l, --limit <number>    Maximum of rows to read into memory. Use -A, --all or
                             set to 0 to disable the limit.
@@ -253,6 +263,7 @@ struct Args {
     arg_input: Option<String>,
     flag_select: SelectColumns,
     flag_pager: bool,
+    flag_interactive: bool,
     flag_theme: String,
     flag_cols: Option<String>,
     flag_delimiter: Option<Delimiter>,
@@ -344,6 +355,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let args = Args::merge(env_args, args);
 
+    if args.flag_interactive {
+        return run_interactive(&args);
+    }
+
     if args.infer_force_colors() {
         colored::control::set_override(true);
     }
@@ -876,6 +891,72 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+fn run_interactive(args: &Args) -> CliResult<()> {
+    if !io::stdout().is_terminal() {
+        Err("-i/--interactive can only be used in a terminal!")?;
+    }
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.flag_select.clone());
+
+    // NOTE: seekable files are lazily paged from disk instead of being loaded whole
+    let (byte_headers, rows) = if rconfig.is_seekable() {
+        let mut io_reader = rconfig.io_reader_for_random_access()?;
+        let offset_before_csv_parsing = io_reader.stream_position()?;
+
+        let mut rdr = rconfig.csv_reader_from_reader(io_reader);
+        let byte_headers = rdr.byte_headers()?.clone();
+
+        let start = if rconfig.no_headers {
+            offset_before_csv_parsing
+        } else {
+            offset_before_csv_parsing + rdr.position().byte()
+        };
+
+        (
+            byte_headers,
+            LazyRows::seekable(rdr, start, rconfig.record_index()?),
+        )
+    } else {
+        let mut rdr = rconfig.reader()?;
+        let byte_headers = rdr.byte_headers()?.clone();
+
+        (byte_headers, LazyRows::buffered(rdr))
+    };
+
+    let sel = rconfig.selection(&byte_headers)?;
+
+    let headers = sel
+        .iter()
+        .map(|i| {
+            if rconfig.no_headers {
+                i.to_string()
+            } else {
+                String::from_utf8_lossy(&byte_headers[*i]).into_owned()
+            }
+        })
+        .collect();
+
+    let options = InteractiveViewOptions {
+        title: args
+            .arg_input
+            .clone()
+            .unwrap_or_else(|| "<stdin>".to_string()),
+        show_index: !args.flag_hide_index,
+        sanitize_emojis: args.flag_sanitize_emojis,
+        formatter: args.flag_significance.map(|s| {
+            Formatter::new()
+                .precision(Precision::Significance(s.get() as u8))
+                .separator(None)
+                .unwrap()
+        }),
+    };
+
+    InteractiveView::new(headers, sel, rows, options).run()
+}
+
 fn adjust_column_widths(widths: &[usize], max_width: usize) -> Vec<usize> {
     widths.iter().map(|m| usize::min(*m, max_width)).collect()
 }
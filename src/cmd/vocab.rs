@@ -5,17 +5,20 @@
 // https://sciencespo.hal.science/tel-03626011v1/file/2017-cointet-hdr-la-cartographie-des-traces-textuelles-comme-methodologie-denquete-en-sciences-sociales.pdf
 // https://pbil.univ-lyon1.fr/R/pdf/tdr35.pdf
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::convert::TryFrom;
 use std::num::NonZeroUsize;
 use std::rc::Rc;
 
 use bstr::ByteSlice;
+use ordered_float::NotNan;
+use paltoquet::tokenizers::WordTokenizerBuilder;
 
 use crate::collections::ClusteredInsertHashmap;
+use crate::collections::FixedReverseHeap;
 use crate::collections::{hash_map::Entry, HashMap};
 use crate::config::{Config, Delimiter};
-use crate::select::SelectColumns;
+use crate::select::{SelectColumns, Selection};
 use crate::util;
 use crate::CliError;
 use crate::CliResult;
@@ -57,7 +60,7 @@ any separator given to the --sep flag).
 The command considers, by default, documents to be a single row of the input
 but can also be symbolized by the value of a column selection given to -D/--doc.
 
-This command can compute 5 kinds of differents vocabulary statistics:
+This command can compute 7 kinds of differents vocabulary statistics:
 
 1. corpus-level statistics (using the \"corpus\" subcommand):
     - doc_count: number of documents in the corpus
@@ -97,6 +100,8 @@ This command can compute 5 kinds of differents vocabulary statistics:
     - G2: G2 score (approx. without the --complete flag)
     - pmi: pointwise mutual information
     - npmi: normalized pointwise mutual information
+    - t_score: t-score of the co-occurrence
+    - dice: Dice coefficient of both tokens
 
     or, using the --distrib flag:
 
@@ -113,6 +118,32 @@ This command can compute 5 kinds of differents vocabulary statistics:
     - count: total number of co-occurrences
     - lgl: the specificity score (ratio of statistically relevant co-occurrences)
 
+6. document similarity (using the \"sim\" subcommand), i.e. the top-k most
+   similar documents of each document, wrt the cosine similarity of their
+   TF-IDF or BM25 vectors, efficiently computed using an inverted index:
+    - (*doc)1: columns representing the first document (suffixed by 1)
+    - (*doc)2: columns representing the second document (suffixed by 2)
+    - similarity: cosine similarity of both documents
+
+    or, using the --query flag, the top-k documents most similar to the query:
+
+    - (*doc): columns representing the document (named like the input)
+    - similarity: cosine similarity of the document and the query
+
+7. keywords (using the \"keywords\" subcommand), i.e. tokens of the target
+   corpus (the input) compared with a reference corpus, read from the given
+   <reference> CSV file using the same flags (the command will error if the
+   reference corpus contains no tokens):
+    - token: some distinct token of the target corpus
+    - target_count: number of occurrences of the token in the target corpus
+    - reference_count: number of occurrences of the token in the reference corpus
+    - target_df: document frequency of the token in the target corpus
+    - reference_df: document frequency of the token in the reference corpus
+    - log_ratio: binary log of the ratio of relative frequencies of the token
+      in both corpora (null frequencies are replaced by 0.5)
+    - G2: log-likelihood keyness score of the token, which is negative when the
+      token is relatively less frequent in the target corpus than in the reference
+
 Note that you should generally avoid giving too much importance wrt
 the statistical relevance of both chi2 & G2 scores when considering
 less than 5 items (absolute term frequencies or co-occurrence counts).
@@ -123,6 +154,8 @@ Usage:
     xan vocab doc [options] [<input>]
     xan vocab doc-token [options] [<input>]
     xan vocab cooc [options] [<input>]
+    xan vocab sim [options] [<input>]
+    xan vocab keywords [options] <reference> [<input>]
     xan vocab --help
 
 vocab options:
@@ -137,7 +170,7 @@ vocab options:
                              it becomes possible to process a file containing only one token
                              per row. Cannot be used without -D, --doc.
 
-vocab doc-token options:
+vocab doc-token & sim options:
     --tf-weight <weight>         TF weighting scheme. One of \"count\", \"binary\", \"ratio\",
                                  or \"log-normal\". [default: count]
     --k1-value <value>           \"k1\" Factor for BM25 computation. [default: 1.2]
@@ -147,6 +180,17 @@ vocab doc-token options:
                                  levels include \"0.5\", \"0.1\", \"0.05\", \"0.025\", \"0.01\",
                                  \"0.005\" and \"0.001\".
 
+vocab sim options:
+    --weighting <scheme>         Weighting of document vectors. Either \"tfidf\", which
+                                 follows --tf-weight, or \"bm25\", which follows both
+                                 the --k1-value & --b-value flags. [default: tfidf]
+    -k, --top <k>                Number of most similar documents to return per document,
+                                 or for the query. [default: 10]
+    --query <text>               Rank documents against the given query instead. The query
+                                 is tokenized like \"xan tokenize words\" would, by default.
+    --lower                      Whether to lowercase query tokens, like
+                                 \"xan tokenize words --lower\".
+
 vocab cooc options:
     -w, --window <n>             Size of the co-occurrence window, in number of tokens around the currently
                                  considered token. If not given, co-occurrences will be computed using the bag
@@ -159,14 +203,22 @@ vocab cooc options:
     --specificity                Compute the lgl specificity score per token instead.
     --min-count <n>              Minimum number of co-occurrence count to be included in the result.
                                  [default: 1]
+    --min-freq <n>               Minimum number of occurrences of both tokens for a co-occurrence
+                                 to be included in the result. Does not apply to --specificity.
+                                 [default: 1]
     --chi2-significance <value>  Filter doc,token pairs by only keeping significant ones wrt their
                                  chi2 score that must be above the given significance level. Accepted
                                  levels include \"0.5\", \"0.1\", \"0.05\", \"0.025\", \"0.01\",
                                  \"0.005\" and \"0.001\".
-    --G2-significance <value>    Filter doc,token pairs by only keeping significant ones wrt their
-                                 G2 score that must be above the given significance level. Accepted
-                                 levels include \"0.5\", \"0.1\", \"0.05\", \"0.025\", \"0.01\",
-                                 \"0.005\" and \"0.001\".
+    --G2-significance <value>    Filter doc,token pairs (or keywords) by only keeping significant
+                                 ones wrt their G2 score that must be above the given significance
+                                 level. Accepted levels include \"0.5\", \"0.1\", \"0.05\", \"0.025\", \"0.01\",
+                                 \"0.005\" and \"0.001\". Note that negative keywords will
+                                 therefore be filtered out.
+
+vocab keywords options:
+    --min-target-count <n>       Minimum number of occurrences of the token in the target corpus
+                                 to be included in the result. [default: 1]
 
 Common options:
     -h, --help             Display this message
@@ -184,7 +236,10 @@ struct Args {
     cmd_doc_token: bool,
     cmd_corpus: bool,
     cmd_cooc: bool,
+    cmd_sim: bool,
+    cmd_keywords: bool,
     arg_input: Option<String>,
+    arg_reference: Option<String>,
     flag_token: Option<SelectColumns>,
     flag_doc: Option<SelectColumns>,
     flag_sep: Option<String>,
@@ -200,6 +255,12 @@ struct Args {
     flag_distrib: bool,
     flag_specificity: bool,
     flag_min_count: usize,
+    flag_min_target_count: usize,
+    flag_min_freq: usize,
+    flag_weighting: SimilarityWeighting,
+    flag_top: NonZeroUsize,
+    flag_query: Option<String>,
+    flag_lower: bool,
     flag_output: Option<String>,
     flag_no_headers: bool,
     flag_delimiter: Option<Delimiter>,
@@ -249,11 +310,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let doc_sel = args
         .flag_doc
+        .as_ref()
         .map(|s| s.selection(&headers, !args.flag_no_headers))
         .transpose()?;
 
     let mut record = csv::ByteRecord::new();
-    let mut i: usize = 0;
 
     let mut wtr = Config::new(&args.flag_output).writer()?;
 
@@ -313,7 +374,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 for bag_of_words in doc_tokens.into_values() {
                     for i in 0..bag_of_words.len() {
                         let source = &bag_of_words[i];
-                        let source_id = cooccurrences.register_token(source.clone());
+                        let source_id = cooccurrences.register_occurrence(source.clone());
 
                         #[allow(clippy::needless_range_loop)]
                         for j in (i + 1)..bag_of_words.len() {
@@ -335,7 +396,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
                     for i in 0..bag_of_words.len() {
                         let source = &bag_of_words[i];
-                        let source_id = cooccurrences.register_token(source.clone());
+                        let source_id = cooccurrences.register_occurrence(source.clone());
 
                         let upper_bound = model
                             .map(|window| (i + 1 + window.get()).min(bag_of_words.len()))
@@ -357,7 +418,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     ($bag_of_words:ident) => {{
                         for i in 0..$bag_of_words.len() {
                             let source = &$bag_of_words[i];
-                            let source_id = cooccurrences.register_token(source.clone());
+                            let source_id = cooccurrences.register_occurrence(source.clone());
 
                             #[allow(clippy::needless_range_loop)]
                             for j in (i + 1)..(i + 1 + window.get()).min($bag_of_words.len()) {
@@ -411,8 +472,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             ];
 
             wtr.write_record(output_headers)?;
-            cooccurrences
-                .for_each_distrib_cooc_record(args.flag_min_count, |r| wtr.write_byte_record(r))?;
+            cooccurrences.for_each_distrib_cooc_record(
+                args.flag_min_count,
+                args.flag_min_freq,
+                |r| wtr.write_byte_record(r),
+            )?;
         } else if args.flag_specificity {
             let output_headers: [&[u8]; 3] = [b"token", b"count", b"lgl"];
 
@@ -423,7 +487,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 |r| wtr.write_byte_record(r),
             )?;
         } else {
-            let output_headers: [&[u8]; 8] = [
+            let output_headers: [&[u8]; 10] = [
                 b"token1",
                 b"token2",
                 b"count",
@@ -432,11 +496,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 b"G2",
                 b"pmi",
                 b"npmi",
+                b"t_score",
+                b"dice",
             ];
 
             wtr.write_record(output_headers)?;
             cooccurrences.for_each_cooc_record(
                 args.flag_min_count,
+                args.flag_min_freq,
                 chi2_significance,
                 g2_significance,
                 |r| wtr.write_byte_record(r),
@@ -446,31 +513,62 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         return Ok(wtr.flush()?);
     }
 
-    let mut vocab = Vocabulary::new();
-
-    while rdr.read_byte_record(&mut record)? {
-        let document: Document = match &doc_sel {
-            Some(sel) => sel.select(&record).map(|cell| cell.to_vec()).collect(),
-            None => vec![i.to_string().into_bytes()],
-        };
+    let vocab = Vocabulary::from_reader(
+        &mut rdr,
+        token_pos,
+        doc_sel.as_ref(),
+        args.flag_sep.as_deref(),
+    )?;
+
+    if args.cmd_keywords {
+        let mut reference_rdr = Config::new(&args.arg_reference)
+            .delimiter(args.flag_delimiter)
+            .no_headers(args.flag_no_headers)
+            .reader()?;
+
+        let reference_headers = reference_rdr.byte_headers()?.clone();
+
+        let reference_token_pos =
+            flag_token.single_selection(&reference_headers, !args.flag_no_headers)?;
+
+        let reference_doc_sel = args
+            .flag_doc
+            .as_ref()
+            .map(|s| s.selection(&reference_headers, !args.flag_no_headers))
+            .transpose()?;
+
+        let reference = Vocabulary::from_reader(
+            &mut reference_rdr,
+            reference_token_pos,
+            reference_doc_sel.as_ref(),
+            args.flag_sep.as_deref(),
+        )?;
 
-        if let Some(sep) = &args.flag_sep {
-            for token in record[token_pos].split_str(sep) {
-                let token: Token = token.trim().to_vec();
+        // NOTE: keyness cannot be computed against an empty reference, since
+        // ratios are relative to its token count.
+        if reference.token_count == 0 {
+            Err("reference corpus contains no tokens!")?;
+        }
 
-                if !token.is_empty() {
-                    vocab.add(document.clone(), token);
-                }
-            }
-        } else {
-            let token: Token = record[token_pos].trim().to_vec();
+        let output_headers: [&[u8]; 7] = [
+            b"token",
+            b"target_count",
+            b"reference_count",
+            b"target_df",
+            b"reference_df",
+            b"log_ratio",
+            b"G2",
+        ];
 
-            if !token.is_empty() {
-                vocab.add(document, token);
-            }
-        }
+        wtr.write_record(output_headers)?;
+        vocab.for_each_keyword_record(
+            &reference,
+            args.flag_min_target_count,
+            g2_significance,
+            |r| wtr.write_byte_record(r),
+        )?;
 
-        i += 1;
+        return Ok(wtr.flush()?);
     }
 
     if args.cmd_token {
@@ -528,6 +626,60 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         wtr.write_byte_record(&output_headers)?;
 
         vocab.for_each_doc_level_record(|r| wtr.write_byte_record(r))?;
+    } else if args.cmd_sim {
+        let mut output_headers = csv::ByteRecord::new();
+
+        let doc_headers: Vec<Vec<u8>> = match &doc_sel {
+            Some(sel) => sel.select(&headers).map(|h| h.to_vec()).collect(),
+            None => vec![b"doc".to_vec()],
+        };
+
+        let weigher = DocumentWeigher {
+            weighting: args.flag_weighting,
+            tf_weighting: args.flag_tf_weight,
+            k1: args.flag_k1_value,
+            b: args.flag_b_value,
+        };
+
+        if let Some(query) = &args.flag_query {
+            for name in doc_headers.iter() {
+                output_headers.push_field(name);
+            }
+
+            output_headers.push_field(b"similarity");
+            wtr.write_byte_record(&output_headers)?;
+
+            let tokenizer = WordTokenizerBuilder::new().build();
+
+            let query_tokens = tokenizer
+                .tokenize(query)
+                .map(|token| {
+                    let text = token.to_pair().0;
+
+                    if args.flag_lower {
+                        text.to_lowercase().into_bytes()
+                    } else {
+                        text.into_bytes()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            vocab.for_each_query_sim_record(&weigher, &query_tokens, args.flag_top.get(), |r| {
+                wtr.write_byte_record(r)
+            })?;
+        } else {
+            for suffix in [b"1", b"2"] {
+                for name in doc_headers.iter() {
+                    output_headers.push_field(&[name.as_slice(), suffix].concat());
+                }
+            }
+
+            output_headers.push_field(b"similarity");
+            wtr.write_byte_record(&output_headers)?;
+
+            vocab
+                .for_each_sim_record(&weigher, args.flag_top.get(), |r| wtr.write_byte_record(r))?;
+        }
     } else if args.cmd_corpus {
         let headers: [&[u8]; 4] = [
             b"doc_count",
@@ -631,6 +783,68 @@ impl TryFrom<String> for TfWeighting {
     }
 }
 
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+enum SimilarityWeighting {
+    TfIdf,
+    Bm25,
+}
+
+impl TryFrom<String> for SimilarityWeighting {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "tfidf" => Self::TfIdf,
+            "bm25" => Self::Bm25,
+            _ => return Err(format!("unsupported --weighting \"{}\"", &value)),
+        })
+    }
+}
+
+struct DocumentWeigher {
+    weighting: SimilarityWeighting,
+    tf_weighting: TfWeighting,
+    k1: f64,
+    b: f64,
+}
+
+impl DocumentWeigher {
+    fn compute(&self, tf: u64, idf: f64, doc_len: usize, average_doc_len: f64) -> f64 {
+        match self.weighting {
+            SimilarityWeighting::TfIdf => self.tf_weighting.compute(tf, doc_len) * idf,
+            SimilarityWeighting::Bm25 => {
+                DocumentTokenStats { tf }.bm25(idf, doc_len, average_doc_len, self.k1, self.b)
+            }
+        }
+    }
+}
+
+type SparseVector = Vec<(TokenID, f64)>;
+
+fn normalize_sparse_vector(vector: &mut SparseVector) {
+    let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+
+    if norm > 0.0 {
+        for (_, w) in vector.iter_mut() {
+            *w /= norm;
+        }
+    }
+
+    // NOTE: tokens present in every document have a null idf
+    vector.retain(|(_, w)| *w != 0.0);
+}
+
+// Documents with their L2-normalized weight vectors, along with an inverted
+// index mapping tokens to their postings
+struct DocumentVectors {
+    documents: Vec<(Document, SparseVector)>,
+    inverted_index: Vec<Vec<(usize, f64)>>,
+}
+
+// Ranking candidates by decreasing similarity, then by document order
+type SimilarityCandidate = (NotNan<f64>, Reverse<usize>);
+
 #[derive(Debug)]
 struct DocumentTokenStats {
     tf: u64,
@@ -704,6 +918,44 @@ impl Vocabulary {
         Self::default()
     }
 
+    fn from_reader<R: std::io::Read>(
+        rdr: &mut csv::Reader<R>,
+        token_pos: usize,
+        doc_sel: Option<&Selection>,
+        sep: Option<&str>,
+    ) -> CliResult<Self> {
+        let mut vocab = Self::new();
+        let mut record = csv::ByteRecord::new();
+        let mut i: usize = 0;
+
+        while rdr.read_byte_record(&mut record)? {
+            let document: Document = match doc_sel {
+                Some(sel) => sel.select(&record).map(|cell| cell.to_vec()).collect(),
+                None => vec![i.to_string().into_bytes()],
+            };
+
+            if let Some(sep) = sep {
+                for token in record[token_pos].split_str(sep) {
+                    let token: Token = token.trim().to_vec();
+
+                    if !token.is_empty() {
+                        vocab.add(document.clone(), token);
+                    }
+                }
+            } else {
+                let token: Token = record[token_pos].trim().to_vec();
+
+                if !token.is_empty() {
+                    vocab.add(document, token);
+                }
+            }
+
+            i += 1;
+        }
+
+        Ok(vocab)
+    }
+
     fn doc_count(&self) -> usize {
         self.documents.len()
     }
@@ -803,6 +1055,62 @@ impl Vocabulary {
         Ok(())
     }
 
+    // NOTE: keyness is computed as per Rayson & Garside (2000) for the G2
+    // score and Hardie (2014) for the log ratio.
+    fn for_each_keyword_record<F, E>(
+        &self,
+        reference: &Self,
+        min_target_count: usize,
+        g2_significance: Option<f64>,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(&csv::ByteRecord) -> Result<(), E>,
+    {
+        let c = self.token_count as f64;
+        let d = reference.token_count as f64;
+
+        let mut record = csv::ByteRecord::new();
+
+        for stats in self.tokens.iter() {
+            if (stats.gf as usize) < min_target_count {
+                continue;
+            }
+
+            let reference_stats = reference
+                .token_ids
+                .get(&stats.text)
+                .map(|id| &reference.tokens[*id]);
+
+            let (reference_gf, reference_df) =
+                reference_stats.map(|s| (s.gf, s.df)).unwrap_or((0, 0));
+
+            let a = stats.gf as f64;
+            let b = reference_gf as f64;
+
+            let g2 = compute_keyness_g2(a, b, c, d);
+
+            if matches!(g2_significance, Some(level) if g2 < level) {
+                continue;
+            }
+
+            let log_ratio = ((a.max(0.5) / c) / (b.max(0.5) / d)).log2();
+
+            record.clear();
+            record.push_field(&stats.text);
+            record.push_field(stats.gf.to_string().as_bytes());
+            record.push_field(reference_gf.to_string().as_bytes());
+            record.push_field(stats.df.to_string().as_bytes());
+            record.push_field(reference_df.to_string().as_bytes());
+            record.push_field(log_ratio.to_string().as_bytes());
+            record.push_field(g2.to_string().as_bytes());
+
+            callback(&record)?;
+        }
+
+        Ok(())
+    }
+
     fn for_each_doc_token_level_record<F, E>(
         self,
         k1: f64,
@@ -883,12 +1191,215 @@ impl Vocabulary {
     }
 }
 
+impl Vocabulary {
+    // NOTE: this drains the documents, along with their stats
+    fn take_document_vectors(&mut self, weigher: &DocumentWeigher) -> DocumentVectors {
+        let n = self.doc_count();
+        let average_doc_len = self.average_doc_len();
+
+        let mut vectors = DocumentVectors {
+            documents: Vec::with_capacity(n),
+            inverted_index: vec![Vec::new(); self.tokens.len()],
+        };
+
+        for (i, (doc, doc_stats)) in std::mem::take(&mut self.documents).into_iter().enumerate() {
+            let doc_len = doc_stats.doc_len();
+
+            let mut vector = doc_stats
+                .tokens
+                .into_iter()
+                .map(|(token_id, doc_token_stats)| {
+                    let idf = self.tokens[token_id].idf(n);
+
+                    (
+                        token_id,
+                        weigher.compute(doc_token_stats.tf, idf, doc_len, average_doc_len),
+                    )
+                })
+                .collect::<SparseVector>();
+
+            vector.sort_by_key(|(token_id, _)| *token_id);
+            normalize_sparse_vector(&mut vector);
+
+            for (token_id, weight) in vector.iter() {
+                vectors.inverted_index[*token_id].push((i, *weight));
+            }
+
+            vectors.documents.push((doc, vector));
+        }
+
+        vectors
+    }
+
+    fn for_each_sim_record<F, E>(
+        mut self,
+        weigher: &DocumentWeigher,
+        top: usize,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(&csv::ByteRecord) -> Result<(), E>,
+    {
+        if self.doc_count() == 0 {
+            return Ok(());
+        }
+
+        let DocumentVectors {
+            documents,
+            inverted_index,
+        } = self.take_document_vectors(weigher);
+
+        let mut scores = vec![0.0; documents.len()];
+        let mut touched: Vec<usize> = Vec::new();
+
+        let mut record = csv::ByteRecord::new();
+
+        for (i, (doc, vector)) in documents.iter().enumerate() {
+            for (token_id, weight) in vector.iter() {
+                for (j, other_weight) in inverted_index[*token_id].iter() {
+                    if *j == i {
+                        continue;
+                    }
+
+                    if scores[*j] == 0.0 {
+                        touched.push(*j);
+                    }
+
+                    scores[*j] += weight * other_weight;
+                }
+            }
+
+            let mut heap: FixedReverseHeap<SimilarityCandidate> =
+                FixedReverseHeap::with_capacity(top);
+
+            for j in touched.drain(..) {
+                heap.push((NotNan::new(scores[j]).unwrap(), Reverse(j)));
+                scores[j] = 0.0;
+            }
+
+            for (similarity, Reverse(j)) in heap.into_sorted_vec() {
+                record.clear();
+
+                for cell in doc.iter().chain(documents[j].0.iter()) {
+                    record.push_field(cell);
+                }
+
+                record.push_field(similarity.to_string().as_bytes());
+
+                callback(&record)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn for_each_query_sim_record<F, E>(
+        mut self,
+        weigher: &DocumentWeigher,
+        query: &[Token],
+        top: usize,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(&csv::ByteRecord) -> Result<(), E>,
+    {
+        let n = self.doc_count();
+
+        if n == 0 {
+            return Ok(());
+        }
+
+        let average_doc_len = self.average_doc_len();
+        let DocumentVectors {
+            documents,
+            inverted_index,
+        } = self.take_document_vectors(weigher);
+
+        let mut query_counts: HashMap<TokenID, u64> = HashMap::new();
+
+        for token in query {
+            if let Some(token_id) = self.token_ids.get(token) {
+                *query_counts.entry(*token_id).or_default() += 1;
+            }
+        }
+
+        let mut query_vector = query_counts
+            .into_iter()
+            .map(|(token_id, tf)| {
+                let idf = self.tokens[token_id].idf(n);
+
+                (
+                    token_id,
+                    weigher.compute(tf, idf, query.len(), average_doc_len),
+                )
+            })
+            .collect::<SparseVector>();
+
+        query_vector.sort_by_key(|(token_id, _)| *token_id);
+        normalize_sparse_vector(&mut query_vector);
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+
+        for (token_id, weight) in query_vector.iter() {
+            for (i, doc_weight) in inverted_index[*token_id].iter() {
+                *scores.entry(*i).or_default() += weight * doc_weight;
+            }
+        }
+
+        let mut heap: FixedReverseHeap<SimilarityCandidate> = FixedReverseHeap::with_capacity(top);
+
+        for (i, score) in scores {
+            heap.push((NotNan::new(score).unwrap(), Reverse(i)));
+        }
+
+        let mut record = csv::ByteRecord::new();
+
+        for (similarity, Reverse(i)) in heap.into_sorted_vec() {
+            record.clear();
+
+            for cell in documents[i].0.iter() {
+                record.push_field(cell);
+            }
+
+            record.push_field(similarity.to_string().as_bytes());
+
+            callback(&record)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[inline]
 fn compute_pmi(x: usize, y: usize, xy: usize, n: usize) -> f64 {
     // NOTE: (xy / n) / ((x / n) * (y / n)) = (xy * z) / (x * y)
     ((xy * n) as f64 / (x * y) as f64).log2()
 }
 
+#[inline]
+fn compute_keyness_g2(a: f64, b: f64, c: f64, d: f64) -> f64 {
+    let expected_a = c * (a + b) / (c + d);
+    let expected_b = d * (a + b) / (c + d);
+
+    let mut g2 = 0.0;
+
+    if a > 0.0 {
+        g2 += a * (a / expected_a).ln();
+    }
+
+    if b > 0.0 {
+        g2 += b * (b / expected_b).ln();
+    }
+
+    // NOTE: the score is signed by the direction of keyness, so that tokens
+    // underused in the target corpus get a negative score.
+    if a < expected_a {
+        -2.0 * g2
+    } else {
+        2.0 * g2
+    }
+}
+
 #[inline]
 fn compute_npmi(xy: usize, n: usize, pmi: f64) -> f64 {
     // If probability is 1, then self-information is 0 and npmi must be 1, meaning full co-occurrence.
@@ -1116,6 +1627,7 @@ fn compute_g2(x: usize, y: usize, xy: usize, n: usize) -> f64 {
 #[derive(Debug)]
 struct CooccurrenceTokenEntry {
     token: Rc<Token>,
+    gf: usize,
     gcf: usize,
     cooc: ClusteredInsertHashmap<TokenID, usize>,
 }
@@ -1124,6 +1636,7 @@ impl CooccurrenceTokenEntry {
     fn new(token: Rc<Token>) -> Self {
         Self {
             token,
+            gf: 0,
             gcf: 0,
             cooc: ClusteredInsertHashmap::new(),
         }
@@ -1171,6 +1684,13 @@ impl Cooccurrences {
         }
     }
 
+    // NOTE: every token occurrence is registered once as a source
+    fn register_occurrence(&mut self, token: Rc<Token>) -> TokenID {
+        let id = self.register_token(token);
+        self.token_entries[id].gf += 1;
+        id
+    }
+
     fn add_cooccurrence(
         &mut self,
         mode: CooccurrenceMode,
@@ -1210,6 +1730,7 @@ impl Cooccurrences {
     fn for_each_cooc_record<F, E>(
         self,
         min_count: usize,
+        min_freq: usize,
         chi2_significance: Option<f64>,
         g2_significance: Option<f64>,
         mut callback: F,
@@ -1223,6 +1744,10 @@ impl Cooccurrences {
         for source_entry in self.token_entries.iter() {
             let x = source_entry.gcf;
 
+            if source_entry.gf < min_freq {
+                continue;
+            }
+
             for (target_id, count) in source_entry.cooc.iter() {
                 if *count < min_count {
                     continue;
@@ -1230,6 +1755,10 @@ impl Cooccurrences {
 
                 let target_entry = &self.token_entries[*target_id];
 
+                if target_entry.gf < min_freq {
+                    continue;
+                }
+
                 let y = target_entry.gcf;
                 let xy = *count;
 
@@ -1255,6 +1784,9 @@ impl Cooccurrences {
                 let pmi = compute_pmi(x, y, xy, n);
                 let npmi = compute_npmi(xy, n, pmi);
 
+                let t_score = (xy as f64 - expected) / (xy as f64).sqrt();
+                let dice = (2 * xy) as f64 / (x + y) as f64;
+
                 csv_record.clear();
                 csv_record.push_field(&source_entry.token);
                 csv_record.push_field(&target_entry.token);
@@ -1270,6 +1802,8 @@ impl Cooccurrences {
                 csv_record.push_field(g2.to_string().as_bytes());
                 csv_record.push_field(pmi.to_string().as_bytes());
                 csv_record.push_field(npmi.to_string().as_bytes());
+                csv_record.push_field(t_score.to_string().as_bytes());
+                csv_record.push_field(dice.to_string().as_bytes());
 
                 callback(&csv_record)?;
             }
@@ -1279,7 +1813,12 @@ impl Cooccurrences {
     }
 
     // NOTE: currently we avoid self loops because they are fiddly
-    fn for_each_distrib_cooc_record<F, E>(self, min_count: usize, mut callback: F) -> Result<(), E>
+    fn for_each_distrib_cooc_record<F, E>(
+        self,
+        min_count: usize,
+        min_freq: usize,
+        mut callback: F,
+    ) -> Result<(), E>
     where
         F: FnMut(&csv::ByteRecord) -> Result<(), E>,
     {
@@ -1335,6 +1874,10 @@ impl Cooccurrences {
 
                 let target_entry = &self.token_entries[*target_id];
 
+                if source_entry.gf < min_freq || target_entry.gf < min_freq {
+                    continue;
+                }
+
                 // We do both entries at once and we optimize by intersection length
                 match source_entry.cooc.len().cmp(&target_entry.cooc.len()) {
                     // Ids serve as tie-breaker if needed
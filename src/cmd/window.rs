@@ -24,6 +24,24 @@ Computing a rolling mean & variance:
 
     $ xan window 'rolling_mean(10, n) as mean, rolling_var(10, n) as var' file.csv
 
+Computing a rolling sum over the last 7 days, given a file sorted by time:
+
+    $ xan window 'rolling_sum(\"7d\", amount, datetime) as weekly' file.csv
+
+To aggregate data over regular time intervals instead, check out
+`xan resample`.
+
+Any regular aggregation function can also be computed over a frame of rows
+surrounding the current one, expressed SQL-style:
+
+    $ xan window 'median(x) over 5 preceding and 5 following as median' file.csv
+
+When no frame is given, the aggregation is computed over the whole file, or
+whole group when using -g/--groupby. Here is how to compute the share of each
+row in its group's total, for instance:
+
+    $ xan window -g country 'sum(n) as total' file.csv | xan map 'n / total as share'
+
 Adding a lagged column:
 
     $ xan window 'lag(n) as \"n-1\"' file.csv
@@ -12,6 +12,7 @@ use bgzip::index::BGZFIndex;
 use bgzip::read::{BGZFReader, IndexedBGZFReader};
 use flate2::read::MultiGzDecoder;
 
+use crate::index::RecordIndex;
 use crate::read::{self, ReverseRead};
 use crate::select::{SelectColumns, Selection};
 use crate::{CliError, CliResult};
@@ -417,6 +418,17 @@ impl Config {
         }
     }
 
+    pub fn is_seekable(&self) -> bool {
+        self.path.is_some() && (!self.compressed || self.is_indexed_gzip())
+    }
+
+    pub fn record_index(&self) -> CliResult<Option<RecordIndex>> {
+        match self.path {
+            Some(ref p) if !self.compressed => Ok(RecordIndex::open(p, self.no_headers)?),
+            _ => Ok(None),
+        }
+    }
+
     pub fn io_reader_for_random_access(&self) -> CliResult<Box<dyn SeekRead + Send + 'static>> {
         let msg = "can't use provided input because it does not allow for random access (e.g. stdin or piping)".to_string();
 
@@ -1,4 +1,9 @@
-use jiff::{civil::Date, civil::DateTime, tz::TimeZone, Error, Timestamp, ToSpan, Unit, Zoned};
+use std::convert::TryFrom;
+
+use jiff::{
+    civil::Date, civil::DateTime, civil::Time, tz::TimeZone, Error, RoundMode, SignedDuration,
+    Span, SpanRelativeTo, Timestamp, ToSpan, Unit, Zoned, ZonedRound,
+};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -81,6 +86,102 @@ pub fn format_partial_date(unit: Unit, date: &Date) -> String {
     }
 }
 
+// NOTE: partial dates are understood as the start of the period they represent,
+// and their precision is returned alongside. Values without time zone are
+// understood in the given time zone (or the system one), and others are
+// converted to it, so that values can be compared & bucketed consistently.
+pub fn parse_temporal_value(
+    string: &str,
+    timezone: Option<&TimeZone>,
+) -> Option<(Zoned, Option<Unit>)> {
+    if let Some(partial_date) = parse_partial_date(string) {
+        let unit = partial_date.as_unit();
+
+        let zoned = partial_date
+            .into_inner()
+            .to_datetime(Time::default())
+            .to_zoned(timezone.cloned().unwrap_or_else(TimeZone::system))
+            .ok()?;
+
+        return Some((zoned, Some(unit)));
+    }
+
+    match parse_zoned(string, None, timezone.cloned()) {
+        Ok(zoned) => Some((zoned, None)),
+        Err(ZonedParseError::TimezoneMismatch) => parse_zoned(string, None, None)
+            .ok()
+            .map(|zoned| (zoned.with_time_zone(timezone.unwrap().clone()), None)),
+        Err(_) => None,
+    }
+}
+
+pub fn parse_temporal_unit(string: &str) -> Option<Unit> {
+    Some(match string {
+        "year" | "years" => Unit::Year,
+        "month" | "months" => Unit::Month,
+        "day" | "days" => Unit::Day,
+        "hour" | "hours" => Unit::Hour,
+        "minute" | "minutes" => Unit::Minute,
+        "second" | "seconds" => Unit::Second,
+        _ => return None,
+    })
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TemporalUnit(Unit);
+
+impl TemporalUnit {
+    pub fn into_inner(self) -> Unit {
+        self.0
+    }
+}
+
+impl TryFrom<String> for TemporalUnit {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        parse_temporal_unit(&value)
+            .map(Self)
+            .ok_or_else(|| format!("invalid unit \"{}\"!", value))
+    }
+}
+
+pub fn floor_zoned(zoned: &Zoned, unit: Unit) -> Zoned {
+    match unit {
+        Unit::Year => zoned.start_of_day().unwrap().first_of_year().unwrap(),
+        Unit::Month => zoned.start_of_day().unwrap().first_of_month().unwrap(),
+        _ => zoned
+            .round(ZonedRound::new().smallest(unit).mode(RoundMode::Trunc))
+            .unwrap(),
+    }
+}
+
+pub fn next_zoned(zoned: &Zoned, unit: Unit) -> Result<Zoned, String> {
+    let span = match unit {
+        Unit::Year => 1.year(),
+        Unit::Month => 1.month(),
+        Unit::Day => 1.day(),
+        Unit::Hour => 1.hour(),
+        Unit::Minute => 1.minute(),
+        Unit::Second => 1.second(),
+        _ => return Err(format!("unsupported temporal unit {:?}!", unit)),
+    };
+
+    zoned.checked_add(span).map_err(|err| err.to_string())
+}
+
+pub fn format_zoned(zoned: &Zoned, unit: Unit) -> String {
+    zoned
+        .strftime(match unit {
+            Unit::Year => "%Y",
+            Unit::Month => "%Y-%m",
+            Unit::Day => "%F",
+            _ => "%FT%T",
+        })
+        .to_string()
+}
+
 const MINUTES_BOUND: i64 = 60;
 const HOURS_BOUND: i64 = MINUTES_BOUND * 60;
 const DAYS_BOUND: i64 = HOURS_BOUND * 24;
@@ -88,18 +189,18 @@ const MONTHS_BOUND: i64 = DAYS_BOUND * 30;
 const YEARS_BOUND: i64 = MONTHS_BOUND * 12;
 
 fn smallest_granularity(zoned: &Zoned) -> Unit {
-    if zoned.month() == 1 {
-        Unit::Year
-    } else if zoned.day() == 1 {
-        Unit::Month
-    } else if zoned.hour() == 0 {
-        Unit::Day
-    } else if zoned.minute() == 0 {
-        Unit::Hour
-    } else if zoned.second() == 0 {
+    if zoned.second() != 0 {
+        Unit::Second
+    } else if zoned.minute() != 0 {
         Unit::Minute
+    } else if zoned.hour() != 0 {
+        Unit::Hour
+    } else if zoned.day() != 1 {
+        Unit::Day
+    } else if zoned.month() != 1 {
+        Unit::Month
     } else {
-        Unit::Second
+        Unit::Year
     }
 }
 
@@ -136,6 +237,16 @@ pub fn could_be_date(string: &str) -> bool {
     string.parse::<DateTime>().is_ok() || is_partial_date(string)
 }
 
+// NOTE: days are considered to be 24 hours long, and durations expressed in
+// months or years cannot be parsed since they have no fixed length.
+pub fn parse_duration(string: &str) -> Option<SignedDuration> {
+    string
+        .parse::<Span>()
+        .ok()?
+        .to_duration(SpanRelativeTo::days_are_24_hours())
+        .ok()
+}
+
 #[derive(Debug, Clone, Copy)]
 enum JiffParseFailureKind {
     InvalidFormat,
@@ -295,6 +406,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_duration() {
+        let tests = [
+            ("2h 30m", Some(SignedDuration::from_mins(150))),
+            ("3 days", Some(SignedDuration::from_hours(72))),
+            ("PT1S", Some(SignedDuration::from_secs(1))),
+            ("1 month", None),
+            ("test", None),
+        ];
+
+        for (string, expected) in tests {
+            assert_eq!(parse_duration(string), expected, "{}", string);
+        }
+    }
+
     #[test]
     fn test_parse_partial_date() {
         let tests = [
@@ -310,4 +436,43 @@ mod tests {
             assert_eq!(parse_partial_date(string), expected, "{}", string);
         }
     }
+    #[test]
+    fn test_infer_temporal_granularity() {
+        let zoned = |string: &str| parse_zoned(string, None, Some(TimeZone::UTC)).unwrap();
+
+        let tests = [
+            ("2024-01-01T00:00:00Z", "2024-01-05T00:00:00Z", Unit::Day),
+            ("2024-01-01T00:00:00Z", "2024-03-05T00:00:00Z", Unit::Day),
+            ("2024-01-01T00:00:00Z", "2025-06-01T00:00:00Z", Unit::Month),
+            ("2024-01-01T00:00:00Z", "2024-01-01T20:00:00Z", Unit::Hour),
+            ("2024-01-01T00:00:00Z", "2024-01-01T01:30:00Z", Unit::Minute),
+        ];
+
+        for (earliest, latest, expected) in tests {
+            assert_eq!(
+                infer_temporal_granularity(&zoned(earliest), &zoned(latest), 10),
+                expected,
+                "{} -> {}",
+                earliest,
+                latest
+            );
+        }
+    }
+
+    #[test]
+    fn test_floor_zoned() {
+        let zoned = parse_zoned("2024-03-17T13:45:12Z", None, Some(TimeZone::UTC)).unwrap();
+
+        let tests = [
+            (Unit::Year, "2024"),
+            (Unit::Month, "2024-03"),
+            (Unit::Day, "2024-03-17"),
+            (Unit::Hour, "2024-03-17T13:00:00"),
+            (Unit::Minute, "2024-03-17T13:45:00"),
+        ];
+
+        for (unit, expected) in tests {
+            assert_eq!(format_zoned(&floor_zoned(&zoned, unit), unit), expected);
+        }
+    }
 }
@@ -0,0 +1,556 @@
+use std::collections::VecDeque;
+
+use crate::collections::{HashMap, IncrementalId, UnionFind};
+use crate::util;
+
+use super::Graph;
+
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+const MODULARITY_EPSILON: f64 = 1e-10;
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub enum CommunityAlgorithm {
+    Louvain,
+    Leiden,
+}
+
+impl TryFrom<String> for CommunityAlgorithm {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "louvain" => Self::Louvain,
+            "leiden" => Self::Leiden,
+            _ => {
+                return Err(format!(
+                    "unknown community detection algorithm \"{}\"",
+                    &value
+                ))
+            }
+        })
+    }
+}
+
+// Relabel arbitrary labels as 0..n, in order of first appearance
+fn relabel(labels: &[usize]) -> (Vec<usize>, usize) {
+    let mut ids = IncrementalId::new();
+    let relabeled = labels.iter().map(|l| ids.get(*l)).collect::<Vec<_>>();
+    let count = relabeled.iter().max().map(|m| m + 1).unwrap_or(0);
+
+    (relabeled, count)
+}
+
+// Weighted adjacency lists, sorted by neighbor so that results are deterministic.
+// When the graph is undirected, `outbound` contains both directions and
+// `inbound` is `None`.
+struct Adjacency {
+    outbound: Vec<Vec<(usize, f64)>>,
+    inbound: Option<Vec<Vec<(usize, f64)>>>,
+}
+
+impl Adjacency {
+    fn len(&self) -> usize {
+        self.outbound.len()
+    }
+
+    fn incoming(&self, node: usize) -> &[(usize, f64)] {
+        match &self.inbound {
+            Some(inbound) => &inbound[node],
+            None => &self.outbound[node],
+        }
+    }
+}
+
+// Undirected weighted graph used by community detection, where self-loops
+// are kept apart so that aggregation is easy.
+struct CommunityGraph {
+    neighbors: Vec<Vec<(usize, f64)>>,
+    self_loops: Vec<f64>,
+}
+
+impl CommunityGraph {
+    fn from_weights(count: usize, weights: Vec<HashMap<usize, f64>>, self_loops: Vec<f64>) -> Self {
+        debug_assert_eq!(weights.len(), count);
+
+        let neighbors = weights
+            .into_iter()
+            .map(|map| {
+                let mut list = map.into_iter().collect::<Vec<_>>();
+                list.sort_by_key(|(j, _)| *j);
+                list
+            })
+            .collect();
+
+        Self {
+            neighbors,
+            self_loops,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn strengths(&self) -> Vec<f64> {
+        self.neighbors
+            .iter()
+            .zip(self.self_loops.iter())
+            .map(|(list, self_loop)| list.iter().map(|(_, w)| w).sum::<f64>() + 2.0 * self_loop)
+            .collect()
+    }
+
+    fn aggregate(&self, partition: &[usize], count: usize) -> Self {
+        let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+        let mut self_loops = vec![0.0; count];
+
+        for (i, list) in self.neighbors.iter().enumerate() {
+            let ci = partition[i];
+
+            self_loops[ci] += self.self_loops[i];
+
+            for (j, w) in list.iter() {
+                let cj = partition[*j];
+
+                if ci == cj {
+                    // NOTE: internal edges are seen from both ends
+                    self_loops[ci] += w / 2.0;
+                } else {
+                    *weights[ci].entry(cj).or_default() += w;
+                }
+            }
+        }
+
+        Self::from_weights(count, weights, self_loops)
+    }
+
+    // Greedily move nodes to the neighboring community maximizing the
+    // modularity gain, until no move improves it anymore.
+    fn move_nodes(&self, membership: &mut [usize], resolution: f64) {
+        let n = self.len();
+        let strengths = self.strengths();
+        let m2: f64 = strengths.iter().sum();
+
+        let mut totals = vec![0.0; n];
+
+        for (i, c) in membership.iter().enumerate() {
+            totals[*c] += strengths[i];
+        }
+
+        let mut community_weights = vec![0.0; n];
+        let mut touched: Vec<usize> = Vec::new();
+
+        loop {
+            let mut moved = false;
+
+            for i in 0..n {
+                let current = membership[i];
+                let k = strengths[i];
+
+                totals[current] -= k;
+
+                for (j, w) in self.neighbors[i].iter() {
+                    let c = membership[*j];
+
+                    if community_weights[c] == 0.0 {
+                        touched.push(c);
+                    }
+
+                    community_weights[c] += w;
+                }
+
+                let mut best = current;
+                let mut best_gain =
+                    community_weights[current] - resolution * totals[current] * k / m2;
+
+                for c in touched.iter().copied() {
+                    let gain = community_weights[c] - resolution * totals[c] * k / m2;
+
+                    if gain > best_gain + MODULARITY_EPSILON {
+                        best = c;
+                        best_gain = gain;
+                    }
+                }
+
+                for c in touched.drain(..) {
+                    community_weights[c] = 0.0;
+                }
+
+                totals[best] += k;
+
+                if best != current {
+                    membership[i] = best;
+                    moved = true;
+                }
+            }
+
+            if !moved {
+                break;
+            }
+        }
+    }
+
+    // Leiden refinement: nodes are merged, within their community, into
+    // well-connected sub-communities only, so that communities stay connected.
+    fn refine(&self, partition: &[usize], count: usize, resolution: f64) -> Vec<usize> {
+        let n = self.len();
+        let strengths = self.strengths();
+        let m2: f64 = strengths.iter().sum();
+
+        let mut community_totals = vec![0.0; count];
+
+        for (i, c) in partition.iter().enumerate() {
+            community_totals[*c] += strengths[i];
+        }
+
+        let mut refined = (0..n).collect::<Vec<_>>();
+        let mut sub_totals = strengths.clone();
+        let mut sub_sizes = vec![1usize; n];
+
+        // Weight of the edges between a sub-community and the rest of its community
+        let mut external = self
+            .neighbors
+            .iter()
+            .enumerate()
+            .map(|(i, list)| {
+                list.iter()
+                    .filter(|(j, _)| partition[*j] == partition[i])
+                    .map(|(_, w)| w)
+                    .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut sub_weights = vec![0.0; n];
+        let mut touched: Vec<usize> = Vec::new();
+
+        for v in 0..n {
+            if sub_sizes[refined[v]] > 1 {
+                continue;
+            }
+
+            let c = partition[v];
+            let k = strengths[v];
+            let community_total = community_totals[c];
+            let node_external = external[v];
+
+            if node_external < resolution * k * (community_total - k) / m2 {
+                continue;
+            }
+
+            for (u, w) in self.neighbors[v].iter() {
+                if partition[*u] != c {
+                    continue;
+                }
+
+                let s = refined[*u];
+
+                if sub_weights[s] == 0.0 {
+                    touched.push(s);
+                }
+
+                sub_weights[s] += w;
+            }
+
+            let mut best: Option<(usize, f64)> = None;
+
+            for s in touched.iter().copied() {
+                let sub_total = sub_totals[s];
+
+                if external[s] < resolution * sub_total * (community_total - sub_total) / m2 {
+                    continue;
+                }
+
+                let gain = sub_weights[s] - resolution * k * sub_total / m2;
+
+                if gain >= 0.0 && !matches!(best, Some((_, best_gain)) if gain <= best_gain) {
+                    best = Some((s, gain));
+                }
+            }
+
+            if let Some((s, _)) = best {
+                external[s] += node_external - 2.0 * sub_weights[s];
+                sub_totals[s] += k;
+                sub_sizes[s] += 1;
+                sub_totals[v] = 0.0;
+                sub_sizes[v] = 0;
+                refined[v] = s;
+            }
+
+            for s in touched.drain(..) {
+                sub_weights[s] = 0.0;
+            }
+        }
+
+        refined
+    }
+
+    fn detect_communities(mut self, algorithm: CommunityAlgorithm, resolution: f64) -> Vec<usize> {
+        let n = self.len();
+
+        let mut node_to_aggregate = (0..n).collect::<Vec<_>>();
+        let mut membership = (0..n).collect::<Vec<_>>();
+
+        loop {
+            if self.strengths().iter().sum::<f64>() == 0.0 {
+                break;
+            }
+
+            self.move_nodes(&mut membership, resolution);
+
+            let (communities, count) = relabel(&membership);
+
+            if count == self.len() {
+                break;
+            }
+
+            let (partition, aggregate_count) = match algorithm {
+                CommunityAlgorithm::Louvain => (communities.clone(), count),
+                CommunityAlgorithm::Leiden => {
+                    relabel(&self.refine(&communities, count, resolution))
+                }
+            };
+
+            if aggregate_count == self.len() {
+                membership = communities;
+                break;
+            }
+
+            // NOTE: with Leiden, the aggregate graph starts from the unrefined partition
+            let mut next_membership = vec![0; aggregate_count];
+
+            for (i, p) in partition.iter().enumerate() {
+                next_membership[*p] = communities[i];
+            }
+
+            for a in node_to_aggregate.iter_mut() {
+                *a = partition[*a];
+            }
+
+            self = self.aggregate(&partition, aggregate_count);
+            membership = next_membership;
+        }
+
+        relabel(
+            &node_to_aggregate
+                .into_iter()
+                .map(|a| membership[a])
+                .collect::<Vec<_>>(),
+        )
+        .0
+    }
+}
+
+impl Graph {
+    fn adjacency(&self) -> Adjacency {
+        let n = self.nodes.len();
+        let undirected = self.is_undirected();
+
+        let mut outbound: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        let mut inbound: Option<Vec<Vec<(usize, f64)>>> =
+            (!undirected).then(|| vec![Vec::new(); n]);
+
+        for edge in self.edges.iter() {
+            outbound[edge.source_id].push((edge.target_id, edge.weight));
+
+            match inbound.as_mut() {
+                Some(lists) => lists[edge.target_id].push((edge.source_id, edge.weight)),
+                None if edge.source_id != edge.target_id => {
+                    outbound[edge.target_id].push((edge.source_id, edge.weight))
+                }
+                None => (),
+            }
+        }
+
+        for list in outbound.iter_mut().chain(inbound.iter_mut().flatten()) {
+            list.sort_by_key(|(j, _)| *j);
+        }
+
+        Adjacency { outbound, inbound }
+    }
+
+    pub fn compute_pagerank(&self, alpha: f64) -> Vec<f64> {
+        let adjacency = self.adjacency();
+        let n = adjacency.len();
+
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let out_strengths = adjacency
+            .outbound
+            .iter()
+            .map(|list| list.iter().map(|(_, w)| w).sum::<f64>())
+            .collect::<Vec<_>>();
+
+        let uniform = 1.0 / n as f64;
+        let mut ranks = vec![uniform; n];
+        let mut next_ranks = vec![0.0; n];
+
+        for _ in 0..PAGERANK_MAX_ITERATIONS {
+            // Dangling nodes redistribute their rank uniformly
+            let dangling: f64 = ranks
+                .iter()
+                .zip(out_strengths.iter())
+                .filter(|(_, s)| **s == 0.0)
+                .map(|(r, _)| r)
+                .sum();
+
+            let base = (1.0 - alpha) * uniform + alpha * dangling * uniform;
+
+            // NOTE: nodes whose out-edges all have a null weight are dangling
+            // and must therefore be skipped, not to divide by zero.
+            for (i, next_rank) in next_ranks.iter_mut().enumerate() {
+                *next_rank = base
+                    + alpha
+                        * adjacency
+                            .incoming(i)
+                            .iter()
+                            .filter(|(j, _)| out_strengths[*j] != 0.0)
+                            .map(|(j, w)| ranks[*j] * w / out_strengths[*j])
+                            .sum::<f64>();
+            }
+
+            let error: f64 = ranks
+                .iter()
+                .zip(next_ranks.iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum();
+
+            std::mem::swap(&mut ranks, &mut next_ranks);
+
+            if error < n as f64 * PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+
+        ranks
+    }
+
+    // Brandes algorithm, with unweighted shortest paths. When `samples` is
+    // given, only this number of random source nodes are considered and the
+    // scores are extrapolated.
+    pub fn compute_betweenness(&self, samples: Option<usize>, seed: Option<usize>) -> Vec<f64> {
+        let adjacency = self.adjacency();
+        let n = adjacency.len();
+
+        let mut sources = (0..n).collect::<Vec<_>>();
+        let mut scale = 1.0;
+
+        if let Some(k) = samples {
+            if k < n {
+                let mut rng = util::acquire_rng(seed);
+
+                sources = rand::seq::index::sample(&mut *rng, n, k).into_vec();
+                sources.sort_unstable();
+                scale = n as f64 / k as f64;
+            }
+        }
+
+        if self.is_undirected() {
+            scale /= 2.0;
+        }
+
+        let mut scores = vec![0.0; n];
+
+        let mut stack: Vec<usize> = Vec::with_capacity(n);
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut sigma = vec![0.0; n];
+        let mut distances: Vec<Option<usize>> = vec![None; n];
+        let mut delta = vec![0.0; n];
+
+        for s in sources {
+            for i in 0..n {
+                predecessors[i].clear();
+                sigma[i] = 0.0;
+                distances[i] = None;
+                delta[i] = 0.0;
+            }
+
+            sigma[s] = 1.0;
+            distances[s] = Some(0);
+            queue.push_back(s);
+
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+
+                let d = distances[v].unwrap();
+
+                for (w, _) in adjacency.outbound[v].iter().copied() {
+                    if distances[w].is_none() {
+                        distances[w] = Some(d + 1);
+                        queue.push_back(w);
+                    }
+
+                    if distances[w] == Some(d + 1) {
+                        sigma[w] += sigma[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            while let Some(w) = stack.pop() {
+                for v in predecessors[w].iter().copied() {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+
+                if w != s {
+                    scores[w] += delta[w];
+                }
+            }
+        }
+
+        for score in scores.iter_mut() {
+            *score *= scale;
+        }
+
+        scores
+    }
+
+    // Weakly connected components when the graph is directed
+    pub fn compute_components(&self) -> Vec<usize> {
+        let mut sets = UnionFind::new();
+
+        for _ in self.nodes.iter() {
+            sets.make_set();
+        }
+
+        for edge in self.edges.iter() {
+            sets.union(edge.source_id, edge.target_id);
+        }
+
+        relabel(
+            &(0..self.nodes.len())
+                .map(|i| sets.find(i))
+                .collect::<Vec<_>>(),
+        )
+        .0
+    }
+
+    // Directed graphs are considered as undirected, summing reciprocal edge weights
+    pub fn compute_communities(
+        &self,
+        algorithm: CommunityAlgorithm,
+        resolution: f64,
+    ) -> Vec<usize> {
+        let n = self.nodes.len();
+
+        let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); n];
+        let mut self_loops = vec![0.0; n];
+
+        for edge in self.edges.iter() {
+            let (s, t) = (edge.source_id, edge.target_id);
+
+            if s == t {
+                self_loops[s] += edge.weight;
+            } else {
+                *weights[s].entry(t).or_default() += edge.weight;
+                *weights[t].entry(s).or_default() += edge.weight;
+            }
+        }
+
+        CommunityGraph::from_weights(n, weights, self_loops)
+            .detect_communities(algorithm, resolution)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use crate::collections::UnionFind;
+use crate::CliResult;
+
+use super::Graph;
+
+impl Graph {
+    // Undirected view of the graph, without self-loops, used to filter nodes
+    fn neighbors(&self) -> Vec<Vec<usize>> {
+        let mut neighbors = vec![Vec::new(); self.nodes.len()];
+
+        for edge in self.edges.iter() {
+            if edge.source_id != edge.target_id {
+                neighbors[edge.source_id].push(edge.target_id);
+                neighbors[edge.target_id].push(edge.source_id);
+            }
+        }
+
+        neighbors
+    }
+
+    // NOTE: since we drop nodes, edges must be remapped to new node ids
+    fn retain_nodes(&mut self, mask: &[bool]) {
+        let mut new_ids: Vec<Option<usize>> = Vec::with_capacity(mask.len());
+        let mut next_id: usize = 0;
+
+        for keep in mask.iter().copied() {
+            if keep {
+                new_ids.push(Some(next_id));
+                next_id += 1;
+            } else {
+                new_ids.push(None);
+            }
+        }
+
+        let mut i: usize = 0;
+
+        self.nodes.retain(|_| {
+            i += 1;
+            mask[i - 1]
+        });
+
+        self.edges.retain_mut(
+            |edge| match (new_ids[edge.source_id], new_ids[edge.target_id]) {
+                (Some(source_id), Some(target_id)) => {
+                    edge.source_id = source_id;
+                    edge.target_id = target_id;
+                    true
+                }
+                _ => false,
+            },
+        );
+    }
+
+    pub fn filter_min_weight(&mut self, min_weight: f64) {
+        self.edges.retain(|edge| edge.weight >= min_weight);
+    }
+
+    // Iteratively drop nodes whose degree is less than k
+    pub fn filter_k_core(&mut self, k: usize) {
+        let neighbors = self.neighbors();
+
+        let mut degrees = neighbors.iter().map(|list| list.len()).collect::<Vec<_>>();
+        let mut mask = vec![true; degrees.len()];
+
+        let mut queue = degrees
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| (*d < k).then_some(i))
+            .collect::<VecDeque<_>>();
+
+        while let Some(node) = queue.pop_front() {
+            if !mask[node] {
+                continue;
+            }
+
+            mask[node] = false;
+
+            for neighbor in neighbors[node].iter().copied() {
+                if mask[neighbor] {
+                    degrees[neighbor] -= 1;
+
+                    if degrees[neighbor] < k {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        self.retain_nodes(&mask);
+    }
+
+    // Only keep nodes at most `radius` hops away from the seeds, ignoring
+    // edge direction
+    pub fn filter_ego(&mut self, seeds: &[&str], radius: usize) -> CliResult<()> {
+        let neighbors = self.neighbors();
+
+        let mut distances: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        for seed in seeds {
+            match self
+                .nodes
+                .iter()
+                .position(|node| node.key.as_str() == *seed)
+            {
+                None => Err(format!("unknown ego node \"{}\"", seed))?,
+                Some(i) => {
+                    distances[i] = Some(0);
+                    queue.push_back(i);
+                }
+            }
+        }
+
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].unwrap();
+
+            if distance >= radius {
+                continue;
+            }
+
+            for neighbor in neighbors[node].iter().copied() {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = Some(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        self.retain_nodes(&distances.iter().map(Option::is_some).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    pub fn filter_largest_component(&mut self) {
+        let mut sets = UnionFind::new();
+
+        for _ in self.nodes.iter() {
+            sets.make_set();
+        }
+
+        for edge in self.edges.iter() {
+            sets.union(edge.source_id, edge.target_id);
+        }
+
+        if let Some(largest_component) = sets.largest() {
+            self.retain_nodes(
+                &(0..self.nodes.len())
+                    .map(|i| sets.find(i) == largest_component)
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 use std::io::Write;
-use std::ops::Not;
+use std::ops::{AddAssign, Not};
 use std::rc::Rc;
 
 use ahash::RandomState;
@@ -8,12 +8,18 @@ use indexmap::{map::Entry as IndexMapEntry, IndexMap};
 use jiff::Zoned;
 use serde_json::Value;
 
-use crate::collections::{HashMap, UnionFind};
 use crate::config::Config;
 use crate::json::{Attributes, JSONType, INTERNER};
 use crate::xml::XMLWriter;
 use crate::CliResult;
 
+mod algorithms;
+mod filters;
+mod projection;
+
+pub use algorithms::CommunityAlgorithm;
+pub use projection::{BipartiteProjection, ProjectionWeight};
+
 fn serialize_value_to_csv(value: &Value) -> Cow<str> {
     match value {
         Value::String(string) => Cow::Borrowed(string),
@@ -33,6 +39,71 @@ impl JSONType {
             Self::Null => "string",
         }
     }
+
+    fn as_graphml_type(&self) -> &str {
+        match self {
+            Self::Float => "double",
+            Self::Integer => "long",
+            Self::String => "string",
+            Self::Null => "string",
+        }
+    }
+}
+
+fn escape_dot_string(string: &str) -> String {
+    format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn serialize_dot_id(id: &str) -> Cow<'_, str> {
+    let is_keyword = ["node", "edge", "graph", "digraph", "subgraph", "strict"]
+        .iter()
+        .any(|keyword| id.eq_ignore_ascii_case(keyword));
+
+    let mut chars = id.chars();
+
+    let is_identifier = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if is_identifier && !is_keyword {
+        Cow::Borrowed(id)
+    } else {
+        Cow::Owned(escape_dot_string(id))
+    }
+}
+
+fn serialize_dot_value(value: &Value) -> String {
+    match value {
+        Value::Number(v) => v.to_string(),
+        _ => escape_dot_string(&serialize_value_to_csv(value)),
+    }
+}
+
+fn write_dot_attributes<W: Write>(
+    writer: &mut W,
+    model: &[ModelAttribute],
+    attributes: &Attributes,
+) -> CliResult<()> {
+    let mut first = true;
+
+    for model_attr in model.iter() {
+        if let Some(value) = attributes.get(model_attr.interner_id) {
+            write!(
+                writer,
+                "{}{}={}",
+                if first { " [" } else { ", " },
+                serialize_dot_id(&model_attr.name),
+                serialize_dot_value(value)
+            )?;
+
+            first = false;
+        }
+    }
+
+    if !first {
+        write!(writer, "]")?;
+    }
+
+    Ok(())
 }
 
 struct GexfNamespace {
@@ -68,6 +139,12 @@ struct Node {
 
 #[derive(Serialize)]
 struct Edge {
+    #[serde(skip)]
+    source_id: usize,
+    #[serde(skip)]
+    target_id: usize,
+    #[serde(skip)]
+    weight: f64,
     source: Rc<String>,
     target: Rc<String>,
     #[serde(skip_serializing_if = "Not::not")]
@@ -111,6 +188,8 @@ pub struct Graph {
     edge_model: Vec<ModelAttribute>,
     nodes: Vec<Node>,
     edges: Vec<Edge>,
+    #[serde(skip_serializing)]
+    weighted: bool,
 }
 
 #[derive(Debug)]
@@ -146,41 +225,99 @@ struct ModelAttribute {
 }
 
 #[derive(Debug)]
-pub enum DegreeMap {
-    Undirected(Vec<usize>),
-    Directed(Vec<(usize, usize)>),
+pub enum NodeColumnValues {
+    Integers(Vec<usize>),
+    Floats(Vec<f64>),
 }
 
-impl DegreeMap {
-    fn new_undirected(capacity: usize) -> Self {
-        Self::Undirected(vec![0; capacity])
+impl NodeColumnValues {
+    fn serialize(&self, i: usize) -> String {
+        match self {
+            Self::Integers(values) => values[i].to_string(),
+            Self::Floats(values) => values[i].to_string(),
+        }
+    }
+}
+
+impl From<Vec<usize>> for NodeColumnValues {
+    fn from(values: Vec<usize>) -> Self {
+        Self::Integers(values)
     }
+}
 
-    fn new_directed(capacity: usize) -> Self {
-        Self::Directed(vec![(0, 0); capacity])
+impl From<Vec<f64>> for NodeColumnValues {
+    fn from(values: Vec<f64>) -> Self {
+        Self::Floats(values)
     }
+}
+
+#[derive(Debug)]
+pub struct NodeColumn {
+    name: String,
+    values: NodeColumnValues,
+}
+
+impl NodeColumn {
+    pub fn new(name: &str, values: impl Into<NodeColumnValues>) -> Self {
+        Self {
+            name: name.to_string(),
+            values: values.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DegreeMap<T> {
+    Undirected(Vec<T>),
+    Directed(Vec<(T, T)>),
+}
 
+impl<T: Copy + Default + AddAssign> DegreeMap<T>
+where
+    NodeColumnValues: From<Vec<T>>,
+{
     fn new(undirected: bool, capacity: usize) -> Self {
         if undirected {
-            Self::new_undirected(capacity)
+            Self::Undirected(vec![T::default(); capacity])
         } else {
-            Self::new_directed(capacity)
+            Self::Directed(vec![(T::default(), T::default()); capacity])
         }
     }
 
-    fn is_undirected(&self) -> bool {
-        matches!(self, Self::Undirected(_))
-    }
-
-    fn add(&mut self, source: usize, target: usize) {
+    fn add(&mut self, source: usize, target: usize, amount: T) {
         match self {
             Self::Undirected(map) => {
-                map[source] += 1;
-                map[target] += 1;
+                map[source] += amount;
+                map[target] += amount;
             }
             Self::Directed(map) => {
-                map[source].1 += 1;
-                map[target].0 += 1;
+                map[source].1 += amount;
+                map[target].0 += amount;
+            }
+        }
+    }
+
+    pub fn into_columns(self, prefix: &str) -> Vec<NodeColumn> {
+        match self {
+            Self::Undirected(map) => vec![NodeColumn::new(&format!("{}degree", prefix), map)],
+            Self::Directed(map) => {
+                let (in_degrees, out_degrees): (Vec<T>, Vec<T>) = map.into_iter().unzip();
+
+                let degrees = in_degrees
+                    .iter()
+                    .zip(out_degrees.iter())
+                    .map(|(i, o)| {
+                        let mut d = *i;
+                        d += *o;
+                        d
+                    })
+                    .collect::<Vec<_>>();
+
+                vec![
+                    NodeColumn::new(&format!("{}degree", prefix), degrees),
+                    NodeColumn::new(&format!("{}in_degree", prefix), in_degrees),
+                    NodeColumn::new(&format!("{}out_degree", prefix), out_degrees),
+                ]
             }
         }
     }
@@ -189,22 +326,16 @@ impl DegreeMap {
 #[derive(Default)]
 pub struct GraphBuilder {
     options: GraphOptions,
-    disjoint_sets: Option<UnionFind>,
     node_model: Vec<ModelAttribute>,
     edge_model: Vec<ModelAttribute>,
     nodes: IndexMap<Rc<String>, Node, RandomState>,
-    edges: HashMap<(usize, usize), Edge>,
+    edges: IndexMap<(usize, usize), Edge, RandomState>,
+    weighted: bool,
 }
 
 impl GraphBuilder {
-    pub fn new(track_largest_component: bool) -> Self {
-        let mut builder = Self::default();
-
-        if track_largest_component {
-            builder.disjoint_sets = Some(UnionFind::new());
-        }
-
-        builder
+    pub fn new() -> Self {
+        Self::default()
     }
 
     fn is_undirected(&self) -> bool {
@@ -215,6 +346,11 @@ impl GraphBuilder {
         self.options.graph_type = GraphType::Undirected;
     }
 
+    // When weighted, aggregated edge weights are exposed as a "weight" edge attribute
+    pub fn mark_as_weighted(&mut self) {
+        self.weighted = true;
+    }
+
     pub fn set_node_model<'a>(
         &mut self,
         headers: impl Iterator<Item = &'a str>,
@@ -254,23 +390,26 @@ impl GraphBuilder {
         let next_id = self.nodes.len();
 
         match self.nodes.entry(rc_key.clone()) {
-            Occupied(entry) => entry.index(),
+            Occupied(mut entry) => {
+                // NOTE: a node may be referenced before its attributes are known
+                if entry.get().attributes.is_empty() {
+                    entry.get_mut().attributes = attributes;
+                }
+
+                entry.index()
+            }
             Vacant(entry) => {
                 entry.insert(Node {
                     key: rc_key,
                     attributes,
                 });
 
-                if let Some(sets) = self.disjoint_sets.as_mut() {
-                    sets.make_set();
-                }
-
                 next_id
             }
         }
     }
 
-    pub fn add_edge(&mut self, source: usize, target: usize, attributes: Attributes) {
+    pub fn add_edge(&mut self, source: usize, target: usize, weight: f64, attributes: Attributes) {
         let undirected = self.is_undirected();
 
         let (source, target) = if source == target {
@@ -286,72 +425,85 @@ impl GraphBuilder {
         let target_node = self.nodes.get_index(target).unwrap().1;
 
         let edge = Edge {
+            source_id: source,
+            target_id: target,
+            weight,
             source: source_node.key.clone(),
             target: target_node.key.clone(),
             undirected,
             attributes,
         };
 
-        if self.edges.insert((source, target), edge).is_some() {
+        // NOTE: parallel edges are merged, last attributes win and weights are summed
+        if let Some(previous_edge) = self.edges.insert((source, target), edge) {
             self.options.multi = true;
+
+            self.edges.get_mut(&(source, target)).unwrap().weight += previous_edge.weight;
+        }
+    }
+
+    pub fn build(mut self) -> Graph {
+        if self.weighted {
+            let weight_id =
+                INTERNER.with_borrow_mut(|interner| interner.register("weight".to_string()));
+            let mut all_integers = true;
+
+            for edge in self.edges.values_mut() {
+                let value = if edge.weight.fract() == 0.0 && edge.weight.abs() < 2f64.powi(53) {
+                    Value::from(edge.weight as i64)
+                } else {
+                    all_integers = false;
+                    Value::from(edge.weight)
+                };
+
+                edge.attributes.insert("weight", value);
+            }
+
+            self.edge_model.push(ModelAttribute {
+                interner_id: weight_id,
+                name: "weight".to_string(),
+                json_type: if all_integers {
+                    JSONType::Integer
+                } else {
+                    JSONType::Float
+                },
+            });
         }
 
-        if let Some(sets) = self.disjoint_sets.as_mut() {
-            sets.union(source, target);
+        Graph {
+            options: self.options,
+            node_model: self.node_model,
+            edge_model: self.edge_model,
+            nodes: self.nodes.into_values().collect(),
+            edges: self.edges.into_values().collect(),
+            weighted: self.weighted,
         }
     }
+}
 
-    pub fn compute_degrees(&self) -> DegreeMap {
+impl Graph {
+    fn is_undirected(&self) -> bool {
+        matches!(self.options.graph_type, GraphType::Undirected)
+    }
+
+    pub fn compute_degrees(&self) -> DegreeMap<usize> {
         let mut degree_map = DegreeMap::new(self.is_undirected(), self.nodes.len());
 
-        for (source, target) in self.edges.keys().copied() {
-            degree_map.add(source, target);
+        for edge in self.edges.iter() {
+            degree_map.add(edge.source_id, edge.target_id, 1);
         }
 
         degree_map
     }
 
-    pub fn build(self) -> Graph {
-        let (nodes, edges) = if let Some(sets) = self.disjoint_sets {
-            let largest_component = sets.largest();
-
-            (
-                self.nodes
-                    .into_values()
-                    .enumerate()
-                    .filter_map(|(i, node)| {
-                        if matches!(largest_component, Some(c) if c != sets.find(i)) {
-                            None
-                        } else {
-                            Some(node)
-                        }
-                    })
-                    .collect(),
-                self.edges
-                    .into_iter()
-                    .filter_map(|((source_id, _), edge)| {
-                        if matches!(largest_component, Some(c) if c != sets.find(source_id)) {
-                            None
-                        } else {
-                            Some(edge)
-                        }
-                    })
-                    .collect(),
-            )
-        } else {
-            (
-                self.nodes.into_values().collect(),
-                self.edges.into_values().collect(),
-            )
-        };
+    pub fn compute_weighted_degrees(&self) -> DegreeMap<f64> {
+        let mut degree_map = DegreeMap::new(self.is_undirected(), self.nodes.len());
 
-        Graph {
-            options: self.options,
-            node_model: self.node_model,
-            edge_model: self.edge_model,
-            nodes,
-            edges,
+        for edge in self.edges.iter() {
+            degree_map.add(edge.source_id, edge.target_id, edge.weight);
         }
+
+        degree_map
     }
 }
 
@@ -533,11 +685,7 @@ impl Graph {
         Ok(())
     }
 
-    pub fn write_csv_nodelist<W: Write>(
-        &self,
-        writer: W,
-        degree_map: Option<DegreeMap>,
-    ) -> CliResult<()> {
+    pub fn write_csv_nodelist<W: Write>(&self, writer: W, columns: &[NodeColumn]) -> CliResult<()> {
         let mut writer = Config::new(&None).csv_writer_from_writer(writer);
 
         let mut record = csv::ByteRecord::new();
@@ -547,13 +695,8 @@ impl Graph {
             record.push_field(attr.name.as_bytes());
         }
 
-        if let Some(map) = &degree_map {
-            record.push_field(b"degree");
-
-            if !map.is_undirected() {
-                record.push_field(b"in_degree");
-                record.push_field(b"out_degree");
-            }
+        for column in columns.iter() {
+            record.push_field(column.name.as_bytes());
         }
 
         writer.write_byte_record(&record)?;
@@ -572,18 +715,42 @@ impl Graph {
                 }
             }
 
-            if let Some(map) = &degree_map {
-                match map {
-                    DegreeMap::Directed(degrees) => {
-                        let degree = degrees[i];
-                        record.push_field((degree.0 + degree.1).to_string().as_bytes());
-                        record.push_field(degree.0.to_string().as_bytes());
-                        record.push_field(degree.1.to_string().as_bytes());
-                    }
-                    DegreeMap::Undirected(degrees) => {
-                        record.push_field(degrees[i].to_string().as_bytes());
-                    }
-                }
+            for column in columns.iter() {
+                record.push_field(column.values.serialize(i).as_bytes());
+            }
+
+            writer.write_byte_record(&record)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_csv_edgelist<W: Write>(&self, writer: W) -> CliResult<()> {
+        let mut writer = Config::new(&None).csv_writer_from_writer(writer);
+
+        let mut record = csv::ByteRecord::new();
+        record.push_field(b"source");
+        record.push_field(b"target");
+
+        for attr in self.edge_model.iter() {
+            record.push_field(attr.name.as_bytes());
+        }
+
+        writer.write_byte_record(&record)?;
+
+        for edge in self.edges.iter() {
+            record.clear();
+            record.push_field(edge.source.as_bytes());
+            record.push_field(edge.target.as_bytes());
+
+            for attr in self.edge_model.iter() {
+                record.push_field(
+                    edge.attributes
+                        .get(attr.interner_id)
+                        .map(serialize_value_to_csv)
+                        .unwrap_or_default()
+                        .as_bytes(),
+                );
             }
 
             writer.write_byte_record(&record)?;
@@ -591,4 +758,165 @@ impl Graph {
 
         Ok(())
     }
+
+    pub fn write_graphml<W: Write>(&self, writer: W) -> CliResult<()> {
+        let mut xml_writer = XMLWriter::new(writer);
+
+        xml_writer.write_declaration()?;
+
+        xml_writer.open(
+            "graphml",
+            [
+                ("xmlns", "http://graphml.graphdrawing.org/xmlns"),
+                ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+                (
+                    "xsi:schemaLocation",
+                    "http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd",
+                ),
+            ],
+        )?;
+
+        // Keys
+        for (prefix, class, model) in [
+            ("n", "node", &self.node_model),
+            ("e", "edge", &self.edge_model),
+        ] {
+            for (i, model_attr) in model.iter().enumerate() {
+                xml_writer.open_empty(
+                    "key",
+                    [
+                        ("id", format!("{}{}", prefix, i).as_str()),
+                        ("for", class),
+                        ("attr.name", model_attr.name.as_str()),
+                        ("attr.type", model_attr.json_type.as_graphml_type()),
+                    ],
+                )?;
+            }
+        }
+
+        xml_writer.open(
+            "graph",
+            [
+                ("id", "G"),
+                ("edgedefault", self.options.graph_type.as_str()),
+            ],
+        )?;
+
+        fn write_data<W: Write>(
+            xml_writer: &mut XMLWriter<W>,
+            prefix: &str,
+            model: &[ModelAttribute],
+            attributes: &Attributes,
+        ) -> CliResult<()> {
+            for (i, model_attr) in model.iter().enumerate() {
+                if let Some(value) = attributes.get(model_attr.interner_id) {
+                    xml_writer.open("data", [("key", format!("{}{}", prefix, i).as_str())])?;
+                    xml_writer.write_text(&serialize_value_to_csv(value))?;
+                    xml_writer.close("data")?;
+                }
+            }
+
+            Ok(())
+        }
+
+        // Node data
+        for node in self.nodes.iter() {
+            if node.attributes.is_empty() {
+                xml_writer.open_empty("node", [("id", node.key.as_str())])?;
+            } else {
+                xml_writer.open("node", [("id", node.key.as_str())])?;
+                write_data(&mut xml_writer, "n", &self.node_model, &node.attributes)?;
+                xml_writer.close("node")?;
+            }
+        }
+
+        // Edge data
+        for edge in self.edges.iter() {
+            let attributes = [
+                ("source", edge.source.as_str()),
+                ("target", edge.target.as_str()),
+            ];
+
+            if edge.attributes.is_empty() {
+                xml_writer.open_empty("edge", attributes)?;
+            } else {
+                xml_writer.open("edge", attributes)?;
+                write_data(&mut xml_writer, "e", &self.edge_model, &edge.attributes)?;
+                xml_writer.close("edge")?;
+            }
+        }
+
+        xml_writer.close("graph")?;
+        xml_writer.close("graphml")?;
+        xml_writer.finish()?;
+
+        Ok(())
+    }
+
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> CliResult<()> {
+        let (graph_keyword, edge_operator) = if self.is_undirected() {
+            ("graph", "--")
+        } else {
+            ("digraph", "->")
+        };
+
+        writeln!(&mut writer, "{} {{", graph_keyword)?;
+
+        for node in self.nodes.iter() {
+            write!(&mut writer, "  {}", escape_dot_string(&node.key))?;
+            write_dot_attributes(&mut writer, &self.node_model, &node.attributes)?;
+            writeln!(&mut writer, ";")?;
+        }
+
+        for edge in self.edges.iter() {
+            write!(
+                &mut writer,
+                "  {} {} {}",
+                escape_dot_string(&edge.source),
+                edge_operator,
+                escape_dot_string(&edge.target)
+            )?;
+            write_dot_attributes(&mut writer, &self.edge_model, &edge.attributes)?;
+            writeln!(&mut writer, ";")?;
+        }
+
+        writeln!(&mut writer, "}}")?;
+
+        Ok(())
+    }
+
+    // NOTE: Pajek has no way to escape double quotes in labels, so we replace them
+    pub fn write_pajek<W: Write>(&self, mut writer: W) -> CliResult<()> {
+        writeln!(&mut writer, "*Vertices {}", self.nodes.len())?;
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            writeln!(&mut writer, "{} \"{}\"", i + 1, node.key.replace('"', "'"))?;
+        }
+
+        writeln!(
+            &mut writer,
+            "{}",
+            if self.is_undirected() {
+                "*Edges"
+            } else {
+                "*Arcs"
+            }
+        )?;
+
+        for edge in self.edges.iter() {
+            if self.weighted {
+                writeln!(
+                    &mut writer,
+                    "{} {} {}",
+                    edge.source_id + 1,
+                    edge.target_id + 1,
+                    edge.weight
+                )?;
+            } else {
+                writeln!(&mut writer, "{} {}", edge.source_id + 1, edge.target_id + 1)?;
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,104 @@
+use ahash::RandomState;
+use indexmap::{IndexMap, IndexSet};
+
+use crate::json::Attributes;
+
+use super::GraphBuilder;
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ProjectionWeight {
+    Count,
+    Jaccard,
+    Newman,
+}
+
+impl TryFrom<String> for ProjectionWeight {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "count" => Self::Count,
+            "jaccard" => Self::Jaccard,
+            "newman" => Self::Newman,
+            _ => return Err(format!("unknown projection weight \"{}\"", &value)),
+        })
+    }
+}
+
+// One-mode projection of a bipartite graph, where nodes of the projected
+// part are linked when they share neighbors in the other part.
+#[derive(Default)]
+pub struct BipartiteProjection {
+    nodes: IndexSet<String, RandomState>,
+    neighborhoods: IndexMap<String, Vec<usize>, RandomState>,
+}
+
+impl BipartiteProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_edge(&mut self, node: &str, other: &str) {
+        let node_id = match self.nodes.get_index_of(node) {
+            Some(i) => i,
+            None => self.nodes.insert_full(node.to_string()).0,
+        };
+
+        match self.neighborhoods.get_mut(other) {
+            Some(neighborhood) => neighborhood.push(node_id),
+            None => {
+                self.neighborhoods.insert(other.to_string(), vec![node_id]);
+            }
+        }
+    }
+
+    pub fn into_graph_builder(self, weight: ProjectionWeight) -> GraphBuilder {
+        let mut builder = GraphBuilder::new();
+
+        builder.mark_as_undirected();
+        builder.mark_as_weighted();
+
+        for node in self.nodes {
+            builder.add_node(node, Attributes::default());
+        }
+
+        let mut degrees = vec![0usize; builder.nodes.len()];
+        let mut pairs: IndexMap<(usize, usize), f64, RandomState> = IndexMap::default();
+
+        for mut neighborhood in self.neighborhoods.into_values() {
+            neighborhood.sort_unstable();
+            neighborhood.dedup();
+
+            let amount = match weight {
+                ProjectionWeight::Newman if neighborhood.len() > 1 => {
+                    1.0 / (neighborhood.len() - 1) as f64
+                }
+                _ => 1.0,
+            };
+
+            for (i, source) in neighborhood.iter().enumerate() {
+                degrees[*source] += 1;
+
+                for target in neighborhood[i + 1..].iter() {
+                    *pairs.entry((*source, *target)).or_default() += amount;
+                }
+            }
+        }
+
+        pairs.sort_unstable_keys();
+
+        for ((source, target), shared) in pairs {
+            let edge_weight = match weight {
+                ProjectionWeight::Jaccard => {
+                    shared / ((degrees[source] + degrees[target]) as f64 - shared)
+                }
+                _ => shared,
+            };
+
+            builder.add_edge(source, target, edge_weight, Attributes::default());
+        }
+
+        builder
+    }
+}
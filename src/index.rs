@@ -0,0 +1,166 @@
+// Sidecar `.xidx` files recording the byte offset of every row of a CSV file,
+// so that we can seek to any row, count rows & segment files in constant time.
+//
+// The file is made of a 32 bytes header:
+//   - the "XANIDX01" magic bytes
+//   - the indexed file len, as a little-endian u64
+//   - the indexed file modification time, as a little-endian u64 of nanoseconds
+//     since the Unix epoch
+//   - the number of indexed rows, as a little-endian u64
+//
+// followed by the little-endian u64 byte offset of each row (including the header
+// row, if any), and a last offset marking the end of the last row.
+use std::fs;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use colored::Colorize;
+
+const MAGIC: &[u8; 8] = b"XANIDX01";
+const HEADER_LEN: u64 = 32;
+
+pub fn index_path_for(path: &Path) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().into_owned() + ".xidx")
+}
+
+fn file_fingerprint(path: &Path) -> io::Result<(u64, u64)> {
+    let metadata = fs::metadata(path)?;
+
+    let mtime = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+
+    Ok((metadata.len(), mtime))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub struct RecordIndexWriter<W: Write + Seek> {
+    writer: BufWriter<W>,
+    file_len: u64,
+    mtime: u64,
+    rows: u64,
+}
+
+impl<W: Write + Seek> RecordIndexWriter<W> {
+    pub fn new(writer: W, indexed_path: &Path) -> io::Result<Self> {
+        let (file_len, mtime) = file_fingerprint(indexed_path)?;
+
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&[0u8; HEADER_LEN as usize])?;
+
+        Ok(Self {
+            writer,
+            file_len,
+            mtime,
+            rows: 0,
+        })
+    }
+
+    pub fn add_row(&mut self, offset: u64) -> io::Result<()> {
+        self.rows += 1;
+        self.writer.write_all(&offset.to_le_bytes())
+    }
+
+    pub fn finish(mut self, end_offset: u64) -> io::Result<u64> {
+        self.writer.write_all(&end_offset.to_le_bytes())?;
+
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.write_all(&self.file_len.to_le_bytes())?;
+        self.writer.write_all(&self.mtime.to_le_bytes())?;
+        self.writer.write_all(&self.rows.to_le_bytes())?;
+        self.writer.flush()?;
+
+        Ok(self.rows)
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordIndex {
+    file: fs::File,
+    rows: u64,
+    skip: u64,
+}
+
+impl RecordIndex {
+    // NOTE: returns None if the index does not exist, and also if it is stale,
+    // in which case a warning is printed on stderr.
+    pub fn open(indexed_path: &Path, no_headers: bool) -> io::Result<Option<Self>> {
+        let index_path = index_path_for(indexed_path);
+
+        if !index_path.is_file() {
+            return Ok(None);
+        }
+
+        let mut file = fs::File::open(&index_path)?;
+        let mut magic = [0u8; 8];
+
+        if file.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a valid index file!", index_path.display()),
+            ));
+        }
+
+        let file_len = read_u64(&mut file)?;
+        let mtime = read_u64(&mut file)?;
+        let rows = read_u64(&mut file)?;
+
+        if file_fingerprint(indexed_path)? != (file_len, mtime) {
+            eprintln!(
+                "{}",
+                format!(
+                    "Ignoring stale index {}. Run `xan index` again to refresh it.",
+                    index_path.display()
+                )
+                .yellow()
+            );
+
+            return Ok(None);
+        }
+
+        let skip = if no_headers { 0 } else { rows.min(1) };
+
+        Ok(Some(Self { file, rows, skip }))
+    }
+
+    // Number of records, not counting the header row if any.
+    pub fn count(&self) -> u64 {
+        self.rows - self.skip
+    }
+
+    // Byte offset of the record at given index. Asking for the offset of
+    // index `count()` will return the byte offset of the end of the last record.
+    pub fn record_offset(&mut self, index: u64) -> io::Result<u64> {
+        let index = index.min(self.count()) + self.skip;
+
+        self.file.seek(SeekFrom::Start(HEADER_LEN + index * 8))?;
+        read_u64(&mut self.file)
+    }
+
+    // Segment the file into at most `chunks` byte ranges containing the same
+    // number of records.
+    pub fn segments(&mut self, chunks: usize) -> io::Result<Vec<(u64, u64)>> {
+        let count = self.count();
+        let chunks = (chunks as u64).min(count).max(1);
+
+        let mut segments = Vec::with_capacity(chunks as usize);
+
+        for i in 0..chunks {
+            segments.push((
+                self.record_offset(i * count / chunks)?,
+                self.record_offset((i + 1) * count / chunks)?,
+            ));
+        }
+
+        Ok(segments)
+    }
+}
@@ -15,12 +15,17 @@ mod collections;
 mod config;
 mod dates;
 mod graph;
+mod index;
 mod json;
 mod moonblade;
 mod ratatui;
 mod read;
 mod scales;
+mod schema;
 mod select;
+mod sql;
+mod svg;
+mod tui;
 mod urls;
 mod util;
 mod xml;
@@ -48,6 +53,7 @@ macro_rules! command_list {
 ## Search & filter
     search      Search for (or replace) patterns in CSV data
     filter      Only keep some CSV rows based on an evaluated expression
+    sql         Query CSV files using SQL
     head        First rows of CSV file
     tail        Last rows of CSV file
     slice       Slice rows of CSV file
@@ -63,15 +69,19 @@ macro_rules! command_list {
     frequency (freq) Show frequency tables
     groupby          Aggregate data by groups of a CSV file
     stats            Compute basic statistics
+    schema           Infer or check the schema of CSV data
     agg              Aggregate data from CSV file
     bins             Dispatch numeric columns into bins
     window           Compute window aggregations (cumsum, rolling mean, lag etc.)
+    resample         Aggregate data over regular time intervals
 
 ## Combine multiple CSV files
     cat         Concatenate by row or column
     join        Join CSV files
     fuzzy-join  Join a CSV file with another containing patterns (e.g. regexes)
     merge       Merge multiple similar already sorted CSV files
+    diff        Compare two versions of a CSV file
+    patch       Apply a diff to a CSV file
 
 ## Add, transform, drop and move columns
     select      Select columns from a CSV file
@@ -87,6 +97,7 @@ macro_rules! command_list {
     behead        Drop header from CSV file
     rename        Rename columns of a CSV file
     input         Read unusually formatted CSV data
+    index         Index rows of a CSV file for random access
     fixlengths    Makes all rows have same length
     fmt           Format CSV output (change field delimiter)
     explode       Explode rows based on some column separator
@@ -257,6 +268,7 @@ enum Command {
     Completions,
     Count,
     Dedup,
+    Diff,
     Drop,
     Enum,
     Eval,
@@ -282,6 +294,7 @@ enum Command {
     Help,
     Hist,
     Implode,
+    Index,
     Input,
     Join,
     Map,
@@ -291,13 +304,16 @@ enum Command {
     P,
     Parallel,
     Partition,
+    Patch,
     Pivot,
     Plot,
     Progress,
     Range,
     Rename,
+    Resample,
     Reverse,
     Sample,
+    Schema,
     Scrape,
     Search,
     Select,
@@ -305,6 +321,7 @@ enum Command {
     Slice,
     Sort,
     Split,
+    Sql,
     Stats,
     Tail,
     To,
@@ -340,6 +357,7 @@ impl Command {
             Command::Completions => cmd::completions::run(argv),
             Command::Count => cmd::count::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
+            Command::Diff => cmd::diff::run(argv),
             Command::Drop => cmd::drop::run(argv),
             Command::Enum => cmd::enumerate::run(argv),
             Command::Eval => cmd::eval::run(argv),
@@ -367,6 +385,7 @@ impl Command {
             }
             Command::Hist => cmd::hist::run(argv),
             Command::Implode => cmd::implode::run(argv),
+            Command::Index => cmd::index::run(argv),
             Command::Input => cmd::input::run(argv),
             Command::Join => cmd::join::run(argv),
             Command::Network => cmd::network::run(argv),
@@ -375,13 +394,16 @@ impl Command {
             Command::Merge => cmd::merge::run(argv),
             Command::Parallel | Command::P => cmd::parallel::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Patch => cmd::patch::run(argv),
             Command::Pivot => cmd::pivot::run(argv),
             Command::Plot => cmd::plot::run(argv),
             Command::Progress => cmd::progress::run(argv),
             Command::Range => cmd::range::run(argv),
             Command::Rename => cmd::rename::run(argv),
+            Command::Resample => cmd::resample::run(argv),
             Command::Reverse => cmd::reverse::run(argv),
             Command::Sample => cmd::sample::run(argv),
+            Command::Schema => cmd::schema::run(argv),
             Command::Scrape => cmd::scrape::run(argv),
             Command::Search => cmd::search::run(argv),
             Command::Select => cmd::select::run(argv),
@@ -389,6 +411,7 @@ impl Command {
             Command::Slice => cmd::slice::run(argv),
             Command::Sort => cmd::sort::run(argv),
             Command::Split => cmd::split::run(argv),
+            Command::Sql => cmd::sql::run(argv),
             Command::Stats => cmd::stats::run(argv),
             Command::Tail => cmd::tail::run(argv),
             Command::To => cmd::to::run(argv),
@@ -579,6 +602,42 @@ impl From<bgzip::BGZFError> for CliError {
     }
 }
 
+impl From<arrow::error::ArrowError> for CliError {
+    fn from(value: arrow::error::ArrowError) -> Self {
+        match value {
+            arrow::error::ArrowError::IoError(_, err) => From::from(err),
+            _ => CliError::Other(value.to_string()),
+        }
+    }
+}
+
+impl From<parquet::errors::ParquetError> for CliError {
+    fn from(value: parquet::errors::ParquetError) -> Self {
+        CliError::Other(value.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for CliError {
+    fn from(value: quick_xml::Error) -> Self {
+        CliError::Other(value.to_string())
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for CliError {
+    fn from(value: quick_xml::events::attributes::AttrError) -> Self {
+        CliError::Other(value.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for CliError {
+    fn from(value: zip::result::ZipError) -> Self {
+        match value {
+            zip::result::ZipError::Io(err) => From::from(err),
+            _ => CliError::Other(value.to_string()),
+        }
+    }
+}
+
 impl From<()> for CliError {
     fn from(_: ()) -> CliError {
         CliError::Other("unknown error".to_string())
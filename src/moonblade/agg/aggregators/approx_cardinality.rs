@@ -1,23 +1,25 @@
-use std::collections::hash_map::RandomState;
-
 use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
 
+use super::sketch::{deserialize_sketch, serialize_sketch, SketchHasherBuilder};
+
+const PRECISION: u8 = 16;
+
 #[derive(Debug, Clone)]
 pub struct ApproxCardinality {
-    register: HyperLogLogPlus<String, RandomState>,
+    register: HyperLogLogPlus<String, SketchHasherBuilder>,
     count: Option<usize>,
 }
 
 impl ApproxCardinality {
     pub fn new() -> Self {
         Self {
-            register: HyperLogLogPlus::new(16, RandomState::new()).unwrap(),
+            register: HyperLogLogPlus::new(PRECISION, SketchHasherBuilder).unwrap(),
             count: None,
         }
     }
 
     pub fn clear(&mut self) {
-        self.register = HyperLogLogPlus::new(16, RandomState::new()).unwrap();
+        self.register = HyperLogLogPlus::new(PRECISION, SketchHasherBuilder).unwrap();
         self.count = None;
     }
 
@@ -36,4 +38,26 @@ impl ApproxCardinality {
     pub fn merge(&mut self, other: Self) {
         self.register.merge(&other.register).unwrap();
     }
+
+    pub fn to_sketch(&self) -> String {
+        serialize_sketch("hll", &self.register)
+    }
+
+    // NOTE: merging sketches is also the way we parse them, since it lets us
+    // check they were built using the same precision.
+    pub fn merge_sketch(&mut self, sketch: &str) -> Result<(), String> {
+        let register: HyperLogLogPlus<String, SketchHasherBuilder> =
+            deserialize_sketch("hll", sketch)?;
+
+        self.register
+            .merge(&register)
+            .map_err(|_| "invalid hll sketch".to_string())
+    }
+
+    pub fn from_sketch(sketch: &str) -> Result<Self, String> {
+        let mut approx_cardinality = Self::new();
+        approx_cardinality.merge_sketch(sketch)?;
+
+        Ok(approx_cardinality)
+    }
 }
@@ -1,5 +1,7 @@
 use tdigest::TDigest;
 
+use super::sketch::{deserialize_sketch, serialize_sketch};
+
 const DIGEST_SIZE: usize = 100;
 const BUFFER_SIZE: usize = 512;
 
@@ -61,4 +63,28 @@ impl ApproxQuantiles {
             other.digest.unwrap(),
         ]));
     }
+
+    pub fn to_sketch(&self) -> String {
+        debug_assert!(self.buffer.is_empty());
+
+        serialize_sketch("tdigest", self.digest.as_ref().unwrap())
+    }
+
+    pub fn merge_sketch(&mut self, sketch: &str) -> Result<(), String> {
+        let digest: TDigest = deserialize_sketch("tdigest", sketch)?;
+
+        self.merge(Self {
+            digest: Some(digest),
+            buffer: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    pub fn from_sketch(sketch: &str) -> Result<Self, String> {
+        Ok(Self {
+            digest: Some(deserialize_sketch("tdigest", sketch)?),
+            buffer: Vec::new(),
+        })
+    }
 }
@@ -7,6 +7,7 @@ mod extent;
 mod first_last;
 mod frequencies;
 mod numbers;
+mod sketch;
 mod sum;
 mod types;
 mod values;
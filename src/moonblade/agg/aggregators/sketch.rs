@@ -0,0 +1,56 @@
+use std::hash::BuildHasher;
+use std::io::{Read, Write};
+
+use base64::prelude::*;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use siphasher::sip::SipHasher13;
+
+// NOTE: sketches must hash values in a deterministic fashion so they can be
+// persisted and merged later on, possibly by another process. This is why we
+// cannot rely on a randomly seeded hasher here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SketchHasherBuilder;
+
+impl BuildHasher for SketchHasherBuilder {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        SipHasher13::new()
+    }
+}
+
+// NOTE: sketches are serialized as `<kind>:<base64>` strings so they can be
+// stored in a CSV cell, the payload being a deflated bincode representation.
+pub fn serialize_sketch<T: Serialize>(kind: &str, sketch: &T) -> String {
+    let bytes = bincode::serialize(sketch).expect("could not serialize sketch");
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&bytes).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut string = String::with_capacity(kind.len() + 1 + compressed.len() * 4 / 3 + 4);
+    string.push_str(kind);
+    string.push(':');
+    BASE64_STANDARD.encode_string(compressed, &mut string);
+
+    string
+}
+
+pub fn deserialize_sketch<T: DeserializeOwned>(kind: &str, string: &str) -> Result<T, String> {
+    let error = || format!("invalid {} sketch", kind);
+
+    let payload = string
+        .strip_prefix(kind)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .ok_or_else(error)?;
+
+    let compressed = BASE64_STANDARD.decode(payload).map_err(|_| error())?;
+
+    let mut bytes = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_end(&mut bytes)
+        .map_err(|_| error())?;
+
+    bincode::deserialize(&bytes).map_err(|_| error())
+}
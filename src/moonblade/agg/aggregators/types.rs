@@ -1,3 +1,7 @@
+use crate::dates;
+use crate::moonblade::types::DynamicNumber;
+use crate::util;
+
 const TYPE_EMPTY: u8 = 0;
 const TYPE_STRING: u8 = 1;
 const TYPE_FLOAT: u8 = 2;
@@ -43,6 +47,30 @@ impl Types {
         self.set(TYPE_URL);
     }
 
+    // NOTE: returns the parsed number, if any, so that callers don't have to
+    // parse it again.
+    pub fn sniff(&mut self, cell: &str) -> Option<DynamicNumber> {
+        if let Ok(number) = cell.parse::<DynamicNumber>() {
+            if number.is_float() {
+                self.set_float();
+            } else {
+                self.set_int();
+            }
+
+            return Some(number);
+        }
+
+        if dates::could_be_date(cell) {
+            self.set_date();
+        } else if util::could_be_url(cell) {
+            self.set_url();
+        } else {
+            self.set_string();
+        }
+
+        None
+    }
+
     pub fn has(&self, pos: u8) -> bool {
         ((self.bitset >> pos) & 1) == 1
     }
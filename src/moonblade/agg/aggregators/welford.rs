@@ -49,6 +49,23 @@ impl Welford {
         self.m2 = m2;
     }
 
+    // NOTE: this is the inverse of `add`, useful when values leave a window
+    // whose size is not fixed.
+    pub fn remove(&mut self, value: f64) {
+        if self.count <= 1 {
+            self.clear();
+            return;
+        }
+
+        let count = self.count - 1;
+        let delta = value - self.mean;
+        let mean = self.mean - delta / count as f64;
+
+        self.m2 = (self.m2 - delta * (value - mean)).max(0.0);
+        self.mean = mean;
+        self.count = count;
+    }
+
     pub fn mean(&self) -> Option<f64> {
         if self.count == 0 {
             return None;
@@ -5,8 +5,9 @@ mod window;
 
 pub use aggregators::{CovarianceWelford, Welford};
 pub use program::{
-    AggregationProgram, GroupAggregationProgram, GroupAlongColumnsAggregationProgram,
-    PivotAggregationProgram,
+    is_aggregation_function, AggregationProgram, GroupAggregationProgram,
+    GroupAlongColumnsAggregationProgram, PivotAggregationProgram, PivotColumnOrder,
+    PivotOutputOptions,
 };
 pub use stats::Stats;
 pub use window::WindowAggregationProgram;
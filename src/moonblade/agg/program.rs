@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
 use std::iter::once;
 use std::sync::Arc;
 
@@ -36,6 +36,8 @@ enum Aggregator {
     Values(Values),
     LexicographicExtent(LexicographicExtent),
     Frequencies(Box<Frequencies>),
+    MergedApproxCardinality(Box<ApproxCardinality>),
+    MergedApproxQuantiles(Box<ApproxQuantiles>),
     Numbers(Numbers),
     RMSWelford(RMSWelford),
     Sum(Sum),
@@ -62,6 +64,8 @@ impl Aggregator {
             Values(inner) => inner.clear(),
             LexicographicExtent(inner) => inner.clear(),
             Frequencies(inner) => inner.clear(),
+            MergedApproxCardinality(inner) => inner.clear(),
+            MergedApproxQuantiles(inner) => inner.clear(),
             Numbers(inner) => inner.clear(),
             RMSWelford(inner) => inner.clear(),
             Sum(inner) => inner.clear(),
@@ -90,6 +94,12 @@ impl Aggregator {
                 inner.merge(other_inner)
             }
             (Frequencies(inner), Frequencies(other_inner)) => inner.merge(*other_inner),
+            (MergedApproxCardinality(inner), MergedApproxCardinality(other_inner)) => {
+                inner.merge(*other_inner)
+            }
+            (MergedApproxQuantiles(inner), MergedApproxQuantiles(other_inner)) => {
+                inner.merge(*other_inner)
+            }
             (Numbers(inner), Numbers(other_inner)) => inner.merge(other_inner),
             (RMSWelford(inner), RMSWelford(other_inner)) => inner.merge(other_inner),
             (Sum(inner), Sum(other_inner)) => inner.merge(other_inner),
@@ -102,10 +112,10 @@ impl Aggregator {
 
     fn finalize(&mut self, parallel: bool) {
         match self {
-            Self::ApproxCardinality(inner) => {
+            Self::ApproxCardinality(inner) | Self::MergedApproxCardinality(inner) => {
                 inner.finalize();
             }
-            Self::ApproxQuantiles(inner) => {
+            Self::ApproxQuantiles(inner) | Self::MergedApproxQuantiles(inner) => {
                 inner.finalize();
             }
             Self::Numbers(inner) => {
@@ -135,6 +145,14 @@ impl Aggregator {
             (ConcreteAggregationMethod::ApproxQuantile(q), Self::ApproxQuantiles(inner)) => {
                 DynamicValue::from(inner.get(*q))
             }
+            (ConcreteAggregationMethod::HllSketch, Self::ApproxCardinality(inner))
+            | (ConcreteAggregationMethod::HllMerge, Self::MergedApproxCardinality(inner)) => {
+                DynamicValue::from(inner.to_sketch())
+            }
+            (ConcreteAggregationMethod::TDigestSketch, Self::ApproxQuantiles(inner))
+            | (ConcreteAggregationMethod::TDigestMerge, Self::MergedApproxQuantiles(inner)) => {
+                DynamicValue::from(inner.to_sketch())
+            }
             (ConcreteAggregationMethod::ArgTop(_, expr_opt, separator), Self::ArgTop(inner)) => {
                 DynamicValue::from(match expr_opt {
                     None => inner
@@ -424,10 +442,11 @@ impl CompositeAggregator {
             ConcreteAggregationMethod::All | ConcreteAggregationMethod::Any => {
                 upsert_aggregator!(AllAny)
             }
-            ConcreteAggregationMethod::ApproxCardinality => {
+            ConcreteAggregationMethod::ApproxCardinality | ConcreteAggregationMethod::HllSketch => {
                 upsert_boxed_aggregator!(ApproxCardinality)
             }
-            ConcreteAggregationMethod::ApproxQuantile(_) => {
+            ConcreteAggregationMethod::ApproxQuantile(_)
+            | ConcreteAggregationMethod::TDigestSketch => {
                 upsert_boxed_aggregator!(ApproxQuantiles)
             }
             ConcreteAggregationMethod::Count
@@ -491,6 +510,40 @@ impl CompositeAggregator {
             | ConcreteAggregationMethod::CountTime(_) => {
                 upsert_boxed_aggregator!(ZonedExtent)
             }
+            ConcreteAggregationMethod::HllMerge => {
+                match self
+                    .methods
+                    .iter()
+                    .position(|item| matches!(item, Aggregator::MergedApproxCardinality(_)))
+                {
+                    Some(idx) => idx,
+                    None => {
+                        let idx = self.methods.len();
+                        self.methods
+                            .push(Aggregator::MergedApproxCardinality(Box::new(
+                                ApproxCardinality::new(),
+                            )));
+                        idx
+                    }
+                }
+            }
+            ConcreteAggregationMethod::TDigestMerge => {
+                match self
+                    .methods
+                    .iter()
+                    .position(|item| matches!(item, Aggregator::MergedApproxQuantiles(_)))
+                {
+                    Some(idx) => idx,
+                    None => {
+                        let idx = self.methods.len();
+                        self.methods
+                            .push(Aggregator::MergedApproxQuantiles(Box::new(
+                                ApproxQuantiles::new(),
+                            )));
+                        idx
+                    }
+                }
+            }
             ConcreteAggregationMethod::Median(_)
             | ConcreteAggregationMethod::Quantile(_)
             | ConcreteAggregationMethod::Quartile(_) => {
@@ -592,6 +645,20 @@ impl CompositeAggregator {
                             frequencies.add(value.try_as_str()?.into_owned());
                         }
                     }
+                    Aggregator::MergedApproxCardinality(approx_cardinality) => {
+                        if !value.is_nullish() {
+                            approx_cardinality
+                                .merge_sketch(&value.try_as_str()?)
+                                .map_err(EvaluationError::Custom)?;
+                        }
+                    }
+                    Aggregator::MergedApproxQuantiles(approx_quantiles) => {
+                        if !value.is_nullish() {
+                            approx_quantiles
+                                .merge_sketch(&value.try_as_str()?)
+                                .map_err(EvaluationError::Custom)?;
+                        }
+                    }
                     Aggregator::Numbers(numbers) => {
                         if !value.is_nullish() {
                             numbers.add(value.try_as_number()?);
@@ -758,6 +825,8 @@ fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, Argum
         }),
         "earliest" => (FunctionArguments::unary(), |_| Ok(Earliest)),
         "first" => (FunctionArguments::unary(), |_| Ok(First)),
+        "hll_merge" => (FunctionArguments::unary(), |_| Ok(HllMerge)),
+        "hll_sketch" => (FunctionArguments::unary(), |_| Ok(HllSketch)),
         "latest" => (FunctionArguments::unary(), |_| Ok(Latest)),
         "last" => (FunctionArguments::unary(), |_| Ok(Last)),
         "lex_first" => (FunctionArguments::unary(), |_| Ok(LexFirst)),
@@ -826,6 +895,8 @@ fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, Argum
         "stddev" | "stddev_pop" => (FunctionArguments::unary(), |_| Ok(StddevPop)),
         "stddev_sample" => (FunctionArguments::unary(), |_| Ok(StddevSample)),
         "sum" => (FunctionArguments::unary(), |_| Ok(Sum)),
+        "tdigest_merge" => (FunctionArguments::unary(), |_| Ok(TDigestMerge)),
+        "tdigest_sketch" => (FunctionArguments::unary(), |_| Ok(TDigestSketch)),
         "top" => (FunctionArguments::with_range(1..=3), |args| {
             Ok(Top(
                 cast_as_static_value(args.first().unwrap(), DynamicValue::try_as_usize)?,
@@ -856,6 +927,8 @@ enum ConcreteAggregationMethod {
     DistinctValues(String),
     Earliest,
     First,
+    HllMerge,
+    HllSketch,
     Latest,
     Last,
     LexFirst,
@@ -874,6 +947,8 @@ enum ConcreteAggregationMethod {
     Ratio(Option<usize>),
     Rms,
     Sum,
+    TDigestMerge,
+    TDigestSketch,
     Values(String),
     VarPop,
     VarSample,
@@ -1163,6 +1238,10 @@ fn run_with_record_on_aggregators<'a>(
     Ok(())
 }
 
+pub fn is_aggregation_function(name: &str) -> bool {
+    get_function_arguments_parser(name).is_some()
+}
+
 #[derive(Debug, Clone)]
 pub struct AggregationProgram {
     aggregators: Vec<CompositeAggregator>,
@@ -1174,7 +1253,20 @@ pub struct AggregationProgram {
 
 impl AggregationProgram {
     pub fn parse(code: &str, headers: &ByteRecord) -> Result<Self, ConcretizationError> {
-        let concrete_aggregations = prepare(code, headers)?;
+        Self::from_concrete_aggregations(prepare(code, headers)?, headers)
+    }
+
+    pub fn from_aggregations(
+        aggregations: Aggregations,
+        headers: &ByteRecord,
+    ) -> Result<Self, ConcretizationError> {
+        Self::from_concrete_aggregations(concretize_aggregations(aggregations, headers)?, headers)
+    }
+
+    fn from_concrete_aggregations(
+        concrete_aggregations: ConcreteAggregations,
+        headers: &ByteRecord,
+    ) -> Result<Self, ConcretizationError> {
         let len = concrete_aggregations.len();
         let planner = ConcreteAggregationPlanner::from(concrete_aggregations);
         let aggregators = planner.instantiate_aggregators();
@@ -1349,37 +1441,92 @@ impl GroupAggregationProgram {
 #[derive(Debug, Clone, Default)]
 struct PivotedColumnNamesIndex {
     names: Vec<Vec<u8>>,
-    seen: BTreeSet<Vec<u8>>,
+    ids: BTreeMap<Vec<u8>, usize>,
 }
 
 impl PivotedColumnNamesIndex {
-    fn add(&mut self, name: &[u8]) {
-        if !self.seen.contains(name) {
-            self.seen.insert(name.to_vec());
-            self.names.push(name.to_vec());
+    fn add(&mut self, name: &[u8]) -> usize {
+        match self.ids.get(name) {
+            Some(id) => *id,
+            None => {
+                let id = self.names.len();
+                self.ids.insert(name.to_vec(), id);
+                self.names.push(name.to_vec());
+                id
+            }
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PivotColumnOrder {
+    #[default]
+    FirstSeen,
+    Name,
+    Numeric,
+    Total,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PivotOutputOptions {
+    pub fill: Vec<u8>,
+    pub column_order: PivotColumnOrder,
+    pub margins_name: Option<Vec<u8>>,
+}
+
+type PivotCells = Vec<Option<Vec<CompositeAggregator>>>;
+
+fn get_pivot_cell<'a>(
+    cells: &'a mut PivotCells,
+    id: usize,
+    planner: &ConcreteAggregationPlanner,
+) -> &'a mut Vec<CompositeAggregator> {
+    if cells.len() <= id {
+        cells.resize_with(id + 1, || None);
+    }
+
+    cells[id].get_or_insert_with(|| planner.instantiate_aggregators())
+}
+
+fn finalize_pivot_cell(
+    planner: &ConcreteAggregationPlanner,
+    aggregators: &mut [CompositeAggregator],
+    headers_index: &HeadersIndex,
+) -> Result<Vec<Vec<u8>>, SpecifiedEvaluationError> {
+    for aggregator in aggregators.iter_mut() {
+        aggregator.finalize(false);
+    }
+
+    planner
+        .results(aggregators, headers_index)
+        .map(|value| value.map(|v| v.serialize_as_bytes().into_owned()))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+struct PivotGroup {
+    cells: PivotCells,
+    total: Option<Vec<CompositeAggregator>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PivotMargins {
+    cells: PivotCells,
+    total: Option<Vec<CompositeAggregator>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct PivotAggregationProgram {
     planner: ConcreteAggregationPlanner,
-    groups: IndexMap<GroupKey, BTreeMap<Vec<u8>, CompositeAggregator>, RandomState>,
+    groups: IndexMap<GroupKey, PivotGroup, RandomState>,
     headers_index: HeadersIndex,
     pivoted_column_names_index: PivotedColumnNamesIndex,
+    margins: Option<PivotMargins>,
 }
 
 impl PivotAggregationProgram {
     pub fn parse(code: &str, headers: &ByteRecord) -> Result<Self, ConcretizationError> {
         let concrete_aggregations = prepare(code, headers)?;
-
-        if concrete_aggregations.len() != 1 {
-            return Err(ConcretizationError::Custom(format!(
-                "expected a single aggregation clause, but got {}",
-                concrete_aggregations.len()
-            )));
-        }
-
         let planner = ConcreteAggregationPlanner::from(concrete_aggregations);
 
         Ok(Self {
@@ -1387,9 +1534,21 @@ impl PivotAggregationProgram {
             groups: IndexMap::with_hasher(RandomState::new()),
             headers_index: HeadersIndex::from_headers(headers),
             pivoted_column_names_index: PivotedColumnNamesIndex::default(),
+            margins: None,
         })
     }
 
+    // NOTE: margins are aggregated over the relevant rows, and not computed
+    // from the pivoted cells, so that non-additive aggregations remain correct.
+    pub fn compute_margins(&mut self) {
+        self.margins = Some(PivotMargins::default());
+    }
+
+    // NOTE: this can be used to register pivoted column names beforehand.
+    pub fn add_pivoted_column_name(&mut self, name: &[u8]) {
+        self.pivoted_column_names_index.add(name);
+    }
+
     pub fn used_column_indices(&self) -> Vec<usize> {
         let mut indices = Vec::new();
 
@@ -1410,61 +1569,225 @@ impl PivotAggregationProgram {
         index: usize,
         record: &ByteRecord,
     ) -> Result<(), SpecifiedEvaluationError> {
-        self.pivoted_column_names_index.add(&pivot);
+        let id = self.pivoted_column_names_index.add(&pivot);
 
         let planner = &self.planner;
 
-        let pivot_map = self.groups.entry(group).or_default();
-
-        let aggregator = pivot_map
-            .entry(pivot)
-            .or_insert_with(|| planner.instantiate_single_aggregator());
+        let pivot_group = self.groups.entry(group).or_default();
 
         run_with_record_on_aggregators(
             planner,
-            once(aggregator),
+            get_pivot_cell(&mut pivot_group.cells, id, planner).iter_mut(),
             index,
             record,
             &self.headers_index,
             None,
-        )
+        )?;
+
+        if let Some(margins) = self.margins.as_mut() {
+            let targets = [
+                pivot_group
+                    .total
+                    .get_or_insert_with(|| planner.instantiate_aggregators()),
+                get_pivot_cell(&mut margins.cells, id, planner),
+                margins
+                    .total
+                    .get_or_insert_with(|| planner.instantiate_aggregators()),
+            ];
+
+            for aggregators in targets {
+                run_with_record_on_aggregators(
+                    planner,
+                    aggregators.iter_mut(),
+                    index,
+                    record,
+                    &self.headers_index,
+                    None,
+                )?;
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn pivoted_column_names(&self) -> &[Vec<u8>] {
-        &self.pivoted_column_names_index.names
+    fn column_order(
+        &self,
+        order: PivotColumnOrder,
+        column_totals: Option<&[Option<Vec<Vec<u8>>>]>,
+    ) -> Vec<usize> {
+        let names = &self.pivoted_column_names_index.names;
+        let mut ids = (0..names.len()).collect::<Vec<_>>();
+
+        let parse = |bytes: &[u8]| fast_float::parse::<f64, &[u8]>(bytes).ok();
+
+        let cmp_numbers = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+
+        match order {
+            PivotColumnOrder::FirstSeen => (),
+            PivotColumnOrder::Name => ids.sort_by(|a, b| names[*a].cmp(&names[*b])),
+            PivotColumnOrder::Numeric => ids.sort_by(|a, b| {
+                cmp_numbers(parse(&names[*a]), parse(&names[*b]))
+                    .then_with(|| names[*a].cmp(&names[*b]))
+            }),
+            PivotColumnOrder::Total => {
+                let column_totals = column_totals.expect("margins were not computed");
+                let total = |id: usize| {
+                    column_totals
+                        .get(id)
+                        .and_then(|cell| cell.as_ref())
+                        .and_then(|values| parse(&values[0]))
+                        .map(|n| -n)
+                };
+
+                ids.sort_by(|a, b| cmp_numbers(total(*a), total(*b)));
+            }
+        };
+
+        ids
     }
 
-    pub fn flush<F, E>(self, mut callback: F) -> Result<(), E>
+    pub fn flush<F, E>(
+        mut self,
+        group_headers: Option<&ByteRecord>,
+        options: &PivotOutputOptions,
+        mut callback: F,
+    ) -> Result<(), E>
     where
         F: FnMut(&csv::ByteRecord) -> Result<(), E>,
         E: From<SpecifiedEvaluationError>,
     {
+        let planner = &self.planner;
+        let headers_index = &self.headers_index;
+
+        let mut margins = self.margins.take();
+
+        let column_totals = margins
+            .as_mut()
+            .map(|m| {
+                m.cells
+                    .iter_mut()
+                    .map(|cell| {
+                        cell.as_mut()
+                            .map(|aggregators| {
+                                finalize_pivot_cell(planner, aggregators, headers_index)
+                            })
+                            .transpose()
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+
+        let order = self.column_order(options.column_order, column_totals.as_deref());
+
+        let names = &self.pivoted_column_names_index.names;
+        let measures = planner.headers().collect::<Vec<_>>();
+        let show_margins = margins.is_some() && options.margins_name.is_some();
+
         let mut record = csv::ByteRecord::new();
-        let names = self.pivoted_column_names_index.names;
 
-        for (group, mut pivot_map) in self.groups.into_iter() {
+        // Headers
+        if let Some(group_headers) = group_headers {
+            record.extend(group_headers);
+
+            for measure in measures.iter() {
+                let mut push_name = |name: &[u8]| {
+                    if measures.len() == 1 {
+                        record.push_field(name);
+                    } else {
+                        let mut full_name = measure.to_vec();
+                        full_name.push(b'_');
+                        full_name.extend_from_slice(name);
+                        record.push_field(&full_name);
+                    }
+                };
+
+                for id in order.iter() {
+                    push_name(&names[*id]);
+                }
+
+                if show_margins {
+                    push_name(options.margins_name.as_ref().unwrap());
+                }
+            }
+
+            callback(&record)?;
+        }
+
+        let mut write_row = |record: &mut csv::ByteRecord,
+                             cells: &[Option<Vec<Vec<u8>>>],
+                             total: Option<Vec<Vec<u8>>>|
+         -> Result<(), E> {
+            for m in 0..measures.len() {
+                for id in order.iter() {
+                    match cells.get(*id).and_then(|cell| cell.as_ref()) {
+                        Some(values) => record.push_field(&values[m]),
+                        None => record.push_field(&options.fill),
+                    }
+                }
+
+                if show_margins {
+                    match &total {
+                        Some(values) => record.push_field(&values[m]),
+                        None => record.push_field(&options.fill),
+                    }
+                }
+            }
+
+            callback(record)
+        };
+
+        let mut group_width: usize = 0;
+
+        for (group, mut pivot_group) in self.groups.into_iter() {
             record.clear();
+            group_width = group.len();
 
             for cell in group {
                 record.push_field(&cell);
             }
 
-            for name in names.iter() {
-                if let Some(aggregator) = pivot_map.get_mut(name) {
-                    aggregator.finalize(false);
+            let cells = pivot_group
+                .cells
+                .iter_mut()
+                .map(|cell| {
+                    cell.as_mut()
+                        .map(|aggregators| finalize_pivot_cell(planner, aggregators, headers_index))
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-                    for value in self
-                        .planner
-                        .results(std::slice::from_ref(aggregator), &self.headers_index)
-                    {
-                        record.push_field(&value?.serialize_as_bytes());
+            let total = match pivot_group.total.as_mut() {
+                Some(aggregators) if show_margins => {
+                    Some(finalize_pivot_cell(planner, aggregators, headers_index)?)
+                }
+                _ => None,
+            };
+
+            write_row(&mut record, &cells, total)?;
+        }
+
+        // Totals row
+        if let (true, Some(m)) = (show_margins, margins.as_mut()) {
+            if let Some(aggregators) = m.total.as_mut() {
+                record.clear();
+
+                for i in 0..group_width {
+                    if i == 0 {
+                        record.push_field(options.margins_name.as_ref().unwrap());
+                    } else {
+                        record.push_field(b"");
                     }
-                } else {
-                    record.push_field(b"");
                 }
-            }
 
-            callback(&record)?;
+                let total = finalize_pivot_cell(planner, aggregators, headers_index)?;
+
+                write_row(&mut record, column_totals.as_ref().unwrap(), Some(total))?;
+            }
         }
 
         Ok(())
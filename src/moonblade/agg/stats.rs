@@ -4,9 +4,7 @@ use super::aggregators::{
     ApproxCardinality, ApproxQuantiles, Count, Extent, Frequencies, LexicographicExtent, Numbers,
     NumericExtent, Sum, Types, Welford,
 };
-use crate::dates;
 use crate::moonblade::types::DynamicNumber;
-use crate::util;
 
 fn map_to_field<T: ToString>(opt: Option<T>) -> Vec<u8> {
     opt.map(|m| m.to_string().as_bytes().to_vec())
@@ -222,13 +220,7 @@ impl Stats {
 
         let cell = std::str::from_utf8(cell).expect("could not decode as utf-8");
 
-        if let Ok(number) = cell.parse::<DynamicNumber>() {
-            if number.is_float() {
-                self.types.set_float();
-            } else {
-                self.types.set_int();
-            }
-
+        if let Some(number) = self.types.sniff(cell) {
             if !number.is_nan() {
                 let float = number.as_float();
 
@@ -244,12 +236,6 @@ impl Stats {
                     approx_quantiles.add(float);
                 }
             }
-        } else if dates::could_be_date(cell) {
-            self.types.set_date();
-        } else if util::could_be_url(cell) {
-            self.types.set_url();
-        } else {
-            self.types.set_string();
         }
 
         if let Some(frequencies) = self.frequencies.as_mut() {
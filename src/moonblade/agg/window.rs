@@ -1,11 +1,15 @@
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 use csv::ByteRecord;
+use jiff::{SignedDuration, Timestamp};
 
 use super::aggregators::{Sum, Welford};
-use crate::moonblade::error::{ConcretizationError, SpecifiedEvaluationError};
+use super::program::{is_aggregation_function, AggregationProgram};
+use crate::dates::parse_duration;
+use crate::moonblade::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
 use crate::moonblade::interpreter::{concretize_expression, eval_expression, ConcreteExpr};
-use crate::moonblade::parser::parse_aggregations;
+use crate::moonblade::parser::{parse_window_aggregations, FrameBound, WindowFrame};
 use crate::moonblade::types::{DynamicNumber, DynamicValue, FunctionArguments, HeadersIndex};
 
 #[derive(Debug)]
@@ -109,6 +113,282 @@ impl RollingWelford {
     }
 }
 
+// NOTE: time windows contain every row whose time is within the given
+// duration of current row's time, i.e. in the ]t - duration, t] interval.
+// Rows must therefore be sorted by time.
+#[derive(Debug)]
+struct TimeWindow<T> {
+    duration: SignedDuration,
+    buffer: VecDeque<(Timestamp, T)>,
+}
+
+impl<T> TimeWindow<T> {
+    fn with_duration(duration: SignedDuration) -> Self {
+        Self {
+            duration,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    fn push<F>(&mut self, time: Timestamp, value: T, mut evict: F) -> Result<(), EvaluationError>
+    where
+        F: FnMut(T),
+    {
+        if let Some((last_time, _)) = self.buffer.back() {
+            if time < *last_time {
+                return Err(EvaluationError::Custom(format!(
+                    "rows must be sorted by time when using time windows, but {} came after {}",
+                    time, last_time
+                )));
+            }
+        }
+
+        let start = time.saturating_sub(self.duration).unwrap();
+
+        while self
+            .buffer
+            .front()
+            .is_some_and(|(past_time, _)| *past_time <= start)
+        {
+            evict(self.buffer.pop_front().unwrap().1);
+        }
+
+        self.buffer.push_back((time, value));
+
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+#[derive(Debug)]
+struct TimeRollingSum {
+    window: TimeWindow<DynamicNumber>,
+    sum: Sum,
+}
+
+impl TimeRollingSum {
+    fn with_duration(duration: SignedDuration) -> Self {
+        Self {
+            window: TimeWindow::with_duration(duration),
+            sum: Sum::new(),
+        }
+    }
+
+    fn add(
+        &mut self,
+        time: Timestamp,
+        number: DynamicNumber,
+    ) -> Result<Option<DynamicNumber>, EvaluationError> {
+        let sum = &mut self.sum;
+
+        self.window
+            .push(time, number, |old_number| sum.add(-old_number))?;
+        self.sum.add(number);
+
+        Ok(self.sum.get())
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+        self.sum.clear();
+    }
+}
+
+#[derive(Debug)]
+struct TimeRollingWelford {
+    window: TimeWindow<f64>,
+    welford: Welford,
+}
+
+impl TimeRollingWelford {
+    fn with_duration(duration: SignedDuration) -> Self {
+        Self {
+            window: TimeWindow::with_duration(duration),
+            welford: Welford::new(),
+        }
+    }
+
+    fn add(
+        &mut self,
+        time: Timestamp,
+        value: f64,
+        stat: WelfordStat,
+    ) -> Result<Option<f64>, EvaluationError> {
+        let welford = &mut self.welford;
+
+        self.window
+            .push(time, value, |old_value| welford.remove(old_value))?;
+        self.welford.add(value);
+
+        Ok(match stat {
+            WelfordStat::Mean => self.welford.mean(),
+            WelfordStat::Var => self.welford.variance(),
+            WelfordStat::Stddev => self.welford.stdev(),
+        })
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+        self.welford.clear();
+    }
+}
+
+fn get_row_at_offset<'a>(
+    offset: isize,
+    index: usize,
+    record: &'a ByteRecord,
+    past_buffer: Option<&'a PastBuffer>,
+    future_buffer: Option<&'a FutureBuffer>,
+) -> Option<(usize, &'a ByteRecord)> {
+    match offset.cmp(&0) {
+        Ordering::Less => past_buffer
+            .and_then(|buffer| buffer.get((-offset - 1) as usize))
+            .map(|(i, r)| (*i, r)),
+        Ordering::Equal => Some((index, record)),
+        Ordering::Greater => future_buffer
+            .and_then(|buffer| buffer.get(offset as usize))
+            .and_then(|(i, r, is_padding)| (!is_padding).then_some((*i, r))),
+    }
+}
+
+// NOTE: framed aggregations run any regular aggregation function over a frame
+// of rows surrounding the current one. Bounded frames are recomputed for each
+// row using the past & future buffers, frames starting at the beginning of the
+// partition are computed incrementally, and frames reaching its end require
+// buffering the whole partition.
+#[derive(Debug)]
+struct FramedAggregation {
+    program: AggregationProgram,
+    frame: WindowFrame,
+    last_fed_index: Option<usize>,
+    results: VecDeque<DynamicValue>,
+}
+
+impl FramedAggregation {
+    fn new(program: AggregationProgram, frame: WindowFrame) -> Self {
+        Self {
+            program,
+            frame,
+            last_fed_index: None,
+            results: VecDeque::new(),
+        }
+    }
+
+    fn extent(&self) -> (usize, usize) {
+        let past = match (self.frame.start, self.frame.end) {
+            (FrameBound::Offset(start), _) => -start,
+            (FrameBound::Unbounded, FrameBound::Offset(end)) => -end,
+            _ => 0,
+        };
+
+        let future = match self.frame.end {
+            FrameBound::Offset(end) => end,
+            FrameBound::Unbounded => 0,
+        };
+
+        (past.max(0) as usize, future.max(0) as usize)
+    }
+
+    fn requires_total_buffer(&self) -> bool {
+        matches!(self.frame.end, FrameBound::Unbounded)
+    }
+
+    fn finalize_value(&mut self) -> Result<DynamicValue, SpecifiedEvaluationError> {
+        let record = self.program.finalize(false)?;
+
+        Ok(DynamicValue::from_owned_bytes(record[0].to_vec()))
+    }
+
+    fn finalize_total(
+        &mut self,
+        total_buffer: &TotalBuffer,
+    ) -> Result<(), SpecifiedEvaluationError> {
+        match self.frame.start {
+            FrameBound::Unbounded => {
+                for (index, record) in total_buffer.iter() {
+                    self.program.run_with_record(*index, record)?;
+                }
+
+                let value = self.finalize_value()?;
+
+                self.results
+                    .extend(std::iter::repeat(value).take(total_buffer.len()));
+            }
+            // NOTE: this is quadratic, but such frames should be quite rare
+            FrameBound::Offset(start) => {
+                for i in 0..total_buffer.len() {
+                    let lo = (i as isize + start).clamp(0, total_buffer.len() as isize) as usize;
+
+                    self.program.clear();
+
+                    for (index, record) in total_buffer[lo..].iter() {
+                        self.program.run_with_record(*index, record)?;
+                    }
+
+                    let value = self.finalize_value()?;
+                    self.results.push_back(value);
+                }
+            }
+        }
+
+        self.program.clear();
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        index: usize,
+        record: &ByteRecord,
+        past_buffer: Option<&PastBuffer>,
+        future_buffer: Option<&FutureBuffer>,
+    ) -> Result<DynamicValue, SpecifiedEvaluationError> {
+        let end = match self.frame.end {
+            FrameBound::Unbounded => return Ok(self.results.pop_front().unwrap()),
+            FrameBound::Offset(end) => end,
+        };
+
+        match self.frame.start {
+            FrameBound::Offset(start) => {
+                self.program.clear();
+
+                for offset in start..=end {
+                    if let Some((i, r)) =
+                        get_row_at_offset(offset, index, record, past_buffer, future_buffer)
+                    {
+                        self.program.run_with_record(i, r)?;
+                    }
+                }
+            }
+            FrameBound::Unbounded => {
+                for offset in end.min(0)..=end {
+                    if let Some((i, r)) =
+                        get_row_at_offset(offset, index, record, past_buffer, future_buffer)
+                    {
+                        if self.last_fed_index.is_some_and(|last| i <= last) {
+                            continue;
+                        }
+
+                        self.program.run_with_record(i, r)?;
+                        self.last_fed_index = Some(i);
+                    }
+                }
+            }
+        }
+
+        self.finalize_value()
+    }
+
+    fn clear(&mut self) {
+        self.program.clear();
+        self.last_fed_index = None;
+        self.results.clear();
+    }
+}
+
 #[derive(Debug)]
 enum ConcreteWindowAggregation {
     Lead(ConcreteExpr, usize, ConcreteExpr),
@@ -120,8 +400,11 @@ enum ConcreteWindowAggregation {
     CumulativeMax(ConcreteExpr, Option<DynamicNumber>),
     RollingSum(ConcreteExpr, RollingSum),
     RollingWelford(ConcreteExpr, WelfordStat, RollingWelford),
+    TimeRollingSum(ConcreteExpr, ConcreteExpr, TimeRollingSum),
+    TimeRollingWelford(ConcreteExpr, ConcreteExpr, WelfordStat, TimeRollingWelford),
     Frac(ConcreteExpr, Sum, Option<usize>),
     DenseRank(ConcreteExpr, Vec<(DynamicNumber, usize)>, VecDeque<usize>),
+    Framed(Box<FramedAggregation>),
 }
 
 fn eval_expression_to_number(
@@ -135,17 +418,36 @@ fn eval_expression_to_number(
     value.try_as_number().map_err(|err| err.anonymous())
 }
 
+fn eval_expression_to_timestamp(
+    expr: &ConcreteExpr,
+    index: usize,
+    record: &ByteRecord,
+    headers_index: &HeadersIndex,
+) -> Result<Timestamp, SpecifiedEvaluationError> {
+    let value = eval_expression(expr, Some(index), record, headers_index)?;
+
+    value
+        .try_as_datetime()
+        .map(|zoned| zoned.timestamp())
+        .map_err(|err| err.anonymous())
+}
+
 impl ConcreteWindowAggregation {
     fn extent(&self) -> (usize, usize) {
         match self {
             Self::Lead(_, n, _) => (0, *n),
             Self::Lag(_, n, _) => (*n, 0),
+            Self::Framed(framed) => framed.extent(),
             _ => (0, 0),
         }
     }
 
     fn requires_total_buffer(&self) -> bool {
-        matches!(self, Self::Frac(_, _, _) | Self::DenseRank(_, _, _))
+        match self {
+            Self::Frac(_, _, _) | Self::DenseRank(_, _, _) => true,
+            Self::Framed(framed) => framed.requires_total_buffer(),
+            _ => false,
+        }
     }
 
     fn aggregate_total(
@@ -176,30 +478,41 @@ impl ConcreteWindowAggregation {
         Ok(())
     }
 
-    fn finalize_total(&mut self) {
-        if let Self::DenseRank(_, numbers, ranks) = self {
-            numbers.sort();
-            ranks.resize(numbers.len(), 0);
+    fn finalize_total(
+        &mut self,
+        total_buffer: &TotalBuffer,
+    ) -> Result<(), SpecifiedEvaluationError> {
+        match self {
+            Self::DenseRank(_, numbers, ranks) => {
+                numbers.sort();
+                ranks.resize(numbers.len(), 0);
 
-            let mut rank: usize = 0;
-            let mut last_number: Option<DynamicNumber> = None;
+                let mut rank: usize = 0;
+                let mut last_number: Option<DynamicNumber> = None;
 
-            for (n, i) in numbers.iter() {
-                match last_number {
-                    None => {
-                        last_number = Some(*n);
-                        rank += 1;
-                    }
-                    Some(l) if l != *n => {
-                        last_number = Some(*n);
-                        rank += 1;
-                    }
-                    _ => {}
-                };
+                for (n, i) in numbers.iter() {
+                    match last_number {
+                        None => {
+                            last_number = Some(*n);
+                            rank += 1;
+                        }
+                        Some(l) if l != *n => {
+                            last_number = Some(*n);
+                            rank += 1;
+                        }
+                        _ => {}
+                    };
 
-                ranks[*i] = rank;
+                    ranks[*i] = rank;
+                }
             }
-        }
+            Self::Framed(framed) if framed.requires_total_buffer() => {
+                framed.finalize_total(total_buffer)?;
+            }
+            _ => (),
+        };
+
+        Ok(())
     }
 
     fn run(
@@ -295,6 +608,25 @@ impl ConcreteWindowAggregation {
 
                 Ok(DynamicValue::from(welford.add(float, *stat)))
             }
+            Self::TimeRollingSum(expr, time_expr, sum) => {
+                let number = eval_expression_to_number(expr, index, record, headers_index)?;
+                let time = eval_expression_to_timestamp(time_expr, index, record, headers_index)?;
+
+                Ok(DynamicValue::from(
+                    sum.add(time, number).map_err(|err| err.anonymous())?,
+                ))
+            }
+            Self::TimeRollingWelford(expr, time_expr, stat, welford) => {
+                let value = eval_expression(expr, Some(index), record, headers_index)?;
+                let float = value.try_as_f64().map_err(|err| err.anonymous())?;
+                let time = eval_expression_to_timestamp(time_expr, index, record, headers_index)?;
+
+                Ok(DynamicValue::from(
+                    welford
+                        .add(time, float, *stat)
+                        .map_err(|err| err.anonymous())?,
+                ))
+            }
             Self::Frac(expr, sum, decimals) => {
                 // NOTE: we are evaluation the expression twice, because it seems less costly
                 // than allocating a cache for every record.
@@ -313,6 +645,7 @@ impl ConcreteWindowAggregation {
                 })
             }
             Self::DenseRank(_, _, ranks) => Ok(DynamicValue::from(ranks.pop_front().unwrap())),
+            Self::Framed(framed) => framed.run(index, record, past_buffer, future_buffer),
         }
     }
 
@@ -336,6 +669,12 @@ impl ConcreteWindowAggregation {
             Self::RollingWelford(_, _, welford) => {
                 welford.clear();
             }
+            Self::TimeRollingSum(_, _, sum) => {
+                sum.clear();
+            }
+            Self::TimeRollingWelford(_, _, _, welford) => {
+                welford.clear();
+            }
             Self::Lag(_, _, _) | Self::Lead(_, _, _) => (),
             Self::Frac(_, sum, _) => {
                 sum.clear();
@@ -344,6 +683,9 @@ impl ConcreteWindowAggregation {
                 numbers.clear();
                 ranks.clear();
             }
+            Self::Framed(framed) => {
+                framed.clear();
+            }
         };
     }
 }
@@ -355,12 +697,27 @@ fn get_function(name: &str) -> Option<FunctionArguments> {
         "lag" | "lead" => FunctionArguments::with_range(1..=3),
         "cumsum" | "cummin" | "cummax" | "dense_rank" => FunctionArguments::unary(),
         "rolling_sum" | "rolling_mean" | "rolling_avg" | "rolling_var" | "rolling_stddev" => {
-            FunctionArguments::binary()
+            FunctionArguments::with_range(2..=3)
         }
         _ => return None,
     })
 }
 
+fn cast_as_duration(arg: &ConcreteExpr) -> Result<SignedDuration, ConcretizationError> {
+    match arg {
+        ConcreteExpr::Value(v) => {
+            let string = v
+                .try_as_str()
+                .map_err(|_| ConcretizationError::NotStaticallyAnalyzable)?;
+
+            parse_duration(&string).ok_or_else(|| {
+                ConcretizationError::Custom(format!("invalid window duration \"{}\"", string))
+            })
+        }
+        _ => Err(ConcretizationError::NotStaticallyAnalyzable),
+    }
+}
+
 fn cast_as_usize(arg: &ConcreteExpr) -> Result<usize, ConcretizationError> {
     match arg {
         ConcreteExpr::Value(v) => v
@@ -376,15 +733,41 @@ fn concretize_window_aggregations(
     input: &str,
     headers: &ByteRecord,
 ) -> Result<ConcreteWindowAggregations, ConcretizationError> {
-    let aggs = parse_aggregations(input).map_err(ConcretizationError::ParseError)?;
+    let aggs = parse_window_aggregations(input).map_err(ConcretizationError::ParseError)?;
 
     let mut concrete_aggs = Vec::with_capacity(aggs.len());
 
-    for mut agg in aggs {
+    for (mut agg, frame) in aggs {
         let func_name = &agg.func_name;
 
-        let arguments_spec = get_function(func_name)
-            .ok_or_else(|| ConcretizationError::UnknownFunction(func_name.to_string()))?;
+        let Some(arguments_spec) = get_function(func_name) else {
+            if !is_aggregation_function(func_name) {
+                return Err(ConcretizationError::UnknownFunction(func_name.to_string()));
+            }
+
+            // NOTE: like in SQL, the frame defaults to the whole partition
+            let frame = frame.unwrap_or(WindowFrame {
+                start: FrameBound::Unbounded,
+                end: FrameBound::Unbounded,
+            });
+
+            let agg_name = agg.agg_name.clone();
+            let program = AggregationProgram::from_aggregations(vec![agg], headers)?;
+
+            concrete_aggs.push((
+                agg_name,
+                ConcreteWindowAggregation::Framed(Box::new(FramedAggregation::new(program, frame))),
+            ));
+
+            continue;
+        };
+
+        if frame.is_some() {
+            return Err(ConcretizationError::Custom(format!(
+                "window function \"{}\" cannot be used with a frame",
+                func_name
+            )));
+        }
 
         arguments_spec
             .validate_arity(agg.args.len())
@@ -447,6 +830,42 @@ fn concretize_window_aggregations(
                     },
                 ))
             }
+            "rolling_sum" | "rolling_mean" | "rolling_avg" | "rolling_var" | "rolling_stddev"
+                if agg.args.len() == 3 =>
+            {
+                let time_expr = concretize_expression(agg.args.pop().unwrap(), headers, None)?;
+                let expr = concretize_expression(agg.args.pop().unwrap(), headers, None)?;
+                let duration = cast_as_duration(&concretize_expression(
+                    agg.args.pop().unwrap(),
+                    headers,
+                    None,
+                )?)?;
+
+                let stat = match func_name.as_str() {
+                    "rolling_sum" => None,
+                    "rolling_mean" | "rolling_avg" => Some(WelfordStat::Mean),
+                    "rolling_var" => Some(WelfordStat::Var),
+                    "rolling_stddev" => Some(WelfordStat::Stddev),
+                    _ => unreachable!(),
+                };
+
+                concrete_aggs.push((
+                    agg.agg_name,
+                    match stat {
+                        None => ConcreteWindowAggregation::TimeRollingSum(
+                            expr,
+                            time_expr,
+                            TimeRollingSum::with_duration(duration),
+                        ),
+                        Some(stat) => ConcreteWindowAggregation::TimeRollingWelford(
+                            expr,
+                            time_expr,
+                            stat,
+                            TimeRollingWelford::with_duration(duration),
+                        ),
+                    },
+                ));
+            }
             "rolling_sum" | "rolling_mean" | "rolling_avg" | "rolling_var" | "rolling_stddev" => {
                 let expr = concretize_expression(agg.args.pop().unwrap(), headers, None)?;
                 let window_size = cast_as_usize(&concretize_expression(
@@ -668,7 +1087,7 @@ impl WindowAggregationProgram {
             }
 
             for (_, agg) in self.aggs.iter_mut() {
-                agg.finalize_total();
+                agg.finalize_total(&total_buffer)?;
             }
 
             for (index, record) in total_buffer.iter() {
@@ -683,12 +1102,22 @@ impl WindowAggregationProgram {
                 .map(|_| b"")
                 .collect::<csv::ByteRecord>();
 
-            for _ in 0..future_buffer.len() {
+            // NOTE: the buffer may not be full if the partition was shorter than
+            // the future extent, so we pad until every buffered row was emitted
+            let mut remaining = future_buffer
+                .iter()
+                .filter(|(_, _, is_padding)| !is_padding)
+                .count();
+
+            while remaining > 0 {
                 from_index += 1;
-                callback(
+
+                if let Some(output_record) =
                     self.run_with_record_impl(from_index, &padding, true)?
-                        .unwrap(),
-                )?;
+                {
+                    callback(output_record)?;
+                    remaining -= 1;
+                }
             }
         }
 
@@ -29,7 +29,7 @@ use crate::collections::HashMap;
 use crate::dates;
 use crate::urls::LRUStems;
 
-use super::agg::aggregators::{Sum, Welford};
+use super::agg::aggregators::{ApproxCardinality, ApproxQuantiles, Sum, Welford};
 use super::error::EvaluationError;
 use super::types::{Argument, BoundArguments, DynamicNumber, DynamicValue, FunctionArguments};
 
@@ -104,6 +104,8 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             FunctionArguments::variadic(2),
         ),
         "endswith" => (endswith, FunctionArguments::binary()),
+        "entries" => (entries, FunctionArguments::unary()),
+        "enumerate" => (enumerate, FunctionArguments::with_range(1..=2)),
         "err" => (err, FunctionArguments::unary()),
         "escape_regex" => (escape_regex, FunctionArguments::unary()),
         "ext" => (ext, FunctionArguments::unary()),
@@ -116,8 +118,10 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             FunctionArguments::unary(),
         ),
         "fmt" => (fmt, FunctionArguments::variadic(2)),
+        "from_entries" => (from_entries, FunctionArguments::unary()),
         "numfmt" => (fmt_number, FunctionArguments::unary()),
         "get" => (get, FunctionArguments::with_range(2..=3)),
+        "hll_cardinality" => (hll_cardinality, FunctionArguments::unary()),
         "html_unescape" => (html_unescape, FunctionArguments::unary()),
         "idiv" => (
             |args| arithmetic_op(args, DynamicNumber::idiv),
@@ -260,6 +264,7 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             |args| sequence_compare(args, Ordering::is_ne),
             FunctionArguments::binary(),
         ),
+        "tdigest_quantile" => (tdigest_quantile, FunctionArguments::binary()),
         "timestamp" => (timestamp, FunctionArguments::unary()),
         "timestamp_ms" => (timestamp_ms, FunctionArguments::unary()),
         "to_fixed" => (to_fixed, FunctionArguments::binary()),
@@ -291,6 +296,7 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             |args| custom_strftime(args, "%Y-%m"),
             FunctionArguments::unary(),
         ),
+        "zip" => (zip, FunctionArguments::variadic(2)),
         _ => return None,
     })
 }
@@ -968,6 +974,72 @@ fn values(args: BoundArguments) -> FunctionResult {
     ))
 }
 
+fn entries(args: BoundArguments) -> FunctionResult {
+    let map = args.get1().try_as_map()?;
+
+    let mut pairs = map.iter().collect::<Vec<_>>();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+
+    Ok(DynamicValue::from(
+        pairs
+            .into_iter()
+            .map(|(k, v)| DynamicValue::from(vec![DynamicValue::from(k.as_str()), v.clone()]))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn from_entries(args: BoundArguments) -> FunctionResult {
+    let list = args.get1().try_as_list()?;
+
+    let mut map: HashMap<String, DynamicValue> = HashMap::with_capacity(list.len());
+
+    for item in list {
+        let pair = item.try_as_list()?;
+
+        if pair.len() != 2 {
+            return Err(EvaluationError::Custom(
+                "from_entries() expects a list of [key, value] pairs".to_string(),
+            ));
+        }
+
+        map.insert(pair[0].try_as_str()?.into_owned(), pair[1].clone());
+    }
+
+    Ok(DynamicValue::from(map))
+}
+
+fn enumerate(args: BoundArguments) -> FunctionResult {
+    let list = args.get1().try_as_list()?;
+    let start = match args.get(1) {
+        Some(arg) => arg.try_as_i64()?,
+        None => 0,
+    };
+
+    Ok(DynamicValue::from(
+        list.iter()
+            .zip(start..)
+            .map(|(item, i)| DynamicValue::from(vec![DynamicValue::from(i), item.clone()]))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn zip(args: BoundArguments) -> FunctionResult {
+    let lists = args
+        .into_iter()
+        .map(|arg| arg.try_into_arc_list())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let len = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+
+    Ok(DynamicValue::from(
+        (0..len)
+            .map(|i| {
+                DynamicValue::from(lists.iter().map(|list| list[i].clone()).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
 fn index_by(args: BoundArguments) -> FunctionResult {
     let list = args.get1().try_as_list()?;
     let key = args.get(1).unwrap().try_as_str()?;
@@ -1611,6 +1683,25 @@ fn random(_args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(rand::rng().random::<f64>()))
 }
 
+// Sketches
+fn hll_cardinality(args: BoundArguments) -> FunctionResult {
+    let mut approx_cardinality =
+        ApproxCardinality::from_sketch(&args.get1_str()?).map_err(EvaluationError::Custom)?;
+
+    approx_cardinality.finalize();
+
+    Ok(DynamicValue::from(approx_cardinality.get()))
+}
+
+fn tdigest_quantile(args: BoundArguments) -> FunctionResult {
+    let (sketch, p) = args.get2();
+
+    let approx_quantiles =
+        ApproxQuantiles::from_sketch(&sketch.try_as_str()?).map_err(EvaluationError::Custom)?;
+
+    Ok(DynamicValue::from(approx_quantiles.get(p.try_as_f64()?)))
+}
+
 // Fuzzy matching
 lazy_static! {
     static ref FINGERPRINT_TOKENIZER: FingerprintTokenizer = FingerprintTokenizer::default();
@@ -1,4 +1,7 @@
+use std::env;
 use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
 
 use arrayvec::ArrayVec;
 use csv::ByteRecord;
@@ -6,12 +9,13 @@ use regex::RegexBuilder;
 
 use crate::collections::HashMap;
 
+use super::error::InvalidArity;
 use super::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
 use super::functions::{get_function, Function};
-use super::parser::{parse_expression, Expr, FunctionCall};
+use super::parser::{parse_expression, parse_prelude, Expr, FunctionCall, FunctionDefinition};
 use super::special_functions::{get_special_function, RuntimeFunction as SpecialFunction};
 use super::types::{
-    BoundArguments, ColumIndexationBy, DynamicValue, EvaluationResult, FunctionArguments,
+    Arity, BoundArguments, ColumIndexationBy, DynamicValue, EvaluationResult, FunctionArguments,
     HeadersIndex, LambdaArguments, BOUND_ARGUMENTS_CAPACITY,
 };
 
@@ -54,6 +58,28 @@ impl GlobalVariables {
     }
 }
 
+// NOTE: values bound by `let` expressions & inlined user-defined functions
+// live in a stack of frames. Local variables are resolved to a (depth, index)
+// position in this stack when concretizing, so that no lookup by name is
+// needed when evaluating.
+#[derive(Debug)]
+pub struct LocalFrame<'a> {
+    values: Vec<DynamicValue>,
+    parent: Option<&'a LocalFrame<'a>>,
+}
+
+impl LocalFrame<'_> {
+    fn get(&self, depth: usize, index: usize) -> &DynamicValue {
+        let mut frame = self;
+
+        for _ in 0..depth {
+            frame = frame.parent.expect("local frames cannot be out-of-bounds");
+        }
+
+        &frame.values[index]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EvaluationContext<'a> {
     pub index: Option<usize>,
@@ -61,6 +87,7 @@ pub struct EvaluationContext<'a> {
     pub headers_index: &'a HeadersIndex,
     pub globals: Option<&'a GlobalVariables>,
     pub lambda_variables: Option<&'a LambdaArguments>,
+    pub locals: Option<&'a LocalFrame<'a>>,
     pub last_value: Option<DynamicValue>,
 }
 
@@ -76,6 +103,7 @@ impl<'a> EvaluationContext<'a> {
             headers_index,
             globals: None,
             lambda_variables: None,
+            locals: None,
             last_value: None,
         }
     }
@@ -99,6 +127,7 @@ impl<'a> EvaluationContext<'a> {
             headers_index,
             globals: None,
             lambda_variables: None,
+            locals: None,
             last_value: None,
         }
     }
@@ -110,6 +139,7 @@ impl<'a> EvaluationContext<'a> {
             headers_index: self.headers_index,
             globals: self.globals,
             lambda_variables: Some(variables),
+            locals: self.locals,
             last_value: self.last_value.clone(),
         }
     }
@@ -121,6 +151,7 @@ impl<'a> EvaluationContext<'a> {
             headers_index: self.headers_index,
             globals: Some(globals),
             lambda_variables: self.lambda_variables,
+            locals: self.locals,
             last_value: self.last_value.clone(),
         }
     }
@@ -132,6 +163,8 @@ pub enum ConcreteExpr {
     GlobalVariable(usize),
     Lambda(Vec<String>, Box<ConcreteExpr>),
     LambdaBinding(String),
+    Let(Vec<ConcreteExpr>, Box<ConcreteExpr>),
+    LocalVariable(usize, usize),
     Value(DynamicValue),
     List(Vec<ConcreteExpr>),
     Map(Vec<(String, ConcreteExpr)>),
@@ -181,6 +214,13 @@ impl ConcreteExpr {
                     expr.used_column_indices(scratch);
                 }
             }
+            Self::Let(values, body) => {
+                for value in values {
+                    value.used_column_indices(scratch);
+                }
+
+                body.used_column_indices(scratch);
+            }
             _ => (),
         };
     }
@@ -253,6 +293,11 @@ impl ConcreteExpr {
                 .expect("lambda_variables MUST be set")
                 .get(name)
                 .clone(),
+            Self::LocalVariable(depth, index) => context
+                .locals
+                .expect("locals MUST be set")
+                .get(*depth, *index)
+                .clone(),
             Self::Underscore => match context.last_value.as_ref() {
                 None => return Err(EvaluationError::UnfillableUnderscore),
                 Some(last_value) => last_value.clone(),
@@ -262,6 +307,7 @@ impl ConcreteExpr {
             | Self::Call(_)
             | Self::SpecialCall(_)
             | Self::Lambda(_, _)
+            | Self::Let(_, _)
             | Self::Pipeline(_) => unreachable!(),
         })
     }
@@ -300,6 +346,21 @@ impl ConcreteExpr {
 
                 Ok(pipeline_context.last_value.unwrap())
             }
+            Self::Let(values, body) => {
+                let mut frame = LocalFrame {
+                    values: Vec::with_capacity(values.len()),
+                    parent: context.locals,
+                };
+
+                for value in values {
+                    frame.values.push(value.evaluate(context)?);
+                }
+
+                let mut body_context = context.clone();
+                body_context.locals = Some(&frame);
+
+                body.evaluate(&body_context)
+            }
             _ => self.bind(context).map_err(|err| err.anonymous()),
         }
     }
@@ -342,7 +403,7 @@ impl ConcreteFunctionCall {
                 ConcreteExpr::SpecialCall(sub_function_call) => {
                     bound_args.push(sub_function_call.run(context)?);
                 }
-                ConcreteExpr::List(_) | ConcreteExpr::Map(_) => {
+                ConcreteExpr::List(_) | ConcreteExpr::Map(_) | ConcreteExpr::Let(_, _) => {
                     bound_args.push(arg.evaluate(context)?)
                 }
                 _ => bound_args.push(arg.bind(context).map_err(|err| err.specify(&self.name))?),
@@ -416,15 +477,210 @@ impl fmt::Debug for ConcreteSpecialFunctionCall {
     }
 }
 
+static PRELUDE: OnceLock<Result<Vec<FunctionDefinition>, String>> = OnceLock::new();
+
+fn read_prelude(path: &str) -> Result<Vec<FunctionDefinition>, String> {
+    let code = fs::read_to_string(path)
+        .map_err(|err| format!("could not read prelude file {}: {}", path, err))?;
+
+    parse_prelude(&code).map_err(|err| format!("could not parse prelude file {}: {}", path, err))
+}
+
+// NOTE: the prelude is read from the file given to this function, or else
+// from the path found in the XAN_PRELUDE environment variable, the first time
+// an expression is concretized.
+pub fn load_prelude(path: &str) -> Result<(), ConcretizationError> {
+    let definitions = read_prelude(path).map_err(ConcretizationError::Custom)?;
+
+    PRELUDE
+        .set(Ok(definitions))
+        .map_err(|_| ConcretizationError::Custom("prelude was already loaded".to_string()))
+}
+
+fn get_prelude() -> Result<&'static Vec<FunctionDefinition>, ConcretizationError> {
+    PRELUDE
+        .get_or_init(|| match env::var("XAN_PRELUDE") {
+            Ok(path) if !path.is_empty() => read_prelude(&path),
+            _ => Ok(vec![]),
+        })
+        .as_ref()
+        .map_err(|err| ConcretizationError::Custom(err.clone()))
+}
+
+// A frame of local variables, either statically known (in which case they
+// are directly substituted) or only known at runtime.
+type ScopeFrame = Vec<(String, Option<DynamicValue>)>;
+
+#[derive(Default)]
+struct ConcretizationScope {
+    frames: Vec<ScopeFrame>,
+    functions: Vec<FunctionDefinition>,
+    inlining: Vec<String>,
+}
+
+impl ConcretizationScope {
+    fn with_prelude() -> Result<Self, ConcretizationError> {
+        Ok(Self {
+            functions: get_prelude()?.clone(),
+            ..Self::default()
+        })
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<ConcreteExpr> {
+        let mut depth: usize = 0;
+
+        for frame in self.frames.iter().rev() {
+            let mut index: usize = 0;
+
+            for (local_name, static_value) in frame.iter() {
+                if local_name == name {
+                    return Some(match static_value {
+                        Some(value) => ConcreteExpr::Value(value.clone()),
+                        None => ConcreteExpr::LocalVariable(depth, index),
+                    });
+                }
+
+                if static_value.is_none() {
+                    index += 1;
+                }
+            }
+
+            // NOTE: frames containing only static values don't exist at runtime
+            if frame.iter().any(|(_, static_value)| static_value.is_none()) {
+                depth += 1;
+            }
+        }
+
+        None
+    }
+
+    fn get_function(&self, name: &str) -> Option<&FunctionDefinition> {
+        self.functions.iter().rev().find(|f| f.name == name)
+    }
+}
+
+// Bind the given concrete values in a new frame before concretizing the
+// given expression, returning a `Let` expression if some of them can only be
+// known at runtime.
+fn concretize_with_frame(
+    names: Vec<String>,
+    values: Vec<ConcreteExpr>,
+    expr: Expr,
+    headers: &ByteRecord,
+    globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
+) -> Result<ConcreteExpr, ConcretizationError> {
+    let mut frame = ScopeFrame::with_capacity(names.len());
+    let mut runtime_values = Vec::new();
+
+    for (name, value) in names.into_iter().zip(values) {
+        match value {
+            ConcreteExpr::Value(static_value) => frame.push((name, Some(static_value))),
+            _ => {
+                frame.push((name, None));
+                runtime_values.push(value);
+            }
+        }
+    }
+
+    scope.frames.push(frame);
+    let result = concretize(expr, headers, globals, scope);
+    scope.frames.pop();
+
+    let concrete_expr = result?;
+
+    Ok(if runtime_values.is_empty() {
+        concrete_expr
+    } else {
+        ConcreteExpr::Let(runtime_values, Box::new(concrete_expr))
+    })
+}
+
+// NOTE: user-defined functions are inlined. Their body is concretized in a
+// fresh scope only containing their parameters, so they cannot capture
+// local variables from the call site.
+fn concretize_user_function_call(
+    definition: FunctionDefinition,
+    call: FunctionCall,
+    headers: &ByteRecord,
+    globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
+) -> Result<ConcreteExpr, ConcretizationError> {
+    let arity = definition.params.len();
+    let actual_arity = call.args.len();
+
+    let invalid_arity = || {
+        ConcretizationError::InvalidArity(
+            definition.name.clone(),
+            InvalidArity::from_arity(Arity::Strict(arity), actual_arity),
+        )
+    };
+
+    if actual_arity != arity {
+        return Err(invalid_arity());
+    }
+
+    if scope.inlining.contains(&definition.name) {
+        return Err(ConcretizationError::Custom(format!(
+            "function \"{}\" cannot be recursive",
+            definition.name
+        )));
+    }
+
+    let mut args: Vec<Option<Expr>> = vec![None; arity];
+    let mut next_positional: usize = 0;
+
+    for (name, arg) in call.args {
+        let position = match name {
+            None => {
+                next_positional += 1;
+                next_positional - 1
+            }
+            Some(name) => definition
+                .params
+                .iter()
+                .position(|param| *param == name)
+                .ok_or(ConcretizationError::UnknownArgumentName(name))?,
+        };
+
+        if args[position].replace(arg).is_some() {
+            return Err(invalid_arity());
+        }
+    }
+
+    let values = args
+        .into_iter()
+        .map(|arg| concretize(arg.unwrap(), headers, globals, scope))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let outer_frames = std::mem::take(&mut scope.frames);
+    scope.inlining.push(definition.name.clone());
+
+    let result = concretize_with_frame(
+        definition.params,
+        values,
+        definition.body,
+        headers,
+        globals,
+        scope,
+    );
+
+    scope.inlining.pop();
+    scope.frames = outer_frames;
+
+    result
+}
+
 fn concretize_arguments(
     function_arguments: &FunctionArguments,
     parsed_args: Vec<(Option<String>, Expr)>,
     headers: &ByteRecord,
     globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
 ) -> Result<Vec<ConcreteExpr>, ConcretizationError> {
     let concrete_args = parsed_args
         .into_iter()
-        .map(|(name, expr)| concretize_expression(expr, headers, globals).map(|r| (name, r)))
+        .map(|(name, expr)| concretize(expr, headers, globals, scope).map(|r| (name, r)))
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(function_arguments
@@ -438,7 +694,13 @@ fn concretize_call(
     call: FunctionCall,
     headers: &ByteRecord,
     globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
 ) -> Result<ConcreteExpr, ConcretizationError> {
+    if let Some(definition) = scope.get_function(&call.name) {
+        let definition = definition.clone();
+        return concretize_user_function_call(definition, call, headers, globals, scope);
+    }
+
     let function_name = &call.name;
     let actual_arity = call.args.len();
 
@@ -471,7 +733,7 @@ fn concretize_call(
 
         // NOTE: special cases wrt branching
         if function_name == "if" || function_name == "unless" {
-            let condition = concretize_expression(call.args[0].clone().1, headers, globals)?;
+            let condition = concretize(call.args[0].clone().1, headers, globals, scope)?;
 
             if let Ok(value) = condition.try_unwrap() {
                 let path = if function_name == "if" {
@@ -486,13 +748,13 @@ fn concretize_call(
                     1
                 };
 
-                return concretize_expression(call.args[path].clone().1, headers, globals);
+                return concretize(call.args[path].clone().1, headers, globals, scope);
             }
         }
 
         if function_name == "or" || function_name == "and" {
             for (_, arg) in call.args.iter() {
-                if let Ok(condition) = concretize_expression(arg.clone(), headers, globals) {
+                if let Ok(condition) = concretize(arg.clone(), headers, globals, scope) {
                     if let Ok(value) = condition.try_unwrap() {
                         let test = if function_name == "or" {
                             value.is_truthy()
@@ -512,7 +774,7 @@ fn concretize_call(
             }
         }
 
-        let concrete_args = concretize_arguments(&arguments, call.args, headers, globals);
+        let concrete_args = concretize_arguments(&arguments, call.args, headers, globals, scope);
 
         // NOTE: special case of bubbling-up exceptions
         if function_name == "try" && concrete_args.is_err() {
@@ -549,7 +811,7 @@ fn concretize_call(
             let concrete_call = ConcreteFunctionCall {
                 name: function_name.clone(),
                 function,
-                args: concretize_arguments(&arguments, call.args, headers, globals)?,
+                args: concretize_arguments(&arguments, call.args, headers, globals, scope)?,
             };
 
             if concrete_call.is_statically_evaluable(&vec![]) {
@@ -570,10 +832,11 @@ fn concretize_list(
     list: Vec<Expr>,
     headers: &ByteRecord,
     globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
 ) -> Result<ConcreteExpr, ConcretizationError> {
     let concrete_list = list
         .into_iter()
-        .map(|item| concretize_expression(item, headers, globals))
+        .map(|item| concretize(item, headers, globals, scope))
         .collect::<Result<Vec<ConcreteExpr>, _>>()?;
 
     // NOTE: here we can collapse to a literal value
@@ -593,10 +856,11 @@ fn concretize_map(
     map: Vec<(String, Expr)>,
     headers: &ByteRecord,
     globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
 ) -> Result<ConcreteExpr, ConcretizationError> {
     let concrete_map = map
         .into_iter()
-        .map(|(k, v)| concretize_expression(v, headers, globals).map(|e| (k, e)))
+        .map(|(k, v)| concretize(v, headers, globals, scope).map(|e| (k, e)))
         .collect::<Result<Vec<(String, ConcreteExpr)>, _>>()?;
 
     // NOTE: here we can collapse to a literal value
@@ -612,10 +876,11 @@ fn concretize_map(
     })
 }
 
-pub fn concretize_expression(
+fn concretize(
     expr: Expr,
     headers: &ByteRecord,
     globals: Option<&GlobalVariables>,
+    scope: &mut ConcretizationScope,
 ) -> Result<ConcreteExpr, ConcretizationError> {
     Ok(match expr {
         Expr::Underscore => ConcreteExpr::Underscore,
@@ -626,6 +891,10 @@ pub fn concretize_expression(
         Expr::Str(v) => ConcreteExpr::Value(DynamicValue::from(v)),
         Expr::BStr(v) => ConcreteExpr::Value(DynamicValue::from_owned_bytes(v)),
         Expr::Identifier(name, unsure) => {
+            if let Some(local) = scope.resolve_local(&name) {
+                return Ok(local);
+            }
+
             if let Some(g) = globals {
                 if let Some(index) = g.get_id(&name) {
                     return Ok(ConcreteExpr::GlobalVariable(index));
@@ -652,24 +921,60 @@ pub fn concretize_expression(
             Ok(regex) => ConcreteExpr::Value(DynamicValue::from(regex)),
             Err(_) => return Err(ConcretizationError::InvalidRegex(pattern)),
         },
-        Expr::Func(call) => concretize_call(call, headers, globals)?,
-        Expr::List(list) => concretize_list(list, headers, globals)?,
-        Expr::Map(map) => concretize_map(map, headers, globals)?,
-        Expr::Lambda(names, expr) => ConcreteExpr::Lambda(
-            names,
-            Box::new(concretize_expression(*expr, headers, globals)?),
-        ),
+        Expr::Func(call) => concretize_call(call, headers, globals, scope)?,
+        Expr::List(list) => concretize_list(list, headers, globals, scope)?,
+        Expr::Map(map) => concretize_map(map, headers, globals, scope)?,
+        Expr::Lambda(names, expr) => {
+            ConcreteExpr::Lambda(names, Box::new(concretize(*expr, headers, globals, scope)?))
+        }
         Expr::Pipeline(pipeline) => ConcreteExpr::Pipeline(
             pipeline
                 .into_iter()
-                .map(|expr| concretize_expression(expr, headers, globals))
+                .map(|expr| concretize(expr, headers, globals, scope))
                 .collect::<Result<Vec<_>, _>>()?,
         ),
         Expr::LambdaBinding(name) => ConcreteExpr::LambdaBinding(name),
+        Expr::Let(name, value, body) => {
+            let value = concretize(*value, headers, globals, scope)?;
+
+            concretize_with_frame(vec![name], vec![value], *body, headers, globals, scope)?
+        }
+        Expr::Define(definitions, body) => {
+            for definition in definitions.iter() {
+                if get_function(&definition.name).is_some()
+                    || get_special_function(&definition.name).is_some()
+                {
+                    return Err(ConcretizationError::Custom(format!(
+                        "cannot redefine built-in function \"{}\"",
+                        definition.name
+                    )));
+                }
+            }
+
+            let count = scope.functions.len();
+            scope.functions.extend(definitions);
+            let result = concretize(*body, headers, globals, scope);
+            scope.functions.truncate(count);
+
+            result?
+        }
         Expr::Slice(_) => unreachable!(),
     })
 }
 
+pub fn concretize_expression(
+    expr: Expr,
+    headers: &ByteRecord,
+    globals: Option<&GlobalVariables>,
+) -> Result<ConcreteExpr, ConcretizationError> {
+    concretize(
+        expr,
+        headers,
+        globals,
+        &mut ConcretizationScope::with_prelude()?,
+    )
+}
+
 pub fn eval_expression_with_globals(
     expr: &ConcreteExpr,
     index: Option<usize>,
@@ -880,6 +1185,54 @@ mod tests {
         assert_eq!(eval_code("full_name?"), Ok(DynamicValue::None));
     }
 
+    #[test]
+    fn test_let_bindings() {
+        assert_eq!(
+            eval_code("let x = a + 1; x * 2"),
+            Ok(DynamicValue::from(70))
+        );
+        assert_eq!(
+            eval_code("let x = a; let y = b; let x = x + y; x - a"),
+            Ok(DynamicValue::from(62))
+        );
+        assert_eq!(
+            eval_code("let k = 2; map([a, b], x => let y = x * k; y + 1)"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(69),
+                DynamicValue::from(125)
+            ]))
+        );
+        assert_eq!(
+            concretize_code("let x = 3; x * 2"),
+            Ok(ConcreteExpr::Value(DynamicValue::Integer(6)))
+        );
+        assert_eq!(
+            concretize_code("let x = a; x"),
+            Ok(ConcreteExpr::Let(
+                vec![ConcreteExpr::Column(2)],
+                Box::new(ConcreteExpr::LocalVariable(0, 0))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_definitions() {
+        assert_eq!(
+            eval_code("fn sq(x) = x * x; fn hyp(x, y) = sq(x) + sq(y); hyp(3, y=4)"),
+            Ok(DynamicValue::from(25))
+        );
+
+        // Function bodies cannot capture local variables from the call site
+        assert_eq!(
+            eval_code("fn f(x) = x + a; let a = 100; f(1)"),
+            Ok(DynamicValue::from(35))
+        );
+
+        assert!(concretize_code("fn f(x) = f(x); f(1)").is_err());
+        assert!(concretize_code("fn len(x) = x; len(1)").is_err());
+        assert!(concretize_code("fn f(x) = x; f(1, 2)").is_err());
+    }
+
     #[test]
     fn test_index() {
         assert_eq!(eval_code("index() + 2"), Ok(DynamicValue::from(4)));
@@ -1354,6 +1707,99 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_higher_order_functions() {
+        fn list(items: Vec<i64>) -> DynamicValue {
+            DynamicValue::from(
+                items
+                    .into_iter()
+                    .map(DynamicValue::from)
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        assert_eq!(
+            eval_code("reduce([1, 2, 3], (acc, x) => acc + x)"),
+            Ok(DynamicValue::from(6))
+        );
+        assert_eq!(
+            eval_code("fold([1, 2, 3], (acc, x, i) => acc + x * i, 10)"),
+            Ok(DynamicValue::from(18))
+        );
+        assert_eq!(
+            eval_code("reduce([], (acc, x) => acc + x)"),
+            Ok(DynamicValue::None)
+        );
+        assert_eq!(
+            eval_code("sort_by([3, 1, 2], x => neg(x))"),
+            Ok(list(vec![3, 2, 1]))
+        );
+        assert_eq!(
+            eval_code("sort_by(['10', '9', 'b', 'a'], x => x) | join(_, '|')"),
+            Ok(DynamicValue::from("9|10|a|b"))
+        );
+        assert_eq!(
+            eval_code("group_by([1, 2, 3, 4, 5], x => x % 2) | get(_, '1')"),
+            Ok(list(vec![1, 3, 5]))
+        );
+        assert_eq!(
+            eval_code("unique_by([1, 2, 3, 4, 5], x => x % 2)"),
+            Ok(list(vec![1, 2]))
+        );
+        assert_eq!(
+            eval_code("flat_map([1, 2], x => [x, x * 10])"),
+            Ok(list(vec![1, 10, 2, 20]))
+        );
+        assert_eq!(
+            eval_code("map([1, 2, 3], (x, i) => x * i)"),
+            Ok(list(vec![0, 2, 6]))
+        );
+        assert_eq!(
+            eval_code("map([[1, 2], [3]], xs => map(xs, x => x + len(xs)))"),
+            Ok(DynamicValue::from(vec![list(vec![3, 4]), list(vec![4])]))
+        );
+        assert_eq!(
+            eval_code("zip([1, 2, 3], [4, 5]) | map(_, pair => pair[0] * pair[1])"),
+            Ok(list(vec![4, 10]))
+        );
+        assert_eq!(
+            eval_code("enumerate([4, 5], 1) | map(_, pair => first(pair))"),
+            Ok(list(vec![1, 2]))
+        );
+
+        // Maps
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | map(_, v => v * 10) | get(_, 'b')"),
+            Ok(DynamicValue::from(20))
+        );
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | filter(_, (v, k) => k eq 'a') | keys(_)"),
+            Ok(DynamicValue::from(vec![DynamicValue::from("a")]))
+        );
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | reduce(_, (acc, v, k) => acc ++ k ++ v, '')"),
+            Ok(DynamicValue::from("a1b2"))
+        );
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | find_index(_, v => v > 1)"),
+            Ok(DynamicValue::from("b"))
+        );
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | entries(_) | from_entries(_) | get(_, 'a')"),
+            Ok(DynamicValue::from(1))
+        );
+        assert_eq!(
+            eval_code("{b: 2, a: 1} | entries(_) | map(_, e => e[0])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("a"),
+                DynamicValue::from("b")
+            ]))
+        );
+
+        assert!(eval_code("sort_by({a: 1}, x => x)").is_err());
+        assert!(eval_code("reduce([1, 2], x => x)").is_err());
+    }
+
     #[test]
     fn test_minmax() {
         assert_eq!(eval_code("min(1, 2, -5, 4)"), Ok(DynamicValue::from(-5)));
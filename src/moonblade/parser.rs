@@ -257,6 +257,26 @@ fn pratt_parse(pairs: Pairs<Rule>) -> Result<Expr, String> {
                 Rule::false_lit => Expr::Bool(false),
                 Rule::null => Expr::Null,
                 Rule::expr => pratt_parse(primary.into_inner())?,
+                Rule::let_expr => {
+                    let mut pairs = primary.into_inner();
+                    let last_pair = pairs.next_back().unwrap();
+
+                    debug_assert!(matches!(last_pair.as_rule(), Rule::expr));
+
+                    let mut body = pratt_parse(last_pair.into_inner())?;
+
+                    for binding in pairs.rev() {
+                        let mut inner = binding.into_inner();
+                        inner.next(); // let keyword
+
+                        let name = inner.next().unwrap().as_str().to_string();
+                        let value = pratt_parse(inner.next().unwrap().into_inner())?;
+
+                        body = Expr::Let(name, Box::new(value), Box::new(body));
+                    }
+
+                    body
+                }
                 Rule::func_chain => pratt_parse(primary.into_inner())?,
                 Rule::lambda => {
                     let mut pairs = primary.into_inner();
@@ -405,6 +425,13 @@ impl FunctionCall {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Slice<T> {
     Full,
@@ -419,6 +446,8 @@ pub enum Expr {
     Pipeline(Vec<Expr>),
     Lambda(Vec<String>, Box<Expr>),
     LambdaBinding(String),
+    Let(String, Box<Expr>, Box<Expr>),
+    Define(Vec<FunctionDefinition>, Box<Expr>),
     Int(i64),
     Float(f64),
     Identifier(String, bool),
@@ -456,6 +485,33 @@ impl Expr {
                     expr.bind_lambda_args(names);
                 }
             }
+            Self::Let(name, value, body) => {
+                value.bind_lambda_args(names);
+
+                if names.contains(name) {
+                    // NOTE: the let binding shadows the lambda argument
+                    let unshadowed = names.iter().filter(|n| *n != name).cloned().collect();
+                    body.bind_lambda_args(&unshadowed);
+                } else {
+                    body.bind_lambda_args(names);
+                }
+            }
+            Self::Define(_, body) => body.bind_lambda_args(names),
+            Self::Pipeline(exprs) => {
+                for expr in exprs.iter_mut() {
+                    expr.bind_lambda_args(names);
+                }
+            }
+            Self::Lambda(inner_names, body) => {
+                // NOTE: nested lambdas can capture arguments of enclosing
+                // ones, unless they shadow them
+                let captured = names
+                    .iter()
+                    .filter(|n| !inner_names.contains(n))
+                    .cloned()
+                    .collect();
+                body.bind_lambda_args(&captured);
+            }
             _ => (),
         };
     }
@@ -485,6 +541,11 @@ impl Expr {
                     expr.simplify();
                 }
             }
+            Self::Let(_, value, body) => {
+                value.simplify();
+                body.simplify();
+            }
+            Self::Define(_, body) => body.simplify(),
             _ => (),
         };
     }
@@ -506,6 +567,11 @@ impl Expr {
                     value.count_underscores(counter);
                 }
             }
+            Expr::Let(_, value, body) => {
+                value.count_underscores(counter);
+                body.count_underscores(counter);
+            }
+            Expr::Define(_, body) => body.count_underscores(counter),
             Expr::Underscore => {
                 *counter += 1;
             }
@@ -530,6 +596,11 @@ impl Expr {
                     value.fill_underscore(with);
                 }
             }
+            Expr::Let(_, value, body) => {
+                value.fill_underscore(with);
+                body.fill_underscore(with);
+            }
+            Expr::Define(_, body) => body.fill_underscore(with),
             Expr::Underscore => {
                 *self = with.clone();
             }
@@ -603,12 +674,73 @@ impl From<String> for ParseError {
     }
 }
 
+fn parse_function_definition(pair: Pair<Rule>) -> Result<FunctionDefinition, ParseError> {
+    debug_assert!(matches!(pair.as_rule(), Rule::fn_def));
+
+    let mut inner = pair.into_inner();
+    inner.next(); // fn keyword
+
+    let name = inner.next().unwrap().as_str().to_lowercase();
+    let params = inner
+        .next()
+        .unwrap()
+        .into_inner()
+        .map(|p| p.as_str().to_string())
+        .collect::<Vec<_>>();
+
+    for (i, param) in params.iter().enumerate() {
+        if params[..i].contains(param) {
+            return Err(ParseError::Custom(format!(
+                "duplicate parameter \"{}\" in definition of function \"{}\"",
+                param, name
+            )));
+        }
+    }
+
+    let body = pratt_parse(inner.next().unwrap().into_inner())?;
+
+    Ok(FunctionDefinition { name, params, body })
+}
+
+// NOTE: function definitions can only appear at the beginning of a program,
+// and are attached to every expression of the program so they can be inlined
+// when concretizing.
+fn parse_function_definitions<'a>(
+    pairs: &mut std::iter::Peekable<Pairs<'a, Rule>>,
+) -> Result<Vec<FunctionDefinition>, ParseError> {
+    let mut definitions = Vec::new();
+
+    while let Some(pair) = pairs.next_if(|p| matches!(p.as_rule(), Rule::fn_def)) {
+        definitions.push(parse_function_definition(pair)?);
+    }
+
+    Ok(definitions)
+}
+
+fn attach_function_definitions(definitions: &[FunctionDefinition], expr: Expr) -> Expr {
+    if definitions.is_empty() {
+        expr
+    } else {
+        Expr::Define(definitions.to_vec(), Box::new(expr))
+    }
+}
+
 pub fn parse_expression(input: &str) -> Result<Expr, ParseError> {
-    let mut pairs = MoonbladePestParser::parse(Rule::full_expr, input)?;
+    let mut pairs = MoonbladePestParser::parse(Rule::full_expr, input)?.peekable();
 
+    let definitions = parse_function_definitions(&mut pairs)?;
     let first_pair = pairs.next().unwrap();
 
-    Ok(pratt_parse(Pairs::single(first_pair))?)
+    Ok(attach_function_definitions(
+        &definitions,
+        pratt_parse(Pairs::single(first_pair))?,
+    ))
+}
+
+pub fn parse_prelude(input: &str) -> Result<Vec<FunctionDefinition>, ParseError> {
+    let mut pairs = MoonbladePestParser::parse(Rule::prelude, input)?.peekable();
+
+    parse_function_definitions(&mut pairs)
 }
 
 fn parse_expression_name(pair: Pair<Rule>) -> String {
@@ -623,7 +755,9 @@ fn parse_expression_name(pair: Pair<Rule>) -> String {
 }
 
 pub fn parse_named_expressions(input: &str) -> Result<Vec<(Expr, String)>, ParseError> {
-    let pairs = MoonbladePestParser::parse(Rule::named_exprs, input)?;
+    let mut pairs = MoonbladePestParser::parse(Rule::named_exprs, input)?.peekable();
+
+    let definitions = parse_function_definitions(&mut pairs)?;
 
     pairs
         .filter(|p| !matches!(p.as_rule(), Rule::EOI))
@@ -649,7 +783,7 @@ pub fn parse_named_expressions(input: &str) -> Result<Vec<(Expr, String)>, Parse
 
             let expr = pratt_parse(Pairs::single(p))?;
 
-            Ok((expr, name))
+            Ok((attach_function_definitions(&definitions, expr), name))
         })
         .collect()
 }
@@ -664,7 +798,9 @@ pub struct Aggregation {
 pub type Aggregations = Vec<Aggregation>;
 
 pub fn parse_aggregations(input: &str) -> Result<Aggregations, ParseError> {
-    let pairs = MoonbladePestParser::parse(Rule::named_aggs, input)?;
+    let mut pairs = MoonbladePestParser::parse(Rule::named_aggs, input)?.peekable();
+
+    let definitions = parse_function_definitions(&mut pairs)?;
 
     pairs
         .filter(|p| !matches!(p.as_rule(), Rule::EOI))
@@ -688,16 +824,157 @@ pub fn parse_aggregations(input: &str) -> Result<Aggregations, ParseError> {
                 _ => unreachable!(),
             };
 
-            let expr = pratt_parse(Pairs::single(p))?;
+            parse_aggregation(&definitions, agg_name, p)
+        })
+        .collect()
+}
 
-            match expr {
-                Expr::Func(call) => Ok(Aggregation {
-                    agg_name,
-                    args: call.args.into_iter().map(|(_, arg)| arg).collect(),
-                    func_name: call.name,
-                }),
-                _ => unreachable!(),
+fn parse_aggregation(
+    definitions: &[FunctionDefinition],
+    agg_name: String,
+    pair: Pair<Rule>,
+) -> Result<Aggregation, ParseError> {
+    let expr = pratt_parse(Pairs::single(pair))?;
+
+    match expr {
+        Expr::Func(call) => Ok(Aggregation {
+            agg_name,
+            args: call
+                .args
+                .into_iter()
+                .map(|(_, arg)| attach_function_definitions(definitions, arg))
+                .collect(),
+            func_name: call.name,
+        }),
+        _ => unreachable!(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameBound {
+    Unbounded,
+    Offset(isize),
+}
+
+// NOTE: frames are expressed as offsets relative to the current row, e.g.
+// "2 preceding and 1 following" becomes [-2, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowFrame {
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+fn parse_frame_bound(pair: Pair<Rule>) -> Result<(FrameBound, bool), ParseError> {
+    let mut inner = pair.into_inner();
+    let first = inner.next().unwrap();
+
+    if matches!(first.as_rule(), Rule::current_row) {
+        return Ok((FrameBound::Offset(0), false));
+    }
+
+    let is_preceding = matches!(inner.next().unwrap().as_rule(), Rule::preceding);
+
+    let bound = match first.as_rule() {
+        Rule::unbounded_keyword => FrameBound::Unbounded,
+        Rule::int => {
+            let n = first
+                .as_str()
+                .replace('_', "")
+                .parse::<isize>()
+                .map_err(|_| {
+                    ParseError::Custom(format!("invalid frame offset {}", first.as_str()))
+                })?;
+
+            FrameBound::Offset(if is_preceding { -n } else { n })
+        }
+        _ => unreachable!(),
+    };
+
+    Ok((bound, is_preceding))
+}
+
+impl WindowFrame {
+    fn from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
+        let text = pair.as_str().to_string();
+        let mut bounds = pair.into_inner().skip(1).step_by(2);
+
+        let (first, first_is_preceding) = parse_frame_bound(bounds.next().unwrap())?;
+
+        let frame = match bounds.next() {
+            // NOTE: like in SQL, a single bound means the frame goes up to the
+            // current row, or starts from it.
+            None => {
+                if first_is_preceding {
+                    Self {
+                        start: first,
+                        end: FrameBound::Offset(0),
+                    }
+                } else {
+                    Self {
+                        start: FrameBound::Offset(0),
+                        end: first,
+                    }
+                }
+            }
+            Some(pair) => {
+                let (second, second_is_preceding) = parse_frame_bound(pair)?;
+
+                let is_valid = match (first, second) {
+                    (FrameBound::Unbounded, FrameBound::Unbounded) => {
+                        first_is_preceding && !second_is_preceding
+                    }
+                    (FrameBound::Unbounded, _) => first_is_preceding,
+                    (_, FrameBound::Unbounded) => !second_is_preceding,
+                    (FrameBound::Offset(a), FrameBound::Offset(b)) => a <= b,
+                };
+
+                if !is_valid {
+                    return Err(ParseError::Custom(format!(
+                        "invalid window frame \"{}\"",
+                        text
+                    )));
+                }
+
+                Self {
+                    start: first,
+                    end: second,
+                }
             }
+        };
+
+        Ok(frame)
+    }
+}
+
+pub type WindowAggregations = Vec<(Aggregation, Option<WindowFrame>)>;
+
+pub fn parse_window_aggregations(input: &str) -> Result<WindowAggregations, ParseError> {
+    let mut pairs = MoonbladePestParser::parse(Rule::named_window_aggs, input)?.peekable();
+
+    let definitions = parse_function_definitions(&mut pairs)?;
+
+    pairs
+        .filter(|p| !matches!(p.as_rule(), Rule::EOI))
+        .map(|p| {
+            let (agg_name, p) = match p.as_rule() {
+                Rule::window_func => (p.as_span().as_str().to_string(), p),
+                Rule::named_window_func => {
+                    let mut inner = p.into_inner();
+
+                    let window_func = inner.next().unwrap();
+                    let name = parse_expression_name(inner.next().unwrap());
+
+                    (name, window_func)
+                }
+                _ => unreachable!(),
+            };
+
+            let mut inner = p.into_inner();
+            let func = inner.next().unwrap();
+
+            let frame = inner.next().map(WindowFrame::from_pair).transpose()?;
+
+            Ok((parse_aggregation(&definitions, agg_name, func)?, frame))
         })
         .collect()
 }
@@ -1015,6 +1292,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_let_bindings() {
+        assert_eq!(
+            parse_expression("let x = a + 1; let y = x * 2; y"),
+            Ok(Let(
+                "x".to_string(),
+                Box::new(func("add", vec![id("a"), Int(1)])),
+                Box::new(Let(
+                    "y".to_string(),
+                    Box::new(func("mul", vec![id("x"), Int(2)])),
+                    Box::new(id("y"))
+                ))
+            ))
+        );
+
+        assert_eq!(
+            parse_expression("map(l, x => let y = x; y + x)"),
+            Ok(func(
+                "map",
+                vec![
+                    id("l"),
+                    lambda(
+                        vec!["x"],
+                        Let(
+                            "y".to_string(),
+                            Box::new(lb("x")),
+                            Box::new(func("add", vec![id("y"), lb("x")]))
+                        )
+                    )
+                ]
+            ))
+        );
+
+        assert_eq!(parse_expression("letter"), Ok(id("letter")));
+    }
+
+    #[test]
+    fn test_function_definitions() {
+        let definition = FunctionDefinition {
+            name: "double".to_string(),
+            params: vec!["x".to_string()],
+            body: func("mul", vec![id("x"), Int(2)]),
+        };
+
+        assert_eq!(
+            parse_expression("fn double(x) = x * 2; double(a)"),
+            Ok(Define(
+                vec![definition.clone()],
+                Box::new(func("double", vec![id("a")]))
+            ))
+        );
+
+        assert_eq!(
+            parse_prelude("# helpers\nfn double(x) = x * 2;\nfn zero() = 0;"),
+            Ok(vec![
+                definition,
+                FunctionDefinition {
+                    name: "zero".to_string(),
+                    params: vec![],
+                    body: Int(0)
+                }
+            ])
+        );
+
+        assert!(parse_expression("fn f(x, x) = x; f(1, 2)").is_err());
+    }
+
     #[test]
     fn test_infix() {
         assert_eq!(
@@ -1207,6 +1551,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_window_aggregations() {
+        let frames = |input: &str| {
+            parse_window_aggregations(input)
+                .map(|aggs| aggs.into_iter().map(|(_, frame)| frame).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            parse_window_aggregations("median(x) over 5 preceding and 5 following as m"),
+            Ok(vec![(
+                Aggregation {
+                    agg_name: "m".to_string(),
+                    func_name: "median".to_string(),
+                    args: vec![id("x")]
+                },
+                Some(WindowFrame {
+                    start: FrameBound::Offset(-5),
+                    end: FrameBound::Offset(5)
+                })
+            )])
+        );
+
+        assert_eq!(
+            frames("sum(x), sum(x) over unbounded preceding, sum(x) over 2 following"),
+            Ok(vec![
+                None,
+                Some(WindowFrame {
+                    start: FrameBound::Unbounded,
+                    end: FrameBound::Offset(0)
+                }),
+                Some(WindowFrame {
+                    start: FrameBound::Offset(0),
+                    end: FrameBound::Offset(2)
+                })
+            ])
+        );
+
+        assert_eq!(
+            frames("count() over current row and unbounded following"),
+            Ok(vec![Some(WindowFrame {
+                start: FrameBound::Offset(0),
+                end: FrameBound::Unbounded
+            })])
+        );
+
+        assert_eq!(
+            parse_window_aggregations("sum(x) over 5 preceding")
+                .unwrap()
+                .remove(0)
+                .0
+                .agg_name,
+            "sum(x) over 5 preceding"
+        );
+
+        assert!(parse_window_aggregations("sum(x) over 1 following and 1 preceding").is_err());
+        assert!(
+            parse_window_aggregations("sum(x) over unbounded following and 1 following").is_err()
+        );
+        assert!(parse_window_aggregations("sum(x) over 5").is_err());
+    }
+
     #[test]
     fn test_named_expressions() {
         assert_eq!(
@@ -801,6 +801,7 @@ impl ScrapingProgram {
             headers_index: &self.headers_index,
             globals: None,
             lambda_variables: None,
+            locals: None,
             last_value: None,
         };
 
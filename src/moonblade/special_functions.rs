@@ -1,15 +1,19 @@
 // NOTE: the runtime function take a &[ConcreteExpr] instead of BoundArguments
 // because they notoriously might want not to bind arguments in the first
 // place (e.g. "if"/"unless").
+use std::cmp::Ordering;
 use std::sync::Arc;
 
 use csv::ByteRecord;
 
+use crate::collections::{HashMap, HashSet};
+
 use super::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
 use super::interpreter::{ConcreteExpr, EvaluationContext};
 use super::parser::FunctionCall;
 use super::types::{
-    Arity, ColumIndexationBy, DynamicValue, EvaluationResult, FunctionArguments, LambdaArguments,
+    Arity, ColumIndexationBy, DynamicNumber, DynamicValue, EvaluationResult, FunctionArguments,
+    LambdaArguments,
 };
 
 pub type ComptimeFunctionResult = Result<Option<ConcreteExpr>, ConcretizationError>;
@@ -155,6 +159,15 @@ pub fn get_special_function(
         "find_index" => higher_order_fn!("find_index", FindIndex),
         "all" => higher_order_fn!("all", All),
         "any" => higher_order_fn!("any", Any),
+        "flat_map" => higher_order_fn!("flat_map", FlatMap),
+        "sort_by" => higher_order_fn!("sort_by", SortBy),
+        "group_by" => higher_order_fn!("group_by", GroupBy),
+        "unique_by" => higher_order_fn!("unique_by", UniqueBy),
+        "reduce" | "fold" => (
+            None,
+            Some(runtime_reduce),
+            FunctionArguments::with_range(2..=3),
+        ),
 
         _ => return None,
     })
@@ -458,14 +471,211 @@ fn runtime_warn(context: &EvaluationContext, args: &[ConcreteExpr]) -> Evaluatio
     Ok(DynamicValue::None)
 }
 
+// NOTE: lambdas given to higher-order functions can take an additional
+// argument receiving the index of the item, when iterating over a list, or
+// its key, when iterating over a map.
+struct LambdaCall<'a> {
+    context: &'a EvaluationContext<'a>,
+    lambda: &'a ConcreteExpr,
+    variables: LambdaArguments,
+    indices: Vec<usize>,
+}
+
+impl<'a> LambdaCall<'a> {
+    fn new(
+        context: &'a EvaluationContext<'a>,
+        arg: &'a ConcreteExpr,
+        arity: Arity,
+    ) -> Result<Self, SpecifiedEvaluationError> {
+        let (names, lambda) = arg.try_as_lambda().map_err(|err| err.anonymous())?;
+
+        // Validating arity
+        arity
+            .validate(names.len())
+            .map_err(|invalid_arity| EvaluationError::InvalidArity(invalid_arity).anonymous())?;
+
+        let mut variables = match context.lambda_variables {
+            None => LambdaArguments::new(),
+            Some(v) => v.clone(),
+        };
+
+        let indices = names.iter().map(|name| variables.register(name)).collect();
+
+        Ok(Self {
+            context,
+            lambda,
+            variables,
+            indices,
+        })
+    }
+
+    fn bind(&mut self, position: usize, value: DynamicValue) {
+        if let Some(i) = self.indices.get(position) {
+            self.variables.set(*i, value);
+        }
+    }
+
+    // NOTE: the value is only computed if the lambda actually needs it
+    fn bind_with<F>(&mut self, position: usize, callback: F)
+    where
+        F: FnOnce() -> DynamicValue,
+    {
+        if let Some(i) = self.indices.get(position) {
+            self.variables.set(*i, callback());
+        }
+    }
+
+    fn evaluate(&self) -> EvaluationResult {
+        self.lambda
+            .evaluate(&self.context.with_lambda_variables(&self.variables))
+    }
+
+    fn call(&mut self, item: &DynamicValue, key: &Key<'_>) -> EvaluationResult {
+        self.call_owned(item.clone(), key)
+    }
+
+    fn call_owned(&mut self, item: DynamicValue, key: &Key<'_>) -> EvaluationResult {
+        self.bind(0, item);
+        self.bind_with(1, || key.to_value());
+        self.evaluate()
+    }
+}
+
+enum Key<'a> {
+    Index(usize),
+    Name(&'a str),
+}
+
+impl Key<'_> {
+    fn to_value(&self) -> DynamicValue {
+        match self {
+            Self::Index(i) => DynamicValue::from(*i),
+            Self::Name(name) => DynamicValue::from(*name),
+        }
+    }
+}
+
+enum Collection {
+    List(Arc<Vec<DynamicValue>>),
+    Map(Arc<HashMap<String, DynamicValue>>),
+}
+
+impl Collection {
+    fn from_value(value: DynamicValue, name: &str) -> Result<Self, SpecifiedEvaluationError> {
+        match value {
+            DynamicValue::List(list) => Ok(Self::List(list)),
+            DynamicValue::Map(map) => Ok(Self::Map(map)),
+            _ => Err(EvaluationError::from_cast(&value, "list").specify(name)),
+        }
+    }
+
+    // NOTE: map entries are iterated over in key order, so that results
+    // remain deterministic.
+    fn iter(&self) -> Box<dyn Iterator<Item = (Key<'_>, &DynamicValue)> + '_> {
+        match self {
+            Self::List(list) => Box::new(
+                list.iter()
+                    .enumerate()
+                    .map(|(i, item)| (Key::Index(i), item)),
+            ),
+            Self::Map(map) => {
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+
+                Box::new(entries.into_iter().map(|(k, v)| (Key::Name(k.as_str()), v)))
+            }
+        }
+    }
+
+    fn is_map(&self) -> bool {
+        matches!(self, Self::Map(_))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::List(list) => list.len(),
+            Self::Map(map) => map.len(),
+        }
+    }
+}
+
+// NOTE: keys used to sort items are totally ordered so that sorting can never
+// be inconsistent: numbers come first, then strings, then lists, then nulls.
+enum SortKey {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<SortKey>),
+    Null,
+}
+
+impl SortKey {
+    fn try_from_value(value: &DynamicValue) -> Result<Self, EvaluationError> {
+        Ok(match value {
+            DynamicValue::None => Self::Null,
+            DynamicValue::List(list) => Self::List(
+                list.iter()
+                    .map(Self::try_from_value)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            _ => match value.try_as_number() {
+                Ok(DynamicNumber::Integer(n)) => Self::Integer(n),
+                Ok(DynamicNumber::Float(f)) => Self::Float(f),
+                Err(_) => Self::String(value.try_as_str()?.into_owned()),
+            },
+        })
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Integer(_) | Self::Float(_) => 0,
+            Self::String(_) => 1,
+            Self::List(_) => 2,
+            Self::Null => 3,
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Float(b)) => (*a as f64).total_cmp(b),
+            (Self::Float(a), Self::Integer(b)) => a.total_cmp(&(*b as f64)),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+            (Self::List(a), Self::List(b)) => {
+                for (x, y) in a.iter().zip(b.iter()) {
+                    let ordering = x.compare(y);
+
+                    if ordering.is_ne() {
+                        return ordering;
+                    }
+                }
+
+                a.len().cmp(&b.len())
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum HigherOrderOperation {
     Filter,
     Map,
+    FlatMap,
     Find,
     FindIndex,
     All,
     Any,
+    SortBy,
+    GroupBy,
+    UniqueBy,
+}
+
+impl HigherOrderOperation {
+    fn accepts_maps(&self) -> bool {
+        !matches!(self, Self::SortBy | Self::GroupBy | Self::UniqueBy)
+    }
 }
 
 fn runtime_higher_order(
@@ -474,78 +684,91 @@ fn runtime_higher_order(
     name: &str,
     op: HigherOrderOperation,
 ) -> EvaluationResult {
-    let list = args
-        .first()
-        .unwrap()
-        .evaluate(context)?
-        .try_into_arc_list()
-        .map_err(|err| err.specify(name))?;
-
-    let (names, lambda) = args
-        .get(1)
-        .unwrap()
-        .try_as_lambda()
-        .map_err(|err| err.anonymous())?;
-
-    // Validating arity
-    Arity::Strict(1)
-        .validate(names.len())
-        .map_err(|invalid_arity| EvaluationError::InvalidArity(invalid_arity).anonymous())?;
-
-    let arg_name = names.first().unwrap();
-
-    let mut variables = match context.lambda_variables {
-        None => LambdaArguments::new(),
-        Some(v) => v.clone(),
+    let value = args.first().unwrap().evaluate(context)?;
+
+    let collection = if op.accepts_maps() {
+        Collection::from_value(value, name)?
+    } else {
+        Collection::List(value.try_into_arc_list().map_err(|err| err.specify(name))?)
     };
 
-    let item_arg_index = variables.register(arg_name);
+    let mut lambda = LambdaCall::new(context, args.get(1).unwrap(), Arity::Range(1..=2))?;
 
     match op {
-        HigherOrderOperation::Map => {
-            let mut new_list = Vec::with_capacity(list.len());
-
-            match Arc::try_unwrap(list) {
-                Ok(owned_list) => {
-                    for item in owned_list {
-                        variables.set(item_arg_index, item);
-
-                        let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
-                        new_list.push(result);
+        HigherOrderOperation::Map => match collection {
+            Collection::List(list) => {
+                let mut new_list = Vec::with_capacity(list.len());
+
+                match Arc::try_unwrap(list) {
+                    Ok(owned_list) => {
+                        for (i, item) in owned_list.into_iter().enumerate() {
+                            new_list.push(lambda.call_owned(item, &Key::Index(i))?);
+                        }
+                    }
+                    Err(borrowed_list) => {
+                        for (i, item) in borrowed_list.iter().enumerate() {
+                            new_list.push(lambda.call(item, &Key::Index(i))?);
+                        }
                     }
                 }
-                Err(borrowed_list) => {
-                    for item in borrowed_list.iter() {
-                        variables.set(item_arg_index, item.clone());
 
-                        let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
-                        new_list.push(result);
+                Ok(DynamicValue::from(new_list))
+            }
+            collection @ Collection::Map(_) => {
+                let mut new_map = HashMap::with_capacity(collection.len());
+
+                for (key, value) in collection.iter() {
+                    let result = lambda.call(value, &key)?;
+
+                    if let Key::Name(k) = key {
+                        new_map.insert(k.to_string(), result);
                     }
                 }
-            }
 
-            Ok(DynamicValue::from(new_list))
-        }
+                Ok(DynamicValue::from(new_map))
+            }
+        },
         HigherOrderOperation::Filter => {
             let mut new_list = Vec::new();
+            let mut new_map = HashMap::new();
 
-            for item in list.iter() {
-                variables.set(item_arg_index, item.clone());
-
-                let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
 
                 if result.is_truthy() {
-                    new_list.push(item.clone());
+                    match key {
+                        Key::Index(_) => new_list.push(item.clone()),
+                        Key::Name(k) => {
+                            new_map.insert(k.to_string(), item.clone());
+                        }
+                    }
+                }
+            }
+
+            Ok(if collection.is_map() {
+                DynamicValue::from(new_map)
+            } else {
+                DynamicValue::from(new_list)
+            })
+        }
+        HigherOrderOperation::FlatMap => {
+            let mut new_list = Vec::with_capacity(collection.len());
+
+            for (key, item) in collection.iter() {
+                match lambda.call(item, &key)? {
+                    DynamicValue::List(sublist) => match Arc::try_unwrap(sublist) {
+                        Ok(owned_sublist) => new_list.extend(owned_sublist),
+                        Err(borrowed_sublist) => new_list.extend(borrowed_sublist.iter().cloned()),
+                    },
+                    result => new_list.push(result),
                 }
             }
 
             Ok(DynamicValue::from(new_list))
         }
         HigherOrderOperation::Find => {
-            for item in list.iter() {
-                variables.set(item_arg_index, item.clone());
-
-                let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
 
                 if result.is_truthy() {
                     return Ok(item.clone());
@@ -555,23 +778,19 @@ fn runtime_higher_order(
             Ok(DynamicValue::None)
         }
         HigherOrderOperation::FindIndex => {
-            for (i, item) in list.iter().enumerate() {
-                variables.set(item_arg_index, item.clone());
-
-                let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
 
                 if result.is_truthy() {
-                    return Ok(DynamicValue::from(i));
+                    return Ok(key.to_value());
                 }
             }
 
             Ok(DynamicValue::None)
         }
         HigherOrderOperation::All => {
-            for item in list.iter() {
-                variables.set(item_arg_index, item.clone());
-
-                let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
 
                 if result.is_falsey() {
                     return Ok(DynamicValue::Boolean(false));
@@ -581,10 +800,8 @@ fn runtime_higher_order(
             Ok(DynamicValue::Boolean(true))
         }
         HigherOrderOperation::Any => {
-            for item in list.iter() {
-                variables.set(item_arg_index, item.clone());
-
-                let result = lambda.evaluate(&context.with_lambda_variables(&variables))?;
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
 
                 if result.is_truthy() {
                     return Ok(DynamicValue::Boolean(true));
@@ -593,5 +810,90 @@ fn runtime_higher_order(
 
             Ok(DynamicValue::Boolean(false))
         }
+        HigherOrderOperation::SortBy => {
+            // NOTE: keys are only computed once per item
+            let mut keyed = Vec::with_capacity(collection.len());
+
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
+                let sort_key = SortKey::try_from_value(&result).map_err(|err| err.specify(name))?;
+
+                keyed.push((sort_key, item));
+            }
+
+            keyed.sort_by(|a, b| a.0.compare(&b.0));
+
+            Ok(DynamicValue::from(
+                keyed
+                    .into_iter()
+                    .map(|(_, item)| item.clone())
+                    .collect::<Vec<_>>(),
+            ))
+        }
+        HigherOrderOperation::GroupBy => {
+            let mut groups: HashMap<String, Vec<DynamicValue>> = HashMap::new();
+
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
+                let group = result.try_as_str().map_err(|err| err.specify(name))?;
+
+                match groups.get_mut(group.as_ref()) {
+                    Some(group_items) => group_items.push(item.clone()),
+                    None => {
+                        groups.insert(group.into_owned(), vec![item.clone()]);
+                    }
+                }
+            }
+
+            Ok(DynamicValue::from(
+                groups
+                    .into_iter()
+                    .map(|(k, v)| (k, DynamicValue::from(v)))
+                    .collect::<HashMap<_, _>>(),
+            ))
+        }
+        HigherOrderOperation::UniqueBy => {
+            let mut seen: HashSet<String> = HashSet::new();
+            let mut new_list = Vec::new();
+
+            for (key, item) in collection.iter() {
+                let result = lambda.call(item, &key)?;
+                let unique_key = result.try_as_str().map_err(|err| err.specify(name))?;
+
+                if !seen.contains(unique_key.as_ref()) {
+                    seen.insert(unique_key.into_owned());
+                    new_list.push(item.clone());
+                }
+            }
+
+            Ok(DynamicValue::from(new_list))
+        }
     }
 }
+
+fn runtime_reduce(context: &EvaluationContext, args: &[ConcreteExpr]) -> EvaluationResult {
+    let collection = Collection::from_value(args.first().unwrap().evaluate(context)?, "reduce")?;
+
+    let mut lambda = LambdaCall::new(context, args.get(1).unwrap(), Arity::Range(2..=3))?;
+
+    let mut entries = collection.iter();
+
+    // NOTE: without an initial value, the first item is used instead
+    let mut accumulator = match args.get(2) {
+        Some(arg) => arg.evaluate(context)?,
+        None => match entries.next() {
+            Some((_, item)) => item.clone(),
+            None => return Ok(DynamicValue::None),
+        },
+    };
+
+    for (key, item) in entries {
+        lambda.bind(0, accumulator);
+        lambda.bind(1, item.clone());
+        lambda.bind_with(2, || key.to_value());
+
+        accumulator = lambda.evaluate()?;
+    }
+
+    Ok(accumulator)
+}
@@ -124,18 +124,17 @@ impl IntoIterator for BoundArguments {
 }
 
 pub const BOUND_ARGUMENTS_CAPACITY: usize = 8;
-const LAMBDA_ARGUMENTS_CAPACITY: usize = 4;
 
+// NOTE: nested lambdas can bind any number of variables, so the stack must
+// be able to grow.
 #[derive(Clone, Debug)]
 pub struct LambdaArguments {
-    stack: ArrayVec<(String, DynamicValue), LAMBDA_ARGUMENTS_CAPACITY>,
+    stack: Vec<(String, DynamicValue)>,
 }
 
 impl LambdaArguments {
     pub fn new() -> Self {
-        Self {
-            stack: ArrayVec::new(),
-        }
+        Self { stack: Vec::new() }
     }
 
     pub fn get(&self, name: &str) -> &DynamicValue {
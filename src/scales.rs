@@ -693,6 +693,22 @@ impl Scale {
         }
     }
 
+    // NOTE: returns the ticks along with their position, as a percentage of
+    // the axis, so they can be placed precisely, e.g. when drawing a svg.
+    pub fn positioned_ticks(&self, count: usize) -> Vec<(f64, String)> {
+        let ticks = match self {
+            Self::Linear(inner) => inner.ticks(count),
+            Self::Log(inner) => inner.ticks(count),
+            Self::Time(inner) => inner.ticks(count),
+        };
+
+        ticks
+            .iter()
+            .map(|tick| self.percent(*tick))
+            .zip(self.formatted_ticks(count))
+            .collect()
+    }
+
     pub fn percent(&self, value: f64) -> f64 {
         match self {
             Self::Linear(inner) => inner.percent(value),
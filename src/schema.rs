@@ -0,0 +1,1202 @@
+// Schemas describing the expected shape of CSV files, that can be inferred
+// from data, validated against & converted from/to Frictionless Table Schema
+// and JSON Schema.
+//
+// NOTE: patterns are always full-match, as in Frictionless Table Schema.
+use std::cmp::Ordering;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::collections::HashSet;
+use crate::dates;
+use crate::moonblade::agg::aggregators::Types;
+use crate::moonblade::DynamicNumber;
+use crate::util;
+
+const FRICTIONLESS_TRUE_VALUES: [&str; 4] = ["true", "True", "TRUE", "1"];
+const FRICTIONLESS_FALSE_VALUES: [&str; 4] = ["false", "False", "FALSE", "0"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    Int,
+    Float,
+    Date,
+    Url,
+    Boolean,
+    String,
+    Any,
+}
+
+impl FieldType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Date => "date",
+            Self::Url => "url",
+            Self::Boolean => "boolean",
+            Self::String => "string",
+            Self::Any => "any",
+        }
+    }
+
+    fn as_frictionless(&self) -> (&str, Option<&str>) {
+        match self {
+            Self::Int => ("integer", None),
+            Self::Float => ("number", None),
+            Self::Date => ("datetime", Some("any")),
+            Self::Url => ("string", Some("uri")),
+            Self::Boolean => ("boolean", None),
+            Self::String => ("string", None),
+            Self::Any => ("any", None),
+        }
+    }
+
+    fn as_json_schema(&self) -> (Option<&str>, Option<&str>) {
+        match self {
+            Self::Int => (Some("integer"), None),
+            Self::Float => (Some("number"), None),
+            Self::Date => (Some("string"), Some("date-time")),
+            Self::Url => (Some("string"), Some("uri")),
+            Self::Boolean => (Some("boolean"), None),
+            Self::String => (Some("string"), None),
+            Self::Any => (None, None),
+        }
+    }
+
+    // NOTE: this accepts native, Frictionless & JSON Schema type names alike
+    fn parse(name: &str, format: Option<&str>) -> Option<Self> {
+        Some(match (name, format) {
+            ("string", Some("uri" | "url")) => Self::Url,
+            ("string", Some("date" | "date-time" | "time")) => Self::Date,
+            ("int" | "integer", _) => Self::Int,
+            ("float" | "number", _) => Self::Float,
+            ("date" | "datetime" | "time" | "year" | "yearmonth", _) => Self::Date,
+            ("url", _) => Self::Url,
+            ("bool" | "boolean", _) => Self::Boolean,
+            ("string", _) => Self::String,
+            ("any", _) => Self::Any,
+            _ => return None,
+        })
+    }
+
+    fn matches(&self, cell: &str) -> bool {
+        match self {
+            Self::Int => matches!(cell.parse::<DynamicNumber>(), Ok(n) if !n.is_float()),
+            Self::Float => cell.parse::<DynamicNumber>().is_ok(),
+            Self::Date => dates::could_be_date(cell),
+            Self::Url => util::could_be_url(cell),
+            Self::Boolean => {
+                FRICTIONLESS_TRUE_VALUES.contains(&cell)
+                    || FRICTIONLESS_FALSE_VALUES.contains(&cell)
+            }
+            Self::String | Self::Any => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub field_type: FieldType,
+    pub nullable: bool,
+    pub unique: bool,
+    pub min: Option<DynamicNumber>,
+    pub max: Option<DynamicNumber>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<String>,
+    pub enum_values: Option<Vec<String>>,
+}
+
+impl Field {
+    fn new(name: &str, field_type: FieldType) -> Self {
+        Self {
+            name: name.to_string(),
+            field_type,
+            nullable: true,
+            unique: false,
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            enum_values: None,
+        }
+    }
+}
+
+fn number_to_json(number: DynamicNumber) -> Value {
+    match number {
+        DynamicNumber::Integer(n) => json!(n),
+        DynamicNumber::Float(f) => json!(f),
+    }
+}
+
+fn compare_numbers(a: DynamicNumber, b: DynamicNumber) -> Option<Ordering> {
+    match (a, b) {
+        (DynamicNumber::Integer(a), DynamicNumber::Integer(b)) => Some(a.cmp(&b)),
+        _ => a.as_float().partial_cmp(&b.as_float()),
+    }
+}
+
+fn json_to_number(value: &Value, key: &str) -> Result<DynamicNumber, String> {
+    match value {
+        Value::Number(n) => Ok(match n.as_i64() {
+            Some(i) => DynamicNumber::Integer(i),
+            None => DynamicNumber::Float(n.as_f64().unwrap()),
+        }),
+        Value::String(s) => s
+            .parse::<DynamicNumber>()
+            .map_err(|_| format!("\"{}\" should be a number, got \"{}\"", key, s)),
+        _ => Err(format!("\"{}\" should be a number", key)),
+    }
+}
+
+fn json_to_usize(value: &Value, key: &str) -> Result<usize, String> {
+    value
+        .as_u64()
+        .map(|n| n as usize)
+        .ok_or_else(|| format!("\"{}\" should be a positive integer", key))
+}
+
+fn json_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        Value::Null => "".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn json_to_enum(value: &Value) -> Result<Vec<String>, String> {
+    Ok(value
+        .as_array()
+        .ok_or("\"enum\" should be a list")?
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(json_to_string)
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaFormat {
+    Xan,
+    Frictionless,
+    JsonSchema,
+}
+
+impl SchemaFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "xan" => Self::Xan,
+            "frictionless" => Self::Frictionless,
+            "json-schema" => Self::JsonSchema,
+            _ => return Err(format!("unknown schema format \"{}\"", name)),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+    pub missing_values: Vec<String>,
+}
+
+impl Schema {
+    pub fn from_json_str(string: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(string).map_err(|err| format!("invalid JSON: {}", err))?;
+
+        let object = value.as_object().ok_or("schema should be a JSON object")?;
+
+        if object.contains_key("properties") {
+            Self::from_json_schema(object)
+        } else if object.contains_key("fields") {
+            Self::from_fields(object)
+        } else {
+            Err("schema should either have \"fields\" (xan or Frictionless Table Schema) or \"properties\" (JSON Schema)".to_string())
+        }
+    }
+
+    // NOTE: xan schemas are very close to Frictionless Table Schemas, so both
+    // are read by this function. Constraints can therefore either be found
+    // directly on fields or inside a "constraints" object.
+    fn from_fields(object: &Map<String, Value>) -> Result<Self, String> {
+        let mut fields = Vec::new();
+
+        for raw_field in object["fields"]
+            .as_array()
+            .ok_or("\"fields\" should be a list")?
+        {
+            let raw_field = raw_field
+                .as_object()
+                .ok_or("fields should be JSON objects")?;
+
+            let name = raw_field
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("fields should have a \"name\"")?;
+
+            let type_name = raw_field
+                .get("type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("any");
+
+            let field_type =
+                FieldType::parse(type_name, raw_field.get("format").and_then(|v| v.as_str()))
+                    .ok_or_else(|| {
+                        format!("unknown type \"{}\" for field \"{}\"", type_name, name)
+                    })?;
+
+            let mut field = Field::new(name, field_type);
+
+            if let Some(nullable) = raw_field.get("nullable").and_then(|v| v.as_bool()) {
+                field.nullable = nullable;
+            }
+
+            let constraints = raw_field.get("constraints").and_then(|v| v.as_object());
+
+            let get = |key: &str, frictionless_key: &str| -> Option<&Value> {
+                raw_field
+                    .get(key)
+                    .or_else(|| constraints.and_then(|c| c.get(frictionless_key)))
+            };
+
+            if let Some(required) = constraints
+                .and_then(|c| c.get("required"))
+                .and_then(|v| v.as_bool())
+            {
+                field.nullable = !required;
+            }
+
+            if let Some(unique) = get("unique", "unique").and_then(|v| v.as_bool()) {
+                field.unique = unique;
+            }
+
+            if let Some(v) = get("min", "minimum") {
+                field.min = Some(json_to_number(v, "min")?);
+            }
+
+            if let Some(v) = get("max", "maximum") {
+                field.max = Some(json_to_number(v, "max")?);
+            }
+
+            if let Some(v) = get("min_length", "minLength") {
+                field.min_length = Some(json_to_usize(v, "min_length")?);
+            }
+
+            if let Some(v) = get("max_length", "maxLength") {
+                field.max_length = Some(json_to_usize(v, "max_length")?);
+            }
+
+            if let Some(v) = get("pattern", "pattern") {
+                field.pattern = Some(
+                    v.as_str()
+                        .ok_or("\"pattern\" should be a string")?
+                        .to_string(),
+                );
+            }
+
+            if let Some(v) = get("enum", "enum") {
+                field.enum_values = Some(json_to_enum(v)?);
+            }
+
+            fields.push(field);
+        }
+
+        let missing_values = match object
+            .get("missing_values")
+            .or_else(|| object.get("missingValues"))
+        {
+            None => vec!["".to_string()],
+            Some(v) => v
+                .as_array()
+                .ok_or("missing values should be a list")?
+                .iter()
+                .map(json_to_string)
+                .collect(),
+        };
+
+        Ok(Self {
+            fields,
+            missing_values,
+        })
+    }
+
+    // NOTE: here the schema is understood as describing a single row, as an
+    // object. Columns not listed as required, or accepting null, are nullable.
+    fn from_json_schema(object: &Map<String, Value>) -> Result<Self, String> {
+        let required = match object.get("required") {
+            None => Vec::new(),
+            Some(v) => v
+                .as_array()
+                .ok_or("\"required\" should be a list")?
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect(),
+        };
+
+        let mut fields = Vec::new();
+
+        for (name, property) in object["properties"]
+            .as_object()
+            .ok_or("\"properties\" should be an object")?
+        {
+            let property = property
+                .as_object()
+                .ok_or("properties should be JSON objects")?;
+
+            let mut nullable = !required.contains(&name.as_str());
+
+            let type_names = match property.get("type") {
+                None => vec![],
+                Some(Value::String(t)) => vec![t.as_str()],
+                Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+                _ => return Err(format!("invalid type for property \"{}\"", name)),
+            };
+
+            if type_names.contains(&"null") {
+                nullable = true;
+            }
+
+            let type_names = type_names
+                .into_iter()
+                .filter(|t| *t != "null")
+                .collect::<Vec<_>>();
+
+            let format = property.get("format").and_then(|v| v.as_str());
+
+            let field_type = match type_names.as_slice() {
+                [] => FieldType::Any,
+                [type_name] => FieldType::parse(type_name, format).ok_or_else(|| {
+                    format!(
+                        "unsupported type \"{}\" for property \"{}\"",
+                        type_name, name
+                    )
+                })?,
+                // NOTE: unions of numerical types can be represented as float
+                types if types.iter().all(|t| *t == "integer" || *t == "number") => {
+                    FieldType::Float
+                }
+                _ => FieldType::Any,
+            };
+
+            let mut field = Field::new(name, field_type);
+            field.nullable = nullable;
+
+            if let Some(v) = property.get("minimum") {
+                field.min = Some(json_to_number(v, "minimum")?);
+            }
+
+            if let Some(v) = property.get("maximum") {
+                field.max = Some(json_to_number(v, "maximum")?);
+            }
+
+            if let Some(v) = property.get("minLength") {
+                field.min_length = Some(json_to_usize(v, "minLength")?);
+            }
+
+            if let Some(v) = property.get("maxLength") {
+                field.max_length = Some(json_to_usize(v, "maxLength")?);
+            }
+
+            // NOTE: JSON Schema patterns are not anchored
+            if let Some(v) = property.get("pattern") {
+                field.pattern = Some(format!(
+                    "[\\s\\S]*(?:{})[\\s\\S]*",
+                    v.as_str().ok_or("\"pattern\" should be a string")?
+                ));
+            }
+
+            if let Some(v) = property.get("enum") {
+                field.enum_values = Some(json_to_enum(v)?);
+            }
+
+            fields.push(field);
+        }
+
+        Ok(Self {
+            fields,
+            missing_values: vec!["".to_string()],
+        })
+    }
+
+    pub fn to_json(&self, format: SchemaFormat) -> Value {
+        match format {
+            SchemaFormat::Xan => self.to_xan_json(),
+            SchemaFormat::Frictionless => self.to_frictionless_json(),
+            SchemaFormat::JsonSchema => self.to_json_schema(),
+        }
+    }
+
+    fn to_xan_json(&self) -> Value {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let mut object = Map::new();
+
+                object.insert("name".to_string(), json!(field.name));
+                object.insert("type".to_string(), json!(field.field_type.as_str()));
+                object.insert("nullable".to_string(), json!(field.nullable));
+
+                if field.unique {
+                    object.insert("unique".to_string(), json!(true));
+                }
+
+                if let Some(min) = field.min {
+                    object.insert("min".to_string(), number_to_json(min));
+                }
+
+                if let Some(max) = field.max {
+                    object.insert("max".to_string(), number_to_json(max));
+                }
+
+                if let Some(min_length) = field.min_length {
+                    object.insert("min_length".to_string(), json!(min_length));
+                }
+
+                if let Some(max_length) = field.max_length {
+                    object.insert("max_length".to_string(), json!(max_length));
+                }
+
+                if let Some(pattern) = &field.pattern {
+                    object.insert("pattern".to_string(), json!(pattern));
+                }
+
+                if let Some(enum_values) = &field.enum_values {
+                    object.insert("enum".to_string(), json!(enum_values));
+                }
+
+                Value::Object(object)
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "fields": fields })
+    }
+
+    fn to_frictionless_json(&self) -> Value {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let mut object = Map::new();
+                let (type_name, format) = field.field_type.as_frictionless();
+
+                object.insert("name".to_string(), json!(field.name));
+                object.insert("type".to_string(), json!(type_name));
+
+                if let Some(format) = format {
+                    object.insert("format".to_string(), json!(format));
+                }
+
+                let mut constraints = Map::new();
+
+                if !field.nullable {
+                    constraints.insert("required".to_string(), json!(true));
+                }
+
+                if field.unique {
+                    constraints.insert("unique".to_string(), json!(true));
+                }
+
+                if let Some(min) = field.min {
+                    constraints.insert("minimum".to_string(), number_to_json(min));
+                }
+
+                if let Some(max) = field.max {
+                    constraints.insert("maximum".to_string(), number_to_json(max));
+                }
+
+                if let Some(min_length) = field.min_length {
+                    constraints.insert("minLength".to_string(), json!(min_length));
+                }
+
+                if let Some(max_length) = field.max_length {
+                    constraints.insert("maxLength".to_string(), json!(max_length));
+                }
+
+                if let Some(pattern) = &field.pattern {
+                    constraints.insert("pattern".to_string(), json!(pattern));
+                }
+
+                if let Some(enum_values) = &field.enum_values {
+                    constraints.insert("enum".to_string(), json!(enum_values));
+                }
+
+                if !constraints.is_empty() {
+                    object.insert("constraints".to_string(), Value::Object(constraints));
+                }
+
+                Value::Object(object)
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "fields": fields, "missingValues": self.missing_values })
+    }
+
+    fn to_json_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for field in self.fields.iter() {
+            let mut object = Map::new();
+            let (type_name, format) = field.field_type.as_json_schema();
+
+            if let Some(type_name) = type_name {
+                if field.nullable {
+                    object.insert("type".to_string(), json!([type_name, "null"]));
+                } else {
+                    object.insert("type".to_string(), json!(type_name));
+                }
+            }
+
+            if let Some(format) = format {
+                object.insert("format".to_string(), json!(format));
+            }
+
+            if !field.nullable {
+                required.push(field.name.clone());
+            }
+
+            if let Some(min) = field.min {
+                object.insert("minimum".to_string(), number_to_json(min));
+            }
+
+            if let Some(max) = field.max {
+                object.insert("maximum".to_string(), number_to_json(max));
+            }
+
+            if let Some(min_length) = field.min_length {
+                object.insert("minLength".to_string(), json!(min_length));
+            }
+
+            if let Some(max_length) = field.max_length {
+                object.insert("maxLength".to_string(), json!(max_length));
+            }
+
+            if let Some(pattern) = &field.pattern {
+                object.insert("pattern".to_string(), json!(format!("^(?:{})$", pattern)));
+            }
+
+            if let Some(enum_values) = &field.enum_values {
+                object.insert("enum".to_string(), json!(enum_values));
+            }
+
+            properties.insert(field.name.clone(), Value::Object(object));
+        }
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": properties,
+            "required": required
+        })
+    }
+}
+
+// Inference
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Digit,
+    Upper,
+    Lower,
+    Literal(char),
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_ascii_digit() {
+            Self::Digit
+        } else if c.is_ascii_uppercase() {
+            Self::Upper
+        } else if c.is_ascii_lowercase() {
+            Self::Lower
+        } else {
+            Self::Literal(c)
+        }
+    }
+
+    fn as_regex(&self) -> String {
+        match self {
+            Self::Digit => "[0-9]".to_string(),
+            Self::Upper => "[A-Z]".to_string(),
+            Self::Lower => "[a-z]".to_string(),
+            Self::Literal(c) => regex::escape(&c.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    class: CharClass,
+    min: usize,
+    max: usize,
+}
+
+// NOTE: a shape is the sequence of runs of character classes of a string,
+// e.g. "AB-123" is [A-Z]{2}, "-", [0-9]{3}. A pattern can be inferred if all
+// the values of a column have the same shape, up to run lengths.
+#[derive(Debug)]
+enum Shape {
+    Unknown,
+    Consistent(Vec<Segment>),
+    Inconsistent,
+}
+
+impl Shape {
+    fn add(&mut self, cell: &str) {
+        let mut runs: Vec<(CharClass, usize)> = Vec::new();
+
+        for c in cell.chars() {
+            let class = CharClass::of(c);
+
+            match runs.last_mut() {
+                Some((last_class, count)) if *last_class == class => *count += 1,
+                _ => runs.push((class, 1)),
+            }
+        }
+
+        match self {
+            Self::Inconsistent => (),
+            Self::Unknown => {
+                *self = Self::Consistent(
+                    runs.into_iter()
+                        .map(|(class, count)| Segment {
+                            class,
+                            min: count,
+                            max: count,
+                        })
+                        .collect(),
+                );
+            }
+            Self::Consistent(segments) => {
+                if segments.len() != runs.len()
+                    || segments
+                        .iter()
+                        .zip(runs.iter())
+                        .any(|(segment, (class, _))| segment.class != *class)
+                {
+                    *self = Self::Inconsistent;
+                    return;
+                }
+
+                for (segment, (_, count)) in segments.iter_mut().zip(runs) {
+                    segment.min = segment.min.min(count);
+                    segment.max = segment.max.max(count);
+                }
+            }
+        }
+    }
+
+    fn to_pattern(&self) -> Option<String> {
+        match self {
+            // NOTE: single runs are not informative enough to be useful
+            Self::Consistent(segments) if segments.len() > 1 => Some(
+                segments
+                    .iter()
+                    .map(|segment| {
+                        let quantifier = match (segment.min, segment.max) {
+                            (1, 1) => "".to_string(),
+                            (min, max) if min == max => format!("{{{}}}", min),
+                            (min, max) => format!("{{{},{}}}", min, max),
+                        };
+
+                        segment.class.as_regex() + &quantifier
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InferenceOptions {
+    pub enum_threshold: usize,
+    pub patterns: bool,
+}
+
+#[derive(Debug)]
+struct FieldSketch {
+    types: Types,
+    count: usize,
+    extent: Option<(DynamicNumber, DynamicNumber)>,
+    length_extent: Option<(usize, usize)>,
+    values: Option<HashSet<String>>,
+    shape: Shape,
+}
+
+impl FieldSketch {
+    fn new(options: &InferenceOptions) -> Self {
+        Self {
+            types: Types::new(),
+            count: 0,
+            extent: None,
+            length_extent: None,
+            values: (options.enum_threshold > 0).then(HashSet::new),
+            shape: Shape::Unknown,
+        }
+    }
+
+    fn add(&mut self, cell: &str, options: &InferenceOptions) {
+        if cell.is_empty() {
+            self.types.set_empty();
+            return;
+        }
+
+        self.count += 1;
+
+        if let Some(number) = self.types.sniff(cell) {
+            if !number.is_nan() {
+                self.extent = Some(match self.extent {
+                    None => (number, number),
+                    Some((min, max)) => (
+                        if number < min { number } else { min },
+                        if number > max { number } else { max },
+                    ),
+                });
+            }
+        }
+
+        let length = cell.chars().count();
+
+        self.length_extent = Some(match self.length_extent {
+            None => (length, length),
+            Some((min, max)) => (min.min(length), max.max(length)),
+        });
+
+        // NOTE: we stop tracking distinct values as soon as there are too many
+        if let Some(values) = self.values.as_mut() {
+            if !values.contains(cell) {
+                if values.len() >= options.enum_threshold {
+                    self.values = None;
+                } else {
+                    values.insert(cell.to_string());
+                }
+            }
+        }
+
+        if options.patterns {
+            self.shape.add(cell);
+        }
+    }
+
+    fn into_field(self, name: &str) -> Field {
+        let field_type = match self.types.most_likely_type() {
+            Some("int") => FieldType::Int,
+            Some("float") => FieldType::Float,
+            Some("date") => FieldType::Date,
+            Some("url") => FieldType::Url,
+            _ => FieldType::String,
+        };
+
+        let mut field = Field::new(name, field_type);
+        field.nullable = self.count == 0 || self.types.has_empty();
+
+        match field_type {
+            FieldType::Int | FieldType::Float => {
+                if let Some((min, max)) = self.extent {
+                    field.min = Some(min);
+                    field.max = Some(max);
+                }
+            }
+            FieldType::String if self.count > 0 => {
+                // NOTE: a column is deemed categorical if at least some of its
+                // values are repeated
+                match self.values {
+                    Some(values) if values.len() < self.count => {
+                        let mut values = values.into_iter().collect::<Vec<_>>();
+                        values.sort();
+
+                        field.enum_values = Some(values);
+                    }
+                    _ => {
+                        if let Some((min, max)) = self.length_extent {
+                            field.min_length = Some(min);
+                            field.max_length = Some(max);
+                        }
+
+                        field.pattern = self.shape.to_pattern();
+                    }
+                }
+            }
+            _ => (),
+        };
+
+        field
+    }
+}
+
+pub struct SchemaInferrer {
+    names: Vec<String>,
+    sketches: Vec<FieldSketch>,
+    options: InferenceOptions,
+}
+
+impl SchemaInferrer {
+    pub fn new(names: Vec<String>, options: InferenceOptions) -> Self {
+        Self {
+            sketches: names.iter().map(|_| FieldSketch::new(&options)).collect(),
+            names,
+            options,
+        }
+    }
+
+    pub fn process(&mut self, record: &csv::ByteRecord) {
+        for (sketch, cell) in self.sketches.iter_mut().zip(record.iter()) {
+            sketch.add(&String::from_utf8_lossy(cell), &self.options);
+        }
+    }
+
+    pub fn finalize(self) -> Schema {
+        Schema {
+            fields: self
+                .sketches
+                .into_iter()
+                .zip(self.names.iter())
+                .map(|(sketch, name)| sketch.into_field(name))
+                .collect(),
+            missing_values: vec!["".to_string()],
+        }
+    }
+}
+
+// Validation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    MissingColumn,
+    ExtraColumn,
+    Nullable,
+    Type,
+    Enum,
+    Min,
+    Max,
+    MinLength,
+    MaxLength,
+    Pattern,
+    Unique,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::MissingColumn => "missing_column",
+                Self::ExtraColumn => "extra_column",
+                Self::Nullable => "nullable",
+                Self::Type => "type",
+                Self::Enum => "enum",
+                Self::Min => "min",
+                Self::Max => "max",
+                Self::MinLength => "min_length",
+                Self::MaxLength => "max_length",
+                Self::Pattern => "pattern",
+                Self::Unique => "unique",
+            }
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Violation {
+    pub row_index: Option<usize>,
+    pub column: String,
+    pub value: String,
+    pub rule: Rule,
+    pub expected: String,
+}
+
+struct FieldValidator {
+    field: Field,
+    column_index: usize,
+    pattern: Option<Regex>,
+    seen: Option<HashSet<Vec<u8>>>,
+}
+
+impl FieldValidator {
+    fn validate<F>(&mut self, row_index: usize, cell: &[u8], is_missing: bool, mut callback: F)
+    where
+        F: FnMut(Violation),
+    {
+        let value = String::from_utf8_lossy(cell);
+
+        let mut report = |rule: Rule, expected: String| {
+            callback(Violation {
+                row_index: Some(row_index),
+                column: self.field.name.clone(),
+                value: value.to_string(),
+                rule,
+                expected,
+            })
+        };
+
+        if is_missing {
+            if !self.field.nullable {
+                report(Rule::Nullable, "false".to_string());
+            }
+
+            return;
+        }
+
+        if let Some(seen) = self.seen.as_mut() {
+            if !seen.insert(cell.to_vec()) {
+                report(Rule::Unique, "true".to_string());
+            }
+        }
+
+        if !self.field.field_type.matches(&value) {
+            report(Rule::Type, self.field.field_type.as_str().to_string());
+
+            // NOTE: other constraints are meaningless if the type is wrong
+            return;
+        }
+
+        if let Some(enum_values) = &self.field.enum_values {
+            if !enum_values.iter().any(|v| v == &value) {
+                report(Rule::Enum, enum_values.join("|"));
+            }
+        }
+
+        // NOTE: NaN is not comparable and is ignored, like when inferring
+        if self.field.min.is_some() || self.field.max.is_some() {
+            if let Ok(number) = value.parse::<DynamicNumber>() {
+                if let Some(min) = self.field.min {
+                    if compare_numbers(number, min) == Some(Ordering::Less) {
+                        report(Rule::Min, min.to_string());
+                    }
+                }
+
+                if let Some(max) = self.field.max {
+                    if compare_numbers(number, max) == Some(Ordering::Greater) {
+                        report(Rule::Max, max.to_string());
+                    }
+                }
+            }
+        }
+
+        if self.field.min_length.is_some() || self.field.max_length.is_some() {
+            let length = value.chars().count();
+
+            if let Some(min_length) = self.field.min_length {
+                if length < min_length {
+                    report(Rule::MinLength, min_length.to_string());
+                }
+            }
+
+            if let Some(max_length) = self.field.max_length {
+                if length > max_length {
+                    report(Rule::MaxLength, max_length.to_string());
+                }
+            }
+        }
+
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(&value) {
+                report(Rule::Pattern, self.field.pattern.clone().unwrap());
+            }
+        }
+    }
+}
+
+pub struct SchemaValidator {
+    validators: Vec<FieldValidator>,
+    missing_values: Vec<Vec<u8>>,
+}
+
+impl SchemaValidator {
+    // NOTE: columns are matched by name, regardless of their order.
+    pub fn new<F>(
+        schema: Schema,
+        headers: &csv::ByteRecord,
+        ignore_extra: bool,
+        mut callback: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(Violation),
+    {
+        let names = headers
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect::<Vec<_>>();
+
+        let mut validators = Vec::new();
+
+        for field in schema.fields.iter() {
+            match names.iter().position(|name| name == &field.name) {
+                None => callback(Violation {
+                    row_index: None,
+                    column: field.name.clone(),
+                    value: "".to_string(),
+                    rule: Rule::MissingColumn,
+                    expected: "".to_string(),
+                }),
+                Some(column_index) => {
+                    let pattern = field
+                        .pattern
+                        .as_ref()
+                        .map(|p| {
+                            Regex::new(&format!("^(?:{})$", p)).map_err(|err| {
+                                format!("invalid pattern for field \"{}\": {}", field.name, err)
+                            })
+                        })
+                        .transpose()?;
+
+                    validators.push(FieldValidator {
+                        field: field.clone(),
+                        column_index,
+                        pattern,
+                        seen: field.unique.then(HashSet::new),
+                    });
+                }
+            }
+        }
+
+        if !ignore_extra {
+            for name in names.iter() {
+                if !schema.fields.iter().any(|field| &field.name == name) {
+                    callback(Violation {
+                        row_index: None,
+                        column: name.clone(),
+                        value: "".to_string(),
+                        rule: Rule::ExtraColumn,
+                        expected: "".to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            validators,
+            missing_values: schema
+                .missing_values
+                .into_iter()
+                .map(|v| v.into_bytes())
+                .collect(),
+        })
+    }
+
+    pub fn validate<F>(&mut self, row_index: usize, record: &csv::ByteRecord, mut callback: F)
+    where
+        F: FnMut(Violation),
+    {
+        for validator in self.validators.iter_mut() {
+            let cell = &record[validator.column_index];
+            let is_missing = self.missing_values.iter().any(|v| v == cell);
+
+            validator.validate(row_index, cell, is_missing, &mut callback);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer(rows: &[&str]) -> Field {
+        let options = InferenceOptions {
+            enum_threshold: 3,
+            patterns: true,
+        };
+
+        let mut sketch = FieldSketch::new(&options);
+
+        for row in rows {
+            sketch.add(row, &options);
+        }
+
+        sketch.into_field("test")
+    }
+
+    #[test]
+    fn test_inference() {
+        let field = infer(&["1", "5", "", "3"]);
+        assert_eq!(field.field_type, FieldType::Int);
+        assert!(field.nullable);
+        assert_eq!(field.min, Some(DynamicNumber::Integer(1)));
+        assert_eq!(field.max, Some(DynamicNumber::Integer(5)));
+
+        let field = infer(&["1", "5.5"]);
+        assert_eq!(field.field_type, FieldType::Float);
+        assert!(!field.nullable);
+
+        let field = infer(&["a", "b", "a", "b"]);
+        assert_eq!(field.field_type, FieldType::String);
+        assert_eq!(
+            field.enum_values,
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let field = infer(&["AB-123", "C-45", "DE-6", "FG-7890"]);
+        assert_eq!(field.enum_values, None);
+        assert_eq!(field.pattern, Some("[A-Z]{1,2}\\-[0-9]{1,4}".to_string()));
+        assert_eq!(field.min_length, Some(4));
+        assert_eq!(field.max_length, Some(7));
+
+        let field = infer(&["john", "Mary", "lucy", "Ted"]);
+        assert_eq!(field.pattern, None);
+    }
+
+    #[test]
+    fn test_formats() {
+        let schema = Schema::from_json_str(
+            r#"{
+                "fields": [
+                    {"name": "id", "type": "integer", "constraints": {"required": true, "minimum": 1}},
+                    {"name": "site", "type": "string", "format": "uri"},
+                    {"name": "kind", "type": "string", "enum": ["a", "b"], "nullable": false}
+                ],
+                "missingValues": ["", "NA"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.fields[0].field_type, FieldType::Int);
+        assert!(!schema.fields[0].nullable);
+        assert_eq!(schema.fields[0].min, Some(DynamicNumber::Integer(1)));
+        assert_eq!(schema.fields[1].field_type, FieldType::Url);
+        assert!(schema.fields[1].nullable);
+        assert!(!schema.fields[2].nullable);
+        assert_eq!(schema.missing_values, vec!["", "NA"]);
+
+        let schema = Schema::from_json_str(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer", "maximum": 10},
+                    "name": {"type": ["string", "null"], "pattern": "^[a-z]"},
+                    "ratio": {"type": ["integer", "number"]}
+                },
+                "required": ["id", "name"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.fields[0].name, "id");
+        assert!(!schema.fields[0].nullable);
+        assert_eq!(schema.fields[0].max, Some(DynamicNumber::Integer(10)));
+        assert!(schema.fields[1].nullable);
+        assert_eq!(schema.fields[2].field_type, FieldType::Float);
+
+        // Round trips
+        for format in [
+            SchemaFormat::Xan,
+            SchemaFormat::Frictionless,
+            SchemaFormat::JsonSchema,
+        ] {
+            let json = schema.to_json(format).to_string();
+            let other = Schema::from_json_str(&json).unwrap();
+
+            assert_eq!(other.fields.len(), 3);
+            assert_eq!(other.fields[0].max, Some(DynamicNumber::Integer(10)));
+            assert_eq!(other.fields[1].nullable, schema.fields[1].nullable);
+        }
+
+        assert!(Schema::from_json_str("[]").is_err());
+        assert!(Schema::from_json_str(r#"{"fields": [{"name": "a", "type": "blob"}]}"#).is_err());
+    }
+}
@@ -0,0 +1,1072 @@
+// Parser for the practical subset of SQL supported by `xan sql`.
+//
+// The parser is a hand-written recursive descent one, lexing tokens on demand
+// so that table names can be arbitrary file paths (e.g. `data/people.csv`)
+// without needing to be quoted.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Concat,
+}
+
+impl BinaryOperator {
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge
+        )
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            Self::Add | Self::Sub | Self::Mul | Self::Div | Self::Mod
+        )
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Or => "OR",
+            Self::And => "AND",
+            Self::Eq => "=",
+            Self::Ne => "<>",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::Concat => "||",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Column(Option<String>, String),
+    Wildcard,
+    Number(String),
+    String(String),
+    Bool(bool),
+    Null,
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Function {
+        name: String,
+        args: Vec<Expr>,
+        distinct: bool,
+    },
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    Like {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        negated: bool,
+        case_insensitive: bool,
+    },
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    Case {
+        operand: Option<Box<Expr>>,
+        branches: Vec<(Expr, Expr)>,
+        otherwise: Option<Box<Expr>>,
+    },
+}
+
+fn write_list(f: &mut fmt::Formatter, exprs: &[Expr]) -> fmt::Result {
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{}", expr)?;
+    }
+
+    Ok(())
+}
+
+// NOTE: this is used to name output columns, so we try to stay as close as
+// possible to what the user wrote.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Column(None, name) => write!(f, "{}", name),
+            Self::Column(Some(table), name) => write!(f, "{}.{}", table, name),
+            Self::Wildcard => write!(f, "*"),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Self::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Self::Null => write!(f, "NULL"),
+            Self::Not(expr) => write!(f, "NOT {}", expr),
+            Self::Neg(expr) => write!(f, "-{}", expr),
+            Self::Binary(left, op, right) => write!(f, "{} {} {}", left, op.as_str(), right),
+            Self::Function {
+                name,
+                args,
+                distinct,
+            } => {
+                write!(f, "{}(", name)?;
+
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+
+                write_list(f, args)?;
+                write!(f, ")")
+            }
+            Self::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                write!(f, "{} {}IN (", expr, if *negated { "NOT " } else { "" })?;
+                write_list(f, list)?;
+                write!(f, ")")
+            }
+            Self::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => write!(
+                f,
+                "{} {}BETWEEN {} AND {}",
+                expr,
+                if *negated { "NOT " } else { "" },
+                low,
+                high
+            ),
+            Self::Like {
+                expr,
+                pattern,
+                negated,
+                case_insensitive,
+            } => write!(
+                f,
+                "{} {}{} {}",
+                expr,
+                if *negated { "NOT " } else { "" },
+                if *case_insensitive { "ILIKE" } else { "LIKE" },
+                pattern
+            ),
+            Self::IsNull { expr, negated } => {
+                write!(f, "{} IS {}NULL", expr, if *negated { "NOT " } else { "" })
+            }
+            Self::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                write!(f, "CASE")?;
+
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+
+                for (condition, result) in branches {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+
+                if let Some(otherwise) = otherwise {
+                    write!(f, " ELSE {}", otherwise)?;
+                }
+
+                write!(f, " END")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectItem {
+    Wildcard(Option<String>),
+    Expr(Expr, Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableReference {
+    pub path: String,
+    pub alias: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: TableReference,
+    pub on: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub expr: Expr,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub distinct: bool,
+    pub items: Vec<SelectItem>,
+    pub from: TableReference,
+    pub joins: Vec<Join>,
+    pub selection: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+const KEYWORDS: [&str; 33] = [
+    "SELECT", "DISTINCT", "FROM", "AS", "WHERE", "GROUP", "BY", "HAVING", "ORDER", "ASC", "DESC",
+    "LIMIT", "OFFSET", "JOIN", "INNER", "LEFT", "OUTER", "ON", "AND", "OR", "NOT", "IN", "BETWEEN",
+    "LIKE", "ILIKE", "IS", "NULL", "TRUE", "FALSE", "CASE", "WHEN", "THEN", "ELSE",
+];
+
+fn is_keyword(word: &str) -> bool {
+    word.eq_ignore_ascii_case("END") || KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    QuotedIdentifier(String),
+    String(String),
+    Number(String),
+    Symbol(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Word(w) => write!(f, "{}", w),
+            Self::QuotedIdentifier(i) => write!(f, "\"{}\"", i),
+            Self::String(s) => write!(f, "'{}'", s),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Symbol(s) => write!(f, "{}", s),
+            Self::End => write!(f, "end of query"),
+        }
+    }
+}
+
+// NOTE: longest symbols must come first
+const SYMBOLS: [&str; 17] = [
+    "<>", "!=", "<=", ">=", "==", "||", "=", "<", ">", "+", "-", "*", "/", "%", "(", ")", ",",
+];
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+type ParseResult<T> = Result<T, String>;
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("--") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_quoted(&self, rest: &str, quote: char) -> ParseResult<(String, usize)> {
+        let mut string = String::new();
+        let mut chars = rest.char_indices().skip(1).peekable();
+
+        while let Some((i, c)) = chars.next() {
+            if c == quote {
+                if let Some((_, next)) = chars.peek() {
+                    if *next == quote {
+                        string.push(quote);
+                        chars.next();
+                        continue;
+                    }
+                }
+
+                return Ok((string, i + 1));
+            }
+
+            string.push(c);
+        }
+
+        Err(format!("unterminated {} quoted string!", quote))
+    }
+
+    // Returns the next token & its length, without consuming it
+    fn lex(&mut self) -> ParseResult<(Token, usize)> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+
+        let c = match rest.chars().next() {
+            None => return Ok((Token::End, 0)),
+            Some(c) => c,
+        };
+
+        if c == '\'' {
+            let (string, len) = self.read_quoted(rest, '\'')?;
+            return Ok((Token::String(string), len));
+        }
+
+        if c == '"' || c == '`' {
+            let (string, len) = self.read_quoted(rest, c)?;
+            return Ok((Token::QuotedIdentifier(string), len));
+        }
+
+        if c.is_ascii_digit() {
+            let mut len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+
+            if rest[len..].starts_with(['e', 'E']) {
+                let exponent = &rest[len + 1..];
+                let sign = usize::from(exponent.starts_with(['+', '-']));
+                let digits = exponent[sign..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(exponent.len() - sign);
+
+                if digits > 0 {
+                    len += 1 + sign + digits;
+                }
+            }
+
+            return Ok((Token::Number(rest[..len].to_string()), len));
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+
+            return Ok((Token::Word(rest[..len].to_string()), len));
+        }
+
+        for symbol in SYMBOLS {
+            if rest.starts_with(symbol) {
+                return Ok((Token::Symbol(symbol), symbol.len()));
+            }
+        }
+
+        if c == '.' || c == ';' {
+            return Ok((Token::Symbol(if c == '.' { "." } else { ";" }), 1));
+        }
+
+        Err(format!("unexpected character '{}'", c))
+    }
+
+    fn peek(&mut self) -> ParseResult<Token> {
+        let pos = self.pos;
+        let token = self.lex().map(|(token, _)| token);
+        self.pos = pos;
+        token
+    }
+
+    fn next(&mut self) -> ParseResult<Token> {
+        let (token, len) = self.lex()?;
+        self.pos += len;
+        Ok(token)
+    }
+
+    fn peek_keyword(&mut self, keyword: &str) -> ParseResult<bool> {
+        Ok(matches!(self.peek()?, Token::Word(w) if w.eq_ignore_ascii_case(keyword)))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> ParseResult<bool> {
+        if self.peek_keyword(keyword)? {
+            self.next()?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        if !self.eat_keyword(keyword)? {
+            return Err(format!("expected {} but found {}", keyword, self.peek()?));
+        }
+
+        Ok(())
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> ParseResult<bool> {
+        if matches!(self.peek()?, Token::Symbol(s) if s == symbol) {
+            self.next()?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> ParseResult<()> {
+        if !self.eat_symbol(symbol)? {
+            return Err(format!(
+                "expected \"{}\" but found {}",
+                symbol,
+                self.peek()?
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> ParseResult<String> {
+        match self.next()? {
+            Token::Word(w) if !is_keyword(&w) => Ok(w),
+            Token::QuotedIdentifier(i) => Ok(i),
+            token => Err(format!("expected an identifier but found {}", token)),
+        }
+    }
+
+    fn optional_alias(&mut self) -> ParseResult<Option<String>> {
+        if self.eat_keyword("AS")? {
+            return self.identifier().map(Some);
+        }
+
+        match self.peek()? {
+            Token::Word(w) if !is_keyword(&w) => self.identifier().map(Some),
+            Token::QuotedIdentifier(_) => self.identifier().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn usize(&mut self) -> ParseResult<usize> {
+        match self.next()? {
+            Token::Number(n) => n
+                .parse::<usize>()
+                .map_err(|_| format!("expected a positive integer but found {}", n)),
+            token => Err(format!("expected a positive integer but found {}", token)),
+        }
+    }
+
+    // NOTE: table names are file paths, which can be quoted, or written as is.
+    fn table_reference(&mut self) -> ParseResult<TableReference> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+
+        let path = if rest.starts_with(['\'', '"', '`']) {
+            let (path, len) = self.read_quoted(rest, rest.chars().next().unwrap())?;
+            self.pos += len;
+            path
+        } else {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || c == ',' || c == ';' || c == ')')
+                .unwrap_or(rest.len());
+
+            if len == 0 {
+                return Err("expected a table name!".to_string());
+            }
+
+            self.pos += len;
+            rest[..len].to_string()
+        };
+
+        let alias = match self.optional_alias()? {
+            Some(alias) => alias,
+            None => {
+                let name = path.rsplit(['/', '\\']).next().unwrap();
+                name.split('.').next().unwrap().to_string()
+            }
+        };
+
+        Ok(TableReference { path, alias })
+    }
+
+    fn query(&mut self) -> ParseResult<Query> {
+        self.expect_keyword("SELECT")?;
+
+        let distinct = self.eat_keyword("DISTINCT")?;
+
+        let mut items = vec![self.select_item()?];
+
+        while self.eat_symbol(",")? {
+            items.push(self.select_item()?);
+        }
+
+        self.expect_keyword("FROM")?;
+
+        let from = self.table_reference()?;
+        let mut joins = Vec::new();
+
+        loop {
+            let kind = if self.eat_keyword("LEFT")? {
+                self.eat_keyword("OUTER")?;
+                JoinKind::Left
+            } else {
+                self.eat_keyword("INNER")?;
+                JoinKind::Inner
+            };
+
+            if !self.eat_keyword("JOIN")? {
+                if kind == JoinKind::Left {
+                    return Err(format!("expected JOIN but found {}", self.peek()?));
+                }
+
+                break;
+            }
+
+            let table = self.table_reference()?;
+            self.expect_keyword("ON")?;
+            let on = self.expr()?;
+
+            joins.push(Join { kind, table, on });
+        }
+
+        let selection = if self.eat_keyword("WHERE")? {
+            Some(self.expr()?)
+        } else {
+            None
+        };
+
+        let mut group_by = Vec::new();
+
+        if self.eat_keyword("GROUP")? {
+            self.expect_keyword("BY")?;
+
+            group_by.push(self.expr()?);
+
+            while self.eat_symbol(",")? {
+                group_by.push(self.expr()?);
+            }
+        }
+
+        let having = if self.eat_keyword("HAVING")? {
+            Some(self.expr()?)
+        } else {
+            None
+        };
+
+        let mut order_by = Vec::new();
+
+        if self.eat_keyword("ORDER")? {
+            self.expect_keyword("BY")?;
+
+            loop {
+                let expr = self.expr()?;
+
+                let descending = if self.eat_keyword("DESC")? {
+                    true
+                } else {
+                    self.eat_keyword("ASC")?;
+                    false
+                };
+
+                order_by.push(OrderBy { expr, descending });
+
+                if !self.eat_symbol(",")? {
+                    break;
+                }
+            }
+        }
+
+        let mut limit = None;
+        let mut offset = None;
+
+        if self.eat_keyword("LIMIT")? {
+            limit = Some(self.usize()?);
+        }
+
+        if self.eat_keyword("OFFSET")? {
+            offset = Some(self.usize()?);
+        }
+
+        self.eat_symbol(";")?;
+
+        match self.peek()? {
+            Token::End => Ok(Query {
+                distinct,
+                items,
+                from,
+                joins,
+                selection,
+                group_by,
+                having,
+                order_by,
+                limit,
+                offset,
+            }),
+            token => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    fn select_item(&mut self) -> ParseResult<SelectItem> {
+        if self.eat_symbol("*")? {
+            return Ok(SelectItem::Wildcard(None));
+        }
+
+        // Qualified wildcard, e.g. `t.*`
+        let pos = self.pos;
+
+        if let Ok(table) = self.identifier() {
+            if self.eat_symbol(".")? && self.eat_symbol("*")? {
+                return Ok(SelectItem::Wildcard(Some(table)));
+            }
+        }
+
+        self.pos = pos;
+
+        let expr = self.expr()?;
+        let alias = self.optional_alias()?;
+
+        Ok(SelectItem::Expr(expr, alias))
+    }
+
+    fn expr(&mut self) -> ParseResult<Expr> {
+        self.or_expr()
+    }
+
+    fn or_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.and_expr()?;
+
+        while self.eat_keyword("OR")? {
+            expr = Expr::Binary(
+                Box::new(expr),
+                BinaryOperator::Or,
+                Box::new(self.and_expr()?),
+            );
+        }
+
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.not_expr()?;
+
+        while self.eat_keyword("AND")? {
+            expr = Expr::Binary(
+                Box::new(expr),
+                BinaryOperator::And,
+                Box::new(self.not_expr()?),
+            );
+        }
+
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> ParseResult<Expr> {
+        if self.eat_keyword("NOT")? {
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+
+        self.comparison_expr()
+    }
+
+    fn comparison_expr(&mut self) -> ParseResult<Expr> {
+        let expr = self.additive_expr()?;
+
+        if self.eat_keyword("IS")? {
+            let negated = self.eat_keyword("NOT")?;
+            self.expect_keyword("NULL")?;
+
+            return Ok(Expr::IsNull {
+                expr: Box::new(expr),
+                negated,
+            });
+        }
+
+        let pos = self.pos;
+        let negated = self.eat_keyword("NOT")?;
+
+        if self.eat_keyword("IN")? {
+            self.expect_symbol("(")?;
+
+            let mut list = vec![self.expr()?];
+
+            while self.eat_symbol(",")? {
+                list.push(self.expr()?);
+            }
+
+            self.expect_symbol(")")?;
+
+            return Ok(Expr::InList {
+                expr: Box::new(expr),
+                list,
+                negated,
+            });
+        }
+
+        if self.eat_keyword("BETWEEN")? {
+            let low = self.additive_expr()?;
+            self.expect_keyword("AND")?;
+            let high = self.additive_expr()?;
+
+            return Ok(Expr::Between {
+                expr: Box::new(expr),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+
+        for (keyword, case_insensitive) in [("LIKE", false), ("ILIKE", true)] {
+            if self.eat_keyword(keyword)? {
+                return Ok(Expr::Like {
+                    expr: Box::new(expr),
+                    pattern: Box::new(self.additive_expr()?),
+                    negated,
+                    case_insensitive,
+                });
+            }
+        }
+
+        if negated {
+            self.pos = pos;
+            return Err(format!("unexpected NOT after {}", expr));
+        }
+
+        let op = match self.peek()? {
+            Token::Symbol("=") | Token::Symbol("==") => BinaryOperator::Eq,
+            Token::Symbol("<>") | Token::Symbol("!=") => BinaryOperator::Ne,
+            Token::Symbol("<") => BinaryOperator::Lt,
+            Token::Symbol("<=") => BinaryOperator::Le,
+            Token::Symbol(">") => BinaryOperator::Gt,
+            Token::Symbol(">=") => BinaryOperator::Ge,
+            _ => return Ok(expr),
+        };
+
+        self.next()?;
+
+        Ok(Expr::Binary(
+            Box::new(expr),
+            op,
+            Box::new(self.additive_expr()?),
+        ))
+    }
+
+    fn additive_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.multiplicative_expr()?;
+
+        loop {
+            let op = match self.peek()? {
+                Token::Symbol("+") => BinaryOperator::Add,
+                Token::Symbol("-") => BinaryOperator::Sub,
+                Token::Symbol("||") => BinaryOperator::Concat,
+                _ => return Ok(expr),
+            };
+
+            self.next()?;
+
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.multiplicative_expr()?));
+        }
+    }
+
+    fn multiplicative_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.unary_expr()?;
+
+        loop {
+            let op = match self.peek()? {
+                Token::Symbol("*") => BinaryOperator::Mul,
+                Token::Symbol("/") => BinaryOperator::Div,
+                Token::Symbol("%") => BinaryOperator::Mod,
+                _ => return Ok(expr),
+            };
+
+            self.next()?;
+
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary_expr()?));
+        }
+    }
+
+    fn unary_expr(&mut self) -> ParseResult<Expr> {
+        if self.eat_symbol("-")? {
+            return Ok(Expr::Neg(Box::new(self.unary_expr()?)));
+        }
+
+        if self.eat_symbol("+")? {
+            return self.unary_expr();
+        }
+
+        self.primary_expr()
+    }
+
+    fn primary_expr(&mut self) -> ParseResult<Expr> {
+        match self.next()? {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::String(s) => Ok(Expr::String(s)),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Token::QuotedIdentifier(name) => self.column(name),
+            Token::Word(word) => {
+                if word.eq_ignore_ascii_case("NULL") {
+                    return Ok(Expr::Null);
+                }
+
+                if word.eq_ignore_ascii_case("TRUE") {
+                    return Ok(Expr::Bool(true));
+                }
+
+                if word.eq_ignore_ascii_case("FALSE") {
+                    return Ok(Expr::Bool(false));
+                }
+
+                if word.eq_ignore_ascii_case("CASE") {
+                    return self.case_expr();
+                }
+
+                if is_keyword(&word) {
+                    return Err(format!("unexpected {}", word.to_uppercase()));
+                }
+
+                if self.eat_symbol("(")? {
+                    return self.function(word.to_lowercase());
+                }
+
+                self.column(word)
+            }
+            token => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    fn column(&mut self, name: String) -> ParseResult<Expr> {
+        if self.eat_symbol(".")? {
+            let column = self.identifier()?;
+            return Ok(Expr::Column(Some(name), column));
+        }
+
+        Ok(Expr::Column(None, name))
+    }
+
+    fn function(&mut self, name: String) -> ParseResult<Expr> {
+        let distinct = self.eat_keyword("DISTINCT")?;
+        let mut args = Vec::new();
+
+        if self.eat_symbol("*")? {
+            args.push(Expr::Wildcard);
+        } else if !matches!(self.peek()?, Token::Symbol(")")) {
+            args.push(self.expr()?);
+
+            while self.eat_symbol(",")? {
+                args.push(self.expr()?);
+            }
+        }
+
+        self.expect_symbol(")")?;
+
+        Ok(Expr::Function {
+            name,
+            args,
+            distinct,
+        })
+    }
+
+    fn case_expr(&mut self) -> ParseResult<Expr> {
+        let operand = if self.peek_keyword("WHEN")? {
+            None
+        } else {
+            Some(Box::new(self.expr()?))
+        };
+
+        let mut branches = Vec::new();
+
+        while self.eat_keyword("WHEN")? {
+            let condition = self.expr()?;
+            self.expect_keyword("THEN")?;
+            branches.push((condition, self.expr()?));
+        }
+
+        if branches.is_empty() {
+            return Err("CASE expects at least one WHEN clause".to_string());
+        }
+
+        let otherwise = if self.eat_keyword("ELSE")? {
+            Some(Box::new(self.expr()?))
+        } else {
+            None
+        };
+
+        self.expect_keyword("END")?;
+
+        Ok(Expr::Case {
+            operand,
+            branches,
+            otherwise,
+        })
+    }
+}
+
+pub fn parse_query(input: &str) -> Result<Query, String> {
+    Parser::new(input).query()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(name: &str) -> Expr {
+        Expr::Column(None, name.to_string())
+    }
+
+    fn num(n: &str) -> Expr {
+        Expr::Number(n.to_string())
+    }
+
+    fn bin(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+        Expr::Binary(Box::new(left), op, Box::new(right))
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse_query(
+            "select name, count(*) as n from data/people.csv p where age + 1 >= 18 and not city = 'Paris' group by name order by n desc limit 10;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.items,
+            vec![
+                SelectItem::Expr(col("name"), None),
+                SelectItem::Expr(
+                    Expr::Function {
+                        name: "count".to_string(),
+                        args: vec![Expr::Wildcard],
+                        distinct: false
+                    },
+                    Some("n".to_string())
+                )
+            ]
+        );
+        assert_eq!(
+            query.from,
+            TableReference {
+                path: "data/people.csv".to_string(),
+                alias: "p".to_string()
+            }
+        );
+        assert_eq!(
+            query.selection,
+            Some(bin(
+                bin(
+                    bin(col("age"), BinaryOperator::Add, num("1")),
+                    BinaryOperator::Ge,
+                    num("18")
+                ),
+                BinaryOperator::And,
+                Expr::Not(Box::new(bin(
+                    col("city"),
+                    BinaryOperator::Eq,
+                    Expr::String("Paris".to_string())
+                )))
+            ))
+        );
+        assert_eq!(query.group_by, vec![col("name")]);
+        assert_eq!(
+            query.order_by,
+            vec![OrderBy {
+                expr: col("n"),
+                descending: true
+            }]
+        );
+        assert_eq!(query.limit, Some(10));
+    }
+
+    #[test]
+    fn test_parse_joins() {
+        let query = parse_query(
+            "SELECT a.*, b.\"full name\" FROM 'a file.csv' AS a LEFT JOIN b.csv ON a.id = b.id",
+        )
+        .unwrap();
+
+        assert_eq!(query.from.path, "a file.csv");
+        assert_eq!(query.from.alias, "a");
+        assert_eq!(query.joins.len(), 1);
+        assert_eq!(query.joins[0].kind, JoinKind::Left);
+        assert_eq!(query.joins[0].table.alias, "b");
+        assert_eq!(
+            query.items,
+            vec![
+                SelectItem::Wildcard(Some("a".to_string())),
+                SelectItem::Expr(
+                    Expr::Column(Some("b".to_string()), "full name".to_string()),
+                    None
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let query = parse_query(
+            "SELECT sum(x * 2), name NOT LIKE 'j%', CASE WHEN x IN (1, 2) THEN 'low' ELSE 'high' END FROM f.csv",
+        )
+        .unwrap();
+
+        let names = query
+            .items
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr(expr, _) => expr.to_string(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                "sum(x * 2)",
+                "name NOT LIKE 'j%'",
+                "CASE WHEN x IN (1, 2) THEN 'low' ELSE 'high' END"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse_query("SELECT FROM f.csv").is_err());
+        assert!(parse_query("SELECT a FROM f.csv WHERE").is_err());
+        assert!(parse_query("SELECT a FROM f.csv LIMIT -1").is_err());
+        assert!(parse_query("SELECT 'a FROM f.csv").is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt::Write;
+use std::fs;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::CliResult;
+
+pub const FONT_SIZE: f64 = 12.0;
+pub const BACKGROUND_COLOR: &str = "#ffffff";
+pub const TEXT_COLOR: &str = "#333333";
+pub const DIM_COLOR: &str = "#999999";
+pub const AXIS_COLOR: &str = "#666666";
+pub const GRID_COLOR: &str = "#e5e5e5";
+
+const SANS_SERIF_FAMILIES: [&str; 5] = [
+    "Arial",
+    "Helvetica",
+    "Liberation Sans",
+    "DejaVu Sans",
+    "Noto Sans",
+];
+
+// NOTE: those colors follow the order used when drawing series in the terminal,
+// i.e. cyan, red, green, yellow, blue, magenta, then dimmed.
+const SERIES_COLORS: [&str; 6] = [
+    "#17becf", "#d62728", "#2ca02c", "#e6b400", "#1f77b4", "#b84fc0",
+];
+
+// NOTE: same as `util::colorizer_by_rainbow`
+const RAINBOW_COLORS: [&str; 7] = [
+    "#d62728", "#2ca02c", "#e6b400", "#1f77b4", "#b84fc0", "#17becf", DIM_COLOR,
+];
+
+pub fn series_color(i: usize) -> &'static str {
+    SERIES_COLORS.get(i).copied().unwrap_or(DIM_COLOR)
+}
+
+pub fn rainbow_color(i: usize) -> &'static str {
+    RAINBOW_COLORS[i % RAINBOW_COLORS.len()]
+}
+
+pub fn rgba_to_hex(color: &[u8; 4]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// NOTE: this is an approximation, since we cannot know which font will be
+// used to render the text in the end.
+pub fn text_width(text: &str, size: f64) -> f64 {
+    text.width() as f64 * size * 0.6
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+impl Anchor {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Start => "start",
+            Self::Middle => "middle",
+            Self::End => "end",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TextStyle<'a> {
+    pub size: f64,
+    pub anchor: Anchor,
+    pub fill: &'a str,
+    pub bold: bool,
+    pub rotate: Option<f64>,
+}
+
+impl Default for TextStyle<'_> {
+    fn default() -> Self {
+        Self {
+            size: FONT_SIZE,
+            anchor: Anchor::Start,
+            fill: TEXT_COLOR,
+            bold: false,
+            rotate: None,
+        }
+    }
+}
+
+impl<'a> TextStyle<'a> {
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn fill(mut self, fill: &'a str) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn rotate(mut self, degrees: f64) -> Self {
+        self.rotate = Some(degrees);
+        self
+    }
+}
+
+/// A minimal SVG document builder, used to export the charts usually drawn
+/// in the terminal.
+#[derive(Debug)]
+pub struct SvgDocument {
+    width: f64,
+    height: f64,
+    body: String,
+}
+
+impl SvgDocument {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width: width.ceil(),
+            height: height.ceil(),
+            body: String::new(),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    // NOTE: useful when the height of the document can only be known after
+    // having drawn everything, e.g. with `xan hist`.
+    pub fn set_height(&mut self, height: f64) {
+        self.height = height.ceil();
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: &str) {
+        writeln!(
+            &mut self.body,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>",
+            x,
+            y,
+            width.max(0.0),
+            height.max(0.0),
+            fill
+        )
+        .unwrap();
+    }
+
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), stroke: &str, stroke_width: f64) {
+        writeln!(
+            &mut self.body,
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            from.0, from.1, to.0, to.1, stroke, stroke_width
+        )
+        .unwrap();
+    }
+
+    pub fn circle(&mut self, center: (f64, f64), radius: f64, fill: &str) {
+        writeln!(
+            &mut self.body,
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"/>",
+            center.0, center.1, radius, fill
+        )
+        .unwrap();
+    }
+
+    pub fn polyline(&mut self, points: &[(f64, f64)], stroke: &str, stroke_width: f64) {
+        if points.is_empty() {
+            return;
+        }
+
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            &mut self.body,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+            points, stroke, stroke_width
+        )
+        .unwrap();
+    }
+
+    // NOTE: the polygon is filled using given opacity & stroked using given color
+    pub fn polygon(&mut self, points: &[(f64, f64)], color: &str, fill_opacity: f64) {
+        if points.is_empty() {
+            return;
+        }
+
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            &mut self.body,
+            "<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"{}\" stroke=\"{}\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>",
+            points, color, fill_opacity, color
+        )
+        .unwrap();
+    }
+
+    pub fn text(&mut self, position: (f64, f64), content: &str, style: &TextStyle) {
+        write!(
+            &mut self.body,
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" fill=\"{}\" text-anchor=\"{}\"",
+            position.0,
+            position.1,
+            style.size,
+            style.fill,
+            style.anchor.as_str()
+        )
+        .unwrap();
+
+        if style.bold {
+            self.body.push_str(" font-weight=\"bold\"");
+        }
+
+        if let Some(degrees) = style.rotate {
+            write!(
+                &mut self.body,
+                " transform=\"rotate({} {:.2} {:.2})\"",
+                degrees, position.0, position.1
+            )
+            .unwrap();
+        }
+
+        writeln!(&mut self.body, ">{}</text>", escape(content)).unwrap();
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n{}</svg>\n",
+            BACKGROUND_COLOR,
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+
+    fn render_png(&self) -> CliResult<Vec<u8>> {
+        use resvg::{tiny_skia, usvg};
+
+        let mut options = usvg::Options::default();
+        let fontdb = options.fontdb_mut();
+        fontdb.load_system_fonts();
+
+        // NOTE: fontdb expects Arial to be available for the generic sans-serif
+        // family, which is seldom the case on linux.
+        if let Some(family) = SANS_SERIF_FAMILIES.iter().find(|family| {
+            fontdb
+                .faces()
+                .any(|face| face.families.iter().any(|(name, _)| name == *family))
+        }) {
+            fontdb.set_sans_serif_family(*family);
+        }
+
+        let tree = usvg::Tree::from_str(&self.render(), &options)
+            .map_err(|err| format!("could not render png: {}", err))?;
+
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or("could not render png: invalid image size")?;
+
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+        Ok(pixmap
+            .encode_png()
+            .map_err(|err| format!("could not render png: {}", err))?)
+    }
+}
+
+/// Where to write an exported chart, given through the --svg & --png flags.
+#[derive(Debug)]
+pub struct ImageOutput {
+    svg_path: Option<String>,
+    png_path: Option<String>,
+}
+
+impl ImageOutput {
+    pub fn new(svg_path: &Option<String>, png_path: &Option<String>) -> Option<Self> {
+        if svg_path.is_none() && png_path.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            svg_path: svg_path.clone(),
+            png_path: png_path.clone(),
+        })
+    }
+
+    pub fn write(&self, document: &SvgDocument) -> CliResult<()> {
+        if let Some(path) = &self.svg_path {
+            fs::write(path, document.render())?;
+        }
+
+        if let Some(path) = &self.png_path {
+            fs::write(path, document.render_png()?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_document() {
+        let mut document = SvgDocument::new(100.0, 50.0);
+        document.line((0.0, 0.0), (10.0, 10.0), AXIS_COLOR, 1.0);
+        document.text(
+            (5.0, 5.0),
+            "<test> & co",
+            &TextStyle::default().anchor(Anchor::Middle).bold(),
+        );
+
+        assert_eq!(
+            document.render(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\" font-family=\"sans-serif\">
+<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>
+<line x1=\"0.00\" y1=\"0.00\" x2=\"10.00\" y2=\"10.00\" stroke=\"#666666\" stroke-width=\"1\"/>
+<text x=\"5.00\" y=\"5.00\" font-size=\"12\" fill=\"#333333\" text-anchor=\"middle\" font-weight=\"bold\">&lt;test&gt; &amp; co</text>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn test_render_png() {
+        let document = SvgDocument::new(20.0, 10.0);
+        let png = document.render_png().unwrap();
+
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
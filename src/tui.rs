@@ -0,0 +1,1229 @@
+use std::cmp::Ordering;
+use std::io::{Read, SeekFrom};
+
+use aho_corasick::AhoCorasick;
+use colored::{Color as ColoredColor, Styles};
+use numfmt::Formatter;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use regex::bytes::RegexBuilder;
+use unicode_width::UnicodeWidthStr;
+
+use crate::cmd::search::Matcher;
+use crate::collections::HashMap;
+use crate::config::SeekRead;
+use crate::index::RecordIndex;
+use crate::select::Selection;
+use crate::util::{self, ColorOrStyles};
+use crate::CliResult;
+
+const MAX_COLUMN_WIDTH: usize = 40;
+const CACHE_CAPACITY: usize = 4096;
+
+const HELP: &[(&str, &str)] = &[
+    ("arrows, hjkl", "move around"),
+    ("page up/down, b/space", "scroll by one page"),
+    ("home/end, g/G", "go to first/last row"),
+    ("0, $", "go to first/last column"),
+    (":", "jump to row index"),
+    ("/", "search (case-insensitive unless uppercase)"),
+    ("\\", "search using a regex"),
+    ("n, N", "go to next/previous match"),
+    ("s", "sort by column (asc, desc, none)"),
+    ("x, -", "hide column"),
+    ("u", "unhide last hidden column"),
+    ("<, >", "move column left/right"),
+    ("i", "toggle index"),
+    ("esc", "clear search"),
+    ("q, ctrl+c", "quit"),
+];
+
+fn seek<R: SeekRead>(reader: &mut csv::Reader<R>, offset: u64) -> CliResult<()> {
+    let mut pos = csv::Position::new();
+    pos.set_byte(offset);
+
+    reader.seek_raw(SeekFrom::Start(offset), pos)?;
+
+    Ok(())
+}
+
+enum Source {
+    Buffered {
+        reader: csv::Reader<Box<dyn Read + Send>>,
+        records: Vec<csv::ByteRecord>,
+    },
+    Seekable {
+        reader: csv::Reader<Box<dyn SeekRead + Send>>,
+        index: Option<RecordIndex>,
+        offsets: Vec<u64>,
+        next_offset: u64,
+        cache: HashMap<usize, csv::ByteRecord>,
+    },
+}
+
+// NOTE: rows are only read when needed. Rows of seekable files are not kept in
+// memory, only their byte offsets (which are not even needed if the file was
+// indexed using `xan index`), while other streams are buffered as they are read.
+pub struct LazyRows {
+    source: Source,
+    complete: bool,
+}
+
+impl LazyRows {
+    pub fn buffered(reader: csv::Reader<Box<dyn Read + Send>>) -> Self {
+        Self {
+            source: Source::Buffered {
+                reader,
+                records: Vec::new(),
+            },
+            complete: false,
+        }
+    }
+
+    // NOTE: `start` is the absolute byte offset of the first record
+    pub fn seekable(
+        reader: csv::Reader<Box<dyn SeekRead + Send>>,
+        start: u64,
+        index: Option<RecordIndex>,
+    ) -> Self {
+        let complete = index.is_some();
+
+        Self {
+            source: Source::Seekable {
+                reader,
+                index,
+                offsets: Vec::new(),
+                next_offset: start,
+                cache: HashMap::new(),
+            },
+            complete,
+        }
+    }
+
+    pub fn known_len(&self) -> usize {
+        match &self.source {
+            Source::Buffered { records, .. } => records.len(),
+            Source::Seekable {
+                index: Some(index), ..
+            } => index.count() as usize,
+            Source::Seekable { offsets, .. } => offsets.len(),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn ensure(&mut self, n: usize) -> CliResult<()> {
+        let known_len = self.known_len();
+
+        if self.complete || known_len >= n {
+            return Ok(());
+        }
+
+        self.scan(known_len, |i, _| i + 1 < n)
+    }
+
+    // NOTE: the callback returns whether to continue scanning
+    pub fn scan<F>(&mut self, from: usize, mut callback: F) -> CliResult<()>
+    where
+        F: FnMut(usize, &csv::ByteRecord) -> bool,
+    {
+        if from > self.known_len() {
+            self.ensure(from)?;
+        }
+
+        match &mut self.source {
+            Source::Buffered { reader, records } => {
+                let mut i = from;
+
+                loop {
+                    if i >= records.len() {
+                        if self.complete {
+                            break;
+                        }
+
+                        let mut record = csv::ByteRecord::new();
+
+                        if !reader.read_byte_record(&mut record)? {
+                            self.complete = true;
+                            break;
+                        }
+
+                        records.push(record);
+                    }
+
+                    if !callback(i, &records[i]) {
+                        break;
+                    }
+
+                    i += 1;
+                }
+            }
+            Source::Seekable {
+                reader,
+                index,
+                offsets,
+                next_offset,
+                ..
+            } => {
+                let offset = match index {
+                    Some(index) => {
+                        if from as u64 >= index.count() {
+                            return Ok(());
+                        }
+
+                        index.record_offset(from as u64)?
+                    }
+                    None => {
+                        if from < offsets.len() {
+                            offsets[from]
+                        } else if self.complete {
+                            return Ok(());
+                        } else {
+                            *next_offset
+                        }
+                    }
+                };
+
+                seek(reader, offset)?;
+
+                let mut record = csv::ByteRecord::new();
+                let mut i = from;
+
+                loop {
+                    if !reader.read_byte_record(&mut record)? {
+                        self.complete = true;
+                        break;
+                    }
+
+                    if index.is_none() && i == offsets.len() {
+                        offsets.push(record.position().unwrap().byte());
+                        *next_offset = reader.position().byte();
+                    }
+
+                    if !callback(i, &record) {
+                        break;
+                    }
+
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&mut self, i: usize) -> CliResult<Option<csv::ByteRecord>> {
+        match &self.source {
+            Source::Buffered { records, .. } => {
+                if let Some(record) = records.get(i) {
+                    return Ok(Some(record.clone()));
+                }
+            }
+            Source::Seekable { cache, .. } => {
+                if let Some(record) = cache.get(&i) {
+                    return Ok(Some(record.clone()));
+                }
+            }
+        }
+
+        let mut found: Option<csv::ByteRecord> = None;
+
+        self.scan(i, |_, record| {
+            found = Some(record.clone());
+            false
+        })?;
+
+        if let (Some(record), Source::Seekable { cache, .. }) = (&found, &mut self.source) {
+            if cache.len() >= CACHE_CAPACITY {
+                cache.clear();
+            }
+
+            cache.insert(i, record.clone());
+        }
+
+        Ok(found)
+    }
+}
+
+// NOTE: numbers come first, then strings, then empty cells, whatever the
+// direction of the sort.
+#[derive(Debug)]
+enum SortKey {
+    Number(f64),
+    Text(Vec<u8>),
+    Empty,
+}
+
+impl SortKey {
+    fn new(cell: &[u8]) -> Self {
+        if cell.is_empty() {
+            return Self::Empty;
+        }
+
+        match fast_float::parse::<f64, &[u8]>(cell) {
+            Ok(f) => Self::Number(f),
+            Err(_) => Self::Text(cell.to_vec()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Number(_) => 0,
+            Self::Text(_) => 1,
+            Self::Empty => 2,
+        }
+    }
+
+    fn compare(&self, other: &Self, descending: bool) -> Ordering {
+        let ordering = match (self, other) {
+            (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+            (Self::Text(a), Self::Text(b)) => a.cmp(b),
+            _ => return self.rank().cmp(&other.rank()),
+        };
+
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+fn style_by_type(cell: &str) -> Style {
+    match util::colorizer_by_type(cell) {
+        ColorOrStyles::Color(color) => Style::new().fg(match color {
+            ColoredColor::Red => Color::Red,
+            ColoredColor::Green => Color::Green,
+            ColoredColor::Blue => Color::Blue,
+            ColoredColor::Cyan => Color::Cyan,
+            ColoredColor::Magenta => Color::Magenta,
+            ColoredColor::Yellow => Color::Yellow,
+            _ => Color::Reset,
+        }),
+        ColorOrStyles::Styles(Styles::Dimmed) => Style::new().add_modifier(Modifier::DIM),
+        ColorOrStyles::Styles(_) => Style::new(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PromptKind {
+    Search,
+    RegexSearch,
+    Jump,
+}
+
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+struct Sorting {
+    column: usize,
+    descending: bool,
+    order: Vec<usize>,
+}
+
+struct DisplayedCell {
+    text: String,
+    matched: bool,
+}
+
+pub struct InteractiveViewOptions {
+    pub title: String,
+    pub show_index: bool,
+    pub sanitize_emojis: bool,
+    pub formatter: Option<Formatter>,
+}
+
+pub struct InteractiveView {
+    options: InteractiveViewOptions,
+    emoji_sanitizer: Option<util::EmojiSanitizer>,
+    headers: Vec<String>,
+    sel: Selection,
+    rows: LazyRows,
+
+    // Displayed columns, as indices in the selection, and hidden ones along
+    // with the position they were hidden from
+    columns: Vec<usize>,
+    hidden: Vec<(usize, usize)>,
+    widths: Vec<usize>,
+    index_width: usize,
+
+    // Cursor & viewport, in rows & positions in `columns`
+    row: usize,
+    col: usize,
+    top: usize,
+    left: usize,
+    height: usize,
+
+    sorting: Option<Sorting>,
+    matcher: Option<Matcher>,
+    prompt: Option<Prompt>,
+    message: Option<String>,
+    show_help: bool,
+    visible: Vec<(usize, Vec<DisplayedCell>)>,
+}
+
+impl InteractiveView {
+    pub fn new(
+        headers: Vec<String>,
+        sel: Selection,
+        rows: LazyRows,
+        options: InteractiveViewOptions,
+    ) -> Self {
+        let widths = headers
+            .iter()
+            .map(|h| h.width().clamp(1, MAX_COLUMN_WIDTH))
+            .collect();
+
+        Self {
+            emoji_sanitizer: options.sanitize_emojis.then(util::EmojiSanitizer::new),
+            options,
+            columns: (0..headers.len()).collect(),
+            hidden: Vec::new(),
+            headers,
+            sel,
+            rows,
+            widths,
+            index_width: 1,
+            row: 0,
+            col: 0,
+            top: 0,
+            left: 0,
+            height: 1,
+            sorting: None,
+            matcher: None,
+            prompt: None,
+            message: None,
+            show_help: false,
+            visible: Vec::new(),
+        }
+    }
+
+    pub fn run(mut self) -> CliResult<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> CliResult<()> {
+        loop {
+            let size = terminal.size()?;
+
+            // NOTE: one line for the headers & one for the status bar
+            self.prepare(
+                size.width as usize,
+                (size.height as usize).saturating_sub(2),
+            )?;
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key)? {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        match &self.sorting {
+            Some(sorting) => sorting.order.len(),
+            None => self.rows.known_len(),
+        }
+    }
+
+    fn current_index(&self) -> Option<usize> {
+        match &self.sorting {
+            Some(sorting) => sorting.order.get(self.row).copied(),
+            None => (self.row < self.rows.known_len()).then_some(self.row),
+        }
+    }
+
+    fn position_of(&self, index: usize) -> Option<usize> {
+        match &self.sorting {
+            Some(sorting) => sorting.order.iter().position(|i| *i == index),
+            None => (index < self.rows.known_len()).then_some(index),
+        }
+    }
+
+    fn format_cell(
+        options: &mut InteractiveViewOptions,
+        emoji_sanitizer: Option<&util::EmojiSanitizer>,
+        cell: &str,
+    ) -> String {
+        let mut cell = util::sanitize_text_for_single_line_printing(cell);
+
+        if let Some(sanitizer) = emoji_sanitizer {
+            cell = sanitizer.sanitize(&cell);
+        }
+
+        if let Some(fmt) = options.formatter.as_mut() {
+            if let Ok(f) = cell.parse::<f64>() {
+                cell = util::format_number_with_formatter(fmt, f);
+            }
+        }
+
+        if cell.trim().is_empty() {
+            "<empty>".to_string()
+        } else {
+            cell
+        }
+    }
+
+    fn fitting_columns(&self, width: usize) -> Vec<usize> {
+        let mut available = if self.options.show_index {
+            width.saturating_sub(self.index_width + 1)
+        } else {
+            width
+        };
+
+        let mut positions = Vec::new();
+
+        for p in self.left..self.columns.len() {
+            let w = self.widths[self.columns[p]];
+
+            if !positions.is_empty() && w > available {
+                break;
+            }
+
+            positions.push(p);
+            available = available.saturating_sub(w + 1);
+
+            if available == 0 {
+                break;
+            }
+        }
+
+        positions
+    }
+
+    fn prepare(&mut self, width: usize, height: usize) -> CliResult<()> {
+        self.height = height.max(1);
+
+        // NOTE: reading one more row than necessary tells us whether we reached the end
+        self.rows.ensure(self.row.max(self.top + self.height) + 1)?;
+
+        let len = self.len();
+
+        self.row = self.row.min(len.saturating_sub(1));
+
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + self.height {
+            self.top = self.row + 1 - self.height;
+        }
+
+        let mut fetched: Vec<(usize, csv::ByteRecord)> = Vec::with_capacity(self.height);
+
+        match &self.sorting {
+            None => {
+                let end = self.top + self.height;
+
+                self.rows.scan(self.top, |i, record| {
+                    fetched.push((i, record.clone()));
+                    i + 1 < end
+                })?;
+            }
+            Some(sorting) => {
+                for i in sorting.order.iter().skip(self.top).take(self.height) {
+                    if let Some(record) = self.rows.get(*i)? {
+                        fetched.push((*i, record));
+                    }
+                }
+            }
+        }
+
+        self.visible.clear();
+
+        for (i, record) in fetched {
+            let mut cells = Vec::with_capacity(self.sel.len());
+
+            for (j, cell) in self.sel.select(&record).enumerate() {
+                let matched = self
+                    .matcher
+                    .as_ref()
+                    .is_some_and(|matcher| matcher.is_match(cell));
+
+                let text = Self::format_cell(
+                    &mut self.options,
+                    self.emoji_sanitizer.as_ref(),
+                    &String::from_utf8_lossy(cell),
+                );
+
+                self.widths[j] = self.widths[j].max(text.width().min(MAX_COLUMN_WIDTH));
+
+                cells.push(DisplayedCell { text, matched });
+            }
+
+            self.visible.push((i, cells));
+        }
+
+        self.index_width = self
+            .visible
+            .iter()
+            .map(|(i, _)| i.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        // Horizontal scrolling
+        self.col = self.col.min(self.columns.len().saturating_sub(1));
+
+        if self.col < self.left {
+            self.left = self.col;
+        }
+
+        loop {
+            match self.fitting_columns(width).last() {
+                Some(p) if self.col > *p => self.left += 1,
+                _ => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [table_area, status_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let positions = self.fitting_columns(table_area.width as usize);
+
+        let mut constraints = Vec::new();
+        let mut header = Vec::new();
+
+        let dimmed = Style::new().add_modifier(Modifier::DIM);
+
+        if self.options.show_index {
+            constraints.push(Constraint::Length(self.index_width as u16));
+            header.push(Cell::from("-").style(dimmed));
+        }
+
+        for p in positions.iter() {
+            let column = self.columns[*p];
+            let mut name = self.headers[column].clone();
+
+            if let Some(sorting) = &self.sorting {
+                if sorting.column == column {
+                    name.push_str(if sorting.descending { " ▼" } else { " ▲" });
+                }
+            }
+
+            let mut style = Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+            if *p == self.col {
+                style = style.fg(Color::Yellow);
+            }
+
+            constraints.push(Constraint::Length(self.widths[column] as u16));
+            header.push(Cell::from(name).style(style));
+        }
+
+        let rows = self.visible.iter().enumerate().map(|(k, (i, cells))| {
+            let is_current_row = self.top + k == self.row;
+            let mut row = Vec::with_capacity(positions.len() + 1);
+
+            if self.options.show_index {
+                row.push(
+                    Cell::from(Line::from(i.to_string()).alignment(Alignment::Right)).style(dimmed),
+                );
+            }
+
+            for p in positions.iter() {
+                let cell = &cells[self.columns[*p]];
+
+                let mut style = style_by_type(&cell.text);
+
+                if cell.matched {
+                    style = style.fg(Color::Black).bg(Color::Yellow);
+                }
+
+                if is_current_row && *p == self.col {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+
+                let mut line = Line::from(cell.text.as_str());
+
+                if fast_float::parse::<f64, &str>(cell.text.trim()).is_ok() {
+                    line = line.alignment(Alignment::Right);
+                }
+
+                row.push(Cell::from(line).style(style));
+            }
+
+            let row = Row::new(row);
+
+            if is_current_row {
+                row.style(Style::new().bg(Color::DarkGray))
+            } else {
+                row
+            }
+        });
+
+        let table = Table::new(rows, constraints)
+            .header(Row::new(header))
+            .column_spacing(1);
+
+        frame.render_widget(table, table_area);
+
+        frame.render_widget(self.status_line(), status_area);
+
+        if self.show_help {
+            self.draw_help(frame);
+        }
+    }
+
+    fn status_line(&self) -> Paragraph<'_> {
+        if let Some(prompt) = &self.prompt {
+            let prefix = match prompt.kind {
+                PromptKind::Search => "/",
+                PromptKind::RegexSearch => "\\",
+                PromptKind::Jump => "row index: ",
+            };
+
+            return Paragraph::new(format!("{}{}", prefix, prompt.input));
+        }
+
+        if let Some(message) = &self.message {
+            return Paragraph::new(message.as_str()).style(Style::new().fg(Color::Red));
+        }
+
+        let mut info = format!(
+            "{} · row {} of {}{}",
+            self.options.title,
+            if self.len() == 0 { 0 } else { self.row + 1 },
+            self.len(),
+            if self.rows.is_complete() { "" } else { "+" }
+        );
+
+        if let Some(column) = self.columns.get(self.col) {
+            info.push_str(&format!(
+                " · column \"{}\" ({} of {})",
+                self.headers[*column],
+                self.col + 1,
+                self.columns.len()
+            ));
+        }
+
+        if !self.hidden.is_empty() {
+            info.push_str(&format!(" · {} hidden", self.hidden.len()));
+        }
+
+        if let Some(sorting) = &self.sorting {
+            info.push_str(&format!(
+                " · sorted by \"{}\"{}",
+                self.headers[sorting.column],
+                if sorting.descending { " (desc)" } else { "" }
+            ));
+        }
+
+        info.push_str(" · ? for help");
+
+        Paragraph::new(info).style(Style::new().add_modifier(Modifier::REVERSED))
+    }
+
+    fn draw_help(&self, frame: &mut Frame) {
+        let key_width = HELP.iter().map(|(key, _)| key.width()).max().unwrap();
+
+        let lines = HELP
+            .iter()
+            .map(|(key, description)| Line::from(format!(" {:<key_width$}  {} ", key, description)))
+            .collect::<Vec<_>>();
+
+        let area = frame.area();
+
+        let width =
+            (lines.iter().map(|line| line.width()).max().unwrap() as u16 + 2).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().borders(Borders::ALL).title(" help ")),
+            popup,
+        );
+    }
+
+    fn update_matcher(&mut self) {
+        let prompt = match &self.prompt {
+            Some(prompt) if prompt.kind != PromptKind::Jump => prompt,
+            _ => return,
+        };
+
+        if prompt.input.is_empty() {
+            self.matcher = None;
+            return;
+        }
+
+        let case_insensitive = !prompt.input.chars().any(|c| c.is_uppercase());
+
+        self.matcher = match prompt.kind {
+            PromptKind::Search => AhoCorasick::new([if case_insensitive {
+                prompt.input.to_lowercase()
+            } else {
+                prompt.input.clone()
+            }])
+            .ok()
+            .map(|pattern| Matcher::Substring(pattern, case_insensitive)),
+            _ => RegexBuilder::new(&prompt.input)
+                .case_insensitive(case_insensitive)
+                .build()
+                .ok()
+                .map(Matcher::Regex),
+        };
+    }
+
+    fn find(&mut self, forward: bool) -> CliResult<()> {
+        let matcher = match &self.matcher {
+            None => return Ok(()),
+            Some(matcher) => matcher,
+        };
+
+        let columns = &self.columns;
+        let sel = &self.sel;
+
+        let is_match =
+            |record: &csv::ByteRecord| columns.iter().any(|j| matcher.is_match(&record[sel[*j]]));
+
+        let mut found: Option<usize> = None;
+
+        match (&self.sorting, forward) {
+            (None, true) => {
+                self.rows.scan(self.row + 1, |i, record| {
+                    if is_match(record) {
+                        found = Some(i);
+                        return false;
+                    }
+
+                    true
+                })?;
+            }
+            (None, false) => {
+                for i in (0..self.row).rev() {
+                    if self.rows.get(i)?.is_some_and(|record| is_match(&record)) {
+                        found = Some(i);
+                        break;
+                    }
+                }
+            }
+            (Some(sorting), _) => {
+                let positions: Box<dyn Iterator<Item = usize>> = if forward {
+                    Box::new(self.row + 1..sorting.order.len())
+                } else {
+                    Box::new((0..self.row).rev())
+                };
+
+                for p in positions {
+                    if self
+                        .rows
+                        .get(sorting.order[p])?
+                        .is_some_and(|record| is_match(&record))
+                    {
+                        found = Some(p);
+                        break;
+                    }
+                }
+            }
+        }
+
+        match found {
+            Some(p) => self.row = p,
+            None => {
+                self.message = Some(format!(
+                    "no match found {} current row",
+                    if forward { "after" } else { "before" }
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    fn jump(&mut self, input: &str) -> CliResult<()> {
+        let index = match input.trim().parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => {
+                self.message = Some(format!("invalid row index \"{}\"", input));
+                return Ok(());
+            }
+        };
+
+        self.rows.ensure(index + 1)?;
+
+        match self.position_of(index) {
+            Some(p) => self.row = p,
+            None => self.message = Some(format!("row index {} is out of range", index)),
+        };
+
+        Ok(())
+    }
+
+    fn toggle_sort(&mut self) -> CliResult<()> {
+        let column = match self.columns.get(self.col) {
+            None => return Ok(()),
+            Some(column) => *column,
+        };
+
+        let next = match &self.sorting {
+            Some(sorting) if sorting.column == column => {
+                if sorting.descending {
+                    None
+                } else {
+                    Some(true)
+                }
+            }
+            _ => Some(false),
+        };
+
+        let current = self.current_index();
+
+        self.sorting = match next {
+            None => None,
+            Some(descending) => {
+                let j = self.sel[column];
+                let mut keys: Vec<(SortKey, usize)> = Vec::new();
+
+                self.rows.scan(0, |i, record| {
+                    keys.push((SortKey::new(&record[j]), i));
+                    true
+                })?;
+
+                keys.sort_by(|a, b| a.0.compare(&b.0, descending));
+
+                Some(Sorting {
+                    column,
+                    descending,
+                    order: keys.into_iter().map(|(_, i)| i).collect(),
+                })
+            }
+        };
+
+        if let Some(p) = current.and_then(|i| self.position_of(i)) {
+            self.row = p;
+        }
+
+        Ok(())
+    }
+
+    fn hide_column(&mut self) {
+        if self.columns.len() <= 1 {
+            self.message = Some("cannot hide the last column".to_string());
+            return;
+        }
+
+        let column = self.columns.remove(self.col);
+        self.hidden.push((self.col, column));
+        self.col = self.col.min(self.columns.len() - 1);
+    }
+
+    fn unhide_column(&mut self) {
+        if let Some((p, column)) = self.hidden.pop() {
+            let p = p.min(self.columns.len());
+
+            self.columns.insert(p, column);
+            self.col = p;
+        }
+    }
+
+    fn move_column(&mut self, right: bool) {
+        if right && self.col + 1 < self.columns.len() {
+            self.columns.swap(self.col, self.col + 1);
+            self.col += 1;
+        } else if !right && self.col > 0 {
+            self.columns.swap(self.col - 1, self.col);
+            self.col -= 1;
+        }
+    }
+
+    // NOTE: returns whether to keep going
+    fn handle_key(&mut self, key: KeyEvent) -> CliResult<bool> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(false);
+        }
+
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => {
+                    if prompt.kind != PromptKind::Jump {
+                        self.matcher = None;
+                    }
+
+                    self.prompt = None;
+                }
+                KeyCode::Enter => {
+                    let prompt = self.prompt.take().unwrap();
+
+                    if prompt.kind == PromptKind::Jump {
+                        self.jump(&prompt.input)?;
+                    } else {
+                        self.find(true)?;
+                    }
+                }
+                KeyCode::Backspace => {
+                    prompt.input.pop();
+                    self.update_matcher();
+                }
+                KeyCode::Char(c) => {
+                    prompt.input.push(c);
+                    self.update_matcher();
+                }
+                _ => (),
+            };
+
+            return Ok(true);
+        }
+
+        if self.show_help {
+            self.show_help = false;
+            return Ok(true);
+        }
+
+        self.message = None;
+
+        let new_prompt = |kind| {
+            Some(Prompt {
+                kind,
+                input: String::new(),
+            })
+        };
+
+        match key.code {
+            KeyCode::Char('q') => return Ok(false),
+            KeyCode::Esc => self.matcher = None,
+            KeyCode::Up | KeyCode::Char('k') => self.row = self.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.row += 1,
+            KeyCode::Left | KeyCode::Char('h') => self.col = self.col.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.col += 1,
+            KeyCode::PageUp | KeyCode::Char('b') => self.row = self.row.saturating_sub(self.height),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.row += self.height,
+            KeyCode::Home | KeyCode::Char('g') => self.row = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                self.rows.ensure(usize::MAX)?;
+                self.row = self.len().saturating_sub(1);
+            }
+            KeyCode::Char('0') | KeyCode::Char('^') => self.col = 0,
+            KeyCode::Char('$') => self.col = self.columns.len().saturating_sub(1),
+            KeyCode::Char(':') => self.prompt = new_prompt(PromptKind::Jump),
+            KeyCode::Char('/') => self.prompt = new_prompt(PromptKind::Search),
+            KeyCode::Char('\\') => self.prompt = new_prompt(PromptKind::RegexSearch),
+            KeyCode::Char('n') => self.find(true)?,
+            KeyCode::Char('N') => self.find(false)?,
+            KeyCode::Char('s') => self.toggle_sort()?,
+            KeyCode::Char('x') | KeyCode::Char('-') => self.hide_column(),
+            KeyCode::Char('u') => self.unhide_column(),
+            KeyCode::Char('<') => self.move_column(false),
+            KeyCode::Char('>') => self.move_column(true),
+            KeyCode::Char('i') => self.options.show_index = !self.options.show_index,
+            KeyCode::Char('?') => self.show_help = true,
+            _ => (),
+        };
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    static DATA: &str = "name,count\njohn,3\nmary,\nlucy,12\nbob,-1\n";
+
+    fn seekable_rows() -> LazyRows {
+        let mut reader = csv::Reader::from_reader(
+            Box::new(Cursor::new(DATA.as_bytes().to_vec())) as Box<dyn SeekRead + Send>
+        );
+        reader.byte_headers().unwrap();
+        let start = reader.position().byte();
+
+        LazyRows::seekable(reader, start, None)
+    }
+
+    fn buffered_rows() -> LazyRows {
+        let mut reader = csv::Reader::from_reader(
+            Box::new(Cursor::new(DATA.as_bytes().to_vec())) as Box<dyn Read + Send>
+        );
+        reader.byte_headers().unwrap();
+
+        LazyRows::buffered(reader)
+    }
+
+    fn name(rows: &mut LazyRows, i: usize) -> Option<String> {
+        rows.get(i)
+            .unwrap()
+            .map(|record| String::from_utf8(record[0].to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_lazy_rows() {
+        for mut rows in [seekable_rows(), buffered_rows()] {
+            assert_eq!(rows.known_len(), 0);
+
+            assert_eq!(name(&mut rows, 1), Some("mary".to_string()));
+            assert_eq!(rows.known_len(), 2);
+            assert!(!rows.is_complete());
+
+            assert_eq!(name(&mut rows, 3), Some("bob".to_string()));
+            assert_eq!(name(&mut rows, 0), Some("john".to_string()));
+            assert_eq!(name(&mut rows, 4), None);
+            assert!(rows.is_complete());
+            assert_eq!(rows.known_len(), 4);
+
+            let mut names = Vec::new();
+
+            rows.scan(2, |_, record| {
+                names.push(String::from_utf8(record[0].to_vec()).unwrap());
+                true
+            })
+            .unwrap();
+
+            assert_eq!(names, vec!["lucy", "bob"]);
+        }
+    }
+
+    fn render(view: &mut InteractiveView) -> Vec<String> {
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 6)).unwrap();
+        view.prepare(40, 4).unwrap();
+        terminal.draw(|frame| view.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+
+        buffer
+            .content
+            .chunks(40)
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn press(view: &mut InteractiveView, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                _ => KeyCode::Char(c),
+            };
+
+            view.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_interactive_view() {
+        let mut view = InteractiveView::new(
+            vec!["name".to_string(), "count".to_string()],
+            Selection::full(2),
+            seekable_rows(),
+            InteractiveViewOptions {
+                title: "test.csv".to_string(),
+                show_index: true,
+                sanitize_emojis: false,
+                formatter: None,
+            },
+        );
+
+        assert_eq!(
+            render(&mut view)[..5],
+            [
+                "- name count",
+                "0 john       3",
+                "1 mary <empty>",
+                "2 lucy      12",
+                "3 bob       -1"
+            ]
+        );
+
+        // Sorting on second column, ascending then descending
+        press(&mut view, "ls");
+        assert_eq!(
+            render(&mut view)[..5],
+            [
+                "- name count ▲",
+                "3 bob       -1",
+                "0 john       3",
+                "2 lucy      12",
+                "1 mary <empty>"
+            ]
+        );
+
+        press(&mut view, "s");
+        assert_eq!(
+            render(&mut view)[..5],
+            [
+                "- name count ▼",
+                "2 lucy      12",
+                "0 john       3",
+                "3 bob       -1",
+                "1 mary <empty>"
+            ]
+        );
+        assert_eq!(view.current_index(), Some(0));
+
+        // Searching
+        press(&mut view, "s/lu\n");
+        assert_eq!(view.current_index(), Some(2));
+        render(&mut view);
+        assert!(view.visible[2].1[0].matched);
+        assert!(!view.visible[0].1[0].matched);
+
+        press(&mut view, "n");
+        assert_eq!(
+            view.message.as_deref(),
+            Some("no match found after current row")
+        );
+
+        // Hiding columns & jumping
+        press(&mut view, "x:3\n");
+        assert_eq!(render(&mut view)[..2], ["- name", "0 john"]);
+        assert_eq!(view.current_index(), Some(3));
+
+        press(&mut view, "u<");
+        assert_eq!(render(&mut view)[..2], ["- count   name", "0       3 john"]);
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let mut keys = ["b", "", "10", "a", "-1", "2"]
+            .iter()
+            .map(|cell| SortKey::new(cell.as_bytes()))
+            .collect::<Vec<_>>();
+
+        keys.sort_by(|a, b| a.compare(b, false));
+
+        assert_eq!(
+            format!("{:?}", keys),
+            "[Number(-1.0), Number(2.0), Number(10.0), Text([97]), Text([98]), Empty]"
+        );
+
+        keys.sort_by(|a, b| a.compare(b, true));
+
+        assert_eq!(
+            format!("{:?}", keys),
+            "[Number(10.0), Number(2.0), Number(-1.0), Text([98]), Text([97]), Empty]"
+        );
+    }
+}
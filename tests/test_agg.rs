@@ -599,3 +599,64 @@ fn agg_along_matrix() {
     let expected = vec![svec!["total"], svec!["15"]];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn agg_sketches() {
+    let wrk = Workdir::new("agg_sketches");
+
+    let mut rows = vec![svec!["day", "user", "n"]];
+
+    for i in 0..100 {
+        rows.push(vec![
+            (if i < 50 { "monday" } else { "tuesday" }).to_string(),
+            format!("user{}", i % 70),
+            i.to_string(),
+        ]);
+    }
+
+    wrk.create("data.csv", rows);
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("day")
+        .arg("hll_sketch(user) as users, tdigest_sketch(n) as n")
+        .arg("data.csv");
+
+    let daily: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    assert_eq!(daily.len(), 3);
+    assert!(daily[1][1].starts_with("hll:"));
+    assert!(daily[1][2].starts_with("tdigest:"));
+
+    wrk.create("daily.csv", daily);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("hll_merge(users) as users, tdigest_merge(n) as n")
+        .arg("daily.csv");
+
+    wrk.create("total.csv", wrk.read_stdout::<Vec<Vec<String>>>(&mut cmd));
+
+    let mut cmd = wrk.command("map");
+    cmd.arg("hll_cardinality(users) as cardinality, tdigest_quantile(n, 0.5) as median")
+        .arg("total.csv");
+
+    let merged: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("approx_cardinality(user) as cardinality, approx_quantile(n, 0.5) as median")
+        .arg("data.csv");
+
+    let direct: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    assert_eq!(merged[0][2..], direct[0]);
+    assert_eq!(merged[1][2], "70");
+    assert_eq!(direct[1][0], "70");
+
+    // NOTE: merged t-digests are only approximately the same
+    let merged_median = merged[1][3].parse::<f64>().unwrap();
+    let direct_median = direct[1][1].parse::<f64>().unwrap();
+    assert!((merged_median - direct_median).abs() < 1.0);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("hll_merge(user) as users").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
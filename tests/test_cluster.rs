@@ -0,0 +1,147 @@
+use crate::workdir::Workdir;
+
+fn names() -> Vec<Vec<String>> {
+    vec![
+        svec!["name"],
+        svec!["University of Paris"],
+        svec!["university of paris"],
+        svec!["Paris, University of"],
+        svec!["University of Paris"],
+        svec!["Sorbonne"],
+        svec!["Sorbone"],
+        svec!["Sorbonne"],
+        svec!["John"],
+    ]
+}
+
+#[test]
+fn cluster() {
+    let wrk = Workdir::new("cluster");
+    wrk.create("data.csv", names());
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "University of Paris", "2", "University of Paris"],
+        svec!["0", "Paris, University of", "1", "University of Paris"],
+        svec!["0", "university of paris", "1", "University of Paris"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_ngrams() {
+    let wrk = Workdir::new("cluster_ngrams");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name"],
+            svec!["New York"],
+            svec!["newyork"],
+            svec!["New-York"],
+            svec!["Boston"],
+        ],
+    );
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name").args(["-m", "ngrams"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "New York", "1", "New York"],
+        svec!["0", "New-York", "1", "New York"],
+        svec!["0", "newyork", "1", "New York"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_soundex() {
+    let wrk = Workdir::new("cluster_soundex");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name"],
+            svec!["Robert Smith"],
+            svec!["Rupert Smyth"],
+            svec!["Alice"],
+        ],
+    );
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name").args(["-m", "soundex"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "Robert Smith", "1", "Robert Smith"],
+        svec!["0", "Rupert Smyth", "1", "Robert Smith"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_levenshtein() {
+    let wrk = Workdir::new("cluster_levenshtein");
+    wrk.create("data.csv", names());
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name").args(["-m", "levenshtein"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "Sorbonne", "2", "Sorbonne"],
+        svec!["0", "Sorbone", "1", "Sorbonne"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_jaccard() {
+    let wrk = Workdir::new("cluster_jaccard");
+    wrk.create("data.csv", names());
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name")
+        .args(["-m", "jaccard", "-t", "0.8"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "University of Paris", "2", "University of Paris"],
+        svec!["0", "Paris, University of", "1", "University of Paris"],
+        svec!["0", "university of paris", "1", "University of Paris"],
+        svec!["1", "Sorbonne", "2", "Sorbonne"],
+        svec!["1", "Sorbone", "1", "Sorbonne"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_key() {
+    let wrk = Workdir::new("cluster_key");
+    wrk.create("data.csv", names());
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name").args(["-k", "lower"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cluster_id", "value", "count", "canonical"],
+        svec!["0", "University of Paris", "2", "University of Paris"],
+        svec!["0", "university of paris", "1", "University of Paris"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cluster_key_and_method() {
+    let wrk = Workdir::new("cluster_key_and_method");
+    wrk.create("data.csv", names());
+    let mut cmd = wrk.command("cluster");
+    cmd.arg("name")
+        .args(["-k", "lower", "-m", "ngrams"])
+        .arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,135 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "old.csv",
+        vec![
+            svec!["id", "name", "age"],
+            svec!["1", "john", "30"],
+            svec!["2", "mary", "25"],
+            svec!["3", "lucy", "40"],
+        ],
+    );
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["id", "name", "city"],
+            svec!["1", "john", "paris"],
+            svec!["3", "lucy-x", "london"],
+            svec!["4", "bob", "rome"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn diff() {
+    let wrk = setup("diff");
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id").arg("old.csv").arg("new.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["diff", "id", "column", "old", "new"],
+        svec!["removed_column", "", "age", "2", ""],
+        svec!["added_column", "", "city", "", "2"],
+        svec!["modified", "1", "city", "", "paris"],
+        svec!["modified", "3", "name", "lucy", "lucy-x"],
+        svec!["modified", "3", "city", "", "london"],
+        svec!["added", "4", "name", "", "bob"],
+        svec!["added", "4", "city", "", "rome"],
+        svec!["removed", "2", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_sorted() {
+    let wrk = setup("diff_sorted");
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id").arg("old.csv").arg("new.csv").arg("--sorted");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["diff", "id", "column", "old", "new"],
+        svec!["removed_column", "", "age", "2", ""],
+        svec!["added_column", "", "city", "", "2"],
+        svec!["modified", "1", "city", "", "paris"],
+        svec!["removed", "2", "", "", ""],
+        svec!["modified", "3", "name", "lucy", "lucy-x"],
+        svec!["modified", "3", "city", "", "london"],
+        svec!["added", "4", "name", "", "bob"],
+        svec!["added", "4", "city", "", "rome"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_moved_columns_and_composite_key() {
+    let wrk = Workdir::new("diff_moved_columns_and_composite_key");
+    wrk.create(
+        "old.csv",
+        vec![
+            svec!["a", "b", "value"],
+            svec!["x", "1", "one"],
+            svec!["x", "2", "two"],
+        ],
+    );
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["value", "a", "b"],
+            svec!["one", "x", "1"],
+            svec!["deux", "x", "2"],
+        ],
+    );
+    let mut cmd = wrk.command("diff");
+    cmd.arg("a,b").arg("old.csv").arg("new.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["diff", "a", "b", "column", "old", "new"],
+        svec!["moved_column", "", "", "value", "2", "0"],
+        svec!["moved_column", "", "", "a", "0", "1"],
+        svec!["moved_column", "", "", "b", "1", "2"],
+        svec!["modified", "x", "2", "value", "two", "deux"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_errors() {
+    let wrk = setup("diff_errors");
+    wrk.create(
+        "duplicates.csv",
+        vec![svec!["id", "name"], svec!["1", "john"], svec!["1", "mary"]],
+    );
+    wrk.create(
+        "added_duplicates.csv",
+        vec![svec!["id", "name"], svec!["5", "bob"], svec!["5", "alice"]],
+    );
+    wrk.create(
+        "unsorted.csv",
+        vec![svec!["id", "name"], svec!["2", "mary"], svec!["1", "john"]],
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id").arg("old.csv").arg("duplicates.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id").arg("old.csv").arg("added_duplicates.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id")
+        .arg("old.csv")
+        .arg("added_duplicates.csv")
+        .arg("-S");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id").arg("old.csv").arg("unsorted.csv").arg("-S");
+    wrk.assert_err(&mut cmd);
+}
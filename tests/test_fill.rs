@@ -0,0 +1,187 @@
+use crate::workdir::Workdir;
+
+fn data() -> Vec<Vec<String>> {
+    vec![
+        svec!["g", "date", "v"],
+        svec!["a", "2024-01-01", "1"],
+        svec!["a", "2024-01-02", ""],
+        svec!["a", "2024-01-04", "4"],
+        svec!["b", "2024-01-01", ""],
+        svec!["b", "2024-01-03", "10"],
+        svec!["a", "2024-01-05", ""],
+    ]
+}
+
+fn fill_column(wrk: &Workdir, args: &[&str]) -> Vec<String> {
+    let mut cmd = wrk.command("fill");
+    cmd.args(args).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    got.into_iter().skip(1).map(|row| row[2].clone()).collect()
+}
+
+#[test]
+fn fill() {
+    let wrk = Workdir::new("fill");
+    wrk.create("data.csv", data());
+
+    assert_eq!(
+        fill_column(&wrk, &[]),
+        svec!["1", "1", "4", "4", "10", "10"]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-v", "0"]),
+        svec!["1", "0", "4", "0", "10", "0"]
+    );
+}
+
+#[test]
+fn fill_methods() {
+    let wrk = Workdir::new("fill_methods");
+    wrk.create("data.csv", data());
+
+    assert_eq!(
+        fill_column(&wrk, &["-m", "backward"]),
+        svec!["1", "4", "4", "10", "10", ""]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-m", "backward", "-v", "0"]),
+        svec!["1", "4", "4", "10", "10", "0"]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-m", "nearest"]),
+        svec!["1", "1", "4", "4", "10", "10"]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-m", "linear", "-s", "v"]),
+        svec!["1", "2.5", "4", "7", "10", ""]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-m", "spline", "-s", "v"]),
+        svec!["1", "2.21875", "4", "6.71875", "10", ""]
+    );
+
+    // Linear interpolation cannot work with non-numerical values
+    let mut cmd = wrk.command("fill");
+    cmd.args(["-m", "linear"]).arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn fill_groupby() {
+    let wrk = Workdir::new("fill_groupby");
+    wrk.create("data.csv", data());
+
+    assert_eq!(
+        fill_column(&wrk, &["-g", "g"]),
+        svec!["1", "1", "4", "", "10", "4"]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-g", "g", "-m", "backward"]),
+        svec!["1", "4", "4", "10", "10", ""]
+    );
+    assert_eq!(
+        fill_column(&wrk, &["-g", "g", "-t", "date", "-m", "linear"]),
+        svec!["1", "2", "4", "", "10", ""]
+    );
+}
+
+#[test]
+fn fill_spine() {
+    let wrk = Workdir::new("fill_spine");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("fill");
+    cmd.args(["-g", "g", "-t", "date", "--spine", "-m", "linear"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["g", "date", "v"],
+        svec!["a", "2024-01-01", "1"],
+        svec!["a", "2024-01-02", "2"],
+        svec!["a", "2024-01-03", "3"],
+        svec!["a", "2024-01-04", "4"],
+        svec!["b", "2024-01-01", ""],
+        svec!["b", "2024-01-02", ""],
+        svec!["b", "2024-01-03", "10"],
+        svec!["a", "2024-01-05", ""],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fill_spine_mixed_timezones() {
+    let wrk = Workdir::new("fill_spine_mixed_timezones");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["date", "v"],
+            svec!["2024-01-03T00:00:00Z", "1"],
+            svec!["2024-01-05", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fill");
+    cmd.env("TZ", "America/New_York")
+        .args(["-t", "date", "--spine", "-u", "day", "-m", "linear"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "v"],
+        svec!["2024-01-03T00:00:00Z", "1"],
+        svec!["2024-01-04", "2"],
+        svec!["2024-01-05", "3"],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fill_spine_datetimes() {
+    let wrk = Workdir::new("fill_spine_datetimes");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["time", "v"],
+            svec!["2024-01-03T10:00:00", "1"],
+            svec!["2024-01-03T13:00:00", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fill");
+    cmd.args(["-t", "time", "-S", "-u", "hour", "-v", "0"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["time", "v"],
+        svec!["2024-01-03T10:00:00", "1"],
+        svec!["2024-01-03T11:00:00", "0"],
+        svec!["2024-01-03T12:00:00", "0"],
+        svec!["2024-01-03T13:00:00", "4"],
+    ];
+
+    assert_eq!(got, expected);
+
+    // Unit is required for datetimes
+    let mut cmd = wrk.command("fill");
+    cmd.args(["-t", "time", "-S"]).arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    // Rows must be sorted
+    wrk.create(
+        "unsorted.csv",
+        vec![
+            svec!["time", "v"],
+            svec!["2024-01-03", "1"],
+            svec!["2024-01-01", "4"],
+        ],
+    );
+    let mut cmd = wrk.command("fill");
+    cmd.args(["-t", "time", "-S"]).arg("unsorted.csv");
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,197 @@
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::workdir::Workdir;
+
+fn create_report_xlsx(wrk: &Workdir) {
+    let mut workbook = Workbook::new();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("report").unwrap();
+    worksheet.write_string(0, 0, "Yearly report").unwrap();
+    worksheet.write_string(2, 0, "country").unwrap();
+    worksheet.write_string(2, 1, "city").unwrap();
+    worksheet
+        .merge_range(3, 0, 4, 0, "France", &Format::new())
+        .unwrap();
+    worksheet.write_string(3, 1, "Paris").unwrap();
+    worksheet.write_string(4, 1, "Lyon").unwrap();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("other").unwrap();
+    worksheet.write_string(0, 0, "Monthly report").unwrap();
+    worksheet.write_string(2, 0, "country").unwrap();
+    worksheet.write_string(2, 1, "city").unwrap();
+    worksheet.write_string(3, 0, "Italy").unwrap();
+    worksheet.write_string(3, 1, "Rome").unwrap();
+
+    workbook.save(wrk.path("report.xlsx")).unwrap();
+}
+
+#[test]
+fn from_xlsx_skip_rows() {
+    let wrk = Workdir::new("from_xlsx_skip_rows");
+    create_report_xlsx(&wrk);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--skip-rows", "2"]).arg("report.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "city"],
+        svec!["France", "Paris"],
+        svec!["", "Lyon"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn from_xlsx_fill_merged() {
+    let wrk = Workdir::new("from_xlsx_fill_merged");
+    create_report_xlsx(&wrk);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--skip-rows", "2", "--fill-merged"])
+        .arg("report.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "city"],
+        svec!["France", "Paris"],
+        svec!["France", "Lyon"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn from_xlsx_all_sheets() {
+    let wrk = Workdir::new("from_xlsx_all_sheets");
+    create_report_xlsx(&wrk);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--skip-rows", "2", "--fill-merged", "--all-sheets"])
+        .arg("report.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["sheet", "country", "city"],
+        svec!["report", "France", "Paris"],
+        svec!["report", "France", "Lyon"],
+        svec!["other", "Italy", "Rome"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--skip-rows", "2", "--output-dir", "sheets"])
+        .arg("report.xlsx");
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("sheets/other.csv"));
+    assert_eq!(got, "country,city\nItaly,Rome\n");
+
+    // Sheets with different headers cannot be concatenated
+    let mut cmd = wrk.command("from");
+    cmd.arg("--all-sheets").arg("report.xlsx");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn from_html() {
+    let wrk = Workdir::new("from_html");
+    wrk.write(
+        "data.html",
+        r#"<html><body>
+<table><tr><td>menu</td></tr></table>
+<table class="data">
+  <thead>
+    <tr><th rowspan="2">country</th><th colspan="2">population</th></tr>
+    <tr><th>2000</th><th>2010</th></tr>
+  </thead>
+  <tbody>
+    <tr><td rowspan="2">France &amp; co</td><td>60</td><td>65</td></tr>
+    <tr><td>1</td><td>2</td></tr>
+    <tr><td>Italy</td><td colspan="2">57 <b>(est.)</b></td></tr>
+  </tbody>
+</table>
+</body></html>"#,
+    );
+
+    let expected = vec![
+        svec!["country", "population 2000", "population 2010"],
+        svec!["France & co", "60", "65"],
+        svec!["France & co", "1", "2"],
+        svec!["Italy", "57 (est.)", "57 (est.)"],
+    ];
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["-n", "1"]).arg("data.html");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--table-selector", "table.data"])
+        .arg("data.html");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    // Tables without headers
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.html");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, vec![svec!["col_0"], svec!["menu"]]);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["-n", "2"]).arg("data.html");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn from_xml() {
+    let wrk = Workdir::new("from_xml");
+    wrk.write(
+        "feed.xml",
+        r#"<?xml version="1.0"?>
+<rss>
+  <channel>
+    <title>Feed</title>
+    <item id="1">
+      <title>First &amp; best</title>
+      <link href="http://a"/>
+      <author><name>John</name></author>
+      <tag>a</tag>
+      <tag>b</tag>
+    </item>
+    <item id="2">
+      <title><![CDATA[Second <b>]]></title>
+      <link href="http://b"/>
+      <author><name>Mary</name></author>
+    </item>
+  </channel>
+</rss>"#,
+    );
+
+    let expected = vec![
+        svec!["@id", "author/name", "link/@href", "tag", "title"],
+        svec!["1", "John", "http://a", "a|b", "First & best"],
+        svec!["2", "Mary", "http://b", "", "Second <b>"],
+    ];
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--row-path", "item"]).arg("feed.xml");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--row-path", "/rss/channel/item"])
+        .arg("feed.xml");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("feed.xml");
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,27 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn heatmap_svg() {
+    let wrk = Workdir::new("heatmap_svg");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "a", "b"],
+            svec!["x", "0", "1"],
+            svec!["y", "2", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("heatmap");
+    cmd.args(["data.csv", "-N", "--svg", "heatmap.svg"]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("heatmap.svg"));
+
+    // NOTE: the first & last cells of the gradient
+    assert!(svg.contains("fill=\"#fff7ec\"/>"));
+    assert!(svg.contains("fill=\"#7f0000\"/>"));
+    assert!(svg.contains(">x</text>"));
+    assert!(svg.contains(">b</text>"));
+    assert_eq!(svg.matches("text-anchor=\"middle\"").count(), 3);
+}
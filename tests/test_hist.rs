@@ -0,0 +1,26 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn hist_svg() {
+    let wrk = Workdir::new("hist_svg");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["field", "value", "count"],
+            svec!["name", "john", "3"],
+            svec!["name", "lucy", "1"],
+            svec!["surname", "smith", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("hist");
+    cmd.args(["data.csv", "--svg", "hist.svg"]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("hist.svg"));
+
+    assert!(svg.contains(">Histogram for name (bars: 2, sum: 4, max: 3)</text>"));
+    assert!(svg.contains(">Histogram for surname (bars: 1, sum: 4, max: 4)</text>"));
+    assert!(svg.contains(">john</text>"));
+    assert!(svg.contains(">75.00%</text>"));
+}
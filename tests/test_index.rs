@@ -0,0 +1,141 @@
+use std::fs;
+
+use crate::workdir::Workdir;
+
+fn numbers() -> Vec<Vec<String>> {
+    let mut rows = vec![svec!["n", "text"]];
+
+    for i in 0..10 {
+        rows.push(vec![i.to_string(), format!("line\n{}", i)]);
+    }
+
+    rows
+}
+
+#[test]
+fn index() {
+    let wrk = Workdir::new("index");
+    wrk.create("data.csv", numbers());
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    assert!(wrk.path("data.csv.xidx").is_file());
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("data.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got.trim(), "10");
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("data.csv").arg("-n");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got.trim(), "11");
+}
+
+#[test]
+fn index_slice() {
+    let wrk = Workdir::new("index_slice");
+    wrk.create("data.csv", numbers());
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["-s", "3", "-l", "2"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["n", "text"],
+        svec!["3", "line\n3"],
+        svec!["4", "line\n4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["-I", "8,1,25"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["n", "text"],
+        svec!["1", "line\n1"],
+        svec!["8", "line\n8"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["-L", "2"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["n", "text"],
+        svec!["8", "line\n8"],
+        svec!["9", "line\n9"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["-i", "1", "-n"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["0", "line\n0"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn index_split() {
+    let wrk = Workdir::new("index_split");
+    wrk.create("data.csv", numbers());
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("split");
+    cmd.args(["-c", "3", "-O", "."]).arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let counts = ["0.csv", "1.csv", "2.csv"]
+        .iter()
+        .map(|name| {
+            let mut cmd = wrk.command("count");
+            cmd.arg(name);
+            wrk.stdout::<String>(&mut cmd).trim().to_string()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(counts, svec!["3", "3", "4"]);
+}
+
+#[test]
+fn index_parallel() {
+    let wrk = Workdir::new("index_parallel");
+    wrk.create("data.csv", numbers());
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("parallel");
+    cmd.args(["count", "-t", "3"]).arg("data.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got.trim(), "10");
+}
+
+#[test]
+fn index_stale() {
+    let wrk = Workdir::new("index_stale");
+    wrk.create("data.csv", numbers());
+
+    let mut cmd = wrk.command("index");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let mut data = fs::read_to_string(wrk.path("data.csv")).unwrap();
+    data.push_str("10,last\n");
+    wrk.write("data.csv", &data);
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("data.csv");
+    let output = wrk.output(&mut cmd);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "11");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stale"));
+}
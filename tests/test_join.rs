@@ -298,3 +298,222 @@ fn join_anti() {
     ];
     assert_eq!(got, expected);
 }
+
+fn sorted_rows(headers: bool, mut rows: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let skip = if headers { 1 } else { 0 };
+    rows[skip..].sort();
+    rows
+}
+
+join_test!(
+    join_inner_external,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.args(["--external", "-m", "0"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            vec![
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+            ],
+        );
+        assert_eq!(super::sorted_rows(headers, got), expected);
+    }
+);
+
+join_test!(
+    join_outer_full_external,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.args(["--full", "--external", "-m", "0"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            vec![
+                svec!["", "", "Orlando", "Disney World"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["New York", "NY", "", ""],
+                svec!["San Francisco", "CA", "", ""],
+            ],
+        );
+        assert_eq!(super::sorted_rows(headers, got), expected);
+    }
+);
+
+join_test!(
+    join_outer_left_external,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.args(["--left", "--external"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            vec![
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["New York", "NY", "", ""],
+                svec!["San Francisco", "CA", "", ""],
+            ],
+        );
+        assert_eq!(super::sorted_rows(headers, got), expected);
+    }
+);
+
+#[test]
+fn join_anti_external() {
+    let wrk = Workdir::new("join_anti_external");
+    wrk.create(
+        "fruits.csv",
+        vec![
+            svec!["id", "fruit"],
+            svec!["1", "mango"],
+            svec!["2", "orange"],
+            svec!["3", "apple"],
+            svec!["4", "cherry"],
+        ],
+    );
+    wrk.create(
+        "index.csv",
+        vec![svec!["fruit"], svec!["MANGO"], svec!["cherry"]],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--anti", "-i", "-e", "-m", "0", "--tmp-dir", "."])
+        .args(["fruit", "fruits.csv", "fruit", "index.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "fruit"],
+        svec!["2", "orange"],
+        svec!["3", "apple"],
+    ];
+    assert_eq!(sorted_rows(true, got), expected);
+}
+
+fn setup_asof(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "trades.csv",
+        vec![
+            svec!["time", "ticker", "qty"],
+            svec!["2024-01-01T10:00:03", "AAPL", "10"],
+            svec!["2024-01-01T10:00:01", "MSFT", "5"],
+            svec!["2024-01-01T09:59:00", "AAPL", "3"],
+            svec!["2024-01-01T10:05:00", "AAPL", "1"],
+        ],
+    );
+    wrk.create(
+        "quotes.csv",
+        vec![
+            svec!["time", "ticker", "price"],
+            svec!["2024-01-01T10:00:00", "AAPL", "100"],
+            svec!["2024-01-01T10:00:02", "AAPL", "101"],
+            svec!["2024-01-01T10:00:00", "MSFT", "50"],
+            svec!["2024-01-01T10:00:04", "MSFT", "51"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn join_asof() {
+    let wrk = setup_asof("join_asof");
+    let mut cmd = wrk.command("join");
+    cmd.args(["--asof", "-B", "ticker", "-R", "quote_"])
+        .args(["time", "trades.csv", "time", "quotes.csv"])
+        .args(["--tolerance", "1m", "--left"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "time",
+            "ticker",
+            "qty",
+            "quote_time",
+            "quote_ticker",
+            "quote_price"
+        ],
+        svec![
+            "2024-01-01T10:00:03",
+            "AAPL",
+            "10",
+            "2024-01-01T10:00:02",
+            "AAPL",
+            "101"
+        ],
+        svec![
+            "2024-01-01T10:00:01",
+            "MSFT",
+            "5",
+            "2024-01-01T10:00:00",
+            "MSFT",
+            "50"
+        ],
+        svec!["2024-01-01T09:59:00", "AAPL", "3", "", "", ""],
+        svec!["2024-01-01T10:05:00", "AAPL", "1", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_asof_direction() {
+    let wrk = Workdir::new("join_asof_direction");
+    wrk.create("left.csv", vec![svec!["n"], svec!["4"], svec!["12"]]);
+    wrk.create(
+        "right.csv",
+        vec![svec!["m"], svec!["10"], svec!["0"], svec!["5"]],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--asof", "--direction", "forward"])
+        .args(["n", "left.csv", "m", "right.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["n", "m"], svec!["4", "5"]];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--asof", "--direction", "nearest", "--tolerance", "3"])
+        .args(["n", "left.csv", "m", "right.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["n", "m"], svec!["4", "5"], svec!["12", "10"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_interval() {
+    let wrk = Workdir::new("join_interval");
+    wrk.create(
+        "values.csv",
+        vec![
+            svec!["v"],
+            svec!["3"],
+            svec!["7"],
+            svec!["15"],
+            svec!["200"],
+        ],
+    );
+    wrk.create(
+        "ranges.csv",
+        vec![
+            svec!["name", "start", "end"],
+            svec!["low", "0", "10"],
+            svec!["mid", "5", "15"],
+            svec!["high", "15", "100"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--interval", "--left"])
+        .args(["v", "values.csv", "start,end", "ranges.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["v", "name", "start", "end"],
+        svec!["3", "low", "0", "10"],
+        svec!["7", "low", "0", "10"],
+        svec!["7", "mid", "5", "15"],
+        svec!["15", "high", "15", "100"],
+        svec!["200", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
@@ -107,3 +107,119 @@ fn map_overwrite() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn map_let_and_fn() {
+    let wrk = Workdir::new("map_let_and_fn");
+    wrk.create(
+        "data.csv",
+        vec![svec!["a", "b"], svec!["1", "4"], svec!["5", "2"]],
+    );
+    let mut cmd = wrk.command("map");
+    cmd.arg("fn sq(x) = x * x; let s = a + b; sq(s) as c, sq(a) as d")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["a", "b", "c", "d"],
+        svec!["1", "4", "25", "1"],
+        svec!["5", "2", "49", "25"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_fn_many_parameters() {
+    let wrk = Workdir::new("map_fn_many_parameters");
+    wrk.create("data.csv", vec![svec!["a"], svec!["1"], svec!["2"]]);
+    let mut cmd = wrk.command("map");
+    cmd.arg("fn f(x1, x2, x3, x4, x5, x6, x7, x8, x9, x10) = x1 + x10; f(a, a, a, a, a, a, a, a, a, a * 10) as c")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "c"], svec!["1", "11"], svec!["2", "22"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_prelude() {
+    let wrk = Workdir::new("map_prelude");
+    wrk.create(
+        "data.csv",
+        vec![svec!["a", "b"], svec!["1", "4"], svec!["5", "2"]],
+    );
+    wrk.write(
+        "prelude.moonblade",
+        "# Helpers\nfn sq(x) = x * x;\nfn dist(x, y) = sq(x - y);\n",
+    );
+
+    let mut cmd = wrk.command("map");
+    cmd.args(["--prelude", "prelude.moonblade"])
+        .arg("dist(a, b) as c")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["a", "b", "c"],
+        svec!["1", "4", "9"],
+        svec!["5", "2", "9"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("map");
+    cmd.env("XAN_PRELUDE", "prelude.moonblade")
+        .arg("sq(b) as c")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["a", "b", "c"],
+        svec!["1", "4", "16"],
+        svec!["5", "2", "4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_higher_order_functions() {
+    let wrk = Workdir::new("map_higher_order_functions");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["json"],
+            svec!["[{\"name\": \"b\", \"n\": 3}, {\"name\": \"a\", \"n\": 1}]"],
+            svec!["[{\"name\": \"c\", \"n\": 2}]"],
+        ],
+    );
+    let mut cmd = wrk.command("map");
+    cmd.arg(
+        "sort_by(parse_json(json), item => item.n) | map(_, item => item.name) | join(_, '|') as names, \
+         reduce(parse_json(json), (acc, item) => acc + item.n, 0) as total",
+    )
+    .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["json", "names", "total"],
+        svec![
+            "[{\"name\": \"b\", \"n\": 3}, {\"name\": \"a\", \"n\": 1}]",
+            "a|b",
+            "4"
+        ],
+        svec!["[{\"name\": \"c\", \"n\": 2}]", "c", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_nested_lambdas() {
+    let wrk = Workdir::new("map_nested_lambdas");
+    wrk.create("data.csv", vec![svec!["a"], svec!["1"]]);
+    let mut cmd = wrk.command("map");
+    cmd.arg("map([[[[[a]]]]], (x1, i1) => map(x1, (x2, i2) => map(x2, (x3, i3) => map(x3, (x4, i4) => map(x4, (x5, i5) => x5 + i1 + i2 + i3 + i4 + i5))))) as c, reduce([1, 2], (acc1, x1, i1) => acc1 + reduce([a, 2], (acc2, x2, i2) => acc2 + reduce([3], (acc3, x3, i3) => acc3 + x3))) as d")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "c", "d"], svec!["1", "[[[[[1]]]]]", "5"]];
+    assert_eq!(got, expected);
+}
@@ -0,0 +1,569 @@
+use crate::workdir::Workdir;
+
+fn edges() -> Vec<Vec<String>> {
+    vec![
+        svec!["source", "target", "weight"],
+        svec!["a", "b", "1"],
+        svec!["a", "c", "1"],
+        svec!["b", "c", "1"],
+        svec!["c", "d", "1"],
+        svec!["d", "e", "1"],
+        svec!["d", "f", "1"],
+        svec!["e", "f", "1"],
+        svec!["a", "b", "2"],
+        svec!["x", "y", "1"],
+    ]
+}
+
+#[test]
+fn network_nodelist_degrees() {
+    let wrk = Workdir::new("network_nodelist_degrees");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-f", "nodelist", "--degrees", "-L"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "degree", "in_degree", "out_degree"],
+        svec!["a", "2", "0", "2"],
+        svec!["b", "2", "1", "1"],
+        svec!["c", "3", "2", "1"],
+        svec!["d", "3", "1", "2"],
+        svec!["e", "2", "1", "1"],
+        svec!["f", "2", "2", "0"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_nodelist_weighted_degrees() {
+    let wrk = Workdir::new("network_nodelist_weighted_degrees");
+    wrk.create("edges.csv", edges());
+
+    // Explicit weights
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "nodelist", "--weighted-degrees"])
+        .args(["-w", "weight", "source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "weighted_degree"],
+        svec!["a", "4"],
+        svec!["b", "4"],
+        svec!["c", "3"],
+        svec!["d", "3"],
+        svec!["e", "2"],
+        svec!["f", "2"],
+        svec!["x", "1"],
+        svec!["y", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    // Counting parallel edges
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "nodelist", "--weighted-degrees"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[1], svec!["a", "3"]);
+    assert_eq!(got[3], svec!["c", "3"]);
+
+    // Invalid weights
+    wrk.create(
+        "invalid.csv",
+        vec![svec!["source", "target", "weight"], svec!["a", "b", "nope"]],
+    );
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-f", "nodelist", "-w", "weight"])
+        .args(["source", "target", "invalid.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn network_nodelist_pagerank() {
+    let wrk = Workdir::new("network_nodelist_pagerank");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "nodelist", "--pagerank"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["node", "pagerank"]);
+
+    let ranks = got[1..]
+        .iter()
+        .map(|row| row[1].parse::<f64>().unwrap())
+        .collect::<Vec<_>>();
+
+    assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    assert!(ranks[2] > ranks[0]);
+    assert_eq!(ranks[4], ranks[5]);
+    assert_eq!(ranks[6], 0.125);
+}
+
+#[test]
+fn network_nodelist_weighted_pagerank_null_weights() {
+    let wrk = Workdir::new("network_nodelist_weighted_pagerank_null_weights");
+    wrk.create(
+        "null.csv",
+        vec![
+            svec!["source", "target", "weight"],
+            svec!["a", "b", "0"],
+            svec!["b", "c", "1"],
+            svec!["c", "a", "2"],
+        ],
+    );
+    wrk.create(
+        "dangling.csv",
+        vec![
+            svec!["source", "target", "weight"],
+            svec!["b", "c", "1"],
+            svec!["c", "a", "2"],
+        ],
+    );
+
+    let mut pageranks = Vec::new();
+
+    for path in ["null.csv", "dangling.csv"] {
+        let mut cmd = wrk.command("network");
+        cmd.args(["edgelist", "-f", "nodelist", "--pagerank", "-w", "weight"])
+            .args(["source", "target", path]);
+
+        let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        got.sort();
+
+        let ranks = got[..3]
+            .iter()
+            .map(|row| row[1].parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(ranks.iter().all(|rank| rank.is_finite()));
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+
+        pageranks.push(got);
+    }
+
+    // Nodes with null out-strength are treated as dangling
+    assert_eq!(pageranks[0], pageranks[1]);
+}
+
+#[test]
+fn network_nodelist_betweenness() {
+    let wrk = Workdir::new("network_nodelist_betweenness");
+    wrk.create("edges.csv", edges());
+
+    // Exact
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "nodelist", "--betweenness"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "betweenness"],
+        svec!["a", "0"],
+        svec!["b", "0"],
+        svec!["c", "6"],
+        svec!["d", "6"],
+        svec!["e", "0"],
+        svec!["f", "0"],
+        svec!["x", "0"],
+        svec!["y", "0"],
+    ];
+    assert_eq!(got, expected);
+
+    // Directed
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-f", "nodelist", "--betweenness"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[3], svec!["c", "6"]);
+    assert_eq!(got[4], svec!["d", "6"]);
+
+    // Sampled
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "nodelist", "--betweenness"])
+        .args(["--samples", "4", "--seed", "123"])
+        .args(["source", "target", "edges.csv"]);
+
+    let first: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let second: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 9);
+}
+
+#[test]
+fn network_nodelist_components() {
+    let wrk = Workdir::new("network_nodelist_components");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-f", "nodelist", "--components"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "component"],
+        svec!["a", "0"],
+        svec!["b", "0"],
+        svec!["c", "0"],
+        svec!["d", "0"],
+        svec!["e", "0"],
+        svec!["f", "0"],
+        svec!["x", "1"],
+        svec!["y", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_nodelist_communities() {
+    let wrk = Workdir::new("network_nodelist_communities");
+    wrk.create("edges.csv", edges());
+
+    let expected = vec![
+        svec!["node", "community"],
+        svec!["a", "0"],
+        svec!["b", "0"],
+        svec!["c", "0"],
+        svec!["d", "1"],
+        svec!["e", "1"],
+        svec!["f", "1"],
+        svec!["x", "2"],
+        svec!["y", "2"],
+    ];
+
+    for algorithm in ["louvain", "leiden"] {
+        let mut cmd = wrk.command("network");
+        cmd.args([
+            "edgelist",
+            "-U",
+            "-f",
+            "nodelist",
+            "--communities",
+            algorithm,
+        ])
+        .args(["source", "target", "edges.csv"]);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        assert_eq!(got, expected);
+    }
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-f", "nodelist", "--communities", "unknown"])
+        .args(["source", "target", "edges.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn network_cooccurrence() {
+    let wrk = Workdir::new("network_cooccurrence");
+    wrk.create(
+        "tweets.csv",
+        vec![
+            svec!["tweet", "hashtag"],
+            svec!["1", "a"],
+            svec!["1", "b"],
+            svec!["2", "a"],
+            svec!["2", "c"],
+            svec!["1", "b"],
+            svec!["3", "a"],
+            svec!["3", "b"],
+        ],
+    );
+
+    // Grouped rows
+    let mut cmd = wrk.command("network");
+    cmd.args(["cooccurrence", "-g", "tweet", "-f", "edgelist"])
+        .args(["hashtag", "tweets.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target", "weight"],
+        svec!["a", "b", "2"],
+        svec!["a", "c", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    // Separated cells
+    wrk.create(
+        "cells.csv",
+        vec![
+            svec!["id", "hashtags"],
+            svec!["1", "a|b|c"],
+            svec!["2", "b|c"],
+            svec!["3", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["cooccurrence", "--sep", "|", "-f", "edgelist"])
+        .args(["hashtags", "cells.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target", "weight"],
+        svec!["a", "b", "1"],
+        svec!["a", "c", "1"],
+        svec!["b", "c", "2"],
+    ];
+    assert_eq!(got, expected);
+
+    // Neither grouped nor separated
+    let mut cmd = wrk.command("network");
+    cmd.args(["cooccurrence", "hashtags", "cells.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn network_adjacency() {
+    let wrk = Workdir::new("network_adjacency");
+    wrk.create(
+        "adjacency.csv",
+        vec![
+            svec!["node", "neighbors", "label"],
+            svec!["a", "b|c", "A"],
+            svec!["b", "c", "B"],
+            svec!["d", "", "D"],
+        ],
+    );
+    let mut cmd = wrk.command("network");
+    cmd.args(["adjacency", "-f", "nodelist"])
+        .args(["node", "neighbors", "adjacency.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "label"],
+        svec!["a", "A"],
+        svec!["b", "B"],
+        svec!["c", ""],
+        svec!["d", "D"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["adjacency", "-f", "edgelist"])
+        .args(["node", "neighbors", "adjacency.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target"],
+        svec!["a", "b"],
+        svec!["a", "c"],
+        svec!["b", "c"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_edgelist_output() {
+    let wrk = Workdir::new("network_edgelist_output");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-L", "-w", "weight", "-f", "edgelist"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target", "weight"],
+        svec!["a", "b", "3"],
+        svec!["a", "c", "1"],
+        svec!["b", "c", "1"],
+        svec!["c", "d", "1"],
+        svec!["d", "e", "1"],
+        svec!["d", "f", "1"],
+        svec!["e", "f", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_graphml() {
+    let wrk = Workdir::new("network_graphml");
+    wrk.create(
+        "edges.csv",
+        vec![svec!["source", "target", "weight"], svec!["a", "b", "1.5"]],
+    );
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-w", "weight", "-f", "graphml"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"<?xml version="1.0" encoding="utf-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
+  <key id="e0" for="edge" attr.name="weight" attr.type="double"/>
+  <graph id="G" edgedefault="directed">
+    <node id="a"/>
+    <node id="b"/>
+    <edge source="a" target="b">
+      <data key="e0">1.5</data>
+    </edge>
+  </graph>
+</graphml>"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_dot() {
+    let wrk = Workdir::new("network_dot");
+    wrk.create(
+        "edges.csv",
+        vec![
+            svec!["source", "target", "type"],
+            svec!["a", "b\"c", "friend"],
+            svec!["b\"c", "a", "foe"],
+        ],
+    );
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-U", "-f", "dot"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "graph {\n  \"a\";\n  \"b\\\"c\";\n  \"a\" -- \"b\\\"c\" [type=\"foe\"];\n}";
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_pajek() {
+    let wrk = Workdir::new("network_pajek");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-L", "-w", "weight", "-f", "pajek"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "*Vertices 6\n1 \"a\"\n2 \"b\"\n3 \"c\"\n4 \"d\"\n5 \"e\"\n6 \"f\"\n*Arcs\n1 2 3\n1 3 1\n2 3 1\n3 4 1\n4 5 1\n4 6 1\n5 6 1";
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_bipartite_projection() {
+    let wrk = Workdir::new("network_bipartite_projection");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["user", "tag"],
+            svec!["u1", "a"],
+            svec!["u1", "b"],
+            svec!["u2", "a"],
+            svec!["u2", "b"],
+            svec!["u2", "c"],
+            svec!["u3", "c"],
+            svec!["u3", "a"],
+            svec!["u1", "a"],
+        ],
+    );
+
+    let expectations = [
+        ("count", ["2", "2", "1"]),
+        (
+            "jaccard",
+            [
+                "0.6666666666666666",
+                "0.6666666666666666",
+                "0.3333333333333333",
+            ],
+        ),
+        ("newman", ["1.5", "1.5", "0.5"]),
+    ];
+
+    for (method, weights) in expectations {
+        let mut cmd = wrk.command("network");
+        cmd.args(["bipartite", "-P", "tag", "--projection-weight", method])
+            .args(["-f", "edgelist", "user", "tag", "data.csv"]);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["source", "target", "weight"],
+            svec!["a", "b", weights[0]],
+            svec!["a", "c", weights[1]],
+            svec!["b", "c", weights[2]],
+        ];
+        assert_eq!(got, expected);
+    }
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["bipartite", "-P", "user", "--min-weight", "2"])
+        .args(["-f", "edgelist", "user", "tag", "data.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target", "weight"],
+        svec!["u1", "u2", "2"],
+        svec!["u2", "u3", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn network_k_core() {
+    let wrk = Workdir::new("network_k_core");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-k", "2", "-f", "nodelist", "--degrees", "-U"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node", "degree"],
+        svec!["a", "2"],
+        svec!["b", "2"],
+        svec!["c", "3"],
+        svec!["d", "3"],
+        svec!["e", "2"],
+        svec!["f", "2"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "-k", "3", "-f", "nodelist"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, vec![svec!["node"]]);
+}
+
+#[test]
+fn network_ego() {
+    let wrk = Workdir::new("network_ego");
+    wrk.create("edges.csv", edges());
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "--ego", "a|y", "-f", "nodelist"])
+        .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["node"],
+        svec!["a"],
+        svec!["b"],
+        svec!["c"],
+        svec!["x"],
+        svec!["y"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("network");
+    cmd.args([
+        "edgelist", "--ego", "a|y", "--radius", "2", "-L", "-f", "edgelist",
+    ])
+    .args(["source", "target", "edges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source", "target", "weight"],
+        svec!["a", "b", "2"],
+        svec!["a", "c", "1"],
+        svec!["b", "c", "1"],
+        svec!["c", "d", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("network");
+    cmd.args(["edgelist", "--ego", "unknown"])
+        .args(["source", "target", "edges.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
@@ -261,3 +261,27 @@ fn parallel_groupby() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn parallel_agg_sketches() {
+    let wrk = Workdir::new("parallel_agg_sketches");
+    wrk.create(
+        "data1.csv",
+        vec![svec!["name"], svec!["john"], svec!["mary"]],
+    );
+    wrk.create(
+        "data2.csv",
+        vec![svec!["name"], svec!["john"], svec!["lucy"]],
+    );
+
+    let mut cmd = wrk.command("parallel");
+    cmd.arg("agg")
+        .arg("approx_cardinality(name) as cardinality, hll_sketch(name) as sketch")
+        .arg("data1.csv")
+        .arg("data2.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    assert_eq!(got[1][0], "3");
+    assert!(got[1][1].starts_with("hll:"));
+}
@@ -0,0 +1,107 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "old.csv",
+        vec![
+            svec!["id", "name", "age"],
+            svec!["1", "john", "30"],
+            svec!["2", "mary", "25"],
+            svec!["3", "lucy", "40"],
+        ],
+    );
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["id", "city", "name"],
+            svec!["1", "paris", "john"],
+            svec!["3", "london", "lucy-x"],
+            svec!["4", "rome", "bob"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn patch() {
+    let wrk = setup("patch");
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id")
+        .arg("old.csv")
+        .arg("new.csv")
+        .args(["-o", "diff.csv"]);
+    wrk.run(&mut cmd);
+
+    let mut cmd = wrk.command("patch");
+    cmd.arg("diff.csv").arg("old.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "city", "name"],
+        svec!["1", "paris", "john"],
+        svec!["3", "london", "lucy-x"],
+        svec!["4", "rome", "bob"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_sorted() {
+    let wrk = setup("patch_sorted");
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["id", "city", "name"],
+            svec!["0", "berlin", "ana"],
+            svec!["1", "paris", "john"],
+            svec!["3", "london", "lucy-x"],
+            svec!["4", "rome", "bob"],
+        ],
+    );
+    let mut cmd = wrk.command("diff");
+    cmd.arg("id")
+        .arg("old.csv")
+        .arg("new.csv")
+        .args(["-S", "-o", "diff.csv"]);
+    wrk.run(&mut cmd);
+
+    let mut cmd = wrk.command("patch");
+    cmd.arg("diff.csv").arg("old.csv").arg("--sorted");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "city", "name"],
+        svec!["0", "berlin", "ana"],
+        svec!["1", "paris", "john"],
+        svec!["3", "london", "lucy-x"],
+        svec!["4", "rome", "bob"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_does_not_apply() {
+    let wrk = setup("patch_does_not_apply");
+    wrk.create(
+        "diff.csv",
+        vec![
+            svec!["diff", "id", "column", "old", "new"],
+            svec!["modified", "2", "name", "martha", "mary-x"],
+        ],
+    );
+    let mut cmd = wrk.command("patch");
+    cmd.arg("diff.csv").arg("old.csv");
+    wrk.assert_err(&mut cmd);
+
+    wrk.create(
+        "diff.csv",
+        vec![
+            svec!["diff", "id", "column", "old", "new"],
+            svec!["removed", "5", "", "", ""],
+        ],
+    );
+    let mut cmd = wrk.command("patch");
+    cmd.arg("diff.csv").arg("old.csv");
+    wrk.assert_err(&mut cmd);
+}
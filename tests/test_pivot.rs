@@ -120,3 +120,186 @@ fn pivot_default() {
     ];
     assert_eq!(got, expected);
 }
+
+fn population_data() -> Vec<Vec<String>> {
+    vec![
+        svec!["country", "name", "year", "population"],
+        svec!["NL", "Amsterdam", "2000", "1005"],
+        svec!["NL", "Amsterdam", "2010", "1065"],
+        svec!["US", "Seattle", "2010", "608"],
+        svec!["US", "Seattle", "2000", "564"],
+        svec!["US", "New York City", "2000", "8015"],
+        svec!["US", "New York City", "2010", "8175"],
+    ]
+}
+
+#[test]
+fn pivot_multiple_aggregations() {
+    let wrk = Workdir::new("pivot_multiple_aggregations");
+    wrk.create("data.csv", population_data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("sum(population) as pop, count() as n")
+        .args(["-g", "country"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "pop_2000", "pop_2010", "n_2000", "n_2010"],
+        svec!["NL", "1005", "1065", "1", "1"],
+        svec!["US", "8579", "8783", "2", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_fill() {
+    let wrk = Workdir::new("pivot_fill");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "color", "n"],
+            svec!["john", "blue", "1"],
+            svec!["mary", "red", "2"],
+        ],
+    );
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("color")
+        .arg("sum(n)")
+        .args(["--fill", "0"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "blue", "red"],
+        svec!["john", "1", "0"],
+        svec!["mary", "0", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_margins() {
+    let wrk = Workdir::new("pivot_margins");
+    wrk.create("data.csv", population_data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("mean(population)")
+        .args(["-g", "country", "--margins"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "2000", "2010", "total"],
+        svec!["NL", "1005", "1065", "1035"],
+        svec!["US", "4289.5", "4391.5", "4340.5"],
+        svec![
+            "total",
+            "3194.6666666666665",
+            "3282.6666666666665",
+            "3238.666666666667"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("sum(population) as pop, count() as n")
+        .args(["-g", "country", "--margins", "--margins-name", "all"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "pop_2000", "pop_2010", "pop_all", "n_2000", "n_2010", "n_all"],
+        svec!["NL", "1005", "1065", "2070", "1", "1", "2"],
+        svec!["US", "8579", "8783", "17362", "2", "2", "4"],
+        svec!["all", "9584", "9848", "19432", "3", "3", "6"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_sort_columns() {
+    let wrk = Workdir::new("pivot_sort_columns");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["group", "key", "n"],
+            svec!["a", "10", "1"],
+            svec!["a", "9", "2"],
+            svec!["b", "100", "3"],
+            svec!["b", "9", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("key")
+        .arg("sum(n)")
+        .args(["--sort-columns", "name"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["group", "10", "100", "9"],
+        svec!["a", "1", "", "2"],
+        svec!["b", "", "3", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("key")
+        .arg("sum(n)")
+        .args(["--sort-columns", "numeric"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["group", "9", "10", "100"],
+        svec!["a", "2", "1", ""],
+        svec!["b", "4", "", "3"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("key")
+        .arg("sum(n)")
+        .args(["--sort-columns", "total"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["group", "9", "100", "10"],
+        svec!["a", "2", "", "1"],
+        svec!["b", "4", "3", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_two_pass() {
+    let wrk = Workdir::new("pivot_two_pass");
+    wrk.create("data.csv", population_data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("name")
+        .arg("first(population)")
+        .args(["-g", "year", "--two-pass"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["year", "Amsterdam", "New York City", "Seattle"],
+        svec!["2000", "1005", "8015", "564"],
+        svec!["2010", "1065", "8175", "608"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("name")
+        .arg("first(population)")
+        .args(["-g", "year", "--two-pass"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,124 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn plot_svg() {
+    let wrk = Workdir::new("plot_svg");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["x", "y", "color"],
+            svec!["1", "2", "blue"],
+            svec!["2", "4", "red"],
+            svec!["3", "3", "blue"],
+            svec!["4", "7", "red"],
+        ],
+    );
+
+    let mut cmd = wrk.command("plot");
+    cmd.args(["x", "y", "data.csv", "-c", "color", "--svg", "plot.svg"]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("plot.svg"));
+
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"500\"")
+    );
+    assert_eq!(svg.matches("<circle").count(), 4);
+    assert!(svg.contains(">blue</text>"));
+    assert!(svg.contains(">red</text>"));
+
+    // Small multiples
+    let mut cmd = wrk.command("plot");
+    cmd.args(["x", "y", "data.csv", "-c", "color", "-S", "1", "-L"])
+        .args(["--svg", "plot.svg", "--image-width", "400"]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("plot.svg"));
+
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"400\" height=\"600\"")
+    );
+    assert_eq!(svg.matches("<polyline").count(), 2);
+}
+
+#[test]
+fn plot_png() {
+    let wrk = Workdir::new("plot_png");
+    wrk.create(
+        "data.csv",
+        vec![svec!["x", "y"], svec!["1", "2"], svec!["2", "4"]],
+    );
+
+    let mut cmd = wrk.command("plot");
+    cmd.args(["x", "y", "data.csv", "--png", "plot.png"]);
+    wrk.run(&mut cmd);
+
+    let png = std::fs::read(wrk.path("plot.png")).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+}
+
+#[test]
+fn plot_box() {
+    let wrk = Workdir::new("plot_box");
+
+    let mut rows = vec![svec!["value", "group"]];
+
+    for i in 1..=9 {
+        rows.push(vec![i.to_string(), "a".to_string()]);
+        rows.push(vec![(i * 2).to_string(), "b".to_string()]);
+    }
+
+    rows.push(svec!["100", "a"]);
+
+    wrk.create("data.csv", rows);
+
+    let mut cmd = wrk.command("plot");
+    cmd.args([
+        "--box", "value", "data.csv", "-c", "group", "--svg", "plot.svg",
+    ]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("plot.svg"));
+
+    // One box per category & a single outlier
+    assert_eq!(svg.matches("<polygon").count(), 2);
+    assert_eq!(svg.matches("<circle").count(), 1);
+    assert!(svg.contains(">a</text>"));
+    assert!(svg.contains(">b</text>"));
+
+    let mut cmd = wrk.command("plot");
+    cmd.args(["--violin", "value", "data.csv", "-c", "group", "-S", "2"])
+        .args(["--svg", "plot.svg"]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("plot.svg"));
+
+    // One violin & median marker per category
+    assert_eq!(svg.matches("<polygon").count(), 2);
+    assert_eq!(svg.matches("<circle").count(), 2);
+
+    let mut cmd = wrk.command("plot");
+    cmd.args([
+        "--density",
+        "value",
+        "data.csv",
+        "-c",
+        "group",
+        "--svg",
+        "plot.svg",
+    ]);
+    wrk.run(&mut cmd);
+
+    let svg: String = wrk.from_str(&wrk.path("plot.svg"));
+
+    assert_eq!(svg.matches("<polyline").count(), 2);
+    assert!(svg.contains(">density</text>"));
+
+    let mut cmd = wrk.command("plot");
+    cmd.args(["--box", "value", "data.csv", "--bandwidth", "0"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("plot");
+    cmd.args(["--box", "value", "data.csv", "-L"]);
+    wrk.assert_err(&mut cmd);
+}
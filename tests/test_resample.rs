@@ -0,0 +1,180 @@
+use crate::workdir::Workdir;
+
+fn events() -> Vec<Vec<String>> {
+    vec![
+        svec!["datetime", "amount"],
+        svec!["2024-01-01T10:00:00", "1"],
+        svec!["2024-01-01T18:00:00", "2"],
+        svec!["2024-01-02T09:00:00", "3"],
+        svec!["", "10"],
+        svec!["2024-01-05T12:00:00", "6"],
+    ]
+}
+
+#[test]
+fn resample() {
+    let wrk = Workdir::new("resample");
+    wrk.create("data.csv", events());
+    let mut cmd = wrk.command("resample");
+    cmd.args([
+        "-u",
+        "day",
+        "datetime",
+        "count() as count, sum(amount) as sum",
+    ])
+    .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["datetime", "count", "sum"],
+        svec!["2024-01-01", "2", "3"],
+        svec!["2024-01-02", "1", "3"],
+        svec!["2024-01-03", "", ""],
+        svec!["2024-01-04", "", ""],
+        svec!["2024-01-05", "1", "6"],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn resample_fill() {
+    let wrk = Workdir::new("resample_fill");
+    wrk.create("data.csv", events());
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "day", "datetime", "sum(amount) as sum", "--fill", "0"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["datetime", "sum"],
+        svec!["2024-01-01", "3"],
+        svec!["2024-01-02", "3"],
+        svec!["2024-01-03", "0"],
+        svec!["2024-01-04", "0"],
+        svec!["2024-01-05", "6"],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn resample_interpolate() {
+    let wrk = Workdir::new("resample_interpolate");
+    wrk.create("data.csv", events());
+    let mut cmd = wrk.command("resample");
+    cmd.args([
+        "-u",
+        "day",
+        "datetime",
+        "sum(amount) as sum",
+        "--interpolate",
+    ])
+    .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["datetime", "sum"],
+        svec!["2024-01-01", "3"],
+        svec!["2024-01-02", "3"],
+        svec!["2024-01-03", "4"],
+        svec!["2024-01-04", "5"],
+        svec!["2024-01-05", "6"],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn resample_infer_unit() {
+    let wrk = Workdir::new("resample_infer_unit");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["month", "n"],
+            svec!["2023-11", "1"],
+            svec!["2024-01", "2"],
+            svec!["2024-01", "3"],
+            svec!["2025-02", "4"],
+        ],
+    );
+    let mut cmd = wrk.command("resample");
+    cmd.args(["month", "sum(n) as n"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    assert_eq!(got.len(), 17);
+    assert_eq!(got[0], svec!["month", "n"]);
+    assert_eq!(got[1], svec!["2023-11", "1"]);
+    assert_eq!(got[3], svec!["2024-01", "5"]);
+    assert_eq!(got[16], svec!["2025-02", "4"]);
+}
+
+#[test]
+fn resample_mixed_timezones() {
+    let wrk = Workdir::new("resample_mixed_timezones");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["date", "n"],
+            svec!["2024-01-03", "1"],
+            svec!["2024-01-03T10:00:00Z", "2"],
+            svec!["2024-01-05T01:00:00Z", "3"],
+        ],
+    );
+    let mut cmd = wrk.command("resample");
+    cmd.env("TZ", "America/New_York")
+        .args(["-u", "day", "date", "sum(n) as n"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "n"],
+        svec!["2024-01-03", "3"],
+        svec!["2024-01-04", "3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn resample_errors() {
+    let wrk = Workdir::new("resample_errors");
+    wrk.create("data.csv", vec![svec!["datetime"], svec!["not a date"]]);
+
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "day", "datetime", "count()"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "week", "datetime", "count()"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn resample_max_buckets() {
+    let wrk = Workdir::new("resample_max_buckets");
+    wrk.create("data.csv", events());
+
+    // 4 days and 2 hours would yield 352,801 buckets
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "seconds", "datetime", "count()"])
+        .args(["--max-buckets", "1000"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "day", "datetime", "count() as count"])
+        .args(["--max-buckets", "5"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 6);
+
+    let mut cmd = wrk.command("resample");
+    cmd.args(["-u", "day", "datetime", "count() as count"])
+        .args(["--max-buckets", "4"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
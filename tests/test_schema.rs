@@ -0,0 +1,251 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "country", "code", "score"],
+            svec!["1", "FR", "AB-123", "0.5"],
+            svec!["2", "US", "CD-456", ""],
+            svec!["3", "FR", "EF-789", "3"],
+            svec!["4", "US", "GH-012", "1.5"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn schema_infer() {
+    let wrk = setup("schema_infer");
+    let mut cmd = wrk.command("schema");
+    cmd.arg("infer").arg("data.csv");
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!({
+        "fields": [
+            {"name": "id", "type": "int", "nullable": false, "min": 1, "max": 4},
+            {"name": "country", "type": "string", "nullable": false, "enum": ["FR", "US"]},
+            {
+                "name": "code",
+                "type": "string",
+                "nullable": false,
+                "min_length": 6,
+                "max_length": 6,
+                "pattern": "[A-Z]{2}\\-[0-9]{3}"
+            },
+            {"name": "score", "type": "float", "nullable": true, "min": 0.5, "max": 3}
+        ]
+    });
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn schema_infer_formats() {
+    let wrk = setup("schema_infer_formats");
+    let mut cmd = wrk.command("schema");
+    cmd.arg("infer")
+        .args(["-f", "frictionless", "-s", "id"])
+        .arg("data.csv");
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!({
+        "fields": [
+            {
+                "name": "id",
+                "type": "integer",
+                "constraints": {"required": true, "minimum": 1, "maximum": 4}
+            }
+        ],
+        "missingValues": [""]
+    });
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("infer")
+        .args(["-f", "json-schema", "-s", "score"])
+        .arg("data.csv");
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    assert_eq!(
+        got["properties"]["score"],
+        serde_json::json!({"type": ["number", "null"], "minimum": 0.5, "maximum": 3})
+    );
+}
+
+#[test]
+fn schema_check() {
+    let wrk = setup("schema_check");
+    let mut cmd = wrk.command("schema");
+    cmd.arg("infer").arg("data.csv").args(["-o", "schema.json"]);
+    wrk.run(&mut cmd);
+
+    // Data conforming to its own schema
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check").arg("schema.json").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["row_index", "column", "value", "rule", "expected"]];
+    assert_eq!(got, expected);
+
+    wrk.create(
+        "bad.csv",
+        vec![
+            svec!["country", "id", "code", "extra"],
+            svec!["DE", "x", "AB-123", ""],
+            svec!["FR", "", "AB1234", ""],
+            svec!["US", "7", "AB-123", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check")
+        .arg("schema.json")
+        .arg("bad.csv")
+        .args(["-o", "report.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("report.csv"));
+    let expected = "\
+row_index,column,value,rule,expected
+,score,,missing_column,
+,extra,,extra_column,
+0,id,x,type,int
+0,country,DE,enum,FR|US
+1,id,,nullable,false
+1,code,AB1234,pattern,[A-Z]{2}\\-[0-9]{3}
+2,id,7,max,4";
+    assert_eq!(got.trim_end(), expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check")
+        .args(["--ignore-extra", "-m", "1"])
+        .arg("schema.json")
+        .arg("bad.csv")
+        .args(["-o", "report.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("report.csv"));
+    let expected = "\
+row_index,column,value,rule,expected
+,score,,missing_column,";
+    assert_eq!(got.trim_end(), expected);
+}
+
+#[test]
+fn schema_check_nan() {
+    let wrk = Workdir::new("schema_check_nan");
+    wrk.create(
+        "data.csv",
+        vec![svec!["v"], svec!["1.5"], svec!["NaN"], svec!["2"]],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("infer").arg("data.csv").args(["-o", "schema.json"]);
+    wrk.run(&mut cmd);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check").arg("schema.json").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["row_index", "column", "value", "rule", "expected"]];
+    assert_eq!(got, expected);
+
+    wrk.create(
+        "bad.csv",
+        vec![svec!["v"], svec!["NaN"], svec!["0.5"], svec!["3"]],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check")
+        .arg("schema.json")
+        .arg("bad.csv")
+        .args(["-o", "report.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("report.csv"));
+    let expected = "\
+row_index,column,value,rule,expected
+1,v,0.5,min,1.5
+2,v,3,max,2";
+    assert_eq!(got.trim_end(), expected);
+}
+
+#[test]
+fn schema_check_frictionless() {
+    let wrk = setup("schema_check_frictionless");
+    wrk.write(
+        "schema.json",
+        r#"{
+            "fields": [
+                {"name": "id", "type": "integer", "constraints": {"unique": true}},
+                {"name": "country", "type": "string", "constraints": {"pattern": "[A-Z]{2}"}},
+                {"name": "code", "type": "string"},
+                {"name": "score", "type": "number", "constraints": {"required": true}}
+            ],
+            "missingValues": ["", "NA"]
+        }"#,
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "country", "code", "score"],
+            svec!["1", "FR", "AB", "NA"],
+            svec!["1", "fr", "CD", "2"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check")
+        .arg("schema.json")
+        .arg("data.csv")
+        .args(["-o", "report.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("report.csv"));
+    let expected = "\
+row_index,column,value,rule,expected
+0,score,NA,nullable,false
+1,id,1,unique,true
+1,country,fr,pattern,[A-Z]{2}";
+    assert_eq!(got.trim_end(), expected);
+}
+
+#[test]
+fn schema_check_json_schema() {
+    let wrk = setup("schema_check_json_schema");
+    wrk.write(
+        "schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "minimum": 2},
+                "country": {"type": "string", "minLength": 2},
+                "code": {"type": "string", "pattern": "-0"},
+                "score": {"type": ["number", "null"]}
+            },
+            "required": ["id", "country", "code"]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check")
+        .arg("schema.json")
+        .arg("data.csv")
+        .args(["-o", "report.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("report.csv"));
+    let expected = "\
+row_index,column,value,rule,expected
+0,id,1,min,2
+0,code,AB-123,pattern,[\\s\\S]*(?:-0)[\\s\\S]*
+1,code,CD-456,pattern,[\\s\\S]*(?:-0)[\\s\\S]*
+2,code,EF-789,pattern,[\\s\\S]*(?:-0)[\\s\\S]*";
+    assert_eq!(got.trim_end(), expected);
+
+    wrk.create("empty.csv", vec![svec!["id"]]);
+    let mut cmd = wrk.command("schema");
+    cmd.arg("check").arg("data.csv").arg("empty.csv");
+    wrk.assert_err(&mut cmd);
+}
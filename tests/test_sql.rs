@@ -0,0 +1,178 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "people.csv",
+        vec![
+            svec!["name", "age", "city"],
+            svec!["john", "34", "1"],
+            svec!["mary", "17", "2"],
+            svec!["lucy", "", "1"],
+            svec!["bob", "52", "3"],
+            svec!["anne", "17", "2"],
+        ],
+    );
+    wrk.create(
+        "cities.csv",
+        vec![
+            svec!["id", "label"],
+            svec!["1", "Paris"],
+            svec!["2", "Lyon"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn sql() {
+    let wrk = setup("sql");
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT name, age + 1 AS next FROM people.csv WHERE age >= 18");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "next"],
+        svec!["john", "35"],
+        svec!["bob", "53"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_expressions() {
+    let wrk = setup("sql_expressions");
+    let mut cmd = wrk.command("sql");
+    cmd.arg(
+        "SELECT upper(name) || '!', CASE WHEN age IS NULL THEN 'unknown' WHEN age < 18 THEN 'minor' ELSE 'adult' END AS status FROM people.csv WHERE name LIKE '%n%' OR name IN ('bob', 'lucy')",
+    );
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["upper(name) || '!'", "status"],
+        svec!["JOHN!", "adult"],
+        svec!["LUCY!", "unknown"],
+        svec!["BOB!", "adult"],
+        svec!["ANNE!", "minor"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_order_by_limit() {
+    let wrk = setup("sql_order_by_limit");
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT DISTINCT age FROM people.csv ORDER BY age DESC LIMIT 2 OFFSET 1");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["age"], svec!["34"], svec!["17"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_group_by() {
+    let wrk = setup("sql_group_by");
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT city, count(*) AS n, max(age) FROM people.csv GROUP BY city HAVING count(*) > 1 ORDER BY city");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "n", "max(age)"],
+        svec!["1", "2", "34"],
+        svec!["2", "2", "17"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT count(*), count(DISTINCT city), sum(age) FROM people.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["count(*)", "count(DISTINCT city)", "sum(age)"],
+        svec!["5", "3", "120"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_aggregates_semantics() {
+    let wrk = setup("sql_aggregates_semantics");
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT min(name), max(name), min(age), max(age) FROM people.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["min(name)", "max(name)", "min(age)", "max(age)"],
+        svec!["anne", "mary", "17", "52"],
+    ];
+    assert_eq!(got, expected);
+
+    // sum over an empty set is NULL
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT sum(age), min(name), count(*) FROM people.csv WHERE age > 100");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["sum(age)", "min(name)", "count(*)"],
+        svec!["", "", "0"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT city, sum(age) FROM people.csv GROUP BY city ORDER BY city");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "sum(age)"],
+        svec!["1", "34"],
+        svec!["2", "34"],
+        svec!["3", "52"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_join() {
+    let wrk = setup("sql_join");
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT p.name, c.label FROM people.csv AS p JOIN cities.csv c ON p.city = c.id");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "label"],
+        svec!["john", "Paris"],
+        svec!["mary", "Lyon"],
+        svec!["lucy", "Paris"],
+        svec!["anne", "Lyon"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("sql");
+    cmd.arg("SELECT label, count(*) FROM people.csv LEFT JOIN cities.csv ON city = id GROUP BY label ORDER BY 2 DESC, 1");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["label", "count(*)"],
+        svec!["Lyon", "2"],
+        svec!["Paris", "2"],
+        svec!["", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sql_errors() {
+    let wrk = setup("sql_errors");
+
+    for query in [
+        "SELECT name FROM people.csv GROUP BY city",
+        "SELECT unknown FROM people.csv",
+        "SELECT name FROM people.csv WHERE count(*) > 1",
+        "SELECT * FROM people.csv JOIN cities.csv ON city < id",
+        "SELECT * FROM people.csv WHERE",
+    ] {
+        let mut cmd = wrk.command("sql");
+        cmd.arg(query);
+        wrk.assert_err(&mut cmd);
+    }
+}
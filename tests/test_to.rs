@@ -167,3 +167,338 @@ fn to_md() {
 | Lucy | 15  |";
     assert_eq!(got, expected);
 }
+
+#[test]
+fn to_parquet() {
+    let wrk = Workdir::new("to_parquet");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count", "ratio"],
+            svec!["john", "1", "0.5"],
+            svec!["mary", "", "2.5"],
+        ],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["-o", "data.parquet"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "count", "ratio"],
+        svec!["john", "1", "0.5"],
+        svec!["mary", "", "2.5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn to_parquet_types() {
+    let wrk = Workdir::new("to_parquet_types");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count", "flag"],
+            svec!["john", "1", "true"],
+            svec!["mary", "2", "False"],
+        ],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["--types", "count:float,flag:bool"])
+        .args(["-o", "data.parquet"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "count", "flag"],
+        svec!["john", "1.0", "true"],
+        svec!["mary", "2.0", "false"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["--types", "name:int"])
+        .args(["-o", "data.parquet"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn to_parquet_types_selection() {
+    let wrk = Workdir::new("to_parquet_types_selection");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "a", "b", "a"],
+            svec!["john", "1", "2", "3"],
+            svec!["mary", "4", "5", "6"],
+        ],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["--types", "a:b:float"])
+        .args(["-o", "data.parquet"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "a", "b", "a"],
+        svec!["john", "1.0", "2.0", "3"],
+        svec!["mary", "4.0", "5.0", "6"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["--types", "a[1]:float,0:string"])
+        .args(["-o", "data.parquet"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "a", "b", "a"],
+        svec!["john", "1", "2", "3.0"],
+        svec!["mary", "4", "5", "6.0"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("parquet")
+        .arg("data.csv")
+        .args(["--types", "1-2:float"])
+        .args(["-o", "data.parquet"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn to_parquet_no_columns() {
+    let wrk = Workdir::new("to_parquet_no_columns");
+    wrk.write("empty.csv", "");
+
+    for format in ["parquet", "arrow"] {
+        let mut cmd = wrk.command("to");
+        cmd.arg(format)
+            .arg("empty.csv")
+            .args(["-o", &format!("data.{}", format)]);
+        wrk.assert_err(&mut cmd);
+    }
+}
+
+#[test]
+fn to_arrow() {
+    let wrk = Workdir::new("to_arrow");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count"],
+            svec!["john", "1"],
+            svec!["mary", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("arrow").arg("data.csv").args(["-o", "data.arrow"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.arrow");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "count"],
+        svec!["john", "1"],
+        svec!["mary", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn to_xlsx() {
+    let wrk = Workdir::new("to_xlsx");
+    wrk.create(
+        "people.csv",
+        vec![
+            svec!["name", "count", "flag", "date", "datetime"],
+            svec!["john", "1", "true", "2023-01-05", "2023-01-05T10:30:00"],
+            svec!["mary", "", "FALSE", "2024-02-29", "2024-02-29 08:00:00"],
+        ],
+    );
+    wrk.create(
+        "cities.csv",
+        vec![svec!["city", "population"], svec!["Paris", "2100000"]],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("xlsx")
+        .args(["people.csv", "cities.csv"])
+        .args(["--freeze", "--autofilter", "--widths", "auto"])
+        .args(["-o", "data.xlsx"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("--list-sheets").arg("data.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["people"], svec!["cities"]];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "count", "flag", "date", "datetime"],
+        svec!["john", "1", "true", "2023-01-05", "2023-01-05T10:30:00"],
+        svec!["mary", "", "false", "2024-02-29", "2024-02-29T08:00:00"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--sheet-name", "cities"]).arg("data.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["city", "population"], svec!["Paris", "2100000"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn to_xlsx_strings() {
+    let wrk = Workdir::new("to_xlsx_strings");
+    wrk.create(
+        "data.csv",
+        vec![svec!["name", "flag"], svec!["john", "TRUE"]],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("xlsx")
+        .arg("data.csv")
+        .arg("--strings")
+        .args(["--sheet-names", "custom"])
+        .args(["-o", "data.xlsx"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--sheet-name", "custom"]).arg("data.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "flag"], svec!["john", "TRUE"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn to_xlsx_ods_lossy_numbers() {
+    let wrk = Workdir::new("to_xlsx_ods_lossy_numbers");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["zip", "id", "count"],
+            svec!["01234", "12345678901234567890", "1"],
+            svec!["75005", "3", "2"],
+            svec!["75006", "4", "003"],
+        ],
+    );
+
+    for format in ["xlsx", "ods"] {
+        let path = format!("data.{}", format);
+
+        let mut cmd = wrk.command("to");
+        cmd.arg(format)
+            .arg("data.csv")
+            .args(["-B", "2"])
+            .args(["-o", &path]);
+        wrk.assert_success(&mut cmd);
+
+        let mut cmd = wrk.command("from");
+        cmd.arg(&path);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["zip", "id", "count"],
+            svec!["01234", "12345678901234567890", "1"],
+            svec!["75005", "3", "2"],
+            svec!["75006", "4", "003"],
+        ];
+        assert_eq!(got, expected);
+    }
+}
+
+#[test]
+fn to_ods() {
+    let wrk = Workdir::new("to_ods");
+    wrk.create(
+        "people.csv",
+        vec![
+            svec!["name", "count", "flag", "date"],
+            svec!["john", "1.5", "true", "2023-01-05"],
+            svec!["mary", "", "false", "2024-02-29"],
+        ],
+    );
+    wrk.create(
+        "cities.csv",
+        vec![svec!["city", "population"], svec!["Paris", "2100000"]],
+    );
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("ods")
+        .args(["people.csv", "cities.csv"])
+        .args(["--freeze", "--autofilter", "--widths", "12"])
+        .args(["-o", "data.ods"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("from");
+    cmd.arg("data.ods");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "count", "flag", "date"],
+        svec!["john", "1.5", "true", "2023-01-05"],
+        svec!["mary", "", "false", "2024-02-29"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("from");
+    cmd.args(["--sheet-index", "1"]).arg("data.ods");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["city", "population"], svec!["Paris", "2100000"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn to_multiple_inputs_errors() {
+    let wrk = Workdir::new("to_multiple_inputs_errors");
+    wrk.create("data.csv", vec![svec!["name"], svec!["john"]]);
+
+    let mut cmd = wrk.command("to");
+    cmd.arg("json").args(["data.csv", "data.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    // Duplicate sheet names
+    let mut cmd = wrk.command("to");
+    cmd.arg("xlsx")
+        .args(["data.csv", "data.csv"])
+        .args(["-o", "data.xlsx"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -240,7 +240,9 @@ fn vocab_cooc_sep_no_doc() {
             "chi2",
             "G2",
             "pmi",
-            "npmi"
+            "npmi",
+            "t_score",
+            "dice"
         ],
         svec![
             "cat",
@@ -250,10 +252,23 @@ fn vocab_cooc_sep_no_doc() {
             "",
             "-1.3862943611198906",
             "-2",
-            "-1"
+            "-1",
+            "-3",
+            "0.25"
         ],
-        svec!["cat", "dog", "2", "2", "0", "0", "0", "0"],
-        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0"],
+        svec![
+            "cat",
+            "dog",
+            "2",
+            "2",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0.6666666666666666"
+        ],
+        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0", "0", "0.4"],
     ];
     assert_eq!(got, expected);
 }
@@ -289,7 +304,9 @@ fn vocab_cooc_no_sep() {
             "chi2",
             "G2",
             "pmi",
-            "npmi"
+            "npmi",
+            "t_score",
+            "dice"
         ],
         svec![
             "cat",
@@ -299,10 +316,23 @@ fn vocab_cooc_no_sep() {
             "",
             "-1.3862943611198906",
             "-2",
-            "-1"
+            "-1",
+            "-3",
+            "0.25"
+        ],
+        svec![
+            "cat",
+            "dog",
+            "2",
+            "2",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0.6666666666666666"
         ],
-        svec!["cat", "dog", "2", "2", "0", "0", "0", "0"],
-        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0"],
+        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0", "0", "0.4"],
     ];
     assert_eq!(got, expected);
 }
@@ -339,7 +369,143 @@ fn vocab_cooc_no_sep_window() {
             "chi2",
             "G2",
             "pmi",
-            "npmi"
+            "npmi",
+            "t_score",
+            "dice"
+        ],
+        svec![
+            "cat",
+            "cat",
+            "1",
+            "4",
+            "",
+            "-1.3862943611198906",
+            "-2",
+            "-1",
+            "-3",
+            "0.25"
+        ],
+        svec![
+            "cat",
+            "dog",
+            "2",
+            "2",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0.6666666666666666"
+        ],
+        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0", "0", "0.4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+fn sim_documents() -> Vec<Vec<String>> {
+    vec![
+        svec!["id", "tokens"],
+        svec!["1", "the cat eats the mouse"],
+        svec!["2", "the cat sleeps"],
+        svec!["3", "a dog eats a bone"],
+        svec!["4", "the dog sleeps"],
+        svec!["5", "the mouse runs"],
+    ]
+}
+
+#[test]
+fn vocab_sim() {
+    let wrk = Workdir::new("vocab_sim");
+    wrk.create("data.csv", sim_documents());
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("sim")
+        .args(["--doc", "id"])
+        .args(["-k", "1"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id1", "id2", "similarity"],
+        svec!["1", "2", "0.43324428849768665"],
+        svec!["2", "4", "0.5143996227192245"],
+        svec!["3", "4", "0.16693265357230422"],
+        svec!["4", "2", "0.5143996227192245"],
+        svec!["5", "1", "0.30539186830224385"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn vocab_sim_bm25() {
+    let wrk = Workdir::new("vocab_sim_bm25");
+    wrk.create("data.csv", sim_documents());
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("sim")
+        .args(["--weighting", "bm25"])
+        .args(["-k", "1"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["doc1", "doc2", "similarity"],
+        svec!["0", "1", "0.4278370167982491"],
+        svec!["1", "3", "0.5143996227192245"],
+        svec!["2", "3", "0.20676422487757232"],
+        svec!["3", "1", "0.5143996227192245"],
+        svec!["4", "0", "0.3015803078257394"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn vocab_sim_query() {
+    let wrk = Workdir::new("vocab_sim_query");
+    wrk.create("data.csv", sim_documents());
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("sim")
+        .args(["--doc", "id"])
+        .args(["--query", "The MOUSE!"])
+        .arg("--lower")
+        .args(["-k", "3"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "similarity"],
+        svec!["1", "0.6040634036357688"],
+        svec!["5", "0.5055626056207594"],
+        svec!["2", "0.040154182486780036"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn vocab_cooc_min_freq() {
+    let wrk = Workdir::new("vocab_cooc_min_freq");
+    wrk.create(
+        "data.csv",
+        vec![svec!["tokens"], svec!["cat|dog|cat"], svec!["cat|rabbit"]],
+    );
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("cooc")
+        .args(["--sep", "|"])
+        .args(["--min-freq", "2"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let expected = vec![
+        svec![
+            "token1",
+            "token2",
+            "count",
+            "expected_count",
+            "chi2",
+            "G2",
+            "pmi",
+            "npmi",
+            "t_score",
+            "dice"
         ],
         svec![
             "cat",
@@ -349,10 +515,187 @@ fn vocab_cooc_no_sep_window() {
             "",
             "-1.3862943611198906",
             "-2",
-            "-1"
+            "-1",
+            "-3",
+            "0.25"
+        ],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn vocab_keywords() {
+    let wrk = Workdir::new("vocab_keywords");
+    wrk.create(
+        "target.csv",
+        vec![
+            svec!["tokens"],
+            svec!["the cat eats the mouse"],
+            svec!["the cat sleeps"],
+        ],
+    );
+    wrk.create(
+        "reference.csv",
+        vec![
+            svec!["tokens"],
+            svec!["the dog eats"],
+            svec!["the dog sleeps"],
+            svec!["the bird"],
+        ],
+    );
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords").arg("reference.csv").arg("target.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "token",
+            "target_count",
+            "reference_count",
+            "target_df",
+            "reference_df",
+            "log_ratio",
+            "G2"
+        ],
+        svec!["the", "3", "3", "2", "3", "0", "0"],
+        svec!["cat", "2", "0", "2", "0", "2", "2.772588722239781"],
+        svec!["eats", "1", "1", "1", "1", "0", "0"],
+        svec!["mouse", "1", "0", "1", "0", "1", "1.3862943611198906"],
+        svec!["sleeps", "1", "1", "1", "1", "0", "0"],
+    ];
+    assert_eq!(got, expected);
+
+    // --min-count & --G2-significance
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords")
+        .args(["--min-target-count", "2"])
+        .args(["--G2-significance", "0.5"])
+        .arg("reference.csv")
+        .arg("target.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "token",
+            "target_count",
+            "reference_count",
+            "target_df",
+            "reference_df",
+            "log_ratio",
+            "G2"
+        ],
+        svec!["cat", "2", "0", "2", "0", "2", "2.772588722239781"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn vocab_keywords_negative() {
+    let wrk = Workdir::new("vocab_keywords_negative");
+    wrk.create(
+        "target.csv",
+        vec![svec!["tokens"], svec!["cat cat cat the"]],
+    );
+    wrk.create(
+        "reference.csv",
+        vec![svec!["tokens"], svec!["the the the dog"]],
+    );
+
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords").arg("reference.csv").arg("target.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "token",
+            "target_count",
+            "reference_count",
+            "target_df",
+            "reference_df",
+            "log_ratio",
+            "G2"
+        ],
+        svec![
+            "cat",
+            "3",
+            "0",
+            "1",
+            "0",
+            "2.584962500721156",
+            "4.1588830833596715"
+        ],
+        svec![
+            "the",
+            "1",
+            "3",
+            "1",
+            "1",
+            "-1.5849625007211563",
+            "-1.0464962875290957"
         ],
-        svec!["cat", "dog", "2", "2", "0", "0", "0", "0"],
-        svec!["cat", "rabbit", "1", "1", "0", "0", "0", "0"],
     ];
     assert_eq!(got, expected);
+
+    // Negative keywords are not significant
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords")
+        .args(["--G2-significance", "0.5"])
+        .arg("reference.csv")
+        .arg("target.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 2);
+    assert_eq!(got[1][0], "cat");
+}
+
+#[test]
+fn vocab_keywords_empty_corpus() {
+    let wrk = Workdir::new("vocab_keywords_empty_corpus");
+    wrk.create(
+        "corpus.csv",
+        vec![svec!["tokens"], svec!["the cat eats the mouse"]],
+    );
+    wrk.create("empty.csv", vec![svec!["tokens"], svec![""]]);
+
+    // Empty target
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords").arg("corpus.csv").arg("empty.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec![
+        "token",
+        "target_count",
+        "reference_count",
+        "target_df",
+        "reference_df",
+        "log_ratio",
+        "G2"
+    ]];
+    assert_eq!(got, expected);
+
+    // Empty reference
+    let mut cmd = wrk.command("vocab");
+    cmd.arg("keywords").arg("empty.csv").arg("corpus.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn vocab_sim_zero_top() {
+    let wrk = Workdir::new("vocab_sim_zero_top");
+    wrk.create("data.csv", sim_documents());
+
+    for query in [None, Some("the mouse")] {
+        let mut cmd = wrk.command("vocab");
+        cmd.arg("sim").args(["-k", "0"]).arg("data.csv");
+
+        if let Some(query) = query {
+            cmd.args(["--query", query]);
+        }
+
+        // NOTE: panics would exit with code 101
+        let output = cmd.output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+    }
 }
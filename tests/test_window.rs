@@ -453,3 +453,187 @@ fn window_dense_rank() {
 
     assert_eq!(got, expected);
 }
+
+#[test]
+fn window_rolling_time() {
+    let wrk = Workdir::new("window_rolling_time");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["datetime", "amount"],
+            svec!["2024-01-01", "1"],
+            svec!["2024-01-03", "2"],
+            svec!["2024-01-08", "3"],
+            svec!["2024-01-09", "4"],
+        ],
+    );
+    let mut cmd = wrk.command("window");
+    cmd.arg(
+        "rolling_sum('7d', amount, datetime) as sum, rolling_mean('2d', amount, datetime) as mean",
+    )
+    .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["datetime", "amount", "sum", "mean"],
+        svec!["2024-01-01", "1", "1", "1"],
+        svec!["2024-01-03", "2", "3", "2"],
+        svec!["2024-01-08", "3", "5", "3"],
+        svec!["2024-01-09", "4", "9", "3.5"],
+    ];
+
+    assert_eq!(got, expected);
+
+    // Unsorted rows
+    wrk.create(
+        "unsorted.csv",
+        vec![
+            svec!["datetime", "amount"],
+            svec!["2024-01-03", "1"],
+            svec!["2024-01-01", "2"],
+        ],
+    );
+    let mut cmd = wrk.command("window");
+    cmd.arg("rolling_sum('7d', amount, datetime)")
+        .arg("unsorted.csv");
+    wrk.assert_err(&mut cmd);
+
+    // Invalid duration
+    let mut cmd = wrk.command("window");
+    cmd.arg("rolling_sum('test', amount, datetime)")
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn window_framed_aggregations() {
+    let wrk = Workdir::new("window_framed_aggregations");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["g", "x"],
+            svec!["a", "1"],
+            svec!["a", "5"],
+            svec!["a", "2"],
+            svec!["a", "8"],
+            svec!["b", "3"],
+            svec!["b", "4"],
+            svec!["b", "10"],
+        ],
+    );
+    let mut cmd = wrk.command("window");
+    cmd.arg("median(x) over 1 preceding and 1 following as median, sum(x) as total, sum(x) over unbounded preceding as cumsum, count() over 1 following and unbounded following as rest, first(x) over 2 preceding and 1 preceding as first")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["g", "x", "median", "total", "cumsum", "rest", "first"],
+        svec!["a", "1", "3", "33", "1", "6", ""],
+        svec!["a", "5", "2", "33", "6", "5", "1"],
+        svec!["a", "2", "5", "33", "8", "4", "1"],
+        svec!["a", "8", "3", "33", "16", "3", "5"],
+        svec!["b", "3", "4", "33", "19", "2", "2"],
+        svec!["b", "4", "4", "33", "23", "1", "8"],
+        svec!["b", "10", "7", "33", "33", "0", "3"],
+    ];
+
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_framed_aggregations_groupby() {
+    let wrk = Workdir::new("window_framed_aggregations_groupby");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["g", "x"],
+            svec!["a", "1"],
+            svec!["a", "5"],
+            svec!["a", "2"],
+            svec!["a", "8"],
+            svec!["b", "3"],
+            svec!["b", "4"],
+            svec!["b", "10"],
+        ],
+    );
+    let mut cmd = wrk.command("window");
+    cmd.args(["-g", "g"])
+        .arg("percentage(x > 2) as pct, max(x) over unbounded preceding and 1 following as max, lag(x)")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["g", "x", "pct", "max", "lag(x)"],
+        svec!["a", "1", "50%", "5", ""],
+        svec!["a", "5", "50%", "5", "1"],
+        svec!["a", "2", "50%", "8", "5"],
+        svec!["a", "8", "50%", "8", "2"],
+        svec!["b", "3", "100%", "4", ""],
+        svec!["b", "4", "100%", "10", "3"],
+        svec!["b", "10", "100%", "10", "4"],
+    ];
+
+    assert_eq!(got, expected);
+
+    // Window functions cannot take a frame
+    let mut cmd = wrk.command("window");
+    cmd.arg("lag(x) over 1 preceding").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    // Invalid frame
+    let mut cmd = wrk.command("window");
+    cmd.arg("sum(x) over 2 following and 1 following")
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn window_framed_aggregations_short_groups() {
+    let wrk = Workdir::new("window_framed_aggregations_short_groups");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["g", "x"],
+            svec!["a", "1"],
+            svec!["a", "2"],
+            svec!["b", "3"],
+            svec!["c", "4"],
+            svec!["c", "5"],
+            svec!["c", "6"],
+        ],
+    );
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-g", "g"])
+        .arg("median(x) over 5 preceding and 5 following as m")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["g", "x", "m"],
+        svec!["a", "1", "1.5"],
+        svec!["a", "2", "1.5"],
+        svec!["b", "3", "3"],
+        svec!["c", "4", "5"],
+        svec!["c", "5", "5"],
+        svec!["c", "6", "5"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-g", "g"])
+        .arg("sum(x) over current row and 2 following as s, lead(x, 3) as l")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["g", "x", "s", "l"],
+        svec!["a", "1", "3", ""],
+        svec!["a", "2", "2", ""],
+        svec!["b", "3", "3", ""],
+        svec!["c", "4", "15", ""],
+        svec!["c", "5", "11", ""],
+        svec!["c", "6", "6", ""],
+    ];
+    assert_eq!(got, expected);
+}
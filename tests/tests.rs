@@ -18,29 +18,41 @@ mod workdir;
 mod test_agg;
 mod test_behead;
 mod test_cat;
+mod test_cluster;
 mod test_count;
 mod test_dedup;
+mod test_diff;
 mod test_enumerate;
 mod test_explode;
+mod test_fill;
 mod test_filter;
 mod test_fixlengths;
 mod test_flatmap;
 mod test_fmt;
 mod test_frequency;
+mod test_from;
 mod test_fuzzy_join;
 mod test_groupby;
 mod test_headers;
+mod test_heatmap;
+mod test_hist;
 mod test_implode;
+mod test_index;
 mod test_join;
 mod test_map;
 mod test_merge;
+mod test_network;
 mod test_parallel;
 mod test_partition;
+mod test_patch;
 mod test_pivot;
+mod test_plot;
 mod test_range;
 mod test_rename;
+mod test_resample;
 mod test_reverse;
 mod test_sample;
+mod test_schema;
 mod test_scrape;
 mod test_search;
 mod test_select;
@@ -48,6 +60,7 @@ mod test_shuffle;
 mod test_slice;
 mod test_sort;
 mod test_split;
+mod test_sql;
 mod test_stats;
 mod test_to;
 mod test_tokenize;